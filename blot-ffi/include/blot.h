@@ -0,0 +1,118 @@
+/* Copyright 2018 Arnau Siches. Licensed under the MIT license. */
+
+#ifndef BLOT_H
+#define BLOT_H
+
+/* Generated with cbindgen. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of every call.
+ */
+typedef enum BlotStatus {
+  BLOT_STATUS_OK = 0,
+  /**
+   * A required pointer is null.
+   */
+  BLOT_STATUS_NULL_POINTER = 1,
+  /**
+   * An input string is not valid UTF-8.
+   */
+  BLOT_STATUS_INVALID_UTF8 = 2,
+  /**
+   * The input is not valid JSON.
+   */
+  BLOT_STATUS_INVALID_JSON = 3,
+  /**
+   * The algorithm name is not supported.
+   */
+  BLOT_STATUS_UNKNOWN_ALGORITHM = 4,
+  /**
+   * The input is not a valid seal.
+   */
+  BLOT_STATUS_INVALID_SEAL = 5,
+  /**
+   * The digest doesn't match the expected one.
+   */
+  BLOT_STATUS_MISMATCH = 6,
+  /**
+   * blot panicked. Please report it as a bug.
+   */
+  BLOT_STATUS_PANIC = 7,
+  /**
+   * The input is valid JSON but not a valid document, e.g. it has an invalid timestamp.
+   */
+  BLOT_STATUS_INVALID_DOCUMENT = 8,
+} BlotStatus;
+
+/**
+ * A parsed seal.
+ */
+typedef struct BlotSeal {
+  /**
+   * The multihash code of the algorithm the seal was computed with.
+   */
+  uint64_t code;
+  /**
+   * The number of meaningful bytes in `digest`.
+   */
+  uint8_t length;
+  uint8_t digest[255];
+} BlotSeal;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Digests a JSON document and writes its hexadecimal multihash to `out`.
+ *
+ * # Safety
+ *
+ * `algorithm` and `json` must be valid NUL-terminated strings and `out` a valid pointer. The
+ * string written to `out` must be released with [`blot_string_free`].
+ */
+BlotStatus blot_digest_json(const char *algorithm, const char *json, char **out);
+
+/**
+ * Checks that a JSON document digests to the expected hexadecimal multihash. Returns
+ * [`BlotStatus::Mismatch`] if it doesn't.
+ *
+ * # Safety
+ *
+ * Every argument must be a valid NUL-terminated string.
+ */
+BlotStatus blot_verify(const char *algorithm, const char *json, const char *expected);
+
+/**
+ * Parses a seal, either `77`-prefixed or `**REDACTED**`-prefixed, into `out`.
+ *
+ * # Safety
+ *
+ * `input` must be a valid NUL-terminated string and `out` a valid pointer.
+ */
+BlotStatus blot_seal_parse(const char *input, BlotSeal *out);
+
+/**
+ * Returns a static description of the status.
+ */
+const char *blot_status_message(BlotStatus status);
+
+/**
+ * Releases a string returned by blot. Null pointers are ignored.
+ *
+ * # Safety
+ *
+ * `string` must be null or a string returned by blot that has not been released yet.
+ */
+void blot_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BLOT_H */
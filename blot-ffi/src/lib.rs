@@ -0,0 +1,338 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! C bindings for blot.
+//!
+//! The header is `include/blot.h`, generated with
+//! `cbindgen --config cbindgen.toml --output include/blot.h`.
+//!
+//! # Ownership
+//!
+//! * Input strings are borrowed: they must be valid NUL-terminated UTF-8 for the duration of the
+//!   call and are never freed by blot.
+//! * Strings returned through an out pointer belong to the caller, who must release them with
+//!   [`blot_string_free`] and nothing else.
+//! * [`blot_status_message`] returns static strings that must not be freed.
+//! * Out pointers are only written when the call returns [`BlotStatus::Ok`].
+//!
+//! No function unwinds into the caller: a panic is reported as [`BlotStatus::Panic`].
+
+extern crate blot;
+
+use blot::multihash::{self, Multihash};
+use blot::seal::Seal;
+use blot::value::de::{DeserializeError, DeserializerOptions};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+
+/// The result of every call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlotStatus {
+    Ok = 0,
+    /// A required pointer is null.
+    NullPointer = 1,
+    /// An input string is not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The input is not valid JSON.
+    InvalidJson = 3,
+    /// The algorithm name is not supported.
+    UnknownAlgorithm = 4,
+    /// The input is not a valid seal.
+    InvalidSeal = 5,
+    /// The digest doesn't match the expected one.
+    Mismatch = 6,
+    /// blot panicked. Please report it as a bug.
+    Panic = 7,
+    /// The input is valid JSON but not a valid document, e.g. it has an invalid timestamp.
+    InvalidDocument = 8,
+}
+
+impl From<DeserializeError> for BlotStatus {
+    fn from(err: DeserializeError) -> BlotStatus {
+        match err {
+            DeserializeError::Json(_) => BlotStatus::InvalidJson,
+            DeserializeError::SealMismatch { .. } | DeserializeError::ForbiddenSeal(_) => {
+                BlotStatus::InvalidSeal
+            }
+            _ => BlotStatus::InvalidDocument,
+        }
+    }
+}
+
+/// A parsed seal.
+#[repr(C)]
+pub struct BlotSeal {
+    /// The multihash code of the algorithm the seal was computed with.
+    pub code: u64,
+    /// The number of meaningful bytes in `digest`.
+    pub length: u8,
+    pub digest: [u8; 255],
+}
+
+/// Calls the function with the digester of the given algorithm name.
+macro_rules! dispatch {
+    ($name:expr, $function:ident($($arg:expr),*)) => {
+        match $name {
+            "sha1" => $function($($arg),*, multihash::Sha1),
+            "sha2-224" => $function($($arg),*, multihash::Sha2224),
+            "sha2-256" => $function($($arg),*, multihash::Sha2256),
+            "sha2-384" => $function($($arg),*, multihash::Sha2384),
+            "dbl-sha2-256" => $function($($arg),*, multihash::DblSha2256),
+            "sha2-512" => $function($($arg),*, multihash::Sha2512),
+            "sha3-224" => $function($($arg),*, multihash::Sha3224),
+            "sha3-256" => $function($($arg),*, multihash::Sha3256),
+            "sha3-384" => $function($($arg),*, multihash::Sha3384),
+            "sha3-512" => $function($($arg),*, multihash::Sha3512),
+            "keccak-256" => $function($($arg),*, multihash::Keccak256),
+            "keccak-512" => $function($($arg),*, multihash::Keccak512),
+            "blake2b-512" => $function($($arg),*, multihash::Blake2b512),
+            "blake2s-256" => $function($($arg),*, multihash::Blake2s256),
+            "blake3" => $function($($arg),*, multihash::Blake3),
+            _ => Err(BlotStatus::UnknownAlgorithm),
+        }
+    };
+}
+
+/// Digests a JSON document and writes its hexadecimal multihash to `out`.
+///
+/// # Safety
+///
+/// `algorithm` and `json` must be valid NUL-terminated strings and `out` a valid pointer. The
+/// string written to `out` must be released with [`blot_string_free`].
+#[no_mangle]
+pub unsafe extern "C" fn blot_digest_json(
+    algorithm: *const c_char,
+    json: *const c_char,
+    out: *mut *mut c_char,
+) -> BlotStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(BlotStatus::NullPointer);
+        }
+
+        let multihash = dispatch!(to_str(algorithm)?, digest_json(to_str(json)?))?;
+        let multihash = CString::new(multihash).map_err(|_| BlotStatus::Panic)?;
+
+        *out = multihash.into_raw();
+
+        Ok(())
+    })
+}
+
+/// Checks that a JSON document digests to the expected hexadecimal multihash. Returns
+/// [`BlotStatus::Mismatch`] if it doesn't.
+///
+/// # Safety
+///
+/// Every argument must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn blot_verify(
+    algorithm: *const c_char,
+    json: *const c_char,
+    expected: *const c_char,
+) -> BlotStatus {
+    guard(|| {
+        let expected = to_str(expected)?;
+        let actual = dispatch!(to_str(algorithm)?, digest_json(to_str(json)?))?;
+
+        if actual.eq_ignore_ascii_case(expected) {
+            Ok(())
+        } else {
+            Err(BlotStatus::Mismatch)
+        }
+    })
+}
+
+/// Parses a seal, either `77`-prefixed or `**REDACTED**`-prefixed, into `out`.
+///
+/// # Safety
+///
+/// `input` must be a valid NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn blot_seal_parse(input: *const c_char, out: *mut BlotSeal) -> BlotStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(BlotStatus::NullPointer);
+        }
+
+        let seal: Seal<multihash::Identity> =
+            Seal::from_str(to_str(input)?).map_err(|_| BlotStatus::InvalidSeal)?;
+        let mut digest = [0; 255];
+        digest[..seal.digest().len()].copy_from_slice(seal.digest());
+
+        *out = BlotSeal {
+            code: seal.tag().code().into(),
+            length: seal.digest().len() as u8,
+            digest,
+        };
+
+        Ok(())
+    })
+}
+
+/// Returns a static description of the status.
+#[no_mangle]
+pub extern "C" fn blot_status_message(status: BlotStatus) -> *const c_char {
+    let message: &'static [u8] = match status {
+        BlotStatus::Ok => b"ok\0",
+        BlotStatus::NullPointer => b"null pointer\0",
+        BlotStatus::InvalidUtf8 => b"invalid UTF-8\0",
+        BlotStatus::InvalidJson => b"invalid JSON\0",
+        BlotStatus::UnknownAlgorithm => b"unknown algorithm\0",
+        BlotStatus::InvalidSeal => b"invalid seal\0",
+        BlotStatus::Mismatch => b"digest mismatch\0",
+        BlotStatus::Panic => b"internal error\0",
+        BlotStatus::InvalidDocument => b"invalid document\0",
+    };
+
+    message.as_ptr() as *const c_char
+}
+
+/// Releases a string returned by blot. Null pointers are ignored.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by blot that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn blot_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+fn guard<F: FnOnce() -> Result<(), BlotStatus>>(call: F) -> BlotStatus {
+    match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(())) => BlotStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => BlotStatus::Panic,
+    }
+}
+
+unsafe fn to_str<'a>(string: *const c_char) -> Result<&'a str, BlotStatus> {
+    if string.is_null() {
+        return Err(BlotStatus::NullPointer);
+    }
+
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| BlotStatus::InvalidUtf8)
+}
+
+fn digest_json<T: Multihash>(json: &str, digester: T) -> Result<String, BlotStatus> {
+    DeserializerOptions::new()
+        .digest_str::<T, T>(json, digester)
+        .map(|hash| hash.to_string())
+        .map_err(BlotStatus::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    const FOO: &str = "1220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038";
+
+    fn c(string: &str) -> CString {
+        CString::new(string).unwrap()
+    }
+
+    #[test]
+    fn digest_and_verify() {
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            let status = blot_digest_json(c("sha2-256").as_ptr(), c(r#""foo""#).as_ptr(), &mut out);
+
+            assert_eq!(status, BlotStatus::Ok);
+            assert_eq!(CStr::from_ptr(out).to_str().unwrap(), FOO);
+            blot_string_free(out);
+
+            assert_eq!(
+                blot_verify(
+                    c("sha2-256").as_ptr(),
+                    c(r#""foo""#).as_ptr(),
+                    c(FOO).as_ptr()
+                ),
+                BlotStatus::Ok
+            );
+            assert_eq!(
+                blot_verify(
+                    c("sha2-256").as_ptr(),
+                    c(r#""bar""#).as_ptr(),
+                    c(FOO).as_ptr()
+                ),
+                BlotStatus::Mismatch
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                blot_digest_json(c("md5").as_ptr(), c("1").as_ptr(), &mut out),
+                BlotStatus::UnknownAlgorithm
+            );
+            assert_eq!(
+                blot_digest_json(c("sha2-256").as_ptr(), c("{").as_ptr(), &mut out),
+                BlotStatus::InvalidJson
+            );
+            assert_eq!(
+                blot_digest_json(ptr::null(), c("1").as_ptr(), &mut out),
+                BlotStatus::NullPointer
+            );
+        }
+
+        assert!(out.is_null());
+    }
+
+    #[test]
+    fn deserialize_errors() {
+        assert_eq!(
+            BlotStatus::from(DeserializeError::ForbiddenSeal(multihash::Stamp::Sha1)),
+            BlotStatus::InvalidSeal
+        );
+        assert_eq!(
+            BlotStatus::from(DeserializeError::DuplicateKey("a".into())),
+            BlotStatus::InvalidDocument
+        );
+    }
+
+    #[test]
+    fn seal_parse() {
+        let mut seal = BlotSeal {
+            code: 0,
+            length: 0,
+            digest: [0; 255],
+        };
+
+        unsafe {
+            assert_eq!(
+                blot_seal_parse(c(&format!("77{}", FOO)).as_ptr(), &mut seal),
+                BlotStatus::Ok
+            );
+            assert_eq!(
+                blot_seal_parse(c("77ff").as_ptr(), &mut seal),
+                BlotStatus::InvalidSeal
+            );
+        }
+
+        assert_eq!(seal.code, 0x12);
+        assert_eq!(seal.length, 32);
+        assert_eq!(&seal.digest[..2], &[0xa6, 0xa6]);
+    }
+
+    #[test]
+    fn status_messages() {
+        let message = unsafe { CStr::from_ptr(blot_status_message(BlotStatus::Mismatch)) };
+
+        assert_eq!(message.to_str().unwrap(), "digest mismatch");
+    }
+}
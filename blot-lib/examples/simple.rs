@@ -8,18 +8,17 @@
 extern crate blot;
 
 use blot::multihash::Sha3256;
-use blot::seal::Seal;
 use blot::value::Value;
 use blot::Blot;
 
 fn main() -> std::io::Result<()> {
     // Creates a `blot::value::Value`.
-    let set: Value<Sha3256> = set!{
+    let set: Value<Sha3256> = value!(set [
         "foo",
         "bar",
-        list![1, 1.0],
-        seal!("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap()
-    };
+        [1, 1.0],
+        seal("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038")
+    ]);
 
     // Computes the blot hash with the SHA3-256 algorithm.
     let hash = set.digest(Sha3256);
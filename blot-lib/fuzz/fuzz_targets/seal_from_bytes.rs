@@ -0,0 +1,15 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate blot;
+
+use blot::multihash::Sha2256;
+use blot::seal::Seal;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(seal) = Seal::<Sha2256>::from_bytes(data) {
+        let reparsed: Seal<Sha2256> = Seal::from_str(&seal.to_string()).expect("A printable seal");
+
+        assert_eq!(reparsed, seal);
+    }
+});
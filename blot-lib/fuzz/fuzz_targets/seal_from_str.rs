@@ -0,0 +1,16 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate blot;
+
+use blot::multihash::Sha2256;
+use blot::seal::Seal;
+use std::str;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = str::from_utf8(data) {
+        let _ = Seal::<Sha2256>::from_str(input);
+        let _ = Seal::<Sha2256>::from_objecthash_str(input);
+        let _ = Seal::<Sha2256>::from_hash_str(input);
+    }
+});
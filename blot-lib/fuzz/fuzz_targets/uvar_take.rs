@@ -0,0 +1,15 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate blot;
+
+use blot::uvar::{self, Uvar};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((uvar, rest)) = Uvar::take(data) {
+        let n = uvar.to_u64().expect("A valid uvar");
+
+        assert_eq!(uvar::decode(data).unwrap(), (n, rest));
+        assert_eq!(uvar::encode_u64(n), uvar.to_bytes());
+    }
+});
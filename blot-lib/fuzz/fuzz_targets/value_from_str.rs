@@ -0,0 +1,17 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate blot;
+
+use blot::core::Blot;
+use blot::multihash::Sha2256;
+use blot::value::de::DeserializerOptions;
+use std::str;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = str::from_utf8(data) {
+        if let Ok(value) = DeserializerOptions::new().from_str::<Sha2256>(input) {
+            value.digest(Sha2256);
+        }
+    }
+});
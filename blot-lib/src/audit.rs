@@ -0,0 +1,274 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Audit traces.
+//!
+//! An [`Auditor`] wraps a digester recording the exact bytes fed to the hashing function for
+//! every node, tag byte included, together with the resulting digest. Comparing the trace of a
+//! value with the intermediate inputs of another Objecthash implementation shows the first node
+//! where they disagree.
+//!
+//! ```
+//! use blot::audit;
+//! use blot::multihash::Sha2256;
+//!
+//! let (hash, trace) = audit::trace(&vec!["foo"], Sha2256);
+//! let inputs: Vec<String> = trace.records().iter().map(|record| record.input_hex()).collect();
+//!
+//! assert_eq!(
+//!     inputs,
+//!     vec![
+//!         "75666f6f",
+//!         "6ca6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038",
+//!     ]
+//! );
+//! assert_eq!(trace.records()[1].digest(), hash.digest());
+//! ```
+//!
+//! Records are listed in the order the digester is called: children before their parent, and the
+//! values of a dictionary before its keys. Redacted values are not hashed so they have no record.
+
+use core::{Blot, FloatPolicy};
+use encoding::Base;
+use multihash::{Harvest, Hash, Multihash};
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Mutex;
+use tag::Tag;
+use uvar::Uvar;
+
+/// Digests the value recording every input fed to the digester.
+pub fn trace<B: Blot + ?Sized, T: Multihash>(value: &B, digester: T) -> (Hash<T>, Trace) {
+    let auditor = Auditor::new(digester);
+    let digest = value.blot(&auditor);
+    let (digester, trace) = auditor.into_parts();
+
+    (Hash::new(digester, digest), trace)
+}
+
+/// A single call to the digester.
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    tag: Tag,
+    input: Vec<u8>,
+    digest: Harvest,
+}
+
+impl Record {
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    /// The bytes fed to the hashing function: the tag byte followed by the payload.
+    pub fn input(&self) -> &[u8] {
+        &self.input
+    }
+
+    pub fn input_hex(&self) -> String {
+        Base::Base16.encode(&self.input)
+    }
+
+    pub fn digest(&self) -> &Harvest {
+        &self.digest
+    }
+}
+
+/// Displayed as the tag name, the input and the digest in hexadecimal.
+impl fmt::Display for Record {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} {} {}",
+            self.tag.name(),
+            self.input_hex(),
+            self.digest
+        )
+    }
+}
+
+/// Every call to a digester, in order.
+#[derive(Debug, Default, PartialEq)]
+pub struct Trace(Vec<Record>);
+
+impl Trace {
+    pub fn records(&self) -> &[Record] {
+        &self.0
+    }
+}
+
+/// Displayed one record per line.
+impl fmt::Display for Trace {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for record in &self.0 {
+            writeln!(formatter, "{}", record)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A digester recording its inputs. It computes the same digests as the wrapped one.
+#[derive(Debug)]
+pub struct Auditor<T: Multihash> {
+    digester: T,
+    // A mutex rather than a cell so the auditor can be shared by parallel digests.
+    records: Mutex<Vec<Record>>,
+}
+
+impl<T: Multihash> Auditor<T> {
+    pub fn new(digester: T) -> Auditor<T> {
+        Auditor {
+            digester,
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Consumes the auditor returning the wrapped digester and the trace so far.
+    pub fn into_parts(self) -> (T, Trace) {
+        let records = self
+            .records
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        (self.digester, Trace(records))
+    }
+
+    fn record(&self, tag: Tag, input: Vec<u8>, digest: &Harvest) {
+        let record = Record {
+            tag,
+            input,
+            digest: digest.as_slice().into(),
+        };
+
+        self.records
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(record);
+    }
+}
+
+impl<T: Multihash> Default for Auditor<T> {
+    fn default() -> Auditor<T> {
+        Auditor::new(T::default())
+    }
+}
+
+/// Auditors are equal if they wrap the same digester, whatever they recorded.
+impl<T: Multihash> PartialEq for Auditor<T> {
+    fn eq(&self, other: &Auditor<T>) -> bool {
+        self.digester == other.digester
+    }
+}
+
+impl<T: Multihash> Multihash for Auditor<T> {
+    type Digester = T::Digester;
+
+    fn name(&self) -> &str {
+        self.digester.name()
+    }
+
+    fn code(&self) -> Uvar {
+        self.digester.code()
+    }
+
+    fn length(&self) -> u8 {
+        self.digester.length()
+    }
+
+    fn float_policy(&self) -> FloatPolicy {
+        self.digester.float_policy()
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let digest = self.digester.digest_primitive(tag, bytes);
+        let mut input = tag.to_bytes().to_vec();
+        input.extend_from_slice(bytes);
+        self.record(tag, input, &digest);
+
+        digest
+    }
+
+    // Untagged input is not part of an Objecthash, so it is not recorded.
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        self.digester.digest_bytes(bytes)
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut input = tag.to_bytes().to_vec();
+
+        for bytes in list {
+            input.extend_from_slice(bytes.as_ref());
+        }
+
+        let digest = self.digester.digest_collection(tag, Some(&input[1..]));
+        self.record(tag, input, &digest);
+
+        digest
+    }
+
+    /// Buffers the whole stream to record it.
+    fn digest_stream<R: Read>(&self, tag: Tag, reader: &mut R) -> io::Result<Harvest> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Ok(self.digest_primitive(tag, &bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dict;
+    use multihash::Sha2256;
+    use value::Value;
+
+    #[test]
+    fn same_digest() {
+        let value: Value<Sha2256> = dict! {
+            "a" => vec![Value::from(1), Value::Null],
+            "b" => Value::Set(vec!["x".into(), "x".into()]),
+        };
+        let (hash, trace) = trace(&value, Sha2256);
+
+        assert_eq!(hash, value.digest(Sha2256));
+        assert_eq!(
+            trace.records().last().map(Record::digest),
+            Some(hash.digest())
+        );
+    }
+
+    #[test]
+    fn dictionaries() {
+        let value: Value<Sha2256> = dict! { "a" => Value::Null };
+        let (_, trace) = trace(&value, Sha2256);
+        let tags: Vec<Tag> = trace.records().iter().map(Record::tag).collect();
+        let dict = &trace.records()[2];
+
+        assert_eq!(tags, vec![Tag::Null, Tag::Unicode, Tag::Dict]);
+        assert_eq!(dict.input()[0], b'd');
+        assert_eq!(&dict.input()[1..33], trace.records()[1].digest().as_slice());
+        assert_eq!(&dict.input()[33..], trace.records()[0].digest().as_slice());
+        assert_eq!(
+            trace.to_string().lines().next(),
+            Some("null 6e 1b16b1df538ba12dc3f97edbb85caa7050d46c148134290feba80f8236c83db9")
+        );
+    }
+
+    #[test]
+    fn streams() {
+        let auditor = Auditor::new(Sha2256);
+        let mut reader: &[u8] = b"foo";
+        let digest = auditor.digest_stream(Tag::Raw, &mut reader).unwrap();
+        let (_, trace) = auditor.into_parts();
+
+        assert_eq!(digest, Sha2256.digest_primitive(Tag::Raw, b"foo"));
+        assert_eq!(trace.records()[0].input(), b"rfoo");
+    }
+}
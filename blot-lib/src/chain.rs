@@ -0,0 +1,321 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Hash-chained entry logs.
+//!
+//! An [`EntryLog`] is an append-only sequence of values where the digest of every entry covers
+//! the digest of the previous one, so the digest of the last entry, the head, commits to the
+//! whole history: changing, removing or reordering any entry changes the head.
+//!
+//! The digest of an entry is the Objecthash of the dictionary
+//! `{"previous": <seal>, "timestamp": <timestamp>, "value": <value>}`, where `previous` is the
+//! seal of the previous entry or null for the first one.
+//!
+//! ```
+//! use blot::chain::EntryLog;
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//!
+//! let mut log: EntryLog<Sha2256> = EntryLog::new();
+//! log.append(Value::from("foo"), "2018-10-13T15:50:00Z").unwrap();
+//! log.append(Value::from("bar"), "2018-10-13T15:51:00Z").unwrap();
+//!
+//! assert_eq!(log.len(), 2);
+//! assert!(log.append(Value::from("baz"), "2018-10-13T15:49:00Z").is_err());
+//! ```
+
+use core::Blot;
+use datetime::{parse_timestamp, TimestampError};
+use merkle::MerkleTree;
+use multihash::{Hash, Multihash};
+use seal::Seal;
+use std::error;
+use std::fmt;
+use value::{DictMap, Value};
+
+/// A value recorded in a log.
+#[derive(Debug)]
+pub struct Entry<T: Multihash> {
+    timestamp: String,
+    value: Value<T>,
+    hash: Hash<T>,
+}
+
+impl<T: Multihash> Entry<T> {
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    pub fn value(&self) -> &Value<T> {
+        &self.value
+    }
+
+    /// The digest of the entry, covering the previous entries.
+    pub fn hash(&self) -> &Hash<T> {
+        &self.hash
+    }
+
+    /// The digest of the entry as a seal, the leaf of the entry in [`EntryLog::tree`].
+    pub fn seal(&self) -> Seal<T> {
+        Hash::new(T::default(), self.hash.digest().as_slice()).into()
+    }
+}
+
+/// An append-only sequence of hash-chained entries.
+#[derive(Debug, Default)]
+pub struct EntryLog<T: Multihash> {
+    entries: Vec<Entry<T>>,
+}
+
+impl<T: Multihash> EntryLog<T> {
+    pub fn new() -> EntryLog<T> {
+        EntryLog {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Rebuilds a log from recorded entries, checking that every digest matches the recomputed
+    /// one.
+    pub fn verify<I>(entries: I) -> Result<EntryLog<T>, ChainError>
+    where
+        I: IntoIterator<Item = (String, Value<T>, Seal<T>)>,
+    {
+        let mut log = EntryLog::new();
+
+        for (index, (timestamp, value, expected)) in entries.into_iter().enumerate() {
+            if !expected.matches(log.append(value, &timestamp)?.digest().as_slice()) {
+                return Err(ChainError::Mismatch { index });
+            }
+        }
+
+        Ok(log)
+    }
+
+    /// Appends the value recorded at the given RFC3339 timestamp and returns the new head.
+    ///
+    /// Timestamps can't go back in time: the timestamp can't be earlier than the one of the last
+    /// entry.
+    pub fn append(&mut self, value: Value<T>, timestamp: &str) -> Result<&Hash<T>, ChainError> {
+        let index = self.entries.len();
+        let instant =
+            parse_timestamp(timestamp).map_err(|error| ChainError::Timestamp { index, error })?;
+
+        if let Some(last) = self.entries.last() {
+            if parse_timestamp(&last.timestamp).ok() > Some(instant) {
+                return Err(ChainError::Unordered { index });
+            }
+        }
+
+        let hash = entry_hash(self.head(), timestamp, &value);
+        self.entries.push(Entry {
+            timestamp: timestamp.to_string(),
+            value,
+            hash,
+        });
+
+        Ok(&self.entries[index].hash)
+    }
+
+    /// The digest of the last entry.
+    pub fn head(&self) -> Option<&Hash<T>> {
+        self.entries.last().map(Entry::hash)
+    }
+
+    pub fn entries(&self) -> &[Entry<T>] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T: Multihash + Clone> EntryLog<T> {
+    /// A Merkle tree over the seals of the entries. Its consistency proofs show that the log
+    /// only grew by appending since a snapshot of a given length, and its inclusion proofs that
+    /// an entry is part of the log without sharing the rest.
+    pub fn tree(&self) -> MerkleTree<T> {
+        MerkleTree::new(T::default(), self.entries.iter().map(Entry::seal))
+    }
+}
+
+/// Digests an entry following the previous one, if any.
+fn entry_hash<T: Multihash>(
+    previous: Option<&Hash<T>>,
+    timestamp: &str,
+    value: &Value<T>,
+) -> Hash<T> {
+    let previous = match previous {
+        Some(hash) => Value::Redacted(Hash::new(T::default(), hash.digest().as_slice()).into()),
+        None => Value::Null,
+    };
+    let mut dict = DictMap::new();
+    dict.insert("previous".to_string(), previous);
+    dict.insert(
+        "timestamp".to_string(),
+        Value::Timestamp(timestamp.to_string()),
+    );
+    dict.insert(
+        "value".to_string(),
+        Value::Redacted(Seal::from_value(value, &T::default())),
+    );
+
+    Value::Dict(dict).digest(T::default())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ChainError {
+    /// The timestamp of the entry at the index is not valid.
+    Timestamp { index: usize, error: TimestampError },
+    /// The entry at the index is earlier than the previous one.
+    Unordered { index: usize },
+    /// The digest of the entry at the index doesn't match the recorded one.
+    Mismatch { index: usize },
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChainError::Timestamp { index, error } => {
+                write!(formatter, "entry {}: {}", index, error)
+            }
+            ChainError::Unordered { index } => {
+                write!(
+                    formatter,
+                    "entry {}: earlier than the previous entry",
+                    index
+                )
+            }
+            ChainError::Mismatch { index } => write!(formatter, "entry {}: digest mismatch", index),
+        }
+    }
+}
+
+impl error::Error for ChainError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ChainError::Timestamp { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dict;
+    use multihash::Sha2256;
+
+    #[test]
+    fn head_covers_the_previous_entry() {
+        let mut log: EntryLog<Sha2256> = EntryLog::new();
+        let first = log
+            .append(Value::from("foo"), "2018-10-13T15:50:00Z")
+            .unwrap()
+            .digest()
+            .as_slice()
+            .to_vec();
+        let value: Value<Sha2256> = dict! {
+            "previous" => Value::Null,
+            "timestamp" => Value::Timestamp("2018-10-13T15:50:00Z".into()),
+            "value" => "foo",
+        };
+
+        assert_eq!(first, value.digest(Sha2256).digest().as_slice());
+
+        log.append(Value::from("bar"), "2018-10-13T16:50:00+01:00")
+            .unwrap();
+        let value: Value<Sha2256> = dict! {
+            "previous" => Value::Redacted(Hash::new(Sha2256, first).into()),
+            "timestamp" => Value::Timestamp("2018-10-13T15:50:00Z".into()),
+            "value" => "bar",
+        };
+
+        assert_eq!(log.head(), Some(&value.digest(Sha2256)));
+    }
+
+    #[test]
+    fn verify() {
+        let mut log: EntryLog<Sha2256> = EntryLog::new();
+        log.append(Value::from("foo"), "2018-10-13T15:50:00Z")
+            .unwrap();
+        log.append(Value::from(1), "2018-10-13T15:50:00Z").unwrap();
+
+        let recorded =
+            |values: Vec<Value<Sha2256>>| -> Vec<(String, Value<Sha2256>, Seal<Sha2256>)> {
+                log.entries()
+                    .iter()
+                    .zip(values)
+                    .map(|(entry, value)| {
+                        let seal = Hash::new(Sha2256, entry.hash().digest().as_slice()).into();
+
+                        (entry.timestamp().to_string(), value, seal)
+                    })
+                    .collect()
+            };
+
+        assert_eq!(
+            EntryLog::verify(recorded(vec!["foo".into(), 1.into()])).map(|log| log.len()),
+            Ok(2)
+        );
+        assert_eq!(
+            EntryLog::verify(recorded(vec!["foo".into(), 2.into()])).map(|log| log.len()),
+            Err(ChainError::Mismatch { index: 1 })
+        );
+        assert_eq!(
+            EntryLog::verify(recorded(vec![1.into(), "foo".into()])).map(|log| log.len()),
+            Err(ChainError::Mismatch { index: 0 })
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        let mut log: EntryLog<Sha2256> = EntryLog::new();
+
+        assert_eq!(
+            log.append(Value::Null, "yesterday").map(|_| ()),
+            Err(ChainError::Timestamp {
+                index: 0,
+                error: TimestampError::Syntax
+            })
+        );
+
+        log.append(Value::Null, "2018-10-13T15:50:00Z").unwrap();
+
+        assert_eq!(
+            log.append(Value::Null, "2018-10-13T16:00:00+01:00")
+                .map(|_| ()),
+            Err(ChainError::Unordered { index: 1 })
+        );
+        assert_eq!(log.len(), 1);
+    }
+
+    #[test]
+    fn snapshots() {
+        let mut log: EntryLog<Sha2256> = EntryLog::new();
+        log.append(Value::from("foo"), "2018-10-13T15:50:00Z")
+            .unwrap();
+        log.append(Value::from("bar"), "2018-10-13T15:51:00Z")
+            .unwrap();
+        let snapshot = log.tree().root();
+        log.append(Value::from("baz"), "2018-10-13T15:52:00Z")
+            .unwrap();
+        let tree = log.tree();
+
+        assert!(tree
+            .consistency_proof(2)
+            .unwrap()
+            .verify(&snapshot, &tree.root()));
+        assert!(tree
+            .proof(2)
+            .unwrap()
+            .verify(&log.entries()[2].seal(), &tree.root()));
+    }
+}
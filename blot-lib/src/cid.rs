@@ -0,0 +1,130 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Content identifiers.
+//!
+//! A [CIDv1](https://github.com/multiformats/cid) wraps a multihash with the codec of the
+//! content it addresses so blot digests can be referenced from IPFS and IPLD tooling.
+//!
+//! ```
+//! use blot::cid::Codec;
+//! use blot::core::Blot;
+//! use blot::multihash::Sha2256;
+//!
+//! let cid = "foo".digest(Sha2256).to_cid(Codec::Raw);
+//!
+//! assert_eq!(
+//!     cid.to_string(),
+//!     "bafkreifgu3s6pa6dmpgzk2j6yge4e2bdcxmvnbuts5zym6nvmmc7eckqha"
+//! );
+//! ```
+//!
+//! Note that the digest is the Objecthash of the value, not the digest of its serialized bytes,
+//! so the CID can only be resolved by tools aware of blot.
+
+use encoding::Base;
+use multihash::{Hash, Multihash};
+use std::fmt;
+use uvar;
+
+/// The multicodec of the addressed content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    Raw,
+    Json,
+    DagJson,
+    DagCbor,
+    /// Any other multicodec.
+    Other(u64),
+}
+
+impl Codec {
+    pub fn code(&self) -> u64 {
+        match self {
+            Codec::Raw => 0x55,
+            Codec::Json => 0x0200,
+            Codec::DagJson => 0x0129,
+            Codec::DagCbor => 0x71,
+            Codec::Other(code) => *code,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Codec> {
+        match name {
+            "raw" => Some(Codec::Raw),
+            "json" => Some(Codec::Json),
+            "dag-json" => Some(Codec::DagJson),
+            "dag-cbor" => Some(Codec::DagCbor),
+            _ => None,
+        }
+    }
+}
+
+/// A version 1 content identifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cid {
+    codec: Codec,
+    multihash: Vec<u8>,
+}
+
+impl Cid {
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// The binary multihash, with its code and length encoded as unsigned varints.
+    pub fn multihash(&self) -> &[u8] {
+        &self.multihash
+    }
+
+    /// The binary CID: version, codec and multihash.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = uvar::encode_u64(1);
+        bytes.extend(uvar::encode_u64(self.codec.code()));
+        bytes.extend_from_slice(&self.multihash);
+
+        bytes
+    }
+
+    /// The CID as a multibase string in the given base.
+    pub fn to_multibase(&self, base: Base) -> String {
+        base.to_multibase(&self.to_bytes())
+    }
+}
+
+/// Formats the CID in base32, the default base for CIDv1.
+impl fmt::Display for Cid {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.to_multibase(Base::Base32))
+    }
+}
+
+impl<T: Multihash> Hash<T> {
+    /// Wraps the hash in a CIDv1 for content of the given codec.
+    pub fn to_cid(&self, codec: Codec) -> Cid {
+        Cid {
+            codec,
+            multihash: self.to_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::Blake2b512;
+
+    #[test]
+    fn multibyte_codes() {
+        let cid = "foo".digest(Blake2b512).to_cid(Codec::DagJson);
+        let bytes = cid.to_bytes();
+
+        assert_eq!(&bytes[..6], &[0x01, 0xa9, 0x02, 0xc0, 0xe4, 0x02]);
+        assert_eq!(bytes[6], 64);
+        assert_eq!(bytes.len(), 7 + 64);
+    }
+}
@@ -10,24 +10,109 @@
 
 use multihash::{Harvest, Hash, Multihash};
 use std;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
+use std::error;
+use std::fmt;
+use std::iter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::rc::Rc;
+use std::sync::Arc;
 use tag::Tag;
+use value::traverse::DigestError;
 
 /// Trait for blot implementations.
 pub trait Blot {
-    fn blot<T: Multihash>(&self, &T) -> Harvest;
+    fn blot<T: Multihash>(&self, digester: &T) -> Harvest;
+
+    /// Like [`blot`](#tymethod.blot), but fails with [`DigestError::NonFiniteFloat`] instead of
+    /// encoding a float the [`FloatPolicy`] of the digester rejects, e.g. NaN under
+    /// [`FloatPolicy::Strict`].
+    ///
+    /// Implementations holding floats must override it; the default cannot fail.
+    fn try_blot<T: Multihash>(&self, digester: &T) -> Result<Harvest, DigestError> {
+        Ok(self.blot(digester))
+    }
 
     fn digest<D: Multihash>(&self, digester: D) -> Hash<D> {
         let digest = self.blot(&digester);
         Hash::new(digester, digest)
     }
+
+    /// Like [`digest`](#method.digest), failing where [`try_blot`](#method.try_blot) does.
+    ///
+    /// ```
+    /// use blot::core::{Blot, FloatPolicy};
+    /// use blot::multihash::{FloatMultihash, Sha2256};
+    /// use blot::value::traverse::DigestError;
+    ///
+    /// let strict = FloatMultihash::new(Sha2256, FloatPolicy::Strict);
+    ///
+    /// assert!(1.5.try_digest(strict.clone()).is_ok());
+    /// assert_eq!(
+    ///     vec![1.5, f64::NAN].try_digest(strict),
+    ///     Err(DigestError::NonFiniteFloat)
+    /// );
+    /// ```
+    fn try_digest<D: Multihash>(&self, digester: D) -> Result<Hash<D>, DigestError> {
+        let digest = self.try_blot(&digester)?;
+        Ok(Hash::new(digester, digest))
+    }
 }
 
-impl<'a, T: ?Sized + Blot> Blot for &'a T {
+impl<T: ?Sized + Blot> Blot for &T {
     #[inline]
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
         T::blot(*self, digester)
     }
+    #[inline]
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        T::try_blot(*self, digester)
+    }
+}
+
+impl<T: ?Sized + Blot> Blot for Box<T> {
+    #[inline]
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        T::blot(self, digester)
+    }
+    #[inline]
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        T::try_blot(self, digester)
+    }
+}
+
+impl<T: ?Sized + Blot> Blot for Rc<T> {
+    #[inline]
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        T::blot(self, digester)
+    }
+    #[inline]
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        T::try_blot(self, digester)
+    }
+}
+
+impl<T: ?Sized + Blot> Blot for Arc<T> {
+    #[inline]
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        T::blot(self, digester)
+    }
+    #[inline]
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        T::try_blot(self, digester)
+    }
+}
+
+impl<'a, T: ?Sized + Blot + ToOwned> Blot for Cow<'a, T> {
+    #[inline]
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        T::blot(self, digester)
+    }
+    #[inline]
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        T::try_blot(self, digester)
+    }
 }
 
 impl Blot for str {
@@ -42,32 +127,51 @@ impl Blot for String {
     }
 }
 
+/// A character is a one-character unicode string.
+impl Blot for char {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        let mut buffer = [0; 4];
+        digester.digest_primitive(Tag::Unicode, self.encode_utf8(&mut buffer).as_bytes())
+    }
+}
+
 impl Blot for [u8] {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
         digester.digest_primitive(Tag::Raw, self)
     }
 }
 
-impl<'a, T: Blot> Blot for Option<T> {
+impl<T: Blot> Blot for Option<T> {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
         match self {
             None => digester.digest_primitive(Tag::Null, "".as_bytes()),
             Some(a) => a.blot(digester),
         }
     }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        match self {
+            None => Ok(self.blot(digester)),
+            Some(a) => a.try_blot(digester),
+        }
+    }
 }
 
-impl<'a> Blot for bool {
+impl Blot for bool {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
         let string = if *self { "1" } else { "0" };
         digester.digest_primitive(Tag::Bool, string.as_bytes())
     }
 }
 
+// Integers are tagged decimal strings whatever their width, so `5u128` and `5i64` have the same
+// digest. Values beyond the `i64` range hash to their full decimal representation; beware that
+// implementations limited to 64-bit integers cannot reproduce them.
 macro_rules! blot_integer (($type:ident) => {
     impl Blot for $type {
         fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-            digester.digest_primitive(Tag::Integer, self.to_string().as_bytes())
+            let mut buffer = itoa::Buffer::new();
+            digester.digest_primitive(Tag::Integer, buffer.format(*self).as_bytes())
         }
     }
 });
@@ -76,44 +180,226 @@ blot_integer!(u8);
 blot_integer!(u16);
 blot_integer!(u32);
 blot_integer!(u64);
+blot_integer!(u128);
 blot_integer!(usize);
 blot_integer!(i8);
 blot_integer!(i16);
 blot_integer!(i32);
 blot_integer!(i64);
+blot_integer!(i128);
 blot_integer!(isize);
 
+fn blot_list<'a, D, I, T>(digester: &D, list: I) -> Harvest
+where
+    D: Multihash,
+    I: IntoIterator<Item = &'a T>,
+    T: 'a + Blot,
+{
+    digester.digest_collection(Tag::List, list.into_iter().map(|item| item.blot(digester)))
+}
+
+fn try_blot_list<'a, D, I, T>(digester: &D, list: I) -> Result<Harvest, DigestError>
+where
+    D: Multihash,
+    I: IntoIterator<Item = &'a T>,
+    T: 'a + Blot,
+{
+    let list = list
+        .into_iter()
+        .map(|item| item.try_blot(digester))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(digester.digest_collection(Tag::List, list))
+}
+
+fn blot_set<'a, D, I, T>(digester: &D, set: I) -> Harvest
+where
+    D: Multihash,
+    I: IntoIterator<Item = &'a T>,
+    T: 'a + Blot,
+{
+    let mut list: Vec<Harvest> = set.into_iter().map(|item| item.blot(digester)).collect();
+
+    list.sort_unstable();
+
+    digester.digest_collection(Tag::Set, list)
+}
+
+fn try_blot_set<'a, D, I, T>(digester: &D, set: I) -> Result<Harvest, DigestError>
+where
+    D: Multihash,
+    I: IntoIterator<Item = &'a T>,
+    T: 'a + Blot,
+{
+    let mut list = set
+        .into_iter()
+        .map(|item| item.try_blot(digester))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    list.sort_unstable();
+
+    Ok(digester.digest_collection(Tag::Set, list))
+}
+
+fn try_blot_entries<'a, D, I, K, V>(digester: &D, entries: I) -> Result<Harvest, DigestError>
+where
+    D: Multihash,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+    K: 'a + Blot,
+    V: 'a + Blot,
+{
+    let entries = entries
+        .into_iter()
+        .map(|(k, v)| Ok((k.try_blot(digester)?, v.try_blot(digester)?)))
+        .collect::<Result<Vec<_>, DigestError>>()?;
+
+    Ok(digest_entries(digester, entries))
+}
+
+/// Big integers are hashed like any other integer, as their decimal representation.
+#[cfg(feature = "num-bigint")]
+impl Blot for ::num_bigint::BigInt {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester.digest_primitive(Tag::Integer, self.to_str_radix(10).as_bytes())
+    }
+}
+
+/// Decimals are hashed as their canonical decimal string, without trailing zeros nor negative
+/// zero, so `1.50` and `1.5` have the same digest. They never match the digest of a float.
+#[cfg(feature = "rust_decimal")]
+impl Blot for ::rust_decimal::Decimal {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester.digest_primitive(Tag::Decimal, self.normalize().to_string().as_bytes())
+    }
+}
+
+// Network addresses are hashed as unicode strings in their standard textual form: dotted decimal
+// for IPv4 and the RFC 5952 compressed lowercase form for IPv6, e.g. `::ffff:192.0.2.1`. Socket
+// addresses append the port, with IPv6 addresses in brackets: `[2001:db8::1]:8080`.
+macro_rules! blot_display (($type:ty) => {
+    impl Blot for $type {
+        fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+            digester.digest_primitive(Tag::Unicode, self.to_string().as_bytes())
+        }
+    }
+});
+
+blot_display!(IpAddr);
+blot_display!(Ipv4Addr);
+blot_display!(Ipv6Addr);
+blot_display!(SocketAddr);
+blot_display!(SocketAddrV4);
+blot_display!(SocketAddrV6);
+
+/// UUIDs are hashed as unicode strings in their lowercase hyphenated form, e.g.
+/// `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+#[cfg(feature = "uuid")]
+impl Blot for ::uuid::Uuid {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        let mut buffer = ::uuid::Uuid::encode_buffer();
+        let text = self.hyphenated().encode_lower(&mut buffer);
+
+        digester.digest_primitive(Tag::Unicode, text.as_bytes())
+    }
+}
+
+/// URLs are hashed as unicode strings in their serialization after parsing, so equivalent
+/// inputs like `HTTP://Example.com` and `http://example.com/` have the same digest.
+#[cfg(feature = "url")]
+impl Blot for ::url::Url {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester.digest_primitive(Tag::Unicode, self.as_str().as_bytes())
+    }
+}
+
 impl<T: Blot> Blot for Vec<T> {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        let list: Vec<Vec<u8>> = self
-            .iter()
-            .map(|item| {
-                item.blot(digester)
-                    .as_ref()
-                    .iter()
-                    .map(|x| *x)
-                    .collect::<Vec<u8>>()
-            }).collect();
+        blot_list(digester, self)
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_list(digester, self)
+    }
+}
+
+impl<T: Blot> Blot for VecDeque<T> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        blot_list(digester, self)
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_list(digester, self)
+    }
+}
+
+impl<T: Blot> Blot for LinkedList<T> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        blot_list(digester, self)
+    }
 
-        digester.digest_collection(Tag::List, list)
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_list(digester, self)
     }
 }
 
+/// Arrays are lists, including byte arrays. Byte slices, `[u8]`, are raw bytes instead, which is
+/// why there is no implementation for slices of other types: use a `Vec` or an array.
+impl<T: Blot, const N: usize> Blot for [T; N] {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        blot_list(digester, self)
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_list(digester, self)
+    }
+}
+
+macro_rules! blot_tuple (($($name:ident)+) => {
+    impl<$($name: Blot),+> Blot for ($($name,)+) {
+        #[allow(non_snake_case)]
+        fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+            let ($(ref $name,)+) = *self;
+            digester.digest_collection(Tag::List, [$($name.blot(digester)),+])
+        }
+
+        #[allow(non_snake_case)]
+        fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+            let ($(ref $name,)+) = *self;
+            Ok(digester.digest_collection(Tag::List, [$($name.try_blot(digester)?),+]))
+        }
+    }
+});
+
+blot_tuple!(A);
+blot_tuple!(A B);
+blot_tuple!(A B C);
+blot_tuple!(A B C E);
+blot_tuple!(A B C E F);
+blot_tuple!(A B C E F G);
+blot_tuple!(A B C E F G H);
+blot_tuple!(A B C E F G H I);
+blot_tuple!(A B C E F G H I J);
+blot_tuple!(A B C E F G H I J K);
+blot_tuple!(A B C E F G H I J K L);
+blot_tuple!(A B C E F G H I J K L M);
+
 impl<T: Blot + Eq + std::hash::Hash> Blot for HashSet<T> {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        let mut list: Vec<Vec<u8>> = self
-            .iter()
-            .map(|item| {
-                item.blot(digester)
-                    .as_ref()
-                    .iter()
-                    .map(|x| *x)
-                    .collect::<Vec<u8>>()
-            }).collect();
+        blot_set(digester, self)
+    }
 
-        list.sort_unstable();
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_set(digester, self)
+    }
+}
 
-        digester.digest_collection(Tag::Set, list)
+impl<T: Blot + Ord> Blot for BTreeSet<T> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        blot_set(digester, self)
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_set(digester, self)
     }
 }
 
@@ -123,19 +409,36 @@ where
     V: Blot + PartialEq,
 {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        let mut list: Vec<Vec<u8>> = self
+        let entries = self
             .iter()
-            .map(|(k, v)| {
-                let mut res: Vec<u8> = Vec::with_capacity(64);
-                res.extend_from_slice(k.blot(digester).as_ref());
-                res.extend_from_slice(v.blot(digester).as_ref());
+            .map(|(k, v)| (k.blot(digester), v.blot(digester)))
+            .collect();
 
-                res
-            }).collect();
+        digest_entries(digester, entries)
+    }
 
-        list.sort_unstable();
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_entries(digester, self)
+    }
+}
 
-        digester.digest_collection(Tag::Dict, list)
+#[cfg(feature = "indexmap")]
+impl<K, V, S> Blot for ::indexmap::IndexMap<K, V, S>
+where
+    K: Blot + Eq + std::hash::Hash,
+    V: Blot + PartialEq,
+{
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        let entries = self
+            .iter()
+            .map(|(k, v)| (k.blot(digester), v.blot(digester)))
+            .collect();
+
+        digest_entries(digester, entries)
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_entries(digester, self)
     }
 }
 
@@ -145,96 +448,266 @@ where
     V: Blot + PartialEq,
 {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        let mut list: Vec<Vec<u8>> = self
+        let entries = self
             .iter()
-            .map(|(k, v)| {
-                let mut res: Vec<u8> = Vec::with_capacity(64);
-                res.extend_from_slice(k.blot(digester).as_ref());
-                res.extend_from_slice(v.blot(digester).as_ref());
+            .map(|(k, v)| (k.blot(digester), v.blot(digester)))
+            .collect();
 
-                res
-            }).collect();
-
-        list.sort_unstable();
+        digest_entries(digester, entries)
+    }
 
-        digester.digest_collection(Tag::Dict, list)
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        try_blot_entries(digester, self)
     }
 }
 
+/// Digests dictionary entries sorted by the concatenation of their key and value digests,
+/// without concatenating them.
+pub(crate) fn digest_entries<D, K, V>(digester: &D, mut entries: Vec<(K, V)>) -> Harvest
+where
+    D: Multihash,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    entries.sort_unstable_by(|(a, x), (b, y)| {
+        let left = a.as_ref().iter().chain(x.as_ref());
+        let right = b.as_ref().iter().chain(y.as_ref());
+
+        left.cmp(right)
+    });
+
+    let list = entries
+        .iter()
+        .flat_map(|(key, value)| iter::once(key.as_ref()).chain(iter::once(value.as_ref())));
+
+    digester.digest_collection(Tag::Dict, list)
+}
+
 impl Blot for f32 {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
         (*self as f64).blot(digester)
     }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        (*self as f64).try_blot(digester)
+    }
 }
 
+/// Floats are encoded following the [`FloatPolicy`] of the digester.
 impl Blot for f64 {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        if self.is_nan() {
-            digester.digest_primitive(Tag::Float, "NaN".as_bytes())
-        } else if self.is_infinite() {
-            let s = if self.is_sign_negative() {
+        digester.float_policy().blot_float(*self, digester)
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        digester.float_policy().try_blot_float(*self, digester)
+    }
+}
+
+/// How floats are encoded before hashing.
+///
+/// Every policy hashes negative zero as zero. Except for [`FloatPolicy::Ieee754`], NaN and the
+/// infinities are hashed as the strings `NaN`, `Infinity` and `-Infinity`. Pick a policy per
+/// digest with a [`FloatMultihash`](../multihash/struct.FloatMultihash.html).
+///
+/// ```
+/// use blot::core::{Blot, FloatPolicy};
+/// use blot::multihash::{FloatMultihash, Sha2256};
+///
+/// let digester = FloatMultihash::new(Sha2256, FloatPolicy::Decimal);
+///
+/// assert_ne!(1.5.digest(digester).digest(), 1.5.digest(Sha2256).digest());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FloatPolicy {
+    /// The Objecthash encoding of the sign, the binary exponent and the mantissa bits, see
+    /// [`float_normalize`].
+    #[default]
+    Objecthash,
+    /// The Objecthash encoding, rejecting NaN and the infinities like several other Objecthash
+    /// implementations do. Only fallible digests, [`Blot::try_digest`] and
+    /// [`Value::digest_with`](../value/enum.Value.html#method.digest_with), can reject them; the
+    /// infallible [`Blot::digest`] encodes them like [`FloatPolicy::Objecthash`].
+    Strict,
+    /// The IEEE 754 binary64 bit pattern, as 8 big-endian bytes. Every NaN has the bit pattern
+    /// of [`f64::NAN`].
+    Ieee754,
+    /// The shortest decimal that reads back as the same float, in scientific notation, e.g.
+    /// `1.5e0` or `1e-10`.
+    Decimal,
+}
+
+impl FloatPolicy {
+    /// Whether the policy accepts the float: any float but NaN and the infinities in strict mode.
+    pub fn permits(&self, f: f64) -> bool {
+        f.is_finite() || *self != FloatPolicy::Strict
+    }
+
+    /// Like [`blot_float`](#method.blot_float), failing with [`DigestError::NonFiniteFloat`] if
+    /// the policy does not permit the float.
+    pub fn try_blot_float<D: Multihash>(
+        &self,
+        f: f64,
+        digester: &D,
+    ) -> Result<Harvest, DigestError> {
+        if self.permits(f) {
+            Ok(self.blot_float(f, digester))
+        } else {
+            Err(DigestError::NonFiniteFloat)
+        }
+    }
+
+    pub fn blot_float<D: Multihash>(&self, f: f64, digester: &D) -> Harvest {
+        // Folds negative zero.
+        let f = if f == 0.0 { 0.0 } else { f };
+
+        if f.is_nan() && *self != FloatPolicy::Ieee754 {
+            return digester.digest_primitive(Tag::Float, "NaN".as_bytes());
+        }
+
+        if f.is_infinite() && *self != FloatPolicy::Ieee754 {
+            let s = if f.is_sign_negative() {
                 "-Infinity"
             } else {
                 "Infinity"
             };
-            digester.digest_primitive(Tag::Float, s.as_bytes())
-        } else {
-            digester.digest_primitive(Tag::Float, float_normalize(*self).as_bytes())
+
+            return digester.digest_primitive(Tag::Float, s.as_bytes());
+        }
+
+        match self {
+            FloatPolicy::Objecthash | FloatPolicy::Strict => {
+                let mut buffer = FloatBuffer::new();
+                normalize_into(f, &mut buffer);
+                digester.digest_primitive(Tag::Float, buffer.as_bytes())
+            }
+            FloatPolicy::Ieee754 => {
+                let bits = if f.is_nan() { f64::NAN } else { f }.to_bits();
+                digester.digest_primitive(Tag::Float, &bits.to_be_bytes())
+            }
+            FloatPolicy::Decimal => {
+                digester.digest_primitive(Tag::Float, format!("{:e}", f).as_bytes())
+            }
         }
     }
 }
 
-pub fn float_normalize(mut f: f64) -> String {
-    if f == 0.0 {
-        return "+0:".to_owned();
+/// The Objecthash normalization of a float: the sign, the exponent `e` such that `f / 2^e` is in
+/// `(0.5, 1]`, a colon and the binary digits of `f / 2^e`, e.g. `+1:1` for `1.0` and `+2:011`
+/// for `3.0`. Zero, either sign, is `+0:`.
+///
+/// ```
+/// use blot::core::{float_normalize, FloatError};
+///
+/// assert_eq!(float_normalize(-1.5), Ok("-1:011".to_string()));
+/// assert_eq!(float_normalize(f64::NAN), Err(FloatError::NotFinite));
+/// ```
+pub fn float_normalize(f: f64) -> Result<String, FloatError> {
+    if !f.is_finite() {
+        return Err(FloatError::NotFinite);
     }
 
-    let mut s = String::new();
+    let mut buffer = FloatBuffer::new();
+    normalize_into(f, &mut buffer);
 
-    // sign
-    if f < 0. {
-        s.push('-');
-        f = -f;
-    } else {
-        s.push('+');
+    Ok(String::from_utf8(buffer.as_bytes().to_vec()).expect("Normalized floats to be ASCII"))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FloatError {
+    /// NaN and the infinities have no normalized form.
+    NotFinite,
+}
+
+impl fmt::Display for FloatError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FloatError::NotFinite => write!(formatter, "NaN and infinities cannot be normalized"),
+        }
+    }
+}
+
+impl error::Error for FloatError {}
+
+/// Stack buffer for normalized floats: a sign, an exponent of at most 5 characters, a colon and
+/// at most 54 mantissa digits.
+struct FloatBuffer {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl FloatBuffer {
+    fn new() -> FloatBuffer {
+        FloatBuffer {
+            bytes: [0; 64],
+            len: 0,
+        }
     }
 
-    // exponent
-    let mut e = 0;
+    fn push(&mut self, byte: u8) {
+        self.bytes[self.len] = byte;
+        self.len += 1;
+    }
 
-    while f > 1. {
-        f = f / 2.;
-        e = e + 1;
+    fn extend(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.push(*byte);
+        }
     }
 
-    while f <= 0.5 {
-        f = f * 2.;
-        e = e - 1;
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
     }
+}
 
-    s.push_str(&e.to_string());
-    s.push(':');
+/// Writes the Objecthash normalization of a finite float without allocating.
+///
+/// The reference implementations halve or double the float until it is in `(0.5, 1]` and then
+/// peel off its binary digits. Both steps are exact for every finite float, so reading the
+/// exponent and the digits straight from the IEEE 754 representation gives the same result
+/// with a bounded number of steps.
+fn normalize_into(f: f64, s: &mut FloatBuffer) {
+    debug_assert!(f.is_finite());
 
-    // mantissa
-    assert!(f <= 1.);
-    assert!(f > 0.5);
-    // TODO: Return Result
+    if f == 0.0 {
+        s.extend(b"+0:");
+        return;
+    }
 
-    while f != 0. {
-        if f >= 1. {
-            s.push('1');
-            f = f - 1.;
-        } else {
-            s.push('0');
-        }
+    s.push(if f < 0.0 { b'-' } else { b'+' });
 
-        assert!(f < 1.);
-        assert!(s.len() < 1000);
+    // |f| = mantissa * 2^exponent, with an odd mantissa of at most 53 bits.
+    let bits = f.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mut mantissa, mut exponent) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased - 1075)
+    };
+    let zeros = mantissa.trailing_zeros();
+    mantissa >>= zeros;
+    exponent += zeros as i32;
+
+    let length = 64 - mantissa.leading_zeros() as i32;
 
-        f = f * 2.;
+    if mantissa == 1 {
+        // A power of two normalizes to exactly 1.
+        s.extend(itoa::Buffer::new().format(exponent).as_bytes());
+        s.extend(b":1");
+        return;
     }
 
-    s
+    s.extend(itoa::Buffer::new().format(exponent + length).as_bytes());
+    s.extend(b":0");
+
+    for position in (0..length).rev() {
+        s.push(if (mantissa >> position) & 1 == 1 {
+            b'1'
+        } else {
+            b'0'
+        });
+    }
 }
 
 #[cfg(test)]
@@ -287,7 +760,7 @@ mod tests {
         let bytes =
             Vec::from_hex("6b18693874513ba13da54d61aafa7cad0c8f5573f3431d6f1c04b07ddb27d6bb")
                 .unwrap();
-        let actual = format!("{}", (&bytes[..]).digest(Sha2256));
+        let actual = format!("{}", bytes[..].digest(Sha2256));
         assert_eq!(actual, expected);
     }
 
@@ -321,6 +794,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn wide_int_blot() {
+        assert_eq!(42u128.digest(Sha2256), 42i64.digest(Sha2256));
+        assert_eq!((-42i128).digest(Sha2256), (-42i64).digest(Sha2256));
+        assert_eq!(
+            u128::MAX.digest(Sha2256).digest(),
+            &Sha2256.digest_primitive(Tag::Integer, u128::MAX.to_string().as_bytes())
+        );
+    }
+
+    #[test]
+    fn char_blot() {
+        assert_eq!('ϓ'.digest(Sha2256), "ϓ".digest(Sha2256));
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn bigint_blot() {
+        use num_bigint::BigInt;
+
+        let big: BigInt = "-123456789012345678901234567890".parse().unwrap();
+
+        assert_eq!(BigInt::from(42).digest(Sha2256), 42.digest(Sha2256));
+        assert_eq!(
+            big.digest(Sha2256).digest(),
+            &Sha2256.digest_primitive(Tag::Integer, b"-123456789012345678901234567890")
+        );
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn decimal_blot() {
+        use rust_decimal::Decimal;
+
+        let decimal = |input: &str| input.parse::<Decimal>().unwrap().digest(Sha2256);
+
+        assert_eq!(decimal("1.50"), decimal("1.5"));
+        assert_eq!(decimal("-0.00"), decimal("0"));
+        assert_eq!(
+            decimal("100.010").digest(),
+            &Sha2256.digest_primitive(Tag::Decimal, b"100.01")
+        );
+        assert_ne!(decimal("1.5"), 1.5.digest(Sha2256));
+    }
+
+    #[test]
+    fn net_blot() {
+        let ipv6: IpAddr = "2001:DB8:0:0:0:0:0:1".parse().unwrap();
+        let socket: SocketAddr = "[2001:db8::1]:8080".parse().unwrap();
+
+        assert_eq!(ipv6.digest(Sha2256), "2001:db8::1".digest(Sha2256));
+        assert_eq!(
+            Ipv4Addr::new(192, 0, 2, 1).digest(Sha2256),
+            "192.0.2.1".digest(Sha2256)
+        );
+        assert_eq!(socket.digest(Sha2256), "[2001:db8::1]:8080".digest(Sha2256));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_blot() {
+        use uuid::Uuid;
+
+        let uuid = Uuid::parse_str("67E55044-10B1-426F-9247-BB680E5FE0C8").unwrap();
+
+        assert_eq!(
+            uuid.digest(Sha2256),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8".digest(Sha2256)
+        );
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn url_blot() {
+        use url::Url;
+
+        let url = Url::parse("HTTP://Example.com").unwrap();
+
+        assert_eq!(url.digest(Sha2256), "http://example.com/".digest(Sha2256));
+    }
+
     #[test]
     fn zero_float_blot() {
         let expected = "122060101d8c9cb988411468e38909571f357daa67bff5a7b0a3f9ae295cd4aba33d";
@@ -354,6 +908,168 @@ mod tests {
         }
     }
 
+    #[test]
+    fn strict_try_digest() {
+        use multihash::FloatMultihash;
+        use std::collections::BTreeMap;
+        use value::traverse::DigestError;
+
+        let strict = FloatMultihash::new(Sha2256, FloatPolicy::Strict);
+        let mut map = BTreeMap::new();
+        map.insert("a", Some(f64::NEG_INFINITY));
+
+        assert_eq!(f64::NAN.try_blot(&strict), Err(DigestError::NonFiniteFloat));
+        assert_eq!(
+            (f32::INFINITY,).try_blot(&strict),
+            Err(DigestError::NonFiniteFloat)
+        );
+        assert_eq!(map.try_blot(&strict), Err(DigestError::NonFiniteFloat));
+        assert_eq!(f64::NAN.try_blot(&Sha2256), Ok(f64::NAN.blot(&Sha2256)));
+        assert_eq!(
+            vec![1.5, 2.0].try_blot(&strict),
+            Ok(vec![1.5, 2.0].blot(&Sha2256))
+        );
+    }
+
+    #[test]
+    fn float_policies() {
+        use multihash::FloatMultihash;
+
+        let digest = |f: f64, policy| {
+            let digester = FloatMultihash::new(Sha2256, policy);
+            f.blot(&digester)
+        };
+        let primitive = |bytes: &[u8]| Sha2256.digest_primitive(Tag::Float, bytes);
+
+        assert_eq!(digest(1.5, FloatPolicy::Objecthash), 1.5.blot(&Sha2256));
+        assert_eq!(
+            digest(1.5, FloatPolicy::Ieee754),
+            primitive(&[0x3f, 0xf8, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(digest(-0.0, FloatPolicy::Ieee754), primitive(&[0; 8]));
+        assert_eq!(
+            digest(f64::INFINITY, FloatPolicy::Ieee754),
+            primitive(&[0x7f, 0xf0, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            digest(-f64::NAN, FloatPolicy::Ieee754),
+            digest(f64::NAN, FloatPolicy::Ieee754)
+        );
+        assert_eq!(
+            digest(f64::NAN, FloatPolicy::Strict),
+            f64::NAN.blot(&Sha2256)
+        );
+        assert!(!FloatPolicy::Strict.permits(f64::NEG_INFINITY));
+        assert!(FloatPolicy::Objecthash.permits(f64::NAN));
+        assert_eq!(digest(1.5, FloatPolicy::Decimal), primitive(b"1.5e0"));
+        assert_eq!(digest(1e-10, FloatPolicy::Decimal), primitive(b"1e-10"));
+        assert_eq!(digest(-0.0, FloatPolicy::Decimal), primitive(b"0e0"));
+        assert_eq!(
+            digest(f64::NAN, FloatPolicy::Decimal),
+            f64::NAN.blot(&Sha2256)
+        );
+        assert_eq!(
+            (1.5, "x").blot(&FloatMultihash::new(Sha2256, FloatPolicy::Decimal)),
+            Sha2256.digest_collection(Tag::List, vec![primitive(b"1.5e0"), "x".blot(&Sha2256)])
+        );
+    }
+
+    #[test]
+    fn float_normalize_extremes() {
+        use std::f64;
+
+        assert_eq!(float_normalize(5e-324), Ok("+-1074:1".to_string()));
+        assert_eq!(
+            float_normalize(f64::MAX),
+            Ok(format!("+1024:0{}", "1".repeat(53)))
+        );
+        assert_eq!(
+            float_normalize(-f64::MIN_POSITIVE),
+            Ok("--1022:1".to_string())
+        );
+        assert_eq!(float_normalize(-0.0), Ok("+0:".to_string()));
+        assert_eq!(float_normalize(f64::INFINITY), Err(FloatError::NotFinite));
+        assert_eq!(float_normalize(f64::NAN), Err(FloatError::NotFinite));
+    }
+
+    /// Port of `floatNormalize` from the Go and Python reference implementations.
+    fn reference_normalize(original: f64) -> Option<String> {
+        if original == 0.0 {
+            return Some("+0:".to_string());
+        }
+
+        let mut f = original;
+        let mut s = String::from(if f < 0.0 { "-" } else { "+" });
+
+        if f < 0.0 {
+            f = -f;
+        }
+
+        let mut e = 0;
+
+        while f > 1.0 {
+            f /= 2.0;
+            e += 1;
+        }
+
+        while f <= 0.5 {
+            f *= 2.0;
+            e -= 1;
+        }
+
+        s.push_str(&format!("{}:", e));
+
+        if f > 1.0 || f <= 0.5 {
+            return None;
+        }
+
+        while f != 0.0 {
+            if f >= 1.0 {
+                s.push('1');
+                f -= 1.0;
+            } else {
+                s.push('0');
+            }
+
+            if f >= 1.0 || s.len() >= 1000 {
+                return None;
+            }
+
+            f *= 2.0;
+        }
+
+        Some(s)
+    }
+
+    #[test]
+    fn float_normalize_powers_of_two() {
+        for exponent in -1074..1024 {
+            let f = if exponent < -1022 {
+                f64::from_bits(1 << (exponent + 1074))
+            } else {
+                f64::from_bits(((exponent + 1023) as u64) << 52)
+            };
+
+            assert_eq!(float_normalize(f).ok(), reference_normalize(f));
+            assert_eq!(float_normalize(f), Ok(format!("+{}:1", exponent)));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn float_normalize_matches_reference(f in proptest::num::f64::ANY) {
+            prop_assume!(f.is_finite());
+            prop_assert_eq!(float_normalize(f).ok(), reference_normalize(f));
+        }
+
+        #[test]
+        fn float_normalize_subnormals(bits in 1u64..(1 << 52), negative: bool) {
+            let f = f64::from_bits(bits | if negative { 1 << 63 } else { 0 });
+
+            prop_assert_eq!(float_normalize(f).ok(), reference_normalize(f));
+        }
+    }
+
     #[test]
     fn empty_list_blot() {
         let expected = "1220acac86c0e609ca906f632b0e2dacccb2b77d22b0621f20ebece1a4835b93f6f0";
@@ -397,6 +1113,59 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn sequences_as_lists() {
+        let expected = vec!["foo", "bar"].digest(Sha2256);
+
+        assert_eq!(["foo", "bar"].digest(Sha2256), expected);
+        assert_eq!(("foo", "bar").digest(Sha2256), expected);
+        assert_eq!(
+            vec!["foo", "bar"]
+                .into_iter()
+                .collect::<VecDeque<_>>()
+                .digest(Sha2256),
+            expected
+        );
+        assert_eq!(
+            vec!["foo", "bar"]
+                .into_iter()
+                .collect::<LinkedList<_>>()
+                .digest(Sha2256),
+            expected
+        );
+        assert_eq!(
+            (1u8, "foo", true).digest(Sha2256).digest(),
+            &Sha2256.digest_collection(
+                Tag::List,
+                vec![1.blot(&Sha2256), "foo".blot(&Sha2256), true.blot(&Sha2256)]
+            )
+        );
+    }
+
+    #[test]
+    fn pointers_blot_inner() {
+        let expected = "foo".digest(Sha2256);
+        let owned: Cow<str> = Cow::Owned("foo".to_string());
+
+        assert_eq!(Box::<str>::from("foo").digest(Sha2256), expected);
+        assert_eq!(Rc::new("foo").digest(Sha2256), expected);
+        assert_eq!(Arc::<str>::from("foo").digest(Sha2256), expected);
+        assert_eq!(Cow::Borrowed("foo").digest(Sha2256), expected);
+        assert_eq!(owned.digest(Sha2256), expected);
+        assert_eq!(
+            vec![Box::new(1), Box::new(2)].digest(Sha2256),
+            vec![1, 2].digest(Sha2256)
+        );
+    }
+
+    #[test]
+    fn btree_set_blot() {
+        let hash_set: HashSet<&str> = ["foo", "bar"].iter().cloned().collect();
+        let btree_set: BTreeSet<&str> = ["foo", "bar"].iter().cloned().collect();
+
+        assert_eq!(btree_set.digest(Sha2256), hash_set.digest(Sha2256));
+    }
+
     #[test]
     fn empty_dict_blot() {
         let expected = "122018ac3e7343f016890c510e93f935261169d9e3f565436429830faf0934f4f8e4";
@@ -413,4 +1182,21 @@ mod tests {
         let actual = format!("{}", dict.digest(Sha2256));
         assert_eq!(actual, expected);
     }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_map_blot() {
+        use indexmap::IndexMap;
+
+        let mut forward: IndexMap<&str, u8> = IndexMap::new();
+        let mut backward: IndexMap<&str, u8> = IndexMap::new();
+        let hash_map: HashMap<&str, u8> = [("a", 1), ("b", 2)].iter().cloned().collect();
+        forward.insert("a", 1);
+        forward.insert("b", 2);
+        backward.insert("b", 2);
+        backward.insert("a", 1);
+
+        assert_eq!(forward.digest(Sha2256), hash_map.digest(Sha2256));
+        assert_eq!(backward.digest(Sha2256), hash_map.digest(Sha2256));
+    }
 }
@@ -0,0 +1,456 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Date and time canonicalization.
+//!
+//! Timestamps are hashed as canonical RFC3339 strings: in UTC with the `Z` suffix and fractional
+//! seconds without trailing zeros, omitted when zero. So the same instant has the same digest
+//! whatever the offset or precision it was written with.
+//!
+//! ```
+//! use blot::core::Blot;
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//!
+//! let utc: Value<Sha2256> = Value::Timestamp("2018-10-13T15:50:00Z".into());
+//! let offset: Value<Sha2256> = Value::Timestamp("2018-10-13T16:50:00.000+01:00".into());
+//!
+//! assert_eq!(utc.digest(Sha2256), offset.digest(Sha2256));
+//! ```
+//!
+//! With the `datetime` feature, [`SystemTime`] and the chrono types `DateTime<Utc>` and
+//! `NaiveDate` implement [`Blot`] as canonical timestamps. A date is the timestamp of its
+//! midnight.
+
+#[cfg(feature = "datetime")]
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+#[cfg(feature = "datetime")]
+use core::Blot;
+use multihash::{Harvest, Multihash};
+use std::error;
+use std::fmt;
+#[cfg(feature = "datetime")]
+use std::time::{SystemTime, UNIX_EPOCH};
+use tag::Tag;
+#[cfg(feature = "datetime")]
+use value::Value;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Why a string is not a valid RFC3339 timestamp.
+#[derive(Debug, PartialEq)]
+pub enum TimestampError {
+    /// The input is not shaped like an RFC3339 timestamp.
+    Syntax,
+    /// A field is out of range, e.g. the 13th month or February 30th.
+    OutOfRange,
+    /// The fractional seconds are finer than nanoseconds.
+    Precision,
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimestampError::Syntax => write!(formatter, "not an RFC3339 timestamp"),
+            TimestampError::OutOfRange => write!(formatter, "date or time out of range"),
+            TimestampError::Precision => write!(formatter, "precision finer than nanoseconds"),
+        }
+    }
+}
+
+impl error::Error for TimestampError {}
+
+/// Renders seconds and nanoseconds since the Unix epoch as a canonical RFC3339 timestamp.
+///
+/// Years outside 0000–9999 are not valid RFC3339 but are rendered all the same.
+///
+/// ```
+/// use blot::datetime::format_timestamp;
+///
+/// assert_eq!(format_timestamp(0, 0), "1970-01-01T00:00:00Z");
+/// assert_eq!(format_timestamp(-1, 500_000_000), "1969-12-31T23:59:59.5Z");
+/// ```
+pub fn format_timestamp(seconds: i64, nanos: u32) -> String {
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let mut output = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+
+    if nanos > 0 {
+        let fraction = format!("{:09}", nanos);
+        output.push('.');
+        output.push_str(fraction.trim_end_matches('0'));
+    }
+
+    output.push('Z');
+    output
+}
+
+/// Rewrites an RFC3339 timestamp in its canonical form. Returns `None` if the input is not a
+/// valid timestamp or has a precision finer than nanoseconds.
+///
+/// ```
+/// use blot::datetime::canonical_timestamp;
+///
+/// assert_eq!(
+///     canonical_timestamp("2018-10-13T16:50:00.120-01:30"),
+///     Some("2018-10-13T18:20:00.12Z".into())
+/// );
+/// assert_eq!(canonical_timestamp("2018-13-45T00:00:00Z"), None);
+/// ```
+pub fn canonical_timestamp(input: &str) -> Option<String> {
+    parse_timestamp(input)
+        .map(|(seconds, nanos)| format_timestamp(seconds, nanos))
+        .ok()
+}
+
+/// Digests a timestamp in its canonical form, or as-is if it cannot be parsed.
+pub(crate) fn blot_timestamp<D: Multihash>(digester: &D, input: &str) -> Harvest {
+    match canonical_timestamp(input) {
+        Some(canonical) => digester.digest_primitive(Tag::Timestamp, canonical.as_bytes()),
+        None => digester.digest_primitive(Tag::Timestamp, input.as_bytes()),
+    }
+}
+
+/// Parses an RFC3339 timestamp into seconds and nanoseconds since the Unix epoch.
+pub(crate) fn parse_timestamp(input: &str) -> Result<(i64, u32), TimestampError> {
+    let bytes = input.as_bytes();
+    let number = |start: usize, end: usize| -> Result<i64, TimestampError> {
+        match input.get(start..end) {
+            Some(digits) if digits.bytes().all(|byte| byte.is_ascii_digit()) => {
+                digits.parse().map_err(|_| TimestampError::Syntax)
+            }
+            _ => Err(TimestampError::Syntax),
+        }
+    };
+
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || (bytes[10] != b'T' && bytes[10] != b't')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return Err(TimestampError::Syntax);
+    }
+
+    let (year, month, day) = (number(0, 4)?, number(5, 7)?, number(8, 10)?);
+    let (hour, minute, second) = (number(11, 13)?, number(14, 16)?, number(17, 19)?);
+
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(TimestampError::OutOfRange);
+    }
+
+    let mut rest = &input[19..];
+    let mut nanos = 0;
+
+    if rest.starts_with('.') {
+        let length = rest[1..].bytes().take_while(u8::is_ascii_digit).count();
+        let fraction = &rest[1..=length];
+
+        if length == 0 {
+            return Err(TimestampError::Syntax);
+        }
+
+        if fraction.bytes().skip(9).any(|byte| byte != b'0') {
+            return Err(TimestampError::Precision);
+        }
+
+        for (position, byte) in fraction.bytes().take(9).enumerate() {
+            nanos += u32::from(byte - b'0') * 10u32.pow(8 - position as u32);
+        }
+
+        rest = &rest[1 + length..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return Err(TimestampError::Syntax),
+            };
+            let (hours, minutes) = (
+                number(input.len() - 5, input.len() - 3)?,
+                number(input.len() - 2, input.len())?,
+            );
+
+            if hours > 23 || minutes > 59 {
+                return Err(TimestampError::OutOfRange);
+            }
+
+            sign * (hours * 3600 + minutes * 60)
+        }
+        _ => return Err(TimestampError::Syntax),
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second
+            - offset;
+
+    Ok((seconds, nanos))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts a proleptic Gregorian date to days since the Unix epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month as u32, day as u32)
+}
+
+#[cfg(feature = "datetime")]
+fn system_time_timestamp(time: &SystemTime) -> String {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => format_timestamp(elapsed.as_secs() as i64, elapsed.subsec_nanos()),
+        Err(err) => {
+            let before = err.duration();
+            let nanos = before.subsec_nanos();
+
+            if nanos == 0 {
+                format_timestamp(-(before.as_secs() as i64), 0)
+            } else {
+                format_timestamp(-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "datetime")]
+/// Leap seconds are folded into the last nanosecond of the previous second.
+fn utc_timestamp(time: &DateTime<Utc>) -> String {
+    format_timestamp(
+        time.timestamp(),
+        time.timestamp_subsec_nanos().min(999_999_999),
+    )
+}
+
+#[cfg(feature = "datetime")]
+fn date_timestamp(date: &NaiveDate) -> String {
+    let midnight = date.and_time(NaiveTime::MIN).and_utc();
+
+    format_timestamp(midnight.timestamp(), 0)
+}
+
+#[cfg(feature = "datetime")]
+impl Blot for SystemTime {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester.digest_primitive(Tag::Timestamp, system_time_timestamp(self).as_bytes())
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl Blot for DateTime<Utc> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester.digest_primitive(Tag::Timestamp, utc_timestamp(self).as_bytes())
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl Blot for NaiveDate {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester.digest_primitive(Tag::Timestamp, date_timestamp(self).as_bytes())
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl<T: Multihash> From<SystemTime> for Value<T> {
+    fn from(time: SystemTime) -> Value<T> {
+        Value::Timestamp(system_time_timestamp(&time))
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl<T: Multihash> From<DateTime<Utc>> for Value<T> {
+    fn from(time: DateTime<Utc>) -> Value<T> {
+        Value::Timestamp(utc_timestamp(&time))
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl<T: Multihash> From<NaiveDate> for Value<T> {
+    fn from(date: NaiveDate) -> Value<T> {
+        Value::Timestamp(date_timestamp(&date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "datetime")]
+    use chrono::{TimeZone, Timelike};
+    use multihash::Sha2256;
+    #[cfg(feature = "datetime")]
+    use std::time::Duration;
+
+    #[test]
+    fn canonical_forms() {
+        let pairs = [
+            ("2018-10-13T15:50:00Z", "2018-10-13T15:50:00Z"),
+            ("2018-10-13t16:50:00+01:00", "2018-10-13T15:50:00Z"),
+            (
+                "2018-10-13T15:50:00.100000000000z",
+                "2018-10-13T15:50:00.1Z",
+            ),
+            ("2000-03-01T00:30:00+01:00", "2000-02-29T23:30:00Z"),
+            (
+                "1969-12-31T23:59:59.999999999-00:00",
+                "1969-12-31T23:59:59.999999999Z",
+            ),
+        ];
+
+        for (input, expected) in pairs.iter() {
+            assert_eq!(canonical_timestamp(input).as_deref(), Some(*expected));
+        }
+    }
+
+    #[test]
+    fn invalid_timestamps() {
+        for input in &[
+            "2018-13-01T00:00:00Z",
+            "2019-02-29T00:00:00Z",
+            "2018-10-13T24:00:00Z",
+            "2018-10-13T23:59:60Z",
+            "2018-10-13T15:50:00",
+            "2018-10-13T15:50:00.Z",
+            "2018-10-13T15:50:00.0000000001Z",
+            "2018-10-13T15:50:00+1:00",
+            "2018-10-13 15:50:00Z",
+            "+018-10-13T15:50:00Z",
+        ] {
+            assert_eq!(canonical_timestamp(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let pairs = [
+            ("2018-10-13", TimestampError::Syntax),
+            ("2018-10-13T15:50:00+01", TimestampError::Syntax),
+            ("2018-02-30T15:50:00Z", TimestampError::OutOfRange),
+            ("2018-10-13T15:50:00+24:00", TimestampError::OutOfRange),
+            ("2018-10-13T15:50:00.1234567891Z", TimestampError::Precision),
+        ];
+
+        for (input, expected) in pairs.iter() {
+            assert_eq!(parse_timestamp(input).as_ref(), Err(expected));
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        for seconds in (-100_000..100_000).map(|day| day * 86_399 + 7) {
+            let timestamp = format_timestamp(seconds, 0);
+
+            assert_eq!(parse_timestamp(&timestamp), Ok((seconds, 0)));
+        }
+    }
+
+    #[test]
+    fn blot_invalid_as_is() {
+        assert_eq!(
+            blot_timestamp(&Sha2256, "2018-13-45T00:00:00Z"),
+            Sha2256.digest_primitive(Tag::Timestamp, b"2018-13-45T00:00:00Z")
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn canonical_rendering() {
+        let instant = Utc
+            .with_ymd_and_hms(2018, 10, 13, 15, 50, 0)
+            .unwrap()
+            .with_nanosecond(120_000_000)
+            .unwrap();
+        let value: Value<Sha2256> = instant.into();
+
+        assert_eq!(value, Value::Timestamp("2018-10-13T15:50:00.12Z".into()));
+        assert_eq!(instant.digest(Sha2256), value.digest(Sha2256));
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn system_time_matches_chrono() {
+        let pairs = [
+            (
+                UNIX_EPOCH + Duration::new(1_539_445_800, 5),
+                1_539_445_800,
+                5,
+            ),
+            (
+                UNIX_EPOCH - Duration::new(86_400 * 366, 1),
+                -86_400 * 366 - 1,
+                999_999_999,
+            ),
+        ];
+
+        for (time, seconds, nanos) in pairs.iter() {
+            let instant = Utc.timestamp_opt(*seconds, *nanos).unwrap();
+
+            assert_eq!(time.digest(Sha2256), instant.digest(Sha2256));
+        }
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn dates_at_midnight() {
+        let date = NaiveDate::from_ymd_opt(1600, 2, 29).unwrap();
+
+        assert_eq!(date_timestamp(&date), "1600-02-29T00:00:00Z");
+        assert_eq!(
+            date.digest(Sha2256),
+            Utc.with_ymd_and_hms(1600, 2, 29, 0, 0, 0)
+                .unwrap()
+                .digest(Sha2256)
+        );
+    }
+}
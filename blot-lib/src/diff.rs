@@ -0,0 +1,261 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Structural diff by digest.
+//!
+//! Two values are compared through their [digest trees](../value/tree/index.html): subtrees with
+//! the same digest are equal and skipped, so only the minimal set of differing paths is reported.
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate blot;
+//! use blot::diff::{diff, PathDiff};
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//!
+//! # fn main() {
+//! let a: Value<Sha2256> = list!["foo", "bar"];
+//! let b: Value<Sha2256> = list!["foo", "baz", "qux"];
+//!
+//! assert_eq!(
+//!     diff(&a, &b),
+//!     vec![
+//!         PathDiff::Changed { path: "[1]".parse().unwrap() },
+//!         PathDiff::Added { path: "[2]".parse().unwrap() },
+//!     ]
+//! );
+//! # }
+//! ```
+
+use multihash::Multihash;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use tag::Tag;
+use value::path::{JsonPath, Segment};
+use value::tree::DigestTree;
+use value::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathDiff {
+    /// The node only exists in the second value.
+    Added { path: JsonPath },
+    /// The node only exists in the first value.
+    Removed { path: JsonPath },
+    /// The node exists in both values with different digests.
+    Changed { path: JsonPath },
+}
+
+impl PathDiff {
+    pub fn path(&self) -> &JsonPath {
+        match self {
+            PathDiff::Added { path } | PathDiff::Removed { path } | PathDiff::Changed { path } => {
+                path
+            }
+        }
+    }
+}
+
+/// Formats the difference as `+ path`, `- path` or `~ path`.
+impl fmt::Display for PathDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let sign = match self {
+            PathDiff::Added { .. } => "+",
+            PathDiff::Removed { .. } => "-",
+            PathDiff::Changed { .. } => "~",
+        };
+
+        if self.path().is_root() {
+            write!(formatter, "{} (root)", sign)
+        } else {
+            write!(formatter, "{} {}", sign, self.path())
+        }
+    }
+}
+
+/// Returns the paths where `a` and `b` differ.
+///
+/// Dictionaries are compared by key and lists by position. Sets are compared by membership:
+/// items missing from the other set are reported with their position in their own set. Nodes of
+/// different types, leaves and seals are reported as changed.
+pub fn diff<T: Multihash + Clone>(a: &Value<T>, b: &Value<T>) -> Vec<PathDiff> {
+    let mut diffs = Vec::new();
+
+    diff_trees(
+        &a.digest_tree(T::default()),
+        &b.digest_tree(T::default()),
+        &mut diffs,
+    );
+
+    diffs
+}
+
+fn last_segment<T: Multihash>(tree: &DigestTree<T>) -> &Segment {
+    tree.path()
+        .segments()
+        .last()
+        .expect("Child nodes to have a path")
+}
+
+fn diff_trees<T: Multihash>(a: &DigestTree<T>, b: &DigestTree<T>, diffs: &mut Vec<PathDiff>) {
+    if a.hash() == b.hash() {
+        return;
+    }
+
+    match (a.tag(), b.tag()) {
+        (Some(Tag::Dict), Some(Tag::Dict)) => {
+            let others: HashMap<&Segment, &DigestTree<T>> = b
+                .children()
+                .iter()
+                .map(|other| (last_segment(other), other))
+                .collect();
+
+            for child in a.children() {
+                match others.get(last_segment(child)) {
+                    Some(other) => diff_trees(child, other, diffs),
+                    None => diffs.push(PathDiff::Removed {
+                        path: child.path().clone(),
+                    }),
+                }
+            }
+
+            let children: HashSet<&Segment> = a.children().iter().map(last_segment).collect();
+
+            for other in b.children() {
+                if !children.contains(last_segment(other)) {
+                    diffs.push(PathDiff::Added {
+                        path: other.path().clone(),
+                    });
+                }
+            }
+        }
+        (Some(Tag::List), Some(Tag::List)) => {
+            for (child, other) in a.children().iter().zip(b.children()) {
+                diff_trees(child, other, diffs);
+            }
+
+            for child in a.children().iter().skip(b.children().len()) {
+                diffs.push(PathDiff::Removed {
+                    path: child.path().clone(),
+                });
+            }
+
+            for other in b.children().iter().skip(a.children().len()) {
+                diffs.push(PathDiff::Added {
+                    path: other.path().clone(),
+                });
+            }
+        }
+        (Some(Tag::Set), Some(Tag::Set)) => {
+            let digests = |tree: &DigestTree<T>| -> HashSet<Vec<u8>> {
+                tree.children()
+                    .iter()
+                    .map(|child| child.hash().digest().as_slice().to_vec())
+                    .collect()
+            };
+            let (left, right) = (digests(a), digests(b));
+
+            for child in a.children() {
+                if !right.contains(child.hash().digest().as_slice()) {
+                    diffs.push(PathDiff::Removed {
+                        path: child.path().clone(),
+                    });
+                }
+            }
+
+            for other in b.children() {
+                if !left.contains(other.hash().digest().as_slice()) {
+                    diffs.push(PathDiff::Added {
+                        path: other.path().clone(),
+                    });
+                }
+            }
+        }
+        _ => diffs.push(PathDiff::Changed {
+            path: a.path().clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+    use value::DictMap;
+
+    fn dict(entries: Vec<(&str, Value<Sha2256>)>) -> Value<Sha2256> {
+        let map: DictMap<Sha2256> = entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+
+        Value::Dict(map)
+    }
+
+    fn path(input: &str) -> JsonPath {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn identical() {
+        let a = dict(vec![("a", 1.into())]);
+
+        assert_eq!(diff(&a, &a.clone()), vec![]);
+    }
+
+    #[test]
+    fn nested_dicts() {
+        let a = dict(vec![
+            ("a", dict(vec![("x", 1.into()), ("y", 2.into())])),
+            ("b", "same".into()),
+            ("c", Value::Null),
+        ]);
+        let b = dict(vec![
+            ("a", dict(vec![("x", 1.into()), ("y", 3.into())])),
+            ("b", "same".into()),
+            ("d", Value::Null),
+        ]);
+
+        assert_eq!(
+            diff(&a, &b),
+            vec![
+                PathDiff::Changed { path: path("a.y") },
+                PathDiff::Removed { path: path("c") },
+                PathDiff::Added { path: path("d") },
+            ]
+        );
+    }
+
+    #[test]
+    fn sets_by_membership() {
+        let a: Value<Sha2256> = Value::Set(vec![1.into(), 2.into(), 3.into()]);
+        let b: Value<Sha2256> = Value::Set(vec![3.into(), 4.into(), 1.into()]);
+
+        assert_eq!(
+            diff(&a, &b),
+            vec![
+                PathDiff::Removed { path: path("[1]") },
+                PathDiff::Added { path: path("[1]") },
+            ]
+        );
+    }
+
+    #[test]
+    fn different_types() {
+        let a: Value<Sha2256> = Value::List(vec![1.into()]);
+        let b: Value<Sha2256> = Value::Set(vec![1.into()]);
+
+        assert_eq!(diff(&a, &b), vec![PathDiff::Changed { path: path("") }]);
+        assert_eq!(diff(&a, &b)[0].to_string(), "~ (root)");
+    }
+
+    #[test]
+    fn seals_match_their_node() {
+        let a = dict(vec![("a", 1.into()), ("b", 2.into())]);
+        let b = a.redact_paths(&[path("a")]);
+
+        assert_eq!(diff(&a, &b), vec![]);
+    }
+}
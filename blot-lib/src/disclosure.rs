@@ -0,0 +1,357 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Selective disclosure bundles.
+//!
+//! A [`Disclosure`] shares a slice of a document while proving it belongs to a known root
+//! digest. It holds a copy of the document where every node is sealed except the disclosed
+//! subtrees, the paths to those subtrees and the root digest.
+//!
+//! Bundles are exchanged as JSON:
+//!
+//! ```json
+//! {
+//!   "root": "1220…",
+//!   "paths": ["a.b[2]"],
+//!   "document": {"a": {"b": ["7712…", "7712…", "disclosed"]}, "c": "7712…"}
+//! }
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! extern crate blot;
+//! extern crate serde_json;
+//!
+//! use blot::core::Blot;
+//! use blot::disclosure::{disclose, Disclosure};
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//!
+//! # fn main() {
+//! let value: Value<Sha2256> =
+//!     serde_json::from_str(r#"{"name": "Alice", "age": 30, "city": "Lisbon"}"#).unwrap();
+//! let bundle = disclose(&value, &["city".parse().unwrap()]);
+//!
+//! let exported = bundle.to_json().to_string();
+//! let imported: Disclosure<Sha2256> = Disclosure::from_str(&exported).unwrap();
+//!
+//! assert!(imported.verify(&value.digest(Sha2256)).is_ok());
+//! # }
+//! ```
+
+use core::Blot;
+use hex::FromHex;
+use multihash::{Hash, Multihash};
+use seal::{Seal, SealError};
+use serde_json::{self, Map};
+use std::error;
+use std::fmt;
+use value::path::{JsonPath, PathError, Segment};
+use value::Value;
+
+#[derive(Debug)]
+pub enum DisclosureError {
+    /// The bundle is not valid JSON or lacks one of its fields.
+    Malformed(String),
+    /// The bundle root is not a valid multihash for the expected algorithm.
+    InvalidRoot(SealError),
+    /// One of the bundle paths can't be parsed.
+    InvalidPath(PathError),
+    /// The document digest doesn't match the bundle root.
+    RootMismatch,
+    /// The bundle root is not the expected one.
+    UnexpectedRoot,
+    /// A disclosed path is missing or sealed in the document.
+    Undisclosed { path: JsonPath },
+}
+
+impl fmt::Display for DisclosureError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DisclosureError::Malformed(reason) => write!(formatter, "malformed bundle: {}", reason),
+            DisclosureError::InvalidRoot(err) => write!(formatter, "invalid root: {}", err),
+            DisclosureError::InvalidPath(err) => write!(formatter, "invalid path: {}", err),
+            DisclosureError::RootMismatch => {
+                write!(formatter, "document digest doesn't match the root")
+            }
+            DisclosureError::UnexpectedRoot => write!(formatter, "unexpected root"),
+            DisclosureError::Undisclosed { path } => {
+                write!(formatter, "path '{}' is not disclosed", path)
+            }
+        }
+    }
+}
+
+impl error::Error for DisclosureError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DisclosureError::InvalidRoot(err) => Some(err),
+            DisclosureError::InvalidPath(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SealError> for DisclosureError {
+    fn from(err: SealError) -> DisclosureError {
+        DisclosureError::InvalidRoot(err)
+    }
+}
+
+impl From<PathError> for DisclosureError {
+    fn from(err: PathError) -> DisclosureError {
+        DisclosureError::InvalidPath(err)
+    }
+}
+
+impl From<serde_json::Error> for DisclosureError {
+    fn from(err: serde_json::Error) -> DisclosureError {
+        DisclosureError::Malformed(err.to_string())
+    }
+}
+
+/// A selective disclosure bundle. See [the module level documentation](index.html) for more.
+#[derive(Debug, PartialEq)]
+pub struct Disclosure<T: Multihash> {
+    root: Hash<T>,
+    paths: Vec<JsonPath>,
+    document: Value<T>,
+}
+
+/// Builds a bundle disclosing the subtrees at the given paths. Everything else is sealed.
+pub fn disclose<T: Multihash + Clone>(value: &Value<T>, paths: &[JsonPath]) -> Disclosure<T> {
+    let segments: Vec<&[Segment]> = paths.iter().map(|path| path.segments()).collect();
+
+    Disclosure {
+        root: value.digest(T::default()),
+        paths: paths.to_vec(),
+        document: prune(value, &segments),
+    }
+}
+
+fn prune<T: Multihash + Clone>(value: &Value<T>, paths: &[&[Segment]]) -> Value<T> {
+    if paths.iter().any(|path| path.is_empty()) {
+        return value.clone();
+    }
+
+    let children = |segment: &Segment| -> Vec<&[Segment]> {
+        paths
+            .iter()
+            .filter(|path| &path[0] == segment)
+            .map(|path| &path[1..])
+            .collect()
+    };
+
+    match value {
+        Value::Dict(dict) if !paths.is_empty() => Value::Dict(
+            dict.iter()
+                .map(|(key, child)| {
+                    let pruned = prune(child, &children(&Segment::Key(key.clone())));
+                    (key.clone(), pruned)
+                })
+                .collect(),
+        ),
+        Value::List(list) if !paths.is_empty() => Value::List(
+            list.iter()
+                .enumerate()
+                .map(|(index, child)| prune(child, &children(&Segment::Index(index))))
+                .collect(),
+        ),
+        Value::Set(list) if !paths.is_empty() => Value::Set(
+            list.iter()
+                .enumerate()
+                .map(|(index, child)| prune(child, &children(&Segment::Index(index))))
+                .collect(),
+        ),
+        Value::Redacted(_) => value.clone(),
+        _ => value.redact_paths(&[JsonPath::root()]),
+    }
+}
+
+impl<T: Multihash> Disclosure<T> {
+    pub fn root(&self) -> &Hash<T> {
+        &self.root
+    }
+
+    pub fn paths(&self) -> &[JsonPath] {
+        &self.paths
+    }
+
+    /// The partially sealed document.
+    pub fn document(&self) -> &Value<T> {
+        &self.document
+    }
+
+    /// Returns the disclosed subtrees alongside their path.
+    pub fn disclosed(&self) -> Vec<(&JsonPath, &Value<T>)> {
+        self.paths
+            .iter()
+            .filter_map(|path| {
+                self.document
+                    .node_at(path.segments())
+                    .map(|node| (path, node))
+            })
+            .collect()
+    }
+
+    /// Checks the bundle is internally consistent: the document digest matches the bundle root
+    /// and every path resolves to an unsealed node.
+    pub fn check(&self) -> Result<(), DisclosureError> {
+        let digest = self.document.blot(&T::default());
+
+        if digest.as_slice() != self.root.digest().as_slice() {
+            return Err(DisclosureError::RootMismatch);
+        }
+
+        for path in &self.paths {
+            match self.document.node_at(path.segments()) {
+                None | Some(Value::Redacted(_)) => {
+                    return Err(DisclosureError::Undisclosed { path: path.clone() })
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the bundle is consistent and proves membership to the given root.
+    pub fn verify(&self, root: &Hash<T>) -> Result<(), DisclosureError> {
+        if root.digest() != self.root.digest() {
+            return Err(DisclosureError::UnexpectedRoot);
+        }
+
+        self.check()
+    }
+
+    /// Exports the bundle as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut map = Map::new();
+
+        map.insert("root".into(), self.root.to_string().into());
+        map.insert(
+            "paths".into(),
+            self.paths
+                .iter()
+                .map(|path| serde_json::Value::String(path.to_string()))
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        map.insert(
+            "document".into(),
+            serde_json::to_value(&self.document).expect("Serializable document"),
+        );
+
+        serde_json::Value::Object(map)
+    }
+
+    /// Imports a bundle from its JSON form. The bundle is checked for consistency.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &str) -> Result<Disclosure<T>, DisclosureError> {
+        let mut json: serde_json::Value = serde_json::from_str(input)?;
+
+        let root = json
+            .get("root")
+            .and_then(|root| root.as_str())
+            .ok_or_else(|| DisclosureError::Malformed("missing root".into()))?;
+        let bytes = Vec::from_hex(root).map_err(SealError::from)?;
+        let seal: Seal<T> = Seal::from_bytes_without_mark(&bytes)?;
+
+        if !seal.is_native() {
+            return Err(DisclosureError::InvalidRoot(SealError::InvalidStamp {
+                actual: seal.tag().code(),
+                expected: T::default().code(),
+            }));
+        }
+
+        let paths = json
+            .get("paths")
+            .and_then(|paths| paths.as_array())
+            .ok_or_else(|| DisclosureError::Malformed("missing paths".into()))?
+            .iter()
+            .map(|path| {
+                path.as_str()
+                    .ok_or_else(|| DisclosureError::Malformed("paths must be strings".into()))
+                    .and_then(|path| Ok(path.parse()?))
+            })
+            .collect::<Result<Vec<JsonPath>, DisclosureError>>()?;
+
+        let document = json
+            .get_mut("document")
+            .map(|document| document.take())
+            .ok_or_else(|| DisclosureError::Malformed("missing document".into()))?;
+
+        let disclosure = Disclosure {
+            root: Hash::new(T::default(), seal.digest().to_vec()),
+            paths,
+            document: serde_json::from_value(document)?,
+        };
+
+        disclosure.check()?;
+
+        Ok(disclosure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+
+    fn document() -> Value<Sha2256> {
+        serde_json::from_str(r#"{"a": {"b": [1, 2, "three"], "c": true}, "d": "foo"}"#).unwrap()
+    }
+
+    #[test]
+    fn disclose_seals_everything_else() {
+        let value = document();
+        let bundle = disclose(&value, &["a.b[2]".parse().unwrap()]);
+
+        let expected: Value<Sha2256> = Value::String("three".into());
+        assert_eq!(bundle.disclosed(), vec![(&bundle.paths[0], &expected)]);
+        assert_eq!(bundle.verify(&value.digest(Sha2256)).unwrap(), ());
+
+        match bundle.document().node_at(&[Segment::Key("d".into())]) {
+            Some(Value::Redacted(_)) => {}
+            other => panic!("expected a seal, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let value = document();
+        let bundle = disclose(&value, &["a.c".parse().unwrap(), "d".parse().unwrap()]);
+        let exported = bundle.to_json().to_string();
+        let imported: Disclosure<Sha2256> = Disclosure::from_str(&exported).unwrap();
+
+        assert_eq!(imported, bundle);
+    }
+
+    #[test]
+    fn tampered_document() {
+        let value = document();
+        let bundle = disclose(&value, &["d".parse().unwrap()]);
+        let exported = bundle.to_json().to_string().replace("\"foo\"", "\"bar\"");
+
+        match Disclosure::<Sha2256>::from_str(&exported) {
+            Err(DisclosureError::RootMismatch) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unexpected_root() {
+        let value = document();
+        let bundle = disclose(&value, &["d".parse().unwrap()]);
+        let other: Value<Sha2256> = Value::Null;
+
+        match bundle.verify(&other.digest(Sha2256)) {
+            Err(DisclosureError::UnexpectedRoot) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
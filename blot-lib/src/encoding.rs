@@ -0,0 +1,467 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Digest encodings.
+//!
+//! Hashes are displayed as hexadecimal multihashes by default. This module encodes them in
+//! other bases, optionally prefixed with their [multibase](https://github.com/multiformats/multibase)
+//! code so the base can be detected when decoding.
+//!
+//! ```
+//! use blot::core::Blot;
+//! use blot::encoding::Base;
+//! use blot::multihash::Sha2256;
+//!
+//! let hash = "foo".digest(Sha2256);
+//! let encoded = hash.to_multibase(Base::Base58Btc);
+//!
+//! assert!(encoded.starts_with("zQm"));
+//! assert_eq!(Base::from_multibase(&encoded).unwrap().1, hash.to_bytes());
+//! ```
+
+use multihash::{Harvest, Hash, Multihash};
+use std::error;
+use std::fmt;
+use std::str;
+
+const BASE16_ALPHABET: &[u8; 16] = b"0123456789abcdef";
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, PartialEq)]
+pub enum EncodingError {
+    /// The input is empty, so it has no multibase prefix.
+    Empty,
+    /// The multibase prefix is not one of the supported bases.
+    UnknownBase(char),
+    /// The character at the given position is not part of the alphabet of the base.
+    InvalidCharacter { character: char, position: usize },
+    /// The input length is not valid for the base.
+    InvalidLength,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingError::Empty => write!(formatter, "empty input"),
+            EncodingError::UnknownBase(code) => write!(formatter, "unknown multibase '{}'", code),
+            EncodingError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                formatter,
+                "invalid character '{}' at position {}",
+                character, position
+            ),
+            EncodingError::InvalidLength => write!(formatter, "invalid length"),
+        }
+    }
+}
+
+impl error::Error for EncodingError {}
+
+/// A supported base.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base {
+    /// Lowercase hexadecimal.
+    Base16,
+    /// RFC 4648 lowercase base32 without padding, the default for CIDs.
+    Base32,
+    /// Bitcoin's base58 alphabet, as used by IPFS.
+    Base58Btc,
+    /// RFC 4648 base64 without padding.
+    Base64,
+}
+
+impl Base {
+    /// The multibase prefix of the base.
+    pub fn code(&self) -> char {
+        match self {
+            Base::Base16 => 'f',
+            Base::Base32 => 'b',
+            Base::Base58Btc => 'z',
+            Base::Base64 => 'm',
+        }
+    }
+
+    pub fn from_code(code: char) -> Option<Base> {
+        match code {
+            'f' => Some(Base::Base16),
+            'b' => Some(Base::Base32),
+            'z' => Some(Base::Base58Btc),
+            'm' => Some(Base::Base64),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Base::Base16 => encode_base16(bytes),
+            Base::Base32 => encode_base32(bytes),
+            Base::Base58Btc => encode_base58(bytes),
+            Base::Base64 => encode_base64(bytes),
+        }
+    }
+
+    pub fn decode(&self, input: &str) -> Result<Vec<u8>, EncodingError> {
+        match self {
+            Base::Base16 => decode_base16(input),
+            Base::Base32 => decode_base32(input),
+            Base::Base58Btc => decode_base58(input),
+            Base::Base64 => decode_base64(input),
+        }
+    }
+
+    /// Encodes the bytes prefixed with the multibase code.
+    pub fn to_multibase(&self, bytes: &[u8]) -> String {
+        format!("{}{}", self.code(), self.encode(bytes))
+    }
+
+    /// Decodes a multibase string, detecting its base from the prefix.
+    pub fn from_multibase(input: &str) -> Result<(Base, Vec<u8>), EncodingError> {
+        let mut chars = input.chars();
+        let code = chars.next().ok_or(EncodingError::Empty)?;
+        let base = Base::from_code(code).ok_or(EncodingError::UnknownBase(code))?;
+
+        Ok((base, base.decode(chars.as_str())?))
+    }
+}
+
+impl Harvest {
+    /// Encodes the bare digest in lowercase hexadecimal, like `Display` but in one allocation.
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    ///
+    /// let hash = "foo".digest(Sha2256);
+    ///
+    /// assert_eq!(hash.digest().to_hex(), hash.digest().to_string());
+    /// ```
+    pub fn to_hex(&self) -> String {
+        encode_base16(self.as_slice())
+    }
+
+    /// Encodes the bare digest as a multibase string.
+    pub fn to_multibase(&self, base: Base) -> String {
+        base.to_multibase(self.as_slice())
+    }
+}
+
+impl<T: Multihash> Hash<T> {
+    /// Encodes the multihash, tag and digest, as a multibase string.
+    pub fn to_multibase(&self, base: Base) -> String {
+        base.to_multibase(&self.to_bytes())
+    }
+}
+
+fn encode_base16(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len() * 2);
+    write_hex(&mut output, bytes).expect("Writing to a String to succeed");
+
+    output
+}
+
+/// Writes the bytes in lowercase hexadecimal, going through a stack buffer instead of
+/// formatting byte by byte.
+pub(crate) fn write_hex<W: fmt::Write>(writer: &mut W, bytes: &[u8]) -> fmt::Result {
+    let mut buffer = [0; 128];
+
+    for chunk in bytes.chunks(buffer.len() / 2) {
+        for (index, byte) in chunk.iter().enumerate() {
+            buffer[index * 2] = BASE16_ALPHABET[usize::from(byte >> 4)];
+            buffer[index * 2 + 1] = BASE16_ALPHABET[usize::from(byte & 0x0f)];
+        }
+
+        let hex = str::from_utf8(&buffer[..chunk.len() * 2]).expect("Hexadecimal to be ASCII");
+        writer.write_str(hex)?;
+    }
+
+    Ok(())
+}
+
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = buffer << 8 | u32::from(*byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    output
+}
+
+fn decode_base32(input: &str) -> Result<Vec<u8>, EncodingError> {
+    let input = input.trim_end_matches('=');
+
+    if [1, 3, 6].contains(&(input.len() % 8)) {
+        return Err(EncodingError::InvalidLength);
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for (position, character) in input.chars().enumerate() {
+        let value = alphabet_position(BASE32_ALPHABET, character.to_ascii_lowercase(), position)?;
+
+        buffer = buffer << 5 | value;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(output)
+}
+
+fn encode_base58(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    // Base 58 digits, least significant first.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+
+    for byte in &bytes[zeros..] {
+        let mut carry = u32::from(*byte);
+
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut output = String::with_capacity(zeros + digits.len());
+    output.extend((0..zeros).map(|_| '1'));
+    output.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|digit| BASE58_ALPHABET[*digit as usize] as char),
+    );
+
+    output
+}
+
+fn decode_base58(input: &str) -> Result<Vec<u8>, EncodingError> {
+    let zeros = input.chars().take_while(|c| *c == '1').count();
+    // Bytes, least significant first.
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+
+    for (position, character) in input.chars().enumerate().skip(zeros) {
+        let mut carry = alphabet_position(BASE58_ALPHABET, character, position)?;
+
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut output = vec![0; zeros];
+    output.extend(bytes.iter().rev());
+
+    Ok(output)
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity((bytes.len() * 4).div_ceil(3));
+
+    for chunk in bytes.chunks(3) {
+        let buffer = chunk.iter().enumerate().fold(0u32, |acc, (i, byte)| {
+            acc | u32::from(*byte) << (16 - i * 8)
+        });
+
+        for i in 0..=chunk.len() {
+            let index = (buffer >> (18 - i * 6)) & 0x3f;
+            output.push(BASE64_ALPHABET[index as usize] as char);
+        }
+    }
+
+    output
+}
+
+fn decode_base64(input: &str) -> Result<Vec<u8>, EncodingError> {
+    let input = input.trim_end_matches('=');
+
+    if input.len() % 4 == 1 {
+        return Err(EncodingError::InvalidLength);
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for (position, character) in input.chars().enumerate() {
+        buffer = buffer << 6 | alphabet_position(BASE64_ALPHABET, character, position)?;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(output)
+}
+
+fn decode_base16(input: &str) -> Result<Vec<u8>, EncodingError> {
+    if !input.len().is_multiple_of(2) {
+        return Err(EncodingError::InvalidLength);
+    }
+
+    let mut output = Vec::with_capacity(input.len() / 2);
+    let mut high = 0;
+
+    for (position, character) in input.chars().enumerate() {
+        let value = character
+            .to_digit(16)
+            .ok_or(EncodingError::InvalidCharacter {
+                character,
+                position,
+            })? as u8;
+
+        if position % 2 == 0 {
+            high = value << 4;
+        } else {
+            output.push(high | value);
+        }
+    }
+
+    Ok(output)
+}
+
+fn alphabet_position(
+    alphabet: &[u8],
+    character: char,
+    position: usize,
+) -> Result<u32, EncodingError> {
+    alphabet
+        .iter()
+        .position(|c| *c as char == character)
+        .map(|index| index as u32)
+        .ok_or(EncodingError::InvalidCharacter {
+            character,
+            position,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base16_vectors() {
+        let long = [0xab; 100];
+        let vectors: &[(&[u8], &str)] =
+            &[(b"", ""), (b"\x00\x0f\xf0", "000ff0"), (b"foo", "666f6f")];
+
+        for (bytes, encoded) in vectors {
+            assert_eq!(&Base::Base16.encode(bytes), encoded);
+            assert_eq!(&Base::Base16.decode(encoded).unwrap(), bytes);
+        }
+
+        assert_eq!(Base::Base16.encode(&long), "ab".repeat(100));
+    }
+
+    #[test]
+    fn base32_vectors() {
+        let vectors: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "my"),
+            (b"fo", "mzxq"),
+            (b"foo", "mzxw6"),
+            (b"foob", "mzxw6yq"),
+            (b"fooba", "mzxw6ytb"),
+            (b"foobar", "mzxw6ytboi"),
+        ];
+
+        for (bytes, encoded) in vectors {
+            assert_eq!(&Base::Base32.encode(bytes), encoded);
+            assert_eq!(&Base::Base32.decode(encoded).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn base58_vectors() {
+        let vectors: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"\0\0hello", "11Cn8eVZg"),
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+        ];
+
+        for (bytes, encoded) in vectors {
+            assert_eq!(&Base::Base58Btc.encode(bytes), encoded);
+            assert_eq!(&Base::Base58Btc.decode(encoded).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn base64_vectors() {
+        let vectors: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+        ];
+
+        for (bytes, encoded) in vectors {
+            assert_eq!(&Base::Base64.encode(bytes), encoded);
+            assert_eq!(&Base::Base64.decode(encoded).unwrap(), bytes);
+        }
+
+        assert_eq!(Base::Base64.decode("Zm9vYg==").unwrap(), b"foob");
+    }
+
+    #[test]
+    fn multibase() {
+        assert_eq!(
+            Base::from_multibase("f1220"),
+            Ok((Base::Base16, vec![0x12, 0x20]))
+        );
+        assert_eq!(Base::from_multibase(""), Err(EncodingError::Empty));
+        assert_eq!(
+            Base::from_multibase("Qm"),
+            Err(EncodingError::UnknownBase('Q'))
+        );
+        assert_eq!(
+            Base::from_multibase("f123"),
+            Err(EncodingError::InvalidLength)
+        );
+        assert_eq!(
+            Base::from_multibase("z0"),
+            Err(EncodingError::InvalidCharacter {
+                character: '0',
+                position: 0
+            })
+        );
+    }
+}
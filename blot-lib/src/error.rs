@@ -0,0 +1,197 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! The library error type.
+//!
+//! Each module reports failures with its own error enum. [`Error`] wraps any of them so
+//! applications can use `?` across modules, and keeps the original as its
+//! [`source`](https://doc.rust-lang.org/std/error/trait.Error.html#method.source).
+//!
+//! ```
+//! use blot::multihash::Sha2256;
+//! use blot::seal::Seal;
+//! use blot::value::path::JsonPath;
+//!
+//! fn parse(seal: &str, path: &str) -> Result<(Seal<Sha2256>, JsonPath), blot::Error> {
+//!     Ok((Seal::from_str(seal)?, path.parse()?))
+//! }
+//!
+//! assert!(parse("foo", "a.b").is_err());
+//! ```
+
+use chain::ChainError;
+use core::FloatError;
+use datetime::TimestampError;
+use encoding::EncodingError;
+use multihash::{MultihashError, RegistryError, StampError};
+use seal::{RedactionError, SealError};
+use std::error;
+use std::fmt;
+use tag::TagError;
+use uvar::UvarError;
+use value::path::PathError;
+use value::schema::PointerError;
+use value::traverse::DigestError;
+use value::ValueError;
+
+#[cfg(feature = "blot_json")]
+use disclosure::DisclosureError;
+#[cfg(all(feature = "blot_json", feature = "sha2"))]
+use register::ItemHashError;
+#[cfg(feature = "blot_json")]
+use salt::SaltError;
+#[cfg(feature = "ed25519")]
+use sign::SignError;
+#[cfg(feature = "blot_json")]
+use stream::StreamError;
+#[cfg(feature = "blot_json")]
+use value::de::DeserializeError;
+#[cfg(feature = "xml")]
+use xml::XmlError;
+
+#[derive(Debug)]
+pub enum Error {
+    Chain(ChainError),
+    Digest(DigestError),
+    Encoding(EncodingError),
+    Float(FloatError),
+    Multihash(MultihashError),
+    Path(PathError),
+    Pointer(PointerError),
+    Redaction(RedactionError),
+    Registry(RegistryError),
+    Seal(SealError),
+    Stamp(StampError),
+    Tag(TagError),
+    Timestamp(TimestampError),
+    Uvar(UvarError),
+    Value(ValueError),
+    #[cfg(feature = "blot_json")]
+    Deserialize(DeserializeError),
+    #[cfg(feature = "blot_json")]
+    Disclosure(DisclosureError),
+    #[cfg(all(feature = "blot_json", feature = "sha2"))]
+    ItemHash(ItemHashError),
+    #[cfg(feature = "blot_json")]
+    Salt(SaltError),
+    #[cfg(feature = "ed25519")]
+    Sign(SignError),
+    #[cfg(feature = "blot_json")]
+    Stream(StreamError),
+    #[cfg(feature = "xml")]
+    Xml(XmlError),
+}
+
+impl Error {
+    /// The wrapped module error.
+    fn inner(&self) -> &(dyn error::Error + 'static) {
+        match self {
+            Error::Chain(err) => err,
+            Error::Digest(err) => err,
+            Error::Encoding(err) => err,
+            Error::Float(err) => err,
+            Error::Multihash(err) => err,
+            Error::Path(err) => err,
+            Error::Pointer(err) => err,
+            Error::Redaction(err) => err,
+            Error::Registry(err) => err,
+            Error::Seal(err) => err,
+            Error::Stamp(err) => err,
+            Error::Tag(err) => err,
+            Error::Timestamp(err) => err,
+            Error::Uvar(err) => err,
+            Error::Value(err) => err,
+            #[cfg(feature = "blot_json")]
+            Error::Deserialize(err) => err,
+            #[cfg(feature = "blot_json")]
+            Error::Disclosure(err) => err,
+            #[cfg(all(feature = "blot_json", feature = "sha2"))]
+            Error::ItemHash(err) => err,
+            #[cfg(feature = "blot_json")]
+            Error::Salt(err) => err,
+            #[cfg(feature = "ed25519")]
+            Error::Sign(err) => err,
+            #[cfg(feature = "blot_json")]
+            Error::Stream(err) => err,
+            #[cfg(feature = "xml")]
+            Error::Xml(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.inner())
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.inner())
+    }
+}
+
+macro_rules! error_from (($variant:ident, $type:ty) => {
+    impl From<$type> for Error {
+        fn from(err: $type) -> Error {
+            Error::$variant(err)
+        }
+    }
+});
+
+error_from!(Chain, ChainError);
+error_from!(Digest, DigestError);
+error_from!(Encoding, EncodingError);
+error_from!(Float, FloatError);
+error_from!(Multihash, MultihashError);
+error_from!(Path, PathError);
+error_from!(Pointer, PointerError);
+error_from!(Redaction, RedactionError);
+error_from!(Registry, RegistryError);
+error_from!(Seal, SealError);
+error_from!(Stamp, StampError);
+error_from!(Tag, TagError);
+error_from!(Timestamp, TimestampError);
+error_from!(Uvar, UvarError);
+error_from!(Value, ValueError);
+#[cfg(feature = "blot_json")]
+error_from!(Deserialize, DeserializeError);
+#[cfg(feature = "blot_json")]
+error_from!(Disclosure, DisclosureError);
+#[cfg(all(feature = "blot_json", feature = "sha2"))]
+error_from!(ItemHash, ItemHashError);
+#[cfg(feature = "blot_json")]
+error_from!(Salt, SaltError);
+#[cfg(feature = "ed25519")]
+error_from!(Sign, SignError);
+#[cfg(feature = "blot_json")]
+error_from!(Stream, StreamError);
+#[cfg(feature = "xml")]
+error_from!(Xml, XmlError);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as StdError;
+    use uvar::Uvar;
+
+    #[test]
+    fn source_chain() {
+        let err: Error = SealError::from(Uvar::from_bytes(&[0x80]).unwrap_err()).into();
+
+        assert_eq!(err.to_string(), "invalid code: truncated uvar");
+        assert_eq!(
+            err.source().map(|source| source.to_string()),
+            Some("invalid code: truncated uvar".to_string())
+        );
+        assert_eq!(
+            err.source()
+                .and_then(|source| source.source())
+                .map(|source| source.to_string()),
+            Some("truncated uvar".to_string())
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! JSON Canonicalization Scheme (JCS) hashing.
+//!
+//! [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) defines a canonical serialization of JSON:
+//! no insignificant whitespace, object keys sorted by their UTF-16 code units, numbers written as
+//! ECMAScript does and strings with the minimal escaping. The JCS digest is a single digest of
+//! the canonical bytes, for interoperability with systems standardised on JCS.
+//!
+//! ```
+//! extern crate serde_json;
+//! extern crate blot;
+//!
+//! use blot::jcs;
+//! use blot::multihash::Sha2256;
+//!
+//! # fn main() {
+//! let document: serde_json::Value =
+//!     serde_json::from_str(r#"{"b": [1.50, 1e30], "a": "€"}"#).unwrap();
+//!
+//! assert_eq!(jcs::canonical_json(&document), r#"{"a":"€","b":[1.5,1e+30]}"#);
+//! assert_eq!(
+//!     jcs::digest(&document, Sha2256).to_string(),
+//!     "12200a37fb81a42561a27da00347619c79175959a5b22b0bd96c9ff06fc35839ebf5"
+//! );
+//! # }
+//! ```
+//!
+//! Like [`register`](../register/index.html) item hashes, a JCS digest is not an Objecthash: it
+//! depends on the serialization, has no types beyond JSON and no redaction.
+
+use multihash::{Hash, Multihash};
+use serde_json::Value;
+
+/// The canonical JSON of a document.
+///
+/// Every number is an IEEE 754 double, so integers beyond 2^53 lose precision as the RFC
+/// prescribes.
+pub fn canonical_json(document: &Value) -> String {
+    let mut output = String::new();
+    write_canonical(document, &mut output);
+
+    output
+}
+
+/// The digest of the canonical JSON of a document.
+pub fn digest<D: Multihash>(document: &Value, digester: D) -> Hash<D> {
+    let digest = digester.digest_bytes(canonical_json(document).as_bytes());
+
+    Hash::new(digester, digest)
+}
+
+fn write_canonical(value: &Value, output: &mut String) {
+    match value {
+        Value::Number(number) => write_number(
+            number.as_f64().expect("Casting JSON Number as f64 failed"),
+            output,
+        ),
+        Value::Array(list) => {
+            output.push('[');
+
+            for (index, item) in list.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                write_canonical(item, output);
+            }
+
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            output.push('{');
+
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                output.push_str(&Value::from(key.as_str()).to_string());
+                output.push(':');
+                write_canonical(item, output);
+            }
+
+            output.push('}');
+        }
+        // Null, booleans and strings are already canonical in `serde_json`.
+        scalar => output.push_str(&scalar.to_string()),
+    }
+}
+
+/// Writes a finite number like ECMAScript's `Number.prototype.toString`.
+fn write_number(value: f64, output: &mut String) {
+    if value == 0.0 {
+        output.push('0');
+        return;
+    }
+
+    if value < 0.0 {
+        output.push('-');
+    }
+
+    // The shortest digits that round-trip, e.g. `1.2345e-7`, then rounded to the even digit
+    // when the value is halfway between two candidates.
+    let shortest = format!("{:e}", value.abs());
+    let precision = shortest
+        .chars()
+        .take_while(|c| *c != 'e')
+        .filter(char::is_ascii_digit)
+        .count()
+        - 1;
+    let scientific = format!("{:.*e}", precision, value.abs());
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').expect("An exponent"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent[1..].parse().expect("A decimal exponent");
+    let length = digits.len() as i32;
+    // The position of the decimal point relative to the digits.
+    let point = exponent + 1;
+
+    if length <= point && point <= 21 {
+        output.push_str(&digits);
+        output.extend((length..point).map(|_| '0'));
+    } else if 0 < point && point <= 21 {
+        output.push_str(&digits[..point as usize]);
+        output.push('.');
+        output.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        output.push_str("0.");
+        output.extend((point..0).map(|_| '0'));
+        output.push_str(&digits);
+    } else {
+        output.push_str(&digits[..1]);
+
+        if length > 1 {
+            output.push('.');
+            output.push_str(&digits[1..]);
+        }
+
+        output.push_str(&format!("e{:+}", exponent));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn rfc_numbers() {
+        let pairs: [(u64, &str); 24] = [
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+
+        for (bits, expected) in pairs.iter() {
+            let mut output = String::new();
+            write_number(f64::from_bits(*bits), &mut output);
+
+            assert_eq!(&output, expected, "{:#x}", bits);
+        }
+    }
+
+    #[test]
+    fn rfc_example() {
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+        let document: Value = serde_json::from_str(input).unwrap();
+
+        assert_eq!(canonical_json(&document), expected);
+    }
+
+    #[test]
+    fn utf16_key_order() {
+        let input = r#"{"\u20ac": 1, "\r": 2, "\ud83d\ude00": 3, "\ufb33": 4, "1": 5, "\u0080": 6, "\u00f6": 7}"#;
+        let document: Value = serde_json::from_str(input).unwrap();
+
+        assert_eq!(
+            canonical_json(&document),
+            "{\"\\r\":2,\"1\":5,\"\u{80}\":6,\"ö\":7,\"€\":1,\"😀\":3,\"\u{fb33}\":4}"
+        );
+    }
+}
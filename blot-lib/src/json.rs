@@ -6,7 +6,9 @@
 
 //! Blot implementation for common JSON.
 //!
-//! This implementation treats all numbers as f64.
+//! Integers are hashed as integers and the rest of numbers as floats, unless the crate is
+//! compiled with the `common_json` feature which treats all numbers as f64 like the original
+//! Objecthash. Use a [`Document`] to pick the [`Numbers`] mode at runtime instead.
 //!
 //! ```
 //! extern crate serde_json;
@@ -21,64 +23,93 @@
 //! assert_eq!(format!("{}", &value.digest(Sha2256)), "122032ae896c413cfdc79eec68be9139c86ded8b279238467c216cf2bec4d5f1e4a2");
 //! ```
 
-use core::Blot;
+use core::{digest_entries, Blot};
 use multihash::{Harvest, Multihash};
 use serde_json::{Map, Number, Value};
 use tag::Tag;
 
-impl Blot for Map<String, Value> {
-    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        let mut list: Vec<Vec<u8>> = self
-            .iter()
-            .map(|(k, v)| {
-                let mut res: Vec<u8> = Vec::with_capacity(64);
-                res.extend_from_slice(k.blot(digester).as_slice());
-                res.extend_from_slice(v.blot(digester).as_slice());
-
-                res
-            }).collect();
-
-        list.sort_unstable();
+/// How JSON numbers are hashed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Numbers {
+    /// Integers are hashed as integers and numbers with a fraction or an exponent as floats.
+    #[default]
+    Auto,
+    /// Every number is hashed as a float, like the Objecthash common JSON.
+    Float,
+}
 
-        digester.digest_collection(Tag::Dict, list)
+impl Numbers {
+    pub fn blot_number<D: Multihash>(&self, number: &Number, digester: &D) -> Harvest {
+        match self {
+            Numbers::Float => number
+                .as_f64()
+                .expect("Casting JSON Number as f64 failed")
+                .blot(digester),
+            Numbers::Auto => {
+                if number.is_f64() {
+                    number
+                        .as_f64()
+                        .expect("Casting JSON Number as f64 failed")
+                        .blot(digester)
+                } else if number.is_u64() {
+                    number
+                        .as_u64()
+                        .expect("Casting JSON Number as u64 failed")
+                        .blot(digester)
+                } else {
+                    number
+                        .as_i64()
+                        .expect("Casting JSON Number as i64 failed")
+                        .blot(digester)
+                }
+            }
+        }
     }
 }
 
+/// The mode of the `Blot` implementations for `serde_json` types, set at compile time.
 #[cfg(feature = "common_json")]
-impl Blot for Number {
-    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        self.as_f64()
-            .expect("Casting JSON Number as f64 failed")
-            .blot(digester)
-    }
+const COMPILED_NUMBERS: Numbers = Numbers::Float;
+#[cfg(not(feature = "common_json"))]
+const COMPILED_NUMBERS: Numbers = Numbers::Auto;
+
+/// A JSON value hashed with the given number mode.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate blot;
+/// use blot::core::Blot;
+/// use blot::json::{Document, Numbers};
+/// use blot::multihash::Sha2256;
+///
+/// let value = serde_json::from_str("[1, 1.0]").unwrap();
+/// let digest = Document::new(&value, Numbers::Float).digest(Sha2256);
+///
+/// assert_eq!(digest, vec![1.0, 1.0].digest(Sha2256));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Document<'a> {
+    value: &'a Value,
+    numbers: Numbers,
 }
 
-#[cfg(not(feature = "common_json"))]
-impl Blot for Number {
-    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
-        if self.is_f64() {
-            self.as_f64()
-                .expect("Casting JSON Number as f64 failed")
-                .blot(digester)
-        } else if self.is_u64() {
-            self.as_u64()
-                .expect("Casting JSON Number as u64 failed")
-                .blot(digester)
-        } else {
-            self.as_i64()
-                .expect("Casting JSON Number as i64 failed")
-                .blot(digester)
-        }
+impl<'a> Document<'a> {
+    pub fn new(value: &'a Value, numbers: Numbers) -> Document<'a> {
+        Document { value, numbers }
+    }
+
+    fn child(&self, value: &'a Value) -> Document<'a> {
+        Document::new(value, self.numbers)
     }
 }
 
-impl Blot for Value {
+impl<'a> Blot for Document<'a> {
     fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
         use hex::FromHex;
-        match self {
+        match self.value {
             Value::Null => None::<u8>.blot(digester),
             Value::Bool(raw) => raw.blot(digester),
-            Value::Number(raw) => raw.blot(digester),
+            Value::Number(raw) => self.numbers.blot_number(raw, digester),
             Value::String(raw) => {
                 // TODO: Consider moving to Seal
                 if raw.starts_with("**REDACTED**") {
@@ -90,12 +121,45 @@ impl Blot for Value {
                     raw.blot(digester)
                 }
             }
-            Value::Array(raw) => raw.blot(digester),
-            Value::Object(raw) => raw.blot(digester),
+            Value::Array(raw) => digester.digest_collection(
+                Tag::List,
+                raw.iter().map(|item| self.child(item).blot(digester)),
+            ),
+            Value::Object(raw) => {
+                let entries = raw
+                    .iter()
+                    .map(|(k, v)| (k.blot(digester), self.child(v).blot(digester)))
+                    .collect();
+
+                digest_entries(digester, entries)
+            }
         }
     }
 }
 
+impl Blot for Map<String, Value> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        let entries = self
+            .iter()
+            .map(|(k, v)| (k.blot(digester), v.blot(digester)))
+            .collect();
+
+        digest_entries(digester, entries)
+    }
+}
+
+impl Blot for Number {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        COMPILED_NUMBERS.blot_number(self, digester)
+    }
+}
+
+impl Blot for Value {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        Document::new(self, COMPILED_NUMBERS).blot(digester)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +184,26 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn runtime_numbers() {
+        let value: Value = serde_json::from_str(r#"{"a": [123], "b": 1.5}"#).unwrap();
+        let float: Value = serde_json::from_str(r#"{"a": [123.0], "b": 1.5}"#).unwrap();
+        let auto = Document::new(&value, Numbers::Auto).digest(Sha2256);
+
+        assert_eq!(
+            format!(
+                "{}",
+                Document::new(&value["a"], Numbers::Float).digest(Sha2256)
+            ),
+            "12202e72db006266ed9cdaa353aa22b9213e8a3c69c838349437c06896b1b34cee36"
+        );
+        assert_eq!(
+            Document::new(&value, Numbers::Float).digest(Sha2256),
+            float.digest(Sha2256)
+        );
+        assert_ne!(auto, float.digest(Sha2256));
+    }
+
     #[cfg(not(feature = "common_json"))]
     mod default {
         use super::*;
@@ -159,12 +243,12 @@ mod tests {
             let pairs = [
                 (
                     r#"["foo", {"bar":["baz", null, 1.0, 1.5, 0.0001, 1000.0, 2.0, -23.1234, 2.0]}]"#,
-                        "1220783a423b094307bcb28d005bc2f026ff44204442ef3513585e7e73b66e3c2213"
+                    "1220783a423b094307bcb28d005bc2f026ff44204442ef3513585e7e73b66e3c2213",
                 ),
                 (
                     r#"["foo", {"bar":["baz", null, 1, 1.5, 0.0001, 1000, 2, -23.1234, 2]}]"#,
-                    "1220726e7ae9e3fadf8a2228bf33e505a63df8db1638fa4f21429673d387dbd1c52a"
-                )
+                    "1220726e7ae9e3fadf8a2228bf33e505a63df8db1638fa4f21429673d387dbd1c52a",
+                ),
             ];
             for (raw, expected) in pairs.iter() {
                 let value: Value = serde_json::from_str(raw).unwrap();
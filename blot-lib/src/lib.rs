@@ -17,7 +17,7 @@
 //! types or use [`value::Value`].
 //!
 //! [`Blot`] requires a hashing function implementing the [`Multihash`] trait. The `default` feature
-//! enables SHA1, SHA2, SHA3 and Blake2.
+//! enables SHA1, SHA2, SHA3, Blake2 and Blake3.
 //!
 //! # Example: primitives
 //!
@@ -43,7 +43,7 @@
 //! use blot::value::Value;
 //!
 //! fn main() {
-//!     let value: Value<Sha3256> = set!{"foo", "bar", list![1, 1.0], set!{}};
+//!     let value: Value<Sha3256> = value!(set ["foo", "bar", [1, 1.0], set []]);
 //!
 //!     println!("{}", value.digest(Sha3256));
 //! }
@@ -58,11 +58,44 @@ extern crate regex;
 extern crate serde;
 #[cfg(feature = "blot_json")]
 extern crate serde_json;
+#[cfg(feature = "salt")]
+extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "num-bigint")]
+extern crate num_bigint;
+#[cfg(feature = "rust_decimal")]
+extern crate rust_decimal;
+#[cfg(feature = "datetime")]
+extern crate chrono;
+#[cfg(feature = "url")]
+extern crate url;
+#[cfg(feature = "uuid")]
+extern crate uuid;
+#[cfg(feature = "zeroize")]
+extern crate zeroize;
+#[cfg(feature = "ed25519")]
+extern crate ed25519_dalek;
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
 extern crate hex;
+extern crate itoa;
+
+#[cfg(any(test, feature = "testing"))]
+#[cfg_attr(test, macro_use)]
+extern crate proptest;
 
 #[cfg(feature = "blake2")]
 extern crate blake2 as crypto_blake2;
+#[cfg(feature = "blake3")]
+extern crate blake3 as crypto_blake3;
+#[cfg(feature = "keyed")]
+extern crate crypto_mac;
+#[cfg(feature = "keyed")]
+extern crate hmac;
 #[cfg(feature = "sha-1")]
 extern crate sha1 as crypto_sha1;
 #[cfg(feature = "sha2")]
@@ -70,15 +103,40 @@ extern crate sha2 as crypto_sha2;
 #[cfg(feature = "sha3")]
 extern crate sha3 as crypto_sha3;
 
+pub mod audit;
+pub mod chain;
+pub mod cid;
 pub mod core;
+pub mod datetime;
+pub mod diff;
+pub mod encoding;
+pub mod error;
+pub mod merkle;
 pub mod multihash;
+pub mod salt;
 pub mod seal;
+pub mod stream;
 pub mod tag;
 pub mod uvar;
 pub mod value;
 
+#[cfg(feature = "blot_json")]
+pub mod disclosure;
+#[cfg(feature = "blot_json")]
+pub mod jcs;
 #[cfg(feature = "blot_json")]
 pub mod json;
+#[cfg(all(feature = "blot_json", feature = "sha2"))]
+pub mod register;
+#[cfg(feature = "ed25519")]
+pub mod sign;
+#[cfg(feature = "blot_json")]
+pub mod vectors;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 pub use core::Blot;
+pub use error::Error;
+#[cfg(feature = "blot_json")]
+pub use value::de::from_reader;
 pub use multihash::Multihash;
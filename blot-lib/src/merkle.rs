@@ -0,0 +1,481 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Merkle trees over list items.
+//!
+//! A [`MerkleTree`] commits to a list through a single root, like the Objecthash of the list, but
+//! any item can be proven to be part of it with an [`InclusionProof`] of logarithmic size, so
+//! large datasets can be verified piecemeal. Trees only grow by appending, and a
+//! [`ConsistencyProof`] proves that a tree is an append-only extension of an older snapshot of
+//! it, given only both roots.
+//!
+//! The tree follows [RFC6962](https://tools.ietf.org/html/rfc6962#section-2.1): the leaves are
+//! the Objecthash digests of the items, the left subtree of a node holds the largest power of two
+//! of leaves smaller than the node, and leaves and nodes are digested with a distinct prefix:
+//!
+//! * leaf: `H(0x72 || 0x00 || digest)`
+//! * node: `H(0x72 || 0x01 || left || right)`
+//! * empty tree: `H(0x72)`
+//!
+//! where `0x72` is the [`Tag::Raw`] byte.
+//!
+//! ```
+//! use blot::merkle::MerkleTree;
+//! use blot::multihash::Sha2256;
+//!
+//! let tree = MerkleTree::new(Sha2256, &["foo", "bar", "baz"]);
+//! let proof = tree.proof(1).unwrap();
+//!
+//! assert!(proof.verify(&"bar", &tree.root()));
+//! assert!(!proof.verify(&"baz", &tree.root()));
+//!
+//! let old_root = tree.root();
+//! let tree = MerkleTree::new(Sha2256, &["foo", "bar", "baz", "qux"]);
+//! let proof = tree.consistency_proof(3).unwrap();
+//!
+//! assert!(proof.verify(&old_root, &tree.root()));
+//! ```
+
+use core::Blot;
+use multihash::{Harvest, Hash, Multihash};
+use tag::Tag;
+
+const LEAF: [u8; 1] = [0x00];
+const NODE: [u8; 1] = [0x01];
+
+/// A Merkle tree over the digests of a list of items.
+#[derive(Debug)]
+pub struct MerkleTree<T: Multihash> {
+    digester: T,
+    leaves: Vec<Harvest>,
+}
+
+impl<T: Multihash + Clone> MerkleTree<T> {
+    /// Builds the tree over the given items, in order.
+    pub fn new<I>(digester: T, items: I) -> MerkleTree<T>
+    where
+        I: IntoIterator,
+        I::Item: Blot,
+    {
+        let leaves = items
+            .into_iter()
+            .map(|item| leaf_hash(&digester, item.blot(&digester).as_slice()))
+            .collect();
+
+        MerkleTree { digester, leaves }
+    }
+
+    /// The number of leaves.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn root(&self) -> Hash<T> {
+        Hash::new(
+            self.digester.clone(),
+            subtree_hash(&self.digester, &self.leaves),
+        )
+    }
+
+    /// The proof that the item at the index is part of the tree, or `None` if the index is out
+    /// of bounds.
+    pub fn proof(&self, index: usize) -> Option<InclusionProof<T>> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut leaves = &self.leaves[..];
+        let mut offset = index;
+
+        // Walks down from the root, so the path is collected from the top and reversed.
+        while leaves.len() > 1 {
+            let split = split(leaves.len());
+
+            if offset < split {
+                path.push(subtree_hash(&self.digester, &leaves[split..]));
+                leaves = &leaves[..split];
+            } else {
+                path.push(subtree_hash(&self.digester, &leaves[..split]));
+                leaves = &leaves[split..];
+                offset -= split;
+            }
+        }
+
+        path.reverse();
+
+        Some(InclusionProof {
+            index,
+            size: self.leaves.len(),
+            path: self.hashes(path),
+        })
+    }
+
+    /// The proof that this tree extends the snapshot of its first `old_size` leaves, or `None`
+    /// if the size is 0 or larger than the tree.
+    pub fn consistency_proof(&self, old_size: usize) -> Option<ConsistencyProof<T>> {
+        if old_size == 0 || old_size > self.leaves.len() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        subproof(&self.digester, old_size, &self.leaves, true, &mut path);
+
+        Some(ConsistencyProof {
+            old_size,
+            new_size: self.leaves.len(),
+            path: self.hashes(path),
+        })
+    }
+
+    fn hashes(&self, digests: Vec<Harvest>) -> Vec<Hash<T>> {
+        digests
+            .into_iter()
+            .map(|digest| Hash::new(self.digester.clone(), digest))
+            .collect()
+    }
+}
+
+/// RFC6962 `SUBPROOF(m, D[n], b)`, where `complete` is `b`: whether the first `old_size` leaves
+/// are a complete subtree of the original tree, whose root the verifier already has.
+fn subproof<T: Multihash>(
+    digester: &T,
+    old_size: usize,
+    leaves: &[Harvest],
+    complete: bool,
+    path: &mut Vec<Harvest>,
+) {
+    if old_size == leaves.len() {
+        if !complete {
+            path.push(subtree_hash(digester, leaves));
+        }
+
+        return;
+    }
+
+    let split = split(leaves.len());
+
+    if old_size <= split {
+        subproof(digester, old_size, &leaves[..split], complete, path);
+        path.push(subtree_hash(digester, &leaves[split..]));
+    } else {
+        subproof(digester, old_size - split, &leaves[split..], false, path);
+        path.push(subtree_hash(digester, &leaves[..split]));
+    }
+}
+
+/// The sibling digests from a leaf up to the root of a tree of the given size.
+#[derive(Debug, PartialEq)]
+pub struct InclusionProof<T: Multihash> {
+    index: usize,
+    size: usize,
+    path: Vec<Hash<T>>,
+}
+
+impl<T: Multihash> InclusionProof<T> {
+    /// Rebuilds a proof, e.g. one received from a server.
+    pub fn new(index: usize, size: usize, path: Vec<Hash<T>>) -> InclusionProof<T> {
+        InclusionProof { index, size, path }
+    }
+
+    /// The index of the proven item.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The number of leaves of the tree.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The sibling digests, from the leaf up.
+    pub fn path(&self) -> &[Hash<T>] {
+        &self.path
+    }
+
+    /// Whether the item is at the index of a tree with the given root, digesting with the
+    /// algorithm of the root.
+    pub fn verify<B: Blot>(&self, item: &B, root: &Hash<T>) -> bool {
+        if self.index >= self.size {
+            return false;
+        }
+
+        let digester = root.tag();
+        let mut hash = leaf_hash(digester, item.blot(digester).as_slice());
+        let mut index = self.index;
+        let mut last = self.size - 1;
+
+        // RFC9162, section 2.1.3.2.
+        for sibling in &self.path {
+            if last == 0 {
+                return false;
+            }
+
+            if index & 1 == 1 || index == last {
+                hash = node_hash(digester, sibling.digest(), &hash);
+
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                hash = node_hash(digester, &hash, sibling.digest());
+            }
+
+            index >>= 1;
+            last >>= 1;
+        }
+
+        last == 0 && &hash == root.digest()
+    }
+}
+
+/// The digests proving that a tree is an append-only extension of an older snapshot of it.
+#[derive(Debug, PartialEq)]
+pub struct ConsistencyProof<T: Multihash> {
+    old_size: usize,
+    new_size: usize,
+    path: Vec<Hash<T>>,
+}
+
+impl<T: Multihash> ConsistencyProof<T> {
+    /// Rebuilds a proof, e.g. one received from a server.
+    pub fn new(old_size: usize, new_size: usize, path: Vec<Hash<T>>) -> ConsistencyProof<T> {
+        ConsistencyProof {
+            old_size,
+            new_size,
+            path,
+        }
+    }
+
+    /// The number of leaves of the older snapshot.
+    pub fn old_size(&self) -> usize {
+        self.old_size
+    }
+
+    /// The number of leaves of the newer tree.
+    pub fn new_size(&self) -> usize {
+        self.new_size
+    }
+
+    pub fn path(&self) -> &[Hash<T>] {
+        &self.path
+    }
+
+    /// Whether the tree with the new root starts with the leaves of the tree with the old root,
+    /// digesting with the algorithm of the new root.
+    pub fn verify(&self, old_root: &Hash<T>, new_root: &Hash<T>) -> bool {
+        if self.old_size == 0 || self.old_size > self.new_size {
+            return false;
+        }
+
+        if self.old_size == self.new_size {
+            return self.path.is_empty() && old_root.digest() == new_root.digest();
+        }
+
+        let digester = new_root.tag();
+        // The old root is left out of the proof when it is a complete subtree of the new tree.
+        let mut path = self.path.iter().map(Hash::digest);
+        let first = if self.old_size.is_power_of_two() {
+            old_root.digest()
+        } else {
+            match path.next() {
+                Some(first) => first,
+                None => return false,
+            }
+        };
+        let mut index = self.old_size - 1;
+        let mut last = self.new_size - 1;
+
+        // RFC9162, section 2.1.4.2.
+        while index & 1 == 1 {
+            index >>= 1;
+            last >>= 1;
+        }
+
+        let mut old_hash = first.as_slice().into();
+        let mut new_hash = first.as_slice().into();
+
+        for sibling in path {
+            if last == 0 {
+                return false;
+            }
+
+            if index & 1 == 1 || index == last {
+                old_hash = node_hash(digester, sibling, &old_hash);
+                new_hash = node_hash(digester, sibling, &new_hash);
+
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                new_hash = node_hash(digester, &new_hash, sibling);
+            }
+
+            index >>= 1;
+            last >>= 1;
+        }
+
+        last == 0 && &old_hash == old_root.digest() && &new_hash == new_root.digest()
+    }
+}
+
+fn leaf_hash<T: Multihash>(digester: &T, digest: &[u8]) -> Harvest {
+    digester.digest_collection(Tag::Raw, [&LEAF[..], digest])
+}
+
+fn node_hash<T: Multihash>(digester: &T, left: &Harvest, right: &Harvest) -> Harvest {
+    digester.digest_collection(Tag::Raw, [&NODE[..], left.as_slice(), right.as_slice()])
+}
+
+/// The root of the subtree over the given leaves.
+fn subtree_hash<T: Multihash>(digester: &T, leaves: &[Harvest]) -> Harvest {
+    match leaves.len() {
+        0 => digester.digest_primitive(Tag::Raw, &[]),
+        1 => leaves[0].as_slice().into(),
+        length => {
+            let split = split(length);
+
+            node_hash(
+                digester,
+                &subtree_hash(digester, &leaves[..split]),
+                &subtree_hash(digester, &leaves[split..]),
+            )
+        }
+    }
+}
+
+/// The largest power of two smaller than the length, which must be greater than 1.
+fn split(length: usize) -> usize {
+    let mut split = 1;
+
+    while split * 2 < length {
+        split *= 2;
+    }
+
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+
+    fn leaf(item: &str) -> Harvest {
+        leaf_hash(&Sha2256, item.blot(&Sha2256).as_slice())
+    }
+
+    #[test]
+    fn splits() {
+        let splits: Vec<usize> = (2..10).map(split).collect();
+
+        assert_eq!(splits, vec![1, 2, 2, 4, 4, 4, 4, 8]);
+    }
+
+    #[test]
+    fn root() {
+        let items = ["a", "b", "c"];
+        let tree = MerkleTree::new(Sha2256, &items);
+        let left = node_hash(&Sha2256, &leaf("a"), &leaf("b"));
+
+        assert_eq!(tree.len(), 3);
+        assert_eq!(
+            tree.root().digest(),
+            &node_hash(&Sha2256, &left, &leaf("c"))
+        );
+        assert_eq!(
+            MerkleTree::new(Sha2256, &items[..1]).root().digest(),
+            &leaf("a")
+        );
+        assert_eq!(
+            MerkleTree::new(Sha2256, Vec::<&str>::new()).root().digest(),
+            &Sha2256.digest_primitive(Tag::Raw, &[])
+        );
+    }
+
+    #[test]
+    fn inclusion_proofs() {
+        let items: Vec<String> = (0..7).map(|n| n.to_string()).collect();
+
+        for size in 1..items.len() + 1 {
+            let tree = MerkleTree::new(Sha2256, &items[..size]);
+            let root = tree.root();
+
+            for (index, item) in items[..size].iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+
+                assert!(proof.verify(item, &root), "{} of {}", index, size);
+                assert!(!proof.verify(&"x", &root));
+            }
+
+            assert_eq!(tree.proof(size), None);
+        }
+    }
+
+    #[test]
+    fn forged_proofs() {
+        let tree = MerkleTree::new(Sha2256, &["a", "b", "c"]);
+        let root = tree.root();
+        let proof = tree.proof(2).unwrap();
+
+        assert_eq!(proof.path().len(), 1);
+        assert!(!InclusionProof::new(1, 3, tree.proof(2).unwrap().path).verify(&"c", &root));
+        assert!(!InclusionProof::new(2, 4, tree.proof(2).unwrap().path).verify(&"c", &root));
+        assert!(!InclusionProof::new(2, 3, Vec::new()).verify(&"c", &root));
+    }
+
+    #[test]
+    fn consistency_proofs() {
+        let items: Vec<String> = (0..10).map(|n| n.to_string()).collect();
+
+        for new_size in 1..items.len() {
+            let tree = MerkleTree::new(Sha2256, &items[..new_size]);
+
+            for old_size in 1..new_size + 1 {
+                let old_root = MerkleTree::new(Sha2256, &items[..old_size]).root();
+                let proof = tree.consistency_proof(old_size).unwrap();
+                let other_root = MerkleTree::new(Sha2256, &items[1..old_size + 1]).root();
+
+                assert!(
+                    proof.verify(&old_root, &tree.root()),
+                    "{} to {}",
+                    old_size,
+                    new_size
+                );
+                assert!(!proof.verify(&other_root, &tree.root()));
+            }
+
+            assert_eq!(tree.consistency_proof(0), None);
+            assert_eq!(tree.consistency_proof(new_size + 1), None);
+        }
+    }
+
+    #[test]
+    fn forged_consistency_proofs() {
+        let old_root = MerkleTree::new(Sha2256, &["a", "b", "c"]).root();
+        let tree = MerkleTree::new(Sha2256, &["a", "b", "c", "d", "e"]);
+        let rewritten = MerkleTree::new(Sha2256, &["a", "x", "c", "d", "e"]);
+
+        assert!(!tree
+            .consistency_proof(3)
+            .unwrap()
+            .verify(&old_root, &rewritten.root()));
+        assert!(!rewritten
+            .consistency_proof(3)
+            .unwrap()
+            .verify(&old_root, &rewritten.root()));
+        assert!(
+            !ConsistencyProof::new(3, 4, tree.consistency_proof(3).unwrap().path)
+                .verify(&old_root, &tree.root())
+        );
+        assert!(!ConsistencyProof::new(3, 5, Vec::new()).verify(&old_root, &tree.root()));
+    }
+}
@@ -6,15 +6,16 @@
 
 //! Blot implementation for blake2.
 
-use super::{Harvest, Multihash, MultihashError};
+use super::{Harvest, Multihash, MultihashError, Stamp};
 use crypto_blake2 as digester;
+use crypto_blake2::digest::{self, VariableOutput};
 use crypto_blake2::Digest;
 use tag::Tag;
 use uvar::Uvar;
 
 // Blake2b-512
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Blake2b512;
 
 impl Default for Blake2b512 {
@@ -58,26 +59,36 @@ impl Multihash for Blake2b512 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 }
 
 // Blake2s-256
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Blake2s256;
 
 impl Default for Blake2s256 {
@@ -121,19 +132,164 @@ impl Multihash for Blake2s256 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
+        }
+
+        digester.result().as_slice().into()
+    }
+}
+
+// Blake2b with a custom output length
+
+/// Blake2b with an output of `LENGTH` bytes, from 1 to 64.
+///
+/// The multihash code is `0xb200` plus the length, from `blake2b-8` (0xb201) to `blake2b-512`
+/// (0xb240). Lengths out of range fail to compile.
+///
+/// ```
+/// use blot::core::Blot;
+/// use blot::multihash::{Blake2b, Multihash};
+///
+/// let tag: Blake2b<32> = Blake2b;
+///
+/// assert_eq!(tag.name(), "blake2b-256");
+/// assert_eq!(u64::from(tag.code()), 0xb220);
+/// assert_eq!("foo".digest(tag).digest().as_slice().len(), 32);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blake2b<const LENGTH: u8>;
+
+impl<const LENGTH: u8> Blake2b<LENGTH> {
+    const OUTPUT: u8 = {
+        assert!(
+            LENGTH != 0 && LENGTH <= 64,
+            "Blake2b length must be between 1 and 64 bytes"
+        );
+        LENGTH
+    };
+}
+
+impl<const LENGTH: u8> Default for Blake2b<LENGTH> {
+    fn default() -> Self {
+        Blake2b
+    }
+}
+
+impl<const LENGTH: u8> From<Blake2b<LENGTH>> for Uvar {
+    fn from(hash: Blake2b<LENGTH>) -> Uvar {
+        hash.code()
+    }
+}
+
+impl<const LENGTH: u8> From<Uvar> for Result<Blake2b<LENGTH>, MultihashError> {
+    fn from(code: Uvar) -> Result<Blake2b<LENGTH>, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0xb200 + u64::from(LENGTH) {
+            Ok(Blake2b)
+        } else {
+            Err(MultihashError::Unknown)
         }
+    }
+}
+
+impl<const LENGTH: u8> Multihash for Blake2b<LENGTH> {
+    type Digester = digester::VarBlake2b;
+
+    fn name(&self) -> &'static str {
+        Stamp::of(self).name()
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0xb200 + u64::from(self.length()))
+    }
+
+    fn length(&self) -> u8 {
+        Self::OUTPUT
+    }
+
+    fn digester(&self) -> Self::Digester {
+        Self::Digester::new_keyed(&[], usize::from(self.length()))
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = self.digester();
+        digest::Input::input(&mut digester, tag.to_bytes());
+        digest::Input::input(&mut digester, bytes);
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digester.variable_result(|output| bytes.copy_from_slice(output))
+        })
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = self.digester();
+        digest::Input::input(&mut digester, bytes);
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digester.variable_result(|output| bytes.copy_from_slice(output))
+        })
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = self.digester();
+        digest::Input::input(&mut digester, tag.to_bytes());
+
+        for bytes in list {
+            digest::Input::input(&mut digester, bytes.as_ref());
+        }
+
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digester.variable_result(|output| bytes.copy_from_slice(output))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+
+    #[test]
+    fn truncated_output() {
+        let hash = "foo".digest(Blake2b::<32>);
+
+        assert_eq!(
+            hash.digest().to_string(),
+            "53cfeb930ffe228604a09fadc0c5f45f038819c606e26169e5094bd43f2d3b41"
+        );
+        assert_eq!(Stamp::of(hash.tag()), Stamp::Blake2b { length: 32 });
+    }
 
-        digester.result().as_ref().to_vec().into()
+    #[test]
+    fn full_output() {
+        assert_eq!(
+            "foo".digest(Blake2b::<64>).digest(),
+            "foo".digest(Blake2b512).digest()
+        );
+        assert_eq!(Blake2b::<64>.name(), "blake2b-512");
+        assert_eq!(Blake2b::<1>.name(), "blake2b-8");
     }
 }
@@ -0,0 +1,112 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Blot implementation for blake3.
+
+use super::{Harvest, Multihash, MultihashError};
+use crypto_blake3 as digester;
+use tag::Tag;
+use uvar::Uvar;
+
+// Blake3
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blake3;
+
+impl Default for Blake3 {
+    fn default() -> Self {
+        Blake3
+    }
+}
+
+impl From<Blake3> for Uvar {
+    fn from(hash: Blake3) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<Blake3, MultihashError> {
+    fn from(code: Uvar) -> Result<Blake3, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x1e {
+            Ok(Blake3)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for Blake3 {
+    type Digester = digester::Hasher;
+
+    fn name(&self) -> &'static str {
+        "blake3"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x1e)
+    }
+
+    fn length(&self) -> u8 {
+        32
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.update(&tag.to_bytes());
+        digester.update(bytes);
+        digester.finalize().as_bytes()[..].into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.update(bytes);
+        digester.finalize().as_bytes()[..].into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.update(&tag.to_bytes());
+
+        for bytes in list {
+            digester.update(bytes.as_ref());
+        }
+
+        digester.finalize().as_bytes()[..].into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+
+    #[test]
+    fn unicode() {
+        let expected = {
+            let mut digester = digester::Hasher::new();
+            digester.update(b"ufoo");
+            digester.finalize().to_hex().to_string()
+        };
+
+        assert_eq!("foo".digest(Blake3).digest().to_string(), expected);
+    }
+
+    #[test]
+    fn stream_matches_primitive() {
+        let mut reader: &[u8] = b"foo";
+
+        assert_eq!(
+            Blake3.digest_stream(Tag::Raw, &mut reader).unwrap(),
+            Blake3.digest_primitive(Tag::Raw, b"foo")
+        );
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Float policies.
+//!
+//! A [`FloatMultihash`] hashes floats following a [`FloatPolicy`] other than the Objecthash
+//! normalization, to match systems that canonicalize floats differently. Everything else is
+//! hashed by the wrapped algorithm as usual.
+//!
+//! ```
+//! use blot::core::{Blot, FloatPolicy};
+//! use blot::multihash::{FloatMultihash, Sha2256};
+//!
+//! let digester = FloatMultihash::new(Sha2256, FloatPolicy::Ieee754);
+//!
+//! assert_eq!("foo".digest(digester.clone()).digest(), "foo".digest(Sha2256).digest());
+//! assert_ne!(1.5.digest(digester).digest(), 1.5.digest(Sha2256).digest());
+//! ```
+//!
+//! The hash keeps the multihash code of the wrapped algorithm. Beware of operations that build
+//! their digester from `Default`: they use the Objecthash policy.
+
+use super::{Harvest, Multihash};
+use core::FloatPolicy;
+use std::io::{self, Read};
+use tag::Tag;
+use uvar::Uvar;
+
+/// A multihash encoding floats with the given policy.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FloatMultihash<T: Multihash> {
+    tag: T,
+    policy: FloatPolicy,
+}
+
+impl<T: Multihash> FloatMultihash<T> {
+    pub fn new(tag: T, policy: FloatPolicy) -> FloatMultihash<T> {
+        FloatMultihash { tag, policy }
+    }
+
+    pub fn tag(&self) -> &T {
+        &self.tag
+    }
+}
+
+impl<T: Multihash> Multihash for FloatMultihash<T> {
+    type Digester = T::Digester;
+
+    fn name(&self) -> &str {
+        self.tag.name()
+    }
+
+    fn code(&self) -> Uvar {
+        self.tag.code()
+    }
+
+    fn length(&self) -> u8 {
+        self.tag.length()
+    }
+
+    fn float_policy(&self) -> FloatPolicy {
+        self.policy
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        self.tag.digest_primitive(tag, bytes)
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        self.tag.digest_bytes(bytes)
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.tag.digest_collection(tag, list)
+    }
+
+    fn digest_stream<R: Read>(&self, tag: Tag, reader: &mut R) -> io::Result<Harvest> {
+        self.tag.digest_stream(tag, reader)
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Blot implementation for the identity multihash.
+
+use super::{Harvest, Multihash, MultihashError};
+use tag::Tag;
+use uvar::Uvar;
+
+/// The identity multihash: the digest is the tagged input itself.
+///
+/// Useful for tests and to embed tiny values. It has no fixed length, so [`Multihash::length`]
+/// is 0 and hashes report the length of their digest, which cannot exceed 255 bytes.
+///
+/// # Panics
+///
+/// Digesting anything longer than 255 bytes, tag included, panics. Only hash inputs known to be
+/// tiny; that is why the command line, the server and the bindings do not offer it.
+///
+/// ```
+/// use blot::core::Blot;
+/// use blot::multihash::Identity;
+///
+/// assert_eq!("foo".digest(Identity).to_string(), "000475666f6f");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identity;
+
+impl Default for Identity {
+    fn default() -> Self {
+        Identity
+    }
+}
+
+impl From<Identity> for Uvar {
+    fn from(hash: Identity) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<Identity, MultihashError> {
+    fn from(code: Uvar) -> Result<Identity, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x00 {
+            Ok(Identity)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for Identity {
+    type Digester = Vec<u8>;
+
+    fn name(&self) -> &'static str {
+        "identity"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x00)
+    }
+
+    fn length(&self) -> u8 {
+        0
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.extend_from_slice(&tag.to_bytes());
+        digester.extend_from_slice(bytes);
+        harvest(digester)
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.extend_from_slice(bytes);
+        harvest(digester)
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.extend_from_slice(&tag.to_bytes());
+
+        for bytes in list {
+            digester.extend_from_slice(bytes.as_ref());
+        }
+
+        harvest(digester)
+    }
+}
+
+fn harvest(bytes: Vec<u8>) -> Harvest {
+    assert!(
+        bytes.len() <= usize::from(u8::MAX),
+        "Identity digests are limited to 255 bytes"
+    );
+
+    bytes.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::Stamp;
+    use seal::Seal;
+
+    #[test]
+    fn collections() {
+        let hash = vec![true].digest(Identity);
+
+        assert_eq!(hash.digest().as_slice(), b"lb1");
+        assert_eq!(hash.to_bytes(), b"\x00\x03lb1".to_vec());
+    }
+
+    #[test]
+    fn seal_roundtrip() {
+        let seal: Seal<Identity> = "foo".digest(Identity).into();
+        let parsed: Seal<Identity> = Seal::from_str(&seal.to_string()).unwrap();
+
+        assert_eq!(seal.tag(), &Stamp::Identity { length: 4 });
+        assert_eq!(parsed, seal);
+    }
+
+    #[test]
+    fn longer_than_inline() {
+        let input = "x".repeat(100);
+        let hash = input.digest(Identity);
+
+        assert_eq!(hash.length(), 101);
+        assert_eq!(&hash.digest().as_slice()[1..], input.as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "limited to 255 bytes")]
+    fn too_long() {
+        "x".repeat(255).digest(Identity);
+    }
+}
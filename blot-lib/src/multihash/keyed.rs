@@ -0,0 +1,244 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Keyed hashing.
+//!
+//! A [`KeyedMultihash`] computes message authentication codes instead of plain digests: HMAC for
+//! SHA2 and the native keyed mode for Blake2. Without the key, third parties cannot brute-force
+//! redacted values by hashing candidates.
+//!
+//! ```
+//! use blot::core::Blot;
+//! use blot::multihash::{KeyedMultihash, Sha2256};
+//!
+//! let keyed = KeyedMultihash::new(Sha2256, b"secret".to_vec()).unwrap();
+//!
+//! assert_ne!(
+//!     "foo".digest(keyed).digest(),
+//!     "foo".digest(Sha2256).digest()
+//! );
+//! ```
+//!
+//! The hash keeps the multihash code of the wrapped algorithm.
+//!
+//! With the `zeroize` feature, the key is wiped when the last clone of the keyed multihash is
+//! dropped. The internal state of the MAC is not.
+
+use super::{wipe, Harvest, Multihash, MultihashError};
+use core::FloatPolicy;
+use crypto_mac::Mac;
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Arc;
+use tag::Tag;
+use uvar::Uvar;
+
+/// Algorithms with a keyed mode.
+pub trait Keyable: Multihash {
+    type Mac: Mac;
+}
+
+#[cfg(feature = "sha2")]
+impl Keyable for super::Sha2256 {
+    type Mac = ::hmac::Hmac<::crypto_sha2::Sha256>;
+}
+
+#[cfg(feature = "sha2")]
+impl Keyable for super::Sha2512 {
+    type Mac = ::hmac::Hmac<::crypto_sha2::Sha512>;
+}
+
+#[cfg(feature = "blake2")]
+impl Keyable for super::Blake2b512 {
+    type Mac = ::crypto_blake2::Blake2b;
+}
+
+#[cfg(feature = "blake2")]
+impl Keyable for super::Blake2s256 {
+    type Mac = ::crypto_blake2::Blake2s;
+}
+
+/// A multihash computed under a secret key.
+///
+/// The default value uses an empty key. Beware of operations that build their digester from
+/// `Default`, like [`Value::redact_paths`](../value/enum.Value.html#method.redact_paths): they
+/// do not use the key. Pass the keyed digester to their `_with` counterparts instead.
+#[derive(Clone, PartialEq)]
+pub struct KeyedMultihash<T: Keyable> {
+    tag: T,
+    key: Arc<Key>,
+}
+
+/// The secret key, shared between clones.
+#[derive(PartialEq)]
+struct Key(Box<[u8]>);
+
+#[cfg(feature = "zeroize")]
+impl Drop for Key {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl<T: Keyable> KeyedMultihash<T> {
+    /// Fails if the key is too long for the algorithm, e.g. longer than 64 bytes for Blake2b.
+    pub fn new(tag: T, mut key: Vec<u8>) -> Result<KeyedMultihash<T>, MultihashError> {
+        let valid = T::Mac::new_varkey(&key).is_ok();
+        let secret = Key(key.as_slice().into());
+        let length = key.len();
+        wipe(&mut key);
+
+        if !valid {
+            return Err(MultihashError::InvalidKeyLength(length));
+        }
+
+        Ok(KeyedMultihash {
+            tag,
+            key: Arc::new(secret),
+        })
+    }
+
+    pub fn tag(&self) -> &T {
+        &self.tag
+    }
+
+    fn mac(&self) -> T::Mac {
+        T::Mac::new_varkey(&self.key.0).expect("Key length to be checked by the constructor")
+    }
+}
+
+impl<T: Keyable> Default for KeyedMultihash<T> {
+    fn default() -> Self {
+        KeyedMultihash {
+            tag: T::default(),
+            key: Arc::new(Key(Box::new([]))),
+        }
+    }
+}
+
+/// Never shows the key.
+impl<T: Keyable + fmt::Debug> fmt::Debug for KeyedMultihash<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("KeyedMultihash")
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+impl<T: Keyable> Multihash for KeyedMultihash<T> {
+    type Digester = T::Digester;
+
+    fn name(&self) -> &str {
+        self.tag.name()
+    }
+
+    fn code(&self) -> Uvar {
+        self.tag.code()
+    }
+
+    fn length(&self) -> u8 {
+        self.tag.length()
+    }
+
+    fn float_policy(&self) -> FloatPolicy {
+        self.tag.float_policy()
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut mac = self.mac();
+        mac.input(&tag.to_bytes());
+        mac.input(bytes);
+        mac.result().code().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut mac = self.mac();
+        mac.input(bytes);
+        mac.result().code().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut mac = self.mac();
+        mac.input(&tag.to_bytes());
+
+        for bytes in list {
+            mac.input(bytes.as_ref());
+        }
+
+        mac.result().code().as_slice().into()
+    }
+
+    fn digest_stream<R: Read>(&self, tag: Tag, reader: &mut R) -> io::Result<Harvest> {
+        let mut mac = self.mac();
+        let mut buffer = [0; 8192];
+        mac.input(&tag.to_bytes());
+
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => mac.input(&buffer[..n]),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        wipe(&mut buffer);
+
+        Ok(mac.result().code().as_slice().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::{Blake2b512, Sha2256};
+
+    #[test]
+    fn hmac_sha2() {
+        // HMAC-SHA256 of "ufoo" with key "key".
+        let keyed = KeyedMultihash::new(Sha2256, b"key".to_vec()).unwrap();
+
+        assert_eq!(
+            "foo".digest(keyed).digest().to_string(),
+            "3a062e9ad8a9205ed27babfbadb676cfaf000aa3d7652425aa611f672f1b86c6"
+        );
+    }
+
+    #[test]
+    fn stream_matches_primitive() {
+        let keyed = KeyedMultihash::new(Blake2b512, b"key".to_vec()).unwrap();
+        let mut reader: &[u8] = b"foo";
+
+        assert_eq!(
+            keyed.digest_stream(Tag::Raw, &mut reader).unwrap(),
+            keyed.digest_primitive(Tag::Raw, b"foo")
+        );
+    }
+
+    #[test]
+    fn clones_share_the_key() {
+        let keyed = KeyedMultihash::new(Sha2256, b"key".to_vec()).unwrap();
+        let expected = "foo".digest(keyed.clone());
+
+        drop(keyed.clone());
+
+        assert_eq!("foo".digest(keyed), expected);
+    }
+
+    #[test]
+    fn key_too_long() {
+        match KeyedMultihash::new(Blake2b512, vec![0; 65]) {
+            Err(MultihashError::InvalidKeyLength(65)) => {}
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+}
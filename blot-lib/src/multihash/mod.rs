@@ -8,9 +8,30 @@
 //!
 //! This module defines the [`Multihash`] trait and the default hashing functions (digesters).
 
+use core::FloatPolicy;
+use encoding;
+use std::cmp::Ordering;
+use std::error;
 use std::fmt;
+use std::hash::Hasher;
+use std::io::{self, Read};
 use tag::Tag;
-use uvar::Uvar;
+use uvar::{self, Uvar};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+mod floats;
+mod identity;
+mod namespace;
+mod policy;
+mod registry;
+mod stamp;
+pub use self::floats::FloatMultihash;
+pub use self::identity::Identity;
+pub use self::namespace::NamespacedMultihash;
+pub use self::policy::AlgorithmPolicy;
+pub use self::registry::{algorithms, find, Algorithm, Digester, Registry, RegistryError};
+pub use self::stamp::{Stamp, StampError};
 
 #[cfg(feature = "sha-1")]
 mod sha1;
@@ -20,17 +41,29 @@ pub use self::sha1::Sha1;
 #[cfg(feature = "sha2")]
 mod sha2;
 #[cfg(feature = "sha2")]
-pub use self::sha2::{Sha2256, Sha2512};
+pub use self::sha2::{DblSha2256, Sha2224, Sha2256, Sha2384, Sha2512};
 
 #[cfg(feature = "sha3")]
 mod sha3;
 #[cfg(feature = "sha3")]
-pub use self::sha3::{Sha3224, Sha3256, Sha3384, Sha3512};
+pub use self::sha3::{
+    Keccak256, Keccak512, Sha3224, Sha3256, Sha3384, Sha3512, Shake128, Shake256,
+};
 
 #[cfg(feature = "blake2")]
 mod blake2;
 #[cfg(feature = "blake2")]
-pub use self::blake2::{Blake2b512, Blake2s256};
+pub use self::blake2::{Blake2b, Blake2b512, Blake2s256};
+
+#[cfg(feature = "blake3")]
+mod blake3;
+#[cfg(feature = "blake3")]
+pub use self::blake3::Blake3;
+
+#[cfg(feature = "keyed")]
+mod keyed;
+#[cfg(feature = "keyed")]
+pub use self::keyed::{Keyable, KeyedMultihash};
 
 /// Multihash trait to be implemented by any algorithm used by Blot.
 ///
@@ -55,51 +88,242 @@ pub trait Multihash: Default + PartialEq {
     fn digester(&self) -> Self::Digester {
         Self::Digester::default()
     }
+    /// How floats are encoded before hashing. See [`FloatMultihash`] to pick another policy.
+    fn float_policy(&self) -> FloatPolicy {
+        FloatPolicy::Objecthash
+    }
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest;
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest;
+    /// Digests the bytes as they are, without a tag, e.g. a document already serialized in a
+    /// canonical form.
+    ///
+    /// The default digests the first byte as the tag of the rest, which hashes the same input.
+    ///
+    /// # Panics
+    ///
+    /// The default panics on empty input. The built-in algorithms override it.
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let (code, rest) = bytes
+            .split_first()
+            .expect("Untagged input to have at least one byte");
+
+        self.digest_primitive(Tag::from_code(*code), rest)
+    }
+    /// Digests the tag followed by every item of the list, in order.
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>;
+    /// Digests the tag followed by every byte of the reader without buffering the whole input.
+    ///
+    /// The default feeds the reader to [`Multihash::digest_collection`] in chunks.
+    fn digest_stream<R: Read>(&self, tag: Tag, reader: &mut R) -> io::Result<Harvest> {
+        let mut chunks = Chunks {
+            reader,
+            error: None,
+        };
+        let digest = self.digest_collection(tag, &mut chunks);
+
+        match chunks.error {
+            Some(err) => Err(err),
+            None => Ok(digest),
+        }
+    }
+}
+
+/// The bytes of a reader in chunks of up to 8 KiB, stopping at the first error.
+struct Chunks<'a, R: 'a> {
+    reader: &'a mut R,
+    error: Option<io::Error>,
+}
+
+impl<'a, R: Read> Iterator for Chunks<'a, R> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        let mut chunk = vec![0; 8192];
+
+        loop {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => return None,
+                Ok(n) => {
+                    chunk.truncate(n);
+                    return Some(chunk);
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.error = Some(err);
+                    return None;
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum MultihashError {
     Unknown,
+    /// The key is too long for the keyed mode of the algorithm.
+    InvalidKeyLength(usize),
+    /// The digest cannot be truncated to the given length.
+    InvalidLength(u8),
 }
 
+impl fmt::Display for MultihashError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultihashError::Unknown => write!(formatter, "unknown multihash"),
+            MultihashError::InvalidKeyLength(length) => {
+                write!(formatter, "a key of {} bytes is not supported", length)
+            }
+            MultihashError::InvalidLength(length) => {
+                write!(formatter, "invalid digest length {}", length)
+            }
+        }
+    }
+}
+
+impl error::Error for MultihashError {}
+
+/// Digests up to this length are kept inline.
+const INLINE_LENGTH: usize = 64;
+
 /// Multihash harvest digest.
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub struct Harvest(Box<[u8]>);
+///
+/// Digests of up to 64 bytes, the output of every built-in algorithm but the identity, are kept
+/// on the stack so hashing a value does not allocate per node.
+pub struct Harvest(Bytes);
+
+enum Bytes {
+    Inline {
+        bytes: [u8; INLINE_LENGTH],
+        length: u8,
+    },
+    Heap(Box<[u8]>),
+}
+
+/// Overwrites sensitive bytes with zeros when the `zeroize` feature is enabled.
+#[allow(unused_variables)]
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    bytes.zeroize();
+}
+
+/// Wipes the digest, which may be an intermediate node of a larger hash.
+#[cfg(feature = "zeroize")]
+impl Drop for Harvest {
+    fn drop(&mut self) {
+        match self.0 {
+            Bytes::Inline { ref mut bytes, .. } => wipe(bytes),
+            Bytes::Heap(ref mut bytes) => wipe(bytes),
+        }
+    }
+}
 
 impl AsRef<[u8]> for Harvest {
     fn as_ref(&self) -> &[u8] {
-        self.0.as_ref()
+        self.as_slice()
     }
 }
 
 impl fmt::Display for Harvest {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        for byte in self.0.as_ref() {
-            write!(formatter, "{:02x}", byte)?;
-        }
+        encoding::write_hex(formatter, self.as_slice())
+    }
+}
+
+impl fmt::Debug for Harvest {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_tuple("Harvest")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
+
+impl PartialEq for Harvest {
+    fn eq(&self, other: &Harvest) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for Harvest {}
+
+impl PartialOrd for Harvest {
+    fn partial_cmp(&self, other: &Harvest) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Harvest {
+    fn cmp(&self, other: &Harvest) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
 
-        Ok(())
+impl ::std::hash::Hash for Harvest {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
     }
 }
 
 impl Harvest {
     pub fn as_slice(&self) -> &[u8] {
-        &self.0
+        match self.0 {
+            Bytes::Inline { ref bytes, length } => &bytes[..usize::from(length)],
+            Bytes::Heap(ref bytes) => bytes,
+        }
+    }
+
+    /// Creates a digest of the given length written in place by `fill`, for digesters that
+    /// output into a buffer.
+    pub(crate) fn filled<F: FnOnce(&mut [u8])>(length: usize, fill: F) -> Harvest {
+        if length <= INLINE_LENGTH {
+            let mut bytes = [0; INLINE_LENGTH];
+            fill(&mut bytes[..length]);
+
+            Harvest(Bytes::Inline {
+                bytes,
+                length: length as u8,
+            })
+        } else {
+            let mut bytes = vec![0; length];
+            fill(&mut bytes);
+
+            Harvest(Bytes::Heap(bytes.into_boxed_slice()))
+        }
+    }
+}
+
+impl From<&[u8]> for Harvest {
+    fn from(slice: &[u8]) -> Self {
+        Harvest::filled(slice.len(), |bytes| bytes.copy_from_slice(slice))
     }
 }
 
 impl From<Vec<u8>> for Harvest {
-    fn from(vec: Vec<u8>) -> Self {
-        Harvest(vec.into_boxed_slice())
+    fn from(mut vec: Vec<u8>) -> Self {
+        if vec.len() <= INLINE_LENGTH {
+            let harvest = vec.as_slice().into();
+            wipe(&mut vec);
+
+            harvest
+        } else {
+            Harvest(Bytes::Heap(vec.into_boxed_slice()))
+        }
     }
 }
 
 impl From<Box<[u8]>> for Harvest {
-    fn from(b: Box<[u8]>) -> Self {
-        Harvest(b)
+    fn from(mut b: Box<[u8]>) -> Self {
+        if b.len() <= INLINE_LENGTH {
+            let harvest = b.as_ref().into();
+            wipe(&mut b);
+
+            harvest
+        } else {
+            Harvest(Bytes::Heap(b))
+        }
     }
 }
 
@@ -125,14 +349,59 @@ impl<T: Multihash> Hash<T> {
     pub fn tag(&self) -> &T {
         &self.tag
     }
+
+    /// The length of the digest. It is the length of the algorithm except for [`Identity`] and
+    /// truncated hashes.
+    pub fn length(&self) -> u8 {
+        self.digest.as_slice().len() as u8
+    }
+
+    /// Keeps the first `length` bytes of the digest, as permitted by the multihash spec.
+    ///
+    /// Fails if the length is 0 or longer than the digest.
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    ///
+    /// let hash = "foo".digest(Sha2256).truncate(16).unwrap();
+    ///
+    /// assert_eq!(hash.to_string(), "1210a6a6e5e783c363cd95693ec189c26823");
+    /// assert!(hash.is_truncated());
+    /// ```
+    pub fn truncate(self, length: u8) -> Result<Hash<T>, MultihashError> {
+        if length == 0 || length > self.length() {
+            return Err(MultihashError::InvalidLength(length));
+        }
+
+        Ok(Hash {
+            digest: self.digest.as_slice()[..usize::from(length)].into(),
+            tag: self.tag,
+        })
+    }
+
+    /// Whether the digest is shorter than the natural output of the algorithm.
+    pub fn is_truncated(&self) -> bool {
+        self.length() < self.tag.length()
+    }
+
+    /// Returns the binary multihash: code and length as uvars, then the digest.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.tag.code().to_bytes();
+        bytes.extend(uvar::encode_u64(u64::from(self.length())));
+        bytes.extend_from_slice(self.digest.as_slice());
+
+        bytes
+    }
+
+    /// Consumes the hash returning its tag and digest.
+    pub fn into_parts(self) -> (T, Harvest) {
+        (self.tag, self.digest)
+    }
 }
 
 impl<T: Multihash> fmt::Display for Hash<T> {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        write!(formatter, "{:02x}", &self.tag.code())?;
-        write!(formatter, "{:02x}", &self.tag.length())?;
-        write!(formatter, "{}", &self.digest)?;
-
-        Ok(())
+        encoding::write_hex(formatter, &self.to_bytes())
     }
 }
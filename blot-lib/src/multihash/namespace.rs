@@ -0,0 +1,191 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Domain separation.
+//!
+//! A [`NamespacedMultihash`] mixes an application namespace into every node it hashes, so two
+//! applications hashing the same data with different namespaces get unrelated digests for the
+//! whole value and for every part of it. [`DigestOptions::namespace`] applies it to a [`Value`].
+//!
+//! [`DigestOptions::namespace`]: ../value/traverse/struct.DigestOptions.html#method.namespace
+//! [`Value`]: ../value/enum.Value.html
+//!
+//! ```
+//! use blot::core::Blot;
+//! use blot::multihash::{NamespacedMultihash, Sha2256};
+//!
+//! let billing = NamespacedMultihash::new(Sha2256, "billing");
+//! let audit = NamespacedMultihash::new(Sha2256, "audit");
+//!
+//! assert_ne!("foo".digest(billing).digest(), "foo".digest(audit).digest());
+//! assert_eq!(
+//!     "foo".digest(NamespacedMultihash::new(Sha2256, "")).digest(),
+//!     "foo".digest(Sha2256).digest()
+//! );
+//! ```
+//!
+//! The input of every node is its tag, the length of the namespace as a [`uvar`], the namespace
+//! and then the usual input. Mixing it after the tag keeps streams hashed in chunks. An empty
+//! namespace hashes like the wrapped algorithm.
+//!
+//! The hash keeps the multihash code of the wrapped algorithm, so the namespace has to be known
+//! to verify a digest.
+//!
+//! [`uvar`]: ../uvar/index.html
+
+use super::{Harvest, Multihash};
+use core::FloatPolicy;
+use std::io::{self, Read};
+use tag::Tag;
+use uvar::{self, Uvar};
+
+/// A multihash separating its digests with the given namespace.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NamespacedMultihash<T: Multihash> {
+    tag: T,
+    namespace: Vec<u8>,
+    /// The length of the namespace followed by the namespace, empty for an empty namespace.
+    prefix: Vec<u8>,
+}
+
+impl<T: Multihash> NamespacedMultihash<T> {
+    pub fn new<N: AsRef<[u8]>>(tag: T, namespace: N) -> NamespacedMultihash<T> {
+        let namespace = namespace.as_ref().to_vec();
+        let mut prefix = Vec::new();
+
+        if !namespace.is_empty() {
+            prefix = uvar::encode_u64(namespace.len() as u64);
+            prefix.extend_from_slice(&namespace);
+        }
+
+        NamespacedMultihash {
+            tag,
+            namespace,
+            prefix,
+        }
+    }
+
+    pub fn tag(&self) -> &T {
+        &self.tag
+    }
+
+    pub fn namespace(&self) -> &[u8] {
+        &self.namespace
+    }
+
+    pub fn into_inner(self) -> T {
+        self.tag
+    }
+}
+
+impl<T: Multihash> Multihash for NamespacedMultihash<T> {
+    type Digester = T::Digester;
+
+    fn name(&self) -> &str {
+        self.tag.name()
+    }
+
+    fn code(&self) -> Uvar {
+        self.tag.code()
+    }
+
+    fn length(&self) -> u8 {
+        self.tag.length()
+    }
+
+    fn float_policy(&self) -> FloatPolicy {
+        self.tag.float_policy()
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        if self.prefix.is_empty() {
+            return self.tag.digest_primitive(tag, bytes);
+        }
+
+        self.tag
+            .digest_collection(tag, vec![&self.prefix[..], bytes])
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        if self.prefix.is_empty() {
+            return self.tag.digest_bytes(bytes);
+        }
+
+        let mut input = self.prefix.clone();
+        input.extend_from_slice(bytes);
+
+        self.tag.digest_bytes(&input)
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        if self.prefix.is_empty() {
+            return self.tag.digest_collection(tag, list);
+        }
+
+        let items: Vec<I::Item> = list.into_iter().collect();
+
+        self.tag.digest_collection(
+            tag,
+            Some(&self.prefix[..])
+                .into_iter()
+                .chain(items.iter().map(|item| item.as_ref())),
+        )
+    }
+
+    fn digest_stream<R: Read>(&self, tag: Tag, reader: &mut R) -> io::Result<Harvest> {
+        self.tag
+            .digest_stream(tag, &mut (&self.prefix[..]).chain(reader))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::{Identity, Sha2256};
+    use stream::RawStream;
+    use value::Value;
+
+    #[test]
+    fn identity_input() {
+        let hash = "foo".digest(NamespacedMultihash::new(Identity, "ns"));
+
+        assert_eq!(hash.digest().as_slice(), b"u\x02nsfoo");
+    }
+
+    #[test]
+    fn every_node() {
+        let namespaced = NamespacedMultihash::new(Sha2256, "ns");
+        let value: Value<Sha2256> = Value::List(vec![Value::String("foo".into())]);
+        let expected = namespaced.digest_collection(
+            Tag::List,
+            vec![namespaced.digest_primitive(Tag::Unicode, b"foo")],
+        );
+
+        assert_eq!(value.digest(namespaced.clone()).digest(), &expected);
+        assert_ne!(
+            value.digest(namespaced).digest(),
+            value.digest(Sha2256).digest()
+        );
+    }
+
+    #[test]
+    fn streams_match_bytes() {
+        let namespaced = NamespacedMultihash::new(Sha2256, "ns");
+        let bytes: Vec<u8> = (0..20000).map(|i| (i % 251) as u8).collect();
+
+        assert_eq!(
+            RawStream::new(&bytes[..])
+                .digest(namespaced.clone())
+                .digest(),
+            bytes[..].digest(namespaced).digest()
+        );
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Algorithm policies.
+//!
+//! An [`AlgorithmPolicy`] decides which algorithms are acceptable, so weak ones like SHA-1 can be
+//! rejected when parsing seals or deserializing documents.
+
+use super::Stamp;
+
+/// An allowlist and a denylist of algorithms, matched by multihash code.
+///
+/// The default policy permits every algorithm. Once an algorithm is allowed, only the allowed
+/// ones are permitted. Denied algorithms are never permitted.
+///
+/// ```
+/// use blot::multihash::{AlgorithmPolicy, Stamp};
+///
+/// let policy = AlgorithmPolicy::new().deny(Stamp::Sha1);
+///
+/// assert!(!policy.permits(&Stamp::Sha1));
+/// assert!(policy.permits(&Stamp::Sha2256));
+///
+/// let policy = AlgorithmPolicy::new().allow(Stamp::Sha2256);
+///
+/// assert!(!policy.permits(&Stamp::Sha3256));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlgorithmPolicy {
+    allowed: Option<Vec<u64>>,
+    denied: Vec<u64>,
+}
+
+impl AlgorithmPolicy {
+    /// Creates a policy that permits every algorithm.
+    pub fn new() -> AlgorithmPolicy {
+        AlgorithmPolicy::default()
+    }
+
+    /// Permits the algorithm, and from then on only the allowed ones.
+    pub fn allow(mut self, stamp: Stamp) -> AlgorithmPolicy {
+        self.allowed
+            .get_or_insert_with(Vec::new)
+            .push(stamp.code().into());
+        self
+    }
+
+    /// Never permits the algorithm, whatever the allowlist says.
+    pub fn deny(mut self, stamp: Stamp) -> AlgorithmPolicy {
+        self.denied.push(stamp.code().into());
+        self
+    }
+
+    /// Whether a seal or hash stamped with the algorithm is acceptable.
+    pub fn permits(&self, stamp: &Stamp) -> bool {
+        let code: u64 = stamp.code().into();
+        let allowed = match self.allowed {
+            Some(ref allowed) => allowed.contains(&code),
+            None => true,
+        };
+
+        allowed && !self.denied.contains(&code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deny_wins() {
+        let policy = AlgorithmPolicy::new()
+            .allow(Stamp::Sha1)
+            .allow(Stamp::Sha2256)
+            .deny(Stamp::Sha1);
+
+        assert!(!policy.permits(&Stamp::Sha1));
+        assert!(policy.permits(&Stamp::Sha2256));
+    }
+
+    #[test]
+    fn any_length() {
+        let policy = AlgorithmPolicy::new().deny(Stamp::Shake128 { length: 32 });
+
+        assert!(!policy.permits(&Stamp::Shake128 { length: 16 }));
+    }
+}
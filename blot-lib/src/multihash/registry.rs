@@ -0,0 +1,371 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Registry of the available algorithms.
+//!
+//! [`algorithms`] lists the digesters compiled into blot. A [`Registry`] extends them at runtime
+//! with `Multihash` implementations from other crates so their seals and stamps can be resolved.
+
+use super::{Harvest, Multihash, Stamp};
+use std::error;
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Arc;
+use tag::Tag;
+
+/// Returns the stamps of the digesters compiled in, following the enabled features, ordered by
+/// multihash code.
+///
+/// ```
+/// use blot::multihash::{self, Stamp};
+///
+/// let names: Vec<&str> = multihash::algorithms().iter().map(Stamp::name).collect();
+///
+/// assert!(names.contains(&"sha2-256"));
+/// ```
+pub fn algorithms() -> Vec<Stamp> {
+    let mut stamps = vec![Stamp::Identity { length: 0 }];
+
+    #[cfg(feature = "sha-1")]
+    stamps.push(Stamp::Sha1);
+    #[cfg(feature = "sha2")]
+    stamps.extend(&[Stamp::Sha2256, Stamp::Sha2512]);
+    #[cfg(feature = "sha3")]
+    stamps.extend(&[
+        Stamp::Sha3512,
+        Stamp::Sha3384,
+        Stamp::Sha3256,
+        Stamp::Sha3224,
+    ]);
+    #[cfg(feature = "sha3")]
+    stamps.extend(&[Stamp::Keccak256, Stamp::Keccak512]);
+    #[cfg(feature = "blake3")]
+    stamps.push(Stamp::Blake3);
+    #[cfg(feature = "sha2")]
+    stamps.extend(&[Stamp::Sha2384, Stamp::DblSha2256, Stamp::Sha2224]);
+    #[cfg(feature = "blake2")]
+    stamps.extend(&[Stamp::Blake2b512, Stamp::Blake2s256]);
+
+    stamps
+}
+
+/// Returns the compiled-in algorithm with the given name.
+///
+/// ```
+/// use blot::multihash::{self, Stamp};
+///
+/// assert_eq!(multihash::find("blake2s-256"), Some(Stamp::Blake2s256));
+/// assert_eq!(multihash::find("md5"), None);
+/// ```
+pub fn find(name: &str) -> Option<Stamp> {
+    algorithms().into_iter().find(|stamp| stamp.name() == name)
+}
+
+/// A [`Multihash`] usable behind a pointer, with its type erased.
+pub trait Digester: Send + Sync {
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest;
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest;
+    fn digest_collection(&self, tag: Tag, list: &mut dyn Iterator<Item = &[u8]>) -> Harvest;
+    fn digest_stream(&self, tag: Tag, reader: &mut dyn Read) -> io::Result<Harvest>;
+}
+
+impl<T: Multihash + Send + Sync> Digester for T {
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        Multihash::digest_primitive(self, tag, bytes)
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        Multihash::digest_bytes(self, bytes)
+    }
+
+    fn digest_collection(&self, tag: Tag, list: &mut dyn Iterator<Item = &[u8]>) -> Harvest {
+        Multihash::digest_collection(self, tag, list)
+    }
+
+    fn digest_stream(&self, tag: Tag, mut reader: &mut dyn Read) -> io::Result<Harvest> {
+        Multihash::digest_stream(self, tag, &mut reader)
+    }
+}
+
+/// A registered algorithm.
+#[derive(Clone)]
+pub struct Algorithm {
+    stamp: Stamp,
+    name: String,
+    digester: Arc<dyn Digester>,
+}
+
+impl Algorithm {
+    pub fn stamp(&self) -> Stamp {
+        self.stamp
+    }
+
+    pub fn code(&self) -> u64 {
+        self.stamp.code().into()
+    }
+
+    pub fn length(&self) -> u8 {
+        self.stamp.length()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn digester(&self) -> &dyn Digester {
+        &*self.digester
+    }
+}
+
+impl fmt::Debug for Algorithm {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Algorithm")
+            .field("stamp", &self.stamp)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Algorithms are equal if they have the same code, length and name.
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Algorithm) -> bool {
+        self.stamp == other.stamp && self.name == other.name
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RegistryError {
+    /// An algorithm with the same multihash code is already registered.
+    DuplicateCode(u64),
+    /// An algorithm with the same name is already registered.
+    DuplicateName(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::DuplicateCode(code) => {
+                write!(formatter, "code {:#x} is already registered", code)
+            }
+            RegistryError::DuplicateName(name) => {
+                write!(formatter, "name '{}' is already registered", name)
+            }
+        }
+    }
+}
+
+impl error::Error for RegistryError {}
+
+/// A set of algorithms resolvable by code or name.
+///
+/// ```
+/// use blot::multihash::{Harvest, Multihash, Registry, Stamp};
+/// use blot::seal::Seal;
+/// use blot::tag::Tag;
+/// use blot::uvar::Uvar;
+///
+/// /// A toy algorithm that keeps the first 4 bytes.
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Head;
+///
+/// impl Multihash for Head {
+///     type Digester = ();
+///
+///     fn length(&self) -> u8 { 4 }
+///     fn code(&self) -> Uvar { Uvar::from(0x8001) }
+///     fn name(&self) -> &str { "head" }
+///
+///     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+///         let mut digest = vec![tag.to_bytes()[0]];
+///         digest.extend(bytes.iter().take(3));
+///         digest.resize(4, 0);
+///         digest.into()
+///     }
+///
+///     fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+///     where
+///         I: IntoIterator,
+///         I::Item: AsRef<[u8]>,
+///     {
+///         let bytes: Vec<u8> = list.into_iter().flat_map(|item| item.as_ref().to_vec()).collect();
+///         self.digest_primitive(tag, &bytes)
+///     }
+/// }
+///
+/// let mut registry = Registry::builtin();
+/// registry.register(Head).unwrap();
+///
+/// assert_eq!(registry.by_name("head").unwrap().code(), 0x8001);
+///
+/// let seal: Seal<blot::multihash::Sha2256> = Seal::from_hash_str_in("81800204deadbeef", &registry).unwrap();
+/// assert_eq!(seal.tag(), &Stamp::Other { code: 0x8001, length: 4 });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Registry {
+    algorithms: Vec<Algorithm>,
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Creates a registry with the compiled-in digesters, see [`algorithms`].
+    pub fn builtin() -> Registry {
+        let mut registry = Registry::new();
+        registry.insert(super::Identity);
+
+        #[cfg(feature = "sha-1")]
+        registry.insert(super::Sha1);
+        #[cfg(feature = "sha2")]
+        {
+            registry.insert(super::Sha2256);
+            registry.insert(super::Sha2512);
+        }
+        #[cfg(feature = "sha3")]
+        {
+            registry.insert(super::Sha3512);
+            registry.insert(super::Sha3384);
+            registry.insert(super::Sha3256);
+            registry.insert(super::Sha3224);
+        }
+        #[cfg(feature = "sha3")]
+        {
+            registry.insert(super::Keccak256);
+            registry.insert(super::Keccak512);
+        }
+        #[cfg(feature = "blake3")]
+        registry.insert(super::Blake3);
+        #[cfg(feature = "sha2")]
+        {
+            registry.insert(super::Sha2384);
+            registry.insert(super::DblSha2256);
+            registry.insert(super::Sha2224);
+        }
+        #[cfg(feature = "blake2")]
+        {
+            registry.insert(super::Blake2b512);
+            registry.insert(super::Blake2s256);
+        }
+
+        registry
+    }
+
+    /// Adds an algorithm. Codes and names must be unique within the registry.
+    pub fn register<T: Multihash + Send + Sync + 'static>(
+        &mut self,
+        tag: T,
+    ) -> Result<(), RegistryError> {
+        let code: u64 = tag.code().into();
+
+        if self.by_code(code).is_some() {
+            return Err(RegistryError::DuplicateCode(code));
+        }
+
+        if self.by_name(tag.name()).is_some() {
+            return Err(RegistryError::DuplicateName(tag.name().to_string()));
+        }
+
+        self.insert(tag);
+
+        Ok(())
+    }
+
+    fn insert<T: Multihash + Send + Sync + 'static>(&mut self, tag: T) {
+        self.algorithms.push(Algorithm {
+            stamp: Stamp::of(&tag),
+            name: tag.name().to_string(),
+            digester: Arc::new(tag),
+        });
+    }
+
+    /// The registered algorithms in registration order.
+    pub fn algorithms(&self) -> &[Algorithm] {
+        &self.algorithms
+    }
+
+    pub fn by_code(&self, code: u64) -> Option<&Algorithm> {
+        self.algorithms
+            .iter()
+            .find(|algorithm| algorithm.code() == code)
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&Algorithm> {
+        self.algorithms
+            .iter()
+            .find(|algorithm| algorithm.name() == name)
+    }
+
+    /// The name of the algorithm behind the stamp, including registered custom algorithms.
+    pub fn name_of(&self, stamp: &Stamp) -> Option<&str> {
+        // The identity has no fixed length.
+        self.by_code(stamp.code().into())
+            .filter(|algorithm| algorithm.length() == stamp.length() || algorithm.length() == 0)
+            .map(Algorithm::name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted_by_code() {
+        let codes: Vec<u64> = algorithms()
+            .iter()
+            .map(|stamp| stamp.code().into())
+            .collect();
+        let mut sorted = codes.clone();
+        sorted.sort_unstable();
+
+        assert_eq!(codes, sorted);
+    }
+
+    #[test]
+    fn builtin_matches_algorithms() {
+        let stamps: Vec<Stamp> = Registry::builtin()
+            .algorithms()
+            .iter()
+            .map(Algorithm::stamp)
+            .collect();
+
+        assert_eq!(stamps, algorithms());
+    }
+
+    #[test]
+    fn duplicates() {
+        use multihash::Sha2256;
+
+        let mut registry = Registry::builtin();
+
+        assert_eq!(
+            registry.register(Sha2256),
+            Err(RegistryError::DuplicateCode(0x12))
+        );
+        assert_eq!(registry.name_of(&Stamp::Sha2256), Some("sha2-256"));
+    }
+
+    #[test]
+    fn erased_digester() {
+        use core::Blot;
+        use multihash::Sha2256;
+
+        let registry = Registry::builtin();
+        let digester = registry.by_name("sha2-256").unwrap().digester();
+        let mut reader: &[u8] = b"foo";
+
+        assert_eq!(
+            digester.digest_primitive(Tag::Unicode, b"foo"),
+            "foo".blot(&Sha2256)
+        );
+        assert_eq!(
+            digester.digest_stream(Tag::Raw, &mut reader).unwrap(),
+            b"foo"[..].blot(&Sha2256)
+        );
+    }
+}
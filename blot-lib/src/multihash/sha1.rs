@@ -12,7 +12,7 @@ use crypto_sha1::Digest;
 use tag::Tag;
 use uvar::Uvar;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha1;
 
 impl Default for Sha1 {
@@ -56,19 +56,29 @@ impl Multihash for Sha1 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 }
@@ -14,7 +14,7 @@ use uvar::Uvar;
 
 // Sha2-256
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha2256;
 
 impl Default for Sha2256 {
@@ -58,26 +58,36 @@ impl Multihash for Sha2256 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 }
 
 // Sha2-512
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha2512;
 
 impl Default for Sha2512 {
@@ -121,19 +131,329 @@ impl Multihash for Sha2512 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+
+        for bytes in list {
+            digester.input(bytes.as_ref());
+        }
+
+        digester.result().as_slice().into()
+    }
+}
+
+// Sha2-224
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sha2224;
+
+impl Default for Sha2224 {
+    fn default() -> Self {
+        Sha2224
+    }
+}
+
+impl From<Sha2224> for Uvar {
+    fn from(hash: Sha2224) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<Sha2224, MultihashError> {
+    fn from(code: Uvar) -> Result<Sha2224, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x1013 {
+            Ok(Sha2224)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for Sha2224 {
+    type Digester = digester::Sha224;
+
+    fn name(&self) -> &'static str {
+        "sha2-224"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x1013)
+    }
+
+    fn length(&self) -> u8 {
+        28
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+
+        for bytes in list {
+            digester.input(bytes.as_ref());
+        }
+
+        digester.result().as_slice().into()
+    }
+}
+
+// Sha2-384
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sha2384;
+
+impl Default for Sha2384 {
+    fn default() -> Self {
+        Sha2384
+    }
+}
+
+impl From<Sha2384> for Uvar {
+    fn from(hash: Sha2384) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<Sha2384, MultihashError> {
+    fn from(code: Uvar) -> Result<Sha2384, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x20 {
+            Ok(Sha2384)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for Sha2384 {
+    type Digester = digester::Sha384;
+
+    fn name(&self) -> &'static str {
+        "sha2-384"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x20)
+    }
+
+    fn length(&self) -> u8 {
+        48
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
+    }
+}
+
+// Double Sha2-256
+
+/// Sha2-256 applied twice, as used by Bitcoin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DblSha2256;
+
+impl Default for DblSha2256 {
+    fn default() -> Self {
+        DblSha2256
+    }
+}
+
+impl From<DblSha2256> for Uvar {
+    fn from(hash: DblSha2256) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<DblSha2256, MultihashError> {
+    fn from(code: Uvar) -> Result<DblSha2256, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x56 {
+            Ok(DblSha2256)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for DblSha2256 {
+    type Digester = digester::Sha256;
+
+    fn name(&self) -> &'static str {
+        "dbl-sha2-256"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x56)
+    }
+
+    fn length(&self) -> u8 {
+        32
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        Self::Digester::digest(&digester.result()).as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(bytes);
+        Self::Digester::digest(&digester.result()).as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+
+        for bytes in list {
+            digester.input(bytes.as_ref());
+        }
+
+        Self::Digester::digest(&digester.result()).as_slice().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+
+    #[test]
+    fn double_sha2() {
+        assert_eq!(
+            "foo".digest(DblSha2256).digest().to_string(),
+            "be261b8d890b540fb3e9a7a9f016245ac6731979338d6c53d23320fc4b137440"
+        );
+    }
+
+    #[test]
+    fn stream_matches_primitive() {
+        let input = vec![0x61; 20000];
+        let mut reader = &input[..];
+
+        assert_eq!(
+            Sha2256.digest_stream(Tag::Raw, &mut reader).unwrap(),
+            Sha2256.digest_primitive(Tag::Raw, &input)
+        );
+    }
+
+    #[test]
+    fn stream_error() {
+        use std::io::{self, Read};
+
+        struct Broken;
+
+        impl Read for Broken {
+            fn read(&mut self, _buffer: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+
+        let err = Sha2256.digest_stream(Tag::Raw, &mut Broken).unwrap_err();
+
+        assert_eq!(err.to_string(), "broken");
+    }
+
+    #[test]
+    fn default_digest_bytes() {
+        #[derive(Default, PartialEq)]
+        struct Bare;
+
+        impl Multihash for Bare {
+            type Digester = ();
+
+            fn length(&self) -> u8 {
+                32
+            }
+
+            fn code(&self) -> Uvar {
+                Uvar::from(0x8001)
+            }
+
+            fn name(&self) -> &str {
+                "bare"
+            }
+
+            fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+                Sha2256.digest_primitive(tag, bytes)
+            }
+
+            fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+            where
+                I: IntoIterator,
+                I::Item: AsRef<[u8]>,
+            {
+                Sha2256.digest_collection(tag, list)
+            }
+        }
+
+        for input in [&b"ufoo"[..], b"\x00", b"{\"a\":1}"].iter() {
+            assert_eq!(Bare.digest_bytes(input), Sha2256.digest_bytes(input));
+        }
     }
 }
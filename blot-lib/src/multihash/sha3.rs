@@ -6,15 +6,16 @@
 
 //! Blot implementation for sha3.
 
-use super::{Harvest, Multihash, MultihashError};
+use super::{Harvest, Multihash, MultihashError, Stamp};
 use crypto_sha3 as digester;
+use crypto_sha3::digest::{self, ExtendableOutput};
 use crypto_sha3::Digest;
 use tag::Tag;
 use uvar::Uvar;
 
 // Sha3-512
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha3512;
 
 impl Default for Sha3512 {
@@ -58,26 +59,36 @@ impl Multihash for Sha3512 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 }
 
 // Sha3-384
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha3384;
 
 impl Default for Sha3384 {
@@ -121,26 +132,36 @@ impl Multihash for Sha3384 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 }
 
 // Sha3-256
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha3256;
 
 impl Default for Sha3256 {
@@ -184,26 +205,36 @@ impl Multihash for Sha3256 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 }
 
 // Sha3-224
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sha3224;
 
 impl Default for Sha3224 {
@@ -247,19 +278,377 @@ impl Multihash for Sha3224 {
 
     fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
         digester.input(bytes);
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
     }
 
-    fn digest_collection(&self, tag: Tag, list: Vec<Vec<u8>>) -> Harvest {
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
         let mut digester = Self::Digester::default();
-        digester.input(&tag.to_bytes());
+        digester.input(tag.to_bytes());
 
         for bytes in list {
-            digester.input(&bytes);
+            digester.input(bytes.as_ref());
         }
 
-        digester.result().as_ref().to_vec().into()
+        digester.result().as_slice().into()
+    }
+}
+
+// Keccak-256
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keccak256;
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Keccak256
+    }
+}
+
+impl From<Keccak256> for Uvar {
+    fn from(hash: Keccak256) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<Keccak256, MultihashError> {
+    fn from(code: Uvar) -> Result<Keccak256, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x1b {
+            Ok(Keccak256)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for Keccak256 {
+    type Digester = digester::Keccak256;
+
+    fn name(&self) -> &'static str {
+        "keccak-256"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x1b)
+    }
+
+    fn length(&self) -> u8 {
+        32
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+
+        for bytes in list {
+            digester.input(bytes.as_ref());
+        }
+
+        digester.result().as_slice().into()
+    }
+}
+
+// Keccak-512
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keccak512;
+
+impl Default for Keccak512 {
+    fn default() -> Self {
+        Keccak512
+    }
+}
+
+impl From<Keccak512> for Uvar {
+    fn from(hash: Keccak512) -> Uvar {
+        hash.code()
+    }
+}
+
+impl From<Uvar> for Result<Keccak512, MultihashError> {
+    fn from(code: Uvar) -> Result<Keccak512, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x1d {
+            Ok(Keccak512)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl Multihash for Keccak512 {
+    type Digester = digester::Keccak512;
+
+    fn name(&self) -> &'static str {
+        "keccak-512"
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x1d)
+    }
+
+    fn length(&self) -> u8 {
+        64
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digester.input(bytes);
+        digester.result().as_slice().into()
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digester.input(tag.to_bytes());
+
+        for bytes in list {
+            digester.input(bytes.as_ref());
+        }
+
+        digester.result().as_slice().into()
+    }
+}
+
+// Shake-128
+
+/// SHAKE128 with an output of `LENGTH` bytes, from 1 to 255.
+///
+/// The multihash code is always 0x18: the length is only recorded in the multihash length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shake128<const LENGTH: u8>;
+
+impl<const LENGTH: u8> Shake128<LENGTH> {
+    const OUTPUT: u8 = {
+        assert!(LENGTH != 0, "SHAKE128 length must be at least 1 byte");
+        LENGTH
+    };
+}
+
+impl<const LENGTH: u8> Default for Shake128<LENGTH> {
+    fn default() -> Self {
+        Shake128
+    }
+}
+
+impl<const LENGTH: u8> From<Shake128<LENGTH>> for Uvar {
+    fn from(hash: Shake128<LENGTH>) -> Uvar {
+        hash.code()
+    }
+}
+
+impl<const LENGTH: u8> From<Uvar> for Result<Shake128<LENGTH>, MultihashError> {
+    fn from(code: Uvar) -> Result<Shake128<LENGTH>, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x18 {
+            Ok(Shake128)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl<const LENGTH: u8> Multihash for Shake128<LENGTH> {
+    type Digester = digester::Shake128;
+
+    fn name(&self) -> &'static str {
+        Stamp::of(self).name()
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x18)
+    }
+
+    fn length(&self) -> u8 {
+        Self::OUTPUT
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digest::Input::input(&mut digester, tag.to_bytes());
+        digest::Input::input(&mut digester, bytes);
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digest::XofReader::read(&mut digester.xof_result(), bytes)
+        })
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digest::Input::input(&mut digester, bytes);
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digest::XofReader::read(&mut digester.xof_result(), bytes)
+        })
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digest::Input::input(&mut digester, tag.to_bytes());
+
+        for bytes in list {
+            digest::Input::input(&mut digester, bytes.as_ref());
+        }
+
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digest::XofReader::read(&mut digester.xof_result(), bytes)
+        })
+    }
+}
+
+// Shake-256
+
+/// SHAKE256 with an output of `LENGTH` bytes, from 1 to 255.
+///
+/// The multihash code is always 0x19: the length is only recorded in the multihash length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shake256<const LENGTH: u8>;
+
+impl<const LENGTH: u8> Shake256<LENGTH> {
+    const OUTPUT: u8 = {
+        assert!(LENGTH != 0, "SHAKE256 length must be at least 1 byte");
+        LENGTH
+    };
+}
+
+impl<const LENGTH: u8> Default for Shake256<LENGTH> {
+    fn default() -> Self {
+        Shake256
+    }
+}
+
+impl<const LENGTH: u8> From<Shake256<LENGTH>> for Uvar {
+    fn from(hash: Shake256<LENGTH>) -> Uvar {
+        hash.code()
+    }
+}
+
+impl<const LENGTH: u8> From<Uvar> for Result<Shake256<LENGTH>, MultihashError> {
+    fn from(code: Uvar) -> Result<Shake256<LENGTH>, MultihashError> {
+        let n: u64 = code.into();
+
+        if n == 0x19 {
+            Ok(Shake256)
+        } else {
+            Err(MultihashError::Unknown)
+        }
+    }
+}
+
+impl<const LENGTH: u8> Multihash for Shake256<LENGTH> {
+    type Digester = digester::Shake256;
+
+    fn name(&self) -> &'static str {
+        Stamp::of(self).name()
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x19)
+    }
+
+    fn length(&self) -> u8 {
+        Self::OUTPUT
+    }
+
+    fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digest::Input::input(&mut digester, tag.to_bytes());
+        digest::Input::input(&mut digester, bytes);
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digest::XofReader::read(&mut digester.xof_result(), bytes)
+        })
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        let mut digester = Self::Digester::default();
+        digest::Input::input(&mut digester, bytes);
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digest::XofReader::read(&mut digester.xof_result(), bytes)
+        })
+    }
+
+    fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut digester = Self::Digester::default();
+        digest::Input::input(&mut digester, tag.to_bytes());
+
+        for bytes in list {
+            digest::Input::input(&mut digester, bytes.as_ref());
+        }
+
+        Harvest::filled(usize::from(self.length()), |bytes| {
+            digest::XofReader::read(&mut digester.xof_result(), bytes)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+
+    #[test]
+    fn shake_lengths() {
+        let short = "foo".digest(Shake128::<16>);
+        let long = "foo".digest(Shake256::<100>);
+
+        assert_eq!(
+            short.digest().to_string(),
+            "df53945943882ecf6058320070ab5f50"
+        );
+        assert!(long
+            .digest()
+            .to_string()
+            .starts_with("254d2c29e96526187722"));
+        assert_eq!(long.digest().as_slice().len(), 100);
+        assert_eq!(Stamp::of(long.tag()), Stamp::Shake256 { length: 100 });
+        assert_eq!(long.tag().name(), "shake-256");
     }
 }
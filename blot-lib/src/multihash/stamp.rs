@@ -0,0 +1,334 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Dynamic multihash tags.
+
+use super::Multihash;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use uvar::Uvar;
+
+/// Names of the blake2b family, by output length in bytes.
+#[rustfmt::skip]
+const BLAKE2B_NAMES: [&str; 64] = [
+    "blake2b-8", "blake2b-16", "blake2b-24", "blake2b-32", "blake2b-40", "blake2b-48",
+    "blake2b-56", "blake2b-64", "blake2b-72", "blake2b-80", "blake2b-88", "blake2b-96",
+    "blake2b-104", "blake2b-112", "blake2b-120", "blake2b-128", "blake2b-136", "blake2b-144",
+    "blake2b-152", "blake2b-160", "blake2b-168", "blake2b-176", "blake2b-184", "blake2b-192",
+    "blake2b-200", "blake2b-208", "blake2b-216", "blake2b-224", "blake2b-232", "blake2b-240",
+    "blake2b-248", "blake2b-256", "blake2b-264", "blake2b-272", "blake2b-280", "blake2b-288",
+    "blake2b-296", "blake2b-304", "blake2b-312", "blake2b-320", "blake2b-328", "blake2b-336",
+    "blake2b-344", "blake2b-352", "blake2b-360", "blake2b-368", "blake2b-376", "blake2b-384",
+    "blake2b-392", "blake2b-400", "blake2b-408", "blake2b-416", "blake2b-424", "blake2b-432",
+    "blake2b-440", "blake2b-448", "blake2b-456", "blake2b-464", "blake2b-472", "blake2b-480",
+    "blake2b-488", "blake2b-496", "blake2b-504", "blake2b-512",
+];
+
+/// Identifies a multihash algorithm at runtime.
+///
+/// Unlike [`Multihash`] implementations, stamps are plain data: they describe an algorithm
+/// without being able to compute digests with it, and they are available regardless of the
+/// enabled digester features.
+///
+/// ```
+/// use blot::multihash::{Sha3256, Stamp};
+///
+/// assert_eq!(Stamp::from_code(0x16), Some(Stamp::Sha3256));
+/// assert_eq!(Stamp::of(&Sha3256), Stamp::Sha3256);
+/// assert_eq!(Stamp::Sha3256.name(), "sha3-256");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stamp {
+    /// The identity multihash, with the length of the embedded value.
+    Identity { length: u8 },
+    Sha1,
+    Sha2224,
+    Sha2256,
+    Sha2384,
+    Sha2512,
+    DblSha2256,
+    Sha3224,
+    Sha3256,
+    Sha3384,
+    Sha3512,
+    Keccak256,
+    Keccak512,
+    /// SHAKE128 with a variable output length.
+    Shake128 { length: u8 },
+    /// SHAKE256 with a variable output length.
+    Shake256 { length: u8 },
+    Blake2b512,
+    Blake2s256,
+    /// Blake2b with an output shorter than 64 bytes.
+    Blake2b { length: u8 },
+    Blake3,
+    /// An algorithm unknown to blot, identified by its multihash code and digest length.
+    Other { code: u64, length: u8 },
+}
+
+impl Stamp {
+    /// Returns the known stamp for the given multihash code.
+    pub fn from_code(code: u64) -> Option<Stamp> {
+        match code {
+            0x11 => Some(Stamp::Sha1),
+            0x12 => Some(Stamp::Sha2256),
+            0x13 => Some(Stamp::Sha2512),
+            0x14 => Some(Stamp::Sha3512),
+            0x15 => Some(Stamp::Sha3384),
+            0x16 => Some(Stamp::Sha3256),
+            0x17 => Some(Stamp::Sha3224),
+            0x1b => Some(Stamp::Keccak256),
+            0x1d => Some(Stamp::Keccak512),
+            0x1e => Some(Stamp::Blake3),
+            0x20 => Some(Stamp::Sha2384),
+            0x56 => Some(Stamp::DblSha2256),
+            0x1013 => Some(Stamp::Sha2224),
+            0xb201..=0xb23f => Some(Stamp::Blake2b {
+                length: (code - 0xb200) as u8,
+            }),
+            0xb240 => Some(Stamp::Blake2b512),
+            0xb260 => Some(Stamp::Blake2s256),
+            _ => None,
+        }
+    }
+
+    /// Returns the known stamp for the given algorithm name, e.g. `sha2-256`.
+    ///
+    /// Like [`Stamp::from_code`], it doesn't resolve algorithms without a fixed length.
+    pub fn from_name(name: &str) -> Option<Stamp> {
+        let stamp = match name {
+            "sha1" => Stamp::Sha1,
+            "sha2-224" => Stamp::Sha2224,
+            "sha2-256" => Stamp::Sha2256,
+            "sha2-384" => Stamp::Sha2384,
+            "sha2-512" => Stamp::Sha2512,
+            "dbl-sha2-256" => Stamp::DblSha2256,
+            "sha3-224" => Stamp::Sha3224,
+            "sha3-256" => Stamp::Sha3256,
+            "sha3-384" => Stamp::Sha3384,
+            "sha3-512" => Stamp::Sha3512,
+            "keccak-256" => Stamp::Keccak256,
+            "keccak-512" => Stamp::Keccak512,
+            "blake2s-256" => Stamp::Blake2s256,
+            "blake3" => Stamp::Blake3,
+            _ => {
+                let position = BLAKE2B_NAMES.iter().position(|known| *known == name)?;
+
+                return Stamp::from_code(0xb201 + position as u64);
+            }
+        };
+
+        Some(stamp)
+    }
+
+    /// Returns the known stamp for the given multihash code and digest length.
+    ///
+    /// Unlike [`Stamp::from_code`], it resolves extendable-output algorithms, whose length is not
+    /// determined by their code.
+    ///
+    /// ```
+    /// use blot::multihash::Stamp;
+    ///
+    /// assert_eq!(Stamp::from_parts(0x19, 20), Some(Stamp::Shake256 { length: 20 }));
+    /// assert_eq!(Stamp::from_parts(0x12, 20), None);
+    /// ```
+    pub fn from_parts(code: u64, length: u8) -> Option<Stamp> {
+        match code {
+            0x18 if length > 0 => Some(Stamp::Shake128 { length }),
+            0x19 if length > 0 => Some(Stamp::Shake256 { length }),
+            0x00 => Some(Stamp::Identity { length }),
+            _ => Stamp::from_code(code).filter(|stamp| stamp.length() == length),
+        }
+    }
+
+    /// Returns the stamp describing the given algorithm.
+    pub fn of<T: Multihash>(tag: &T) -> Stamp {
+        let code: u64 = tag.code().into();
+
+        Stamp::from_parts(code, tag.length()).unwrap_or(Stamp::Other {
+            code,
+            length: tag.length(),
+        })
+    }
+
+    pub fn code(&self) -> Uvar {
+        let code = match self {
+            Stamp::Identity { .. } => 0x00,
+            Stamp::Sha1 => 0x11,
+            Stamp::Sha2256 => 0x12,
+            Stamp::Sha2512 => 0x13,
+            Stamp::Sha3512 => 0x14,
+            Stamp::Sha3384 => 0x15,
+            Stamp::Sha3256 => 0x16,
+            Stamp::Sha3224 => 0x17,
+            Stamp::Shake128 { .. } => 0x18,
+            Stamp::Shake256 { .. } => 0x19,
+            Stamp::Keccak256 => 0x1b,
+            Stamp::Keccak512 => 0x1d,
+            Stamp::Blake3 => 0x1e,
+            Stamp::Sha2384 => 0x20,
+            Stamp::DblSha2256 => 0x56,
+            Stamp::Sha2224 => 0x1013,
+            Stamp::Blake2b { length } => 0xb200 + u64::from(*length),
+            Stamp::Blake2b512 => 0xb240,
+            Stamp::Blake2s256 => 0xb260,
+            Stamp::Other { code, .. } => *code,
+        };
+
+        Uvar::from(code)
+    }
+
+    pub fn length(&self) -> u8 {
+        match self {
+            Stamp::Sha1 => 20,
+            Stamp::Sha2224 | Stamp::Sha3224 => 28,
+            Stamp::Sha2256
+            | Stamp::DblSha2256
+            | Stamp::Sha3256
+            | Stamp::Keccak256
+            | Stamp::Blake2s256
+            | Stamp::Blake3 => 32,
+            Stamp::Sha2384 | Stamp::Sha3384 => 48,
+            Stamp::Sha2512 | Stamp::Sha3512 | Stamp::Keccak512 | Stamp::Blake2b512 => 64,
+            Stamp::Identity { length }
+            | Stamp::Shake128 { length }
+            | Stamp::Shake256 { length }
+            | Stamp::Blake2b { length }
+            | Stamp::Other { length, .. } => *length,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stamp::Identity { .. } => "identity",
+            Stamp::Sha1 => "sha1",
+            Stamp::Sha2224 => "sha2-224",
+            Stamp::Sha2256 => "sha2-256",
+            Stamp::Sha2384 => "sha2-384",
+            Stamp::Sha2512 => "sha2-512",
+            Stamp::DblSha2256 => "dbl-sha2-256",
+            Stamp::Sha3224 => "sha3-224",
+            Stamp::Sha3256 => "sha3-256",
+            Stamp::Sha3384 => "sha3-384",
+            Stamp::Sha3512 => "sha3-512",
+            Stamp::Shake128 { .. } => "shake-128",
+            Stamp::Shake256 { .. } => "shake-256",
+            Stamp::Keccak256 => "keccak-256",
+            Stamp::Keccak512 => "keccak-512",
+            Stamp::Blake2b512 => "blake2b-512",
+            Stamp::Blake2s256 => "blake2s-256",
+            Stamp::Blake2b { length } => match length {
+                1..=64 => BLAKE2B_NAMES[usize::from(*length) - 1],
+                _ => "unknown",
+            },
+            Stamp::Blake3 => "blake3",
+            Stamp::Other { .. } => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StampError {
+    /// No known algorithm has the multihash code.
+    UnknownCode(u64),
+    /// No known algorithm has the name.
+    UnknownName(String),
+}
+
+impl fmt::Display for StampError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StampError::UnknownCode(code) => write!(formatter, "unknown algorithm {:#x}", code),
+            StampError::UnknownName(name) => write!(formatter, "unknown algorithm '{}'", name),
+        }
+    }
+}
+
+impl error::Error for StampError {}
+
+/// Fallible conversion from a multihash code, typically read from a seal prefix.
+///
+/// ```
+/// use blot::multihash::{Stamp, StampError};
+/// use std::convert::TryFrom;
+///
+/// assert_eq!(Stamp::try_from(0x12), Ok(Stamp::Sha2256));
+/// assert_eq!(Stamp::try_from(0x99), Err(StampError::UnknownCode(0x99)));
+/// ```
+impl TryFrom<u64> for Stamp {
+    type Error = StampError;
+
+    fn try_from(code: u64) -> Result<Stamp, StampError> {
+        Stamp::from_code(code).ok_or(StampError::UnknownCode(code))
+    }
+}
+
+/// Fallible conversion from an algorithm name, typically given on the command line.
+impl TryFrom<&str> for Stamp {
+    type Error = StampError;
+
+    fn try_from(name: &str) -> Result<Stamp, StampError> {
+        Stamp::from_name(name).ok_or_else(|| StampError::UnknownName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::*;
+
+    #[test]
+    fn matches_digesters() {
+        fn check<T: Multihash>(tag: T) {
+            let stamp = Stamp::of(&tag);
+
+            assert_ne!(stamp.name(), "unknown");
+            assert_eq!(stamp.name(), tag.name());
+            assert_eq!(stamp.code(), tag.code());
+            assert_eq!(stamp.length(), tag.length());
+        }
+
+        check(Identity);
+        check(Sha1);
+        check(Sha2224);
+        check(Sha2256);
+        check(Sha2384);
+        check(DblSha2256);
+        check(Sha2512);
+        check(Sha3224);
+        check(Sha3256);
+        check(Sha3384);
+        check(Sha3512);
+        check(Keccak256);
+        check(Keccak512);
+        check(Blake2b512);
+        check(Blake2s256);
+        check(Blake2b::<20>);
+        check(Shake128::<16>);
+        check(Shake256::<100>);
+        check(Blake3);
+    }
+
+    #[test]
+    fn unknown_code() {
+        assert_eq!(Stamp::from_code(0x00), None);
+    }
+
+    #[test]
+    fn names_roundtrip() {
+        for code in 0..0x10000 {
+            if let Some(stamp) = Stamp::from_code(code) {
+                assert_eq!(Stamp::try_from(stamp.name()), Ok(stamp));
+            }
+        }
+
+        assert_eq!(
+            Stamp::try_from("shake-128"),
+            Err(StampError::UnknownName("shake-128".into()))
+        );
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! GOV.UK registers item hashes.
+//!
+//! [Registers](https://spec.openregister.org) identify an item by the SHA-256 digest of its
+//! canonical JSON, written as `sha-256:<hex>`. The canonical form has no insignificant whitespace,
+//! object keys sorted by code point and non-ASCII characters left unescaped.
+//!
+//! ```
+//! extern crate serde_json;
+//! extern crate blot;
+//!
+//! use blot::register::{self, ItemHash};
+//!
+//! # fn main() {
+//! let item: serde_json::Value =
+//!     serde_json::from_str(r#"{"name": "United Kingdom", "country": "GB"}"#).unwrap();
+//! let hash = register::item_hash(&item);
+//!
+//! assert_eq!(
+//!     register::canonical_json(&item),
+//!     r#"{"country":"GB","name":"United Kingdom"}"#
+//! );
+//! assert_eq!(
+//!     hash.to_string(),
+//!     "sha-256:74d528a1e3e821892bbdfb0e98d9e00ff5234a02e85e80d7a758f0f5cb170192"
+//! );
+//! assert_eq!(hash.to_string().parse::<ItemHash>().unwrap(), hash);
+//! # }
+//! ```
+//!
+//! Unlike every other digest in blot, an item hash is the digest of serialized bytes, not the
+//! Objecthash of the item.
+
+use crypto_sha2::{Digest, Sha256};
+use hex;
+use multihash::{Hash, Sha2256};
+use serde_json::Value;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+const PREFIX: &str = "sha-256:";
+
+/// The canonical JSON of a register item.
+pub fn canonical_json(item: &Value) -> String {
+    let mut output = String::new();
+    write_canonical(item, &mut output);
+
+    output
+}
+
+fn write_canonical(value: &Value, output: &mut String) {
+    match value {
+        Value::Array(list) => {
+            output.push('[');
+
+            for (index, item) in list.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                write_canonical(item, output);
+            }
+
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            output.push('{');
+
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                output.push_str(&Value::from(key.as_str()).to_string());
+                output.push(':');
+                write_canonical(item, output);
+            }
+
+            output.push('}');
+        }
+        scalar => output.push_str(&scalar.to_string()),
+    }
+}
+
+/// The item hash of a register item.
+pub fn item_hash(item: &Value) -> ItemHash {
+    let digest = Sha256::digest(canonical_json(item).as_bytes());
+
+    ItemHash(Hash::new(Sha2256, digest.as_slice()))
+}
+
+/// A SHA-256 item hash, displayed as `sha-256:<hex>`.
+#[derive(Debug, PartialEq)]
+pub struct ItemHash(Hash<Sha2256>);
+
+impl ItemHash {
+    /// The digest as a sha2-256 multihash.
+    pub fn hash(&self) -> &Hash<Sha2256> {
+        &self.0
+    }
+}
+
+impl fmt::Display for ItemHash {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}{}", PREFIX, self.0.digest())
+    }
+}
+
+impl FromStr for ItemHash {
+    type Err = ItemHashError;
+
+    fn from_str(input: &str) -> Result<ItemHash, ItemHashError> {
+        if !input.starts_with(PREFIX) {
+            return Err(ItemHashError::Prefix);
+        }
+
+        let digest = hex::decode(&input[PREFIX.len()..]).map_err(|_| ItemHashError::Digest)?;
+
+        if digest.len() != 32 {
+            return Err(ItemHashError::Digest);
+        }
+
+        Ok(ItemHash(Hash::new(Sha2256, digest)))
+    }
+}
+
+/// Why a string is not a valid item hash.
+#[derive(Debug, PartialEq)]
+pub enum ItemHashError {
+    /// The string doesn't start with `sha-256:`.
+    Prefix,
+    /// The digest is not 32 bytes of hexadecimal.
+    Digest,
+}
+
+impl fmt::Display for ItemHashError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ItemHashError::Prefix => write!(formatter, "expected the '{}' prefix", PREFIX),
+            ItemHashError::Digest => write!(formatter, "expected a 64-digit hexadecimal digest"),
+        }
+    }
+}
+
+impl error::Error for ItemHashError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn item(input: &str) -> Value {
+        serde_json::from_str(input).unwrap()
+    }
+
+    #[test]
+    fn canonical() {
+        let value = item("{\"b\": [\"x\", \"y\"], \"a\": \"\u{e9} \\\"q\\\"\\n\"}");
+
+        assert_eq!(
+            canonical_json(&value),
+            "{\"a\":\"\u{e9} \\\"q\\\"\\n\",\"b\":[\"x\",\"y\"]}"
+        );
+        assert_eq!(
+            item_hash(&value).to_string(),
+            "sha-256:a7e3e3294b78410a3a4a0a11b3163a7276271e829f9954e6ca879030436464e1"
+        );
+        assert_eq!(
+            item_hash(&item(r#"{"z": {"b": 1, "a": null}}"#)).to_string(),
+            "sha-256:329746ee81a645367e3e9b443bd4163aadac8de19f2adb677ff1116c4e58e565"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("sha-1:00".parse::<ItemHash>(), Err(ItemHashError::Prefix));
+        assert_eq!("sha-256:zz".parse::<ItemHash>(), Err(ItemHashError::Digest));
+        assert_eq!("sha-256:00".parse::<ItemHash>(), Err(ItemHashError::Digest));
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Salted hashing.
+//!
+//! Redacting a low-entropy leaf (e.g. a boolean) doesn't hide it: anyone can hash every
+//! candidate and compare it with the seal. Salting mixes a secret random value into each leaf so
+//! its digest can't be brute-forced without the salt.
+//!
+//! A salted leaf is hashed as the list `[salt, leaf]` where `salt` is a raw value. Salts are
+//! keyed by the path of the leaf in the original document and kept apart from it, so they can
+//! be shared only with the parties allowed to verify the salted digest.
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate blot;
+//! use blot::core::Blot;
+//! use blot::multihash::Sha2256;
+//! use blot::salt::{self, Salts};
+//! use blot::value::Value;
+//!
+//! # fn main() {
+//! let value: Value<Sha2256> = list![Value::Bool(true), "foo"];
+//! let salts = Salts::generate(&value);
+//! let digest = salt::apply(&value, &salts).digest(Sha2256);
+//!
+//! assert!(salt::verify(&value, &salts, &digest));
+//! assert_ne!(digest, value.digest(Sha2256));
+//! # }
+//! ```
+
+use core::Blot;
+use multihash::{wipe, Hash, Multihash};
+use std::collections::HashMap;
+use value::path::{JsonPath, Segment};
+use value::Value;
+
+#[cfg(feature = "salt")]
+use rand::rngs::OsRng;
+#[cfg(feature = "salt")]
+use rand::RngCore;
+
+#[cfg(feature = "blot_json")]
+use hex::FromHex;
+#[cfg(feature = "blot_json")]
+use serde_json::{self, Map};
+#[cfg(feature = "blot_json")]
+use std::error;
+#[cfg(feature = "blot_json")]
+use std::fmt;
+#[cfg(feature = "blot_json")]
+use value::path::PathError;
+
+/// Length in bytes of generated salts.
+pub const SALT_LENGTH: usize = 32;
+
+/// Per-leaf salts keyed by path.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Salts(HashMap<JsonPath, Vec<u8>>);
+
+impl Salts {
+    pub fn new() -> Salts {
+        Salts(HashMap::new())
+    }
+
+    /// Generates a random salt for every leaf of the value.
+    #[cfg(feature = "salt")]
+    pub fn generate<T: Multihash>(value: &Value<T>) -> Salts {
+        let mut salts = Salts::new();
+        let mut rng = OsRng;
+
+        for path in leaf_paths(value, &JsonPath::root()) {
+            let mut salt = vec![0; SALT_LENGTH];
+            rng.fill_bytes(&mut salt);
+            salts.insert(path, salt);
+        }
+
+        salts
+    }
+
+    pub fn insert(&mut self, path: JsonPath, salt: Vec<u8>) {
+        if let Some(mut previous) = self.0.insert(path, salt) {
+            wipe(&mut previous);
+        }
+    }
+
+    pub fn get(&self, path: &JsonPath) -> Option<&[u8]> {
+        self.0.get(path).map(|salt| salt.as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Exports the salts as a JSON object of paths to hexadecimal salts.
+    #[cfg(feature = "blot_json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        let map: Map<String, serde_json::Value> = self
+            .0
+            .iter()
+            .map(|(path, salt)| {
+                let hex: String = salt.iter().map(|byte| format!("{:02x}", byte)).collect();
+                (path.to_string(), hex.into())
+            })
+            .collect();
+
+        serde_json::Value::Object(map)
+    }
+
+    /// Imports salts from their JSON form. See [`Salts::to_json`].
+    #[cfg(feature = "blot_json")]
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &str) -> Result<Salts, SaltError> {
+        let map: HashMap<String, String> =
+            serde_json::from_str(input).map_err(|err| SaltError::Malformed(err.to_string()))?;
+        let mut salts = Salts::new();
+
+        for (path, hex) in map {
+            let salt = Vec::from_hex(&hex).map_err(|err| SaltError::Malformed(err.to_string()))?;
+            salts.insert(path.parse()?, salt);
+        }
+
+        Ok(salts)
+    }
+}
+
+/// Wipes the salts with the `zeroize` feature. Values built by [`apply`] hold copies of them.
+#[cfg(feature = "zeroize")]
+impl Drop for Salts {
+    fn drop(&mut self) {
+        for salt in self.0.values_mut() {
+            wipe(salt);
+        }
+    }
+}
+
+#[cfg(feature = "blot_json")]
+#[derive(Debug, PartialEq)]
+pub enum SaltError {
+    /// The salts are not a JSON object of hexadecimal strings.
+    Malformed(String),
+    /// One of the salt paths can't be parsed.
+    InvalidPath(PathError),
+}
+
+#[cfg(feature = "blot_json")]
+impl fmt::Display for SaltError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaltError::Malformed(reason) => write!(formatter, "malformed salts: {}", reason),
+            SaltError::InvalidPath(err) => write!(formatter, "invalid path: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "blot_json")]
+impl error::Error for SaltError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SaltError::Malformed(_) => None,
+            SaltError::InvalidPath(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "blot_json")]
+impl From<PathError> for SaltError {
+    fn from(err: PathError) -> SaltError {
+        SaltError::InvalidPath(err)
+    }
+}
+
+fn leaf_paths<T: Multihash>(value: &Value<T>, path: &JsonPath) -> Vec<JsonPath> {
+    match value {
+        Value::Dict(dict) => dict
+            .iter()
+            .flat_map(|(key, child)| leaf_paths(child, &path.join(Segment::Key(key.clone()))))
+            .collect(),
+        Value::List(list) | Value::Set(list) => list
+            .iter()
+            .enumerate()
+            .flat_map(|(index, child)| leaf_paths(child, &path.join(Segment::Index(index))))
+            .collect(),
+        Value::Redacted(_) => Vec::new(),
+        _ => vec![path.clone()],
+    }
+}
+
+/// Returns a copy of the value where every salted leaf is replaced by the list `[salt, leaf]`.
+///
+/// The result can be redacted with
+/// [`Value::redact_paths`](../value/enum.Value.html#method.redact_paths) like any other value.
+pub fn apply<T: Multihash + Clone>(value: &Value<T>, salts: &Salts) -> Value<T> {
+    apply_at(value, salts, &JsonPath::root())
+}
+
+fn apply_at<T: Multihash + Clone>(value: &Value<T>, salts: &Salts, path: &JsonPath) -> Value<T> {
+    match value {
+        Value::Dict(dict) => Value::Dict(
+            dict.iter()
+                .map(|(key, child)| {
+                    let child = apply_at(child, salts, &path.join(Segment::Key(key.clone())));
+                    (key.clone(), child)
+                })
+                .collect(),
+        ),
+        Value::List(list) => Value::List(
+            list.iter()
+                .enumerate()
+                .map(|(index, child)| apply_at(child, salts, &path.join(Segment::Index(index))))
+                .collect(),
+        ),
+        Value::Set(list) => Value::Set(
+            list.iter()
+                .enumerate()
+                .map(|(index, child)| apply_at(child, salts, &path.join(Segment::Index(index))))
+                .collect(),
+        ),
+        Value::Redacted(_) => value.clone(),
+        leaf => match salts.get(path) {
+            Some(salt) => Value::List(vec![Value::Raw(salt.to_vec()), leaf.clone()]),
+            None => leaf.clone(),
+        },
+    }
+}
+
+/// Checks the salted digest of the value matches the expected one.
+pub fn verify<T: Multihash + Clone>(value: &Value<T>, salts: &Salts, expected: &Hash<T>) -> bool {
+    apply(value, salts).blot(&T::default()) == *expected.digest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dict;
+    use list;
+    use multihash::Sha2256;
+    use seal::verify_redacted;
+
+    fn document() -> Value<Sha2256> {
+        dict!{"admin" => Value::Bool(true), "tags" => list!["a", "b"]}
+    }
+
+    #[cfg(feature = "salt")]
+    #[test]
+    fn salts_every_leaf() {
+        let value = document();
+        let salts = Salts::generate(&value);
+
+        assert_eq!(salts.len(), 3);
+        assert!(salts.get(&"admin".parse().unwrap()).is_some());
+        assert!(salts.get(&"tags[1]".parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn unsalted_leaves_are_untouched() {
+        let value = document();
+
+        assert_eq!(apply(&value, &Salts::new()), value);
+    }
+
+    #[test]
+    fn salted_digest() {
+        let value: Value<Sha2256> = Value::Bool(true);
+        let mut salts = Salts::new();
+        salts.insert(JsonPath::root(), vec![0; 4]);
+        let expected = Value::List(vec![Value::Raw(vec![0; 4]), Value::Bool(true)]);
+
+        assert_eq!(apply(&value, &salts), expected);
+        assert!(verify(&value, &salts, &expected.digest(Sha2256)));
+        assert!(!verify(&value, &Salts::new(), &expected.digest(Sha2256)));
+    }
+
+    #[cfg(feature = "salt")]
+    #[test]
+    fn redact_salted() {
+        let value = document();
+        let salts = Salts::generate(&value);
+        let salted = apply(&value, &salts);
+        let redacted = salted.redact_paths(&["admin".parse().unwrap()]);
+
+        assert!(verify_redacted(&salted, &redacted).is_ok());
+        assert!(verify(&value, &salts, &redacted.digest(Sha2256)));
+    }
+
+    #[cfg(all(feature = "salt", feature = "blot_json"))]
+    #[test]
+    fn json_roundtrip() {
+        let salts = Salts::generate(&document());
+        let imported = Salts::from_str(&salts.to_json().to_string()).unwrap();
+
+        assert_eq!(imported, salts);
+    }
+}
@@ -7,22 +7,86 @@
 //! Sealed digest multihash.
 //!
 //! Type [`Seal`] represents a sealed digest multihash.
+//!
+//! A seal remembers the algorithm it was computed with as a [`Stamp`], so a document hashed
+//! with one algorithm can embed seals computed with another. Seals are hashed as-is regardless
+//! of the document algorithm.
 
 use core::Blot;
+use encoding::Base;
 use hex::{FromHex, FromHexError};
-use multihash::{Harvest, Multihash};
-use uvar::{Uvar, UvarError};
+use multihash::{Algorithm, AlgorithmPolicy, Harvest, Hash, Multihash, Registry, Stamp};
+#[cfg(feature = "blot_json")]
+use serde::de::{self, Deserialize, Deserializer};
+#[cfg(feature = "blot_json")]
+use serde::ser::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+use std::hash;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use uvar::{self, Uvar, UvarError};
+use value::path::{JsonPath, Segment};
+use value::Value;
 
 #[derive(Debug)]
 pub enum SealError {
-    InvalidStamp { actual: Uvar, expected: Uvar },
+    /// The seal algorithm is not the expected one.
+    InvalidStamp {
+        actual: Uvar,
+        expected: Uvar,
+    },
+    /// The seal algorithm is not known to blot.
+    UnknownStamp(Uvar),
+    /// The seal algorithm is not permitted by the policy.
+    Forbidden(Stamp),
     NotRedacted,
     DigestTooShort,
-    UnexpectedLength { actual: u8, expected: u8 },
+    /// The digest is longer than the 255 bytes a multihash can declare.
+    DigestTooLong,
+    UnexpectedLength {
+        actual: u8,
+        expected: u8,
+    },
     UvarParseError(UvarError),
     HexError(FromHexError),
 }
 
+impl fmt::Display for SealError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SealError::InvalidStamp { actual, expected } => write!(
+                formatter,
+                "expected algorithm {:#x}, found {:#x}",
+                expected, actual
+            ),
+            SealError::UnknownStamp(code) => write!(formatter, "unknown algorithm {:#x}", code),
+            SealError::Forbidden(stamp) => write!(formatter, "{} is forbidden", stamp.name()),
+            SealError::NotRedacted => write!(formatter, "missing seal mark"),
+            SealError::DigestTooShort => write!(formatter, "digest too short"),
+            SealError::DigestTooLong => write!(formatter, "digest too long"),
+            SealError::UnexpectedLength { actual, expected } => write!(
+                formatter,
+                "expected a digest of {} bytes, found {}",
+                expected, actual
+            ),
+            SealError::UvarParseError(err) => write!(formatter, "invalid code: {}", err),
+            SealError::HexError(err) => write!(formatter, "{}", err),
+        }
+    }
+}
+
+impl error::Error for SealError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SealError::UvarParseError(err) => Some(err),
+            SealError::HexError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<UvarError> for SealError {
     fn from(err: UvarError) -> SealError {
         SealError::UvarParseError(err)
@@ -38,30 +102,164 @@ impl From<FromHexError> for SealError {
 /// 0x77 is equivalent to the original `**REDACTED**` mark.
 pub const SEAL_MARK: u8 = 0x77;
 
+/// The shortest truncated digest [`Seal::matches`] accepts, 128 bits.
+pub const MIN_TRUNCATED_LENGTH: usize = 16;
+
 /// The `Seal` type. See [the module level documentation](index.html) for more.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// `T` is the algorithm of the document the seal belongs to, which is not necessarily the one
+/// the seal was computed with. See [`Seal::tag`].
+///
+/// Parsing guarantees that the digest is exactly as long as the multihash declares and no longer
+/// than the output of its algorithm, so hashing or formatting a seal never fails.
+#[derive(Clone, PartialEq)]
 pub struct Seal<T: Multihash> {
-    tag: T,
+    tag: Stamp,
     digest: Vec<u8>,
+    algorithm: PhantomData<T>,
+}
+
+impl<T: Multihash> Eq for Seal<T> {}
+
+/// Orders seals by algorithm code, then digest length, then digest bytes.
+impl<T: Multihash> PartialOrd for Seal<T> {
+    fn partial_cmp(&self, other: &Seal<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Multihash> Ord for Seal<T> {
+    fn cmp(&self, other: &Seal<T>) -> Ordering {
+        let key = |seal: &Seal<T>| (seal.tag.code(), seal.tag.length());
+
+        key(self)
+            .cmp(&key(other))
+            .then_with(|| self.digest.cmp(&other.digest))
+    }
+}
+
+impl<T: Multihash> hash::Hash for Seal<T> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.tag.hash(state);
+        self.digest.hash(state);
+    }
+}
+
+impl<T: Multihash> fmt::Debug for Seal<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Seal")
+            .field("tag", &self.tag)
+            .field("digest", &self.digest)
+            .finish()
+    }
 }
 
 impl<T: Multihash> Seal<T> {
+    /// Creates a `Seal` from the digest of the given value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blot::multihash::Sha2256;
+    /// use blot::seal::Seal;
+    /// use blot::value::Value;
+    ///
+    /// let value: Value<Sha2256> = "foo".into();
+    /// let seal = Seal::from_value(&value, &Sha2256);
+    ///
+    /// assert_eq!(seal, Seal::from_str("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap());
+    /// ```
+    pub fn from_value(value: &Value<T>, tag: &T) -> Seal<T> {
+        let digest = value.blot(tag).as_slice().to_vec();
+
+        Seal {
+            tag: stamp(tag, &digest),
+            digest,
+            algorithm: PhantomData,
+        }
+    }
+
     pub fn digest(&self) -> &[u8] {
         &self.digest
     }
 
-    pub fn tag(&self) -> &T {
+    /// The algorithm the seal was computed with.
+    pub fn tag(&self) -> &Stamp {
         &self.tag
     }
 
-    pub fn digest_hex(&self) -> String {
-        let mut result = String::new();
+    /// Whether the seal was computed with the document algorithm `T`.
+    ///
+    /// ```
+    /// use blot::multihash::{Sha2256, Sha3256};
+    /// use blot::seal::Seal;
+    ///
+    /// let seal: Seal<Sha3256> = Seal::from_str("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap();
+    ///
+    /// assert!(!seal.is_native());
+    /// ```
+    pub fn is_native(&self) -> bool {
+        self.tag == Stamp::of(&T::default())
+    }
 
-        for byte in &self.digest {
-            result.push_str(&format!("{:02x}", byte));
+    /// Fails with [`SealError::Forbidden`] if the policy does not permit the seal algorithm.
+    ///
+    /// ```
+    /// use blot::multihash::{AlgorithmPolicy, Sha2256, Stamp};
+    /// use blot::seal::Seal;
+    ///
+    /// let policy = AlgorithmPolicy::new().deny(Stamp::Sha1);
+    /// let seal: Seal<Sha2256> = Seal::from_str("7711140beec7b5ea3f0fdbc95d0dd47f3c5bc275da8a33").unwrap();
+    ///
+    /// assert!(seal.check(&policy).is_err());
+    /// ```
+    pub fn check(&self, policy: &AlgorithmPolicy) -> Result<(), SealError> {
+        if policy.permits(&self.tag) {
+            Ok(())
+        } else {
+            Err(SealError::Forbidden(self.tag))
         }
+    }
 
-        result
+    /// Whether the seal digest is shorter than the natural output of its algorithm.
+    pub fn is_truncated(&self) -> bool {
+        self.digest.len() < usize::from(self.tag.length())
+    }
+
+    /// Whether the seal was made from the given digest, comparing only the first bytes if the
+    /// seal is truncated to at least [`MIN_TRUNCATED_LENGTH`] bytes.
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::seal::Seal;
+    ///
+    /// let seal: Seal<Sha2256> = Seal::from_str("771210a6a6e5e783c363cd95693ec189c26823").unwrap();
+    ///
+    /// assert!(seal.is_truncated());
+    /// assert!(seal.matches("foo".digest(Sha2256).digest().as_slice()));
+    /// ```
+    pub fn matches(&self, digest: &[u8]) -> bool {
+        if self.digest.len() == digest.len() {
+            return self.digest == digest;
+        }
+
+        self.digest.len() >= MIN_TRUNCATED_LENGTH && digest.starts_with(&self.digest)
+    }
+
+    pub fn digest_hex(&self) -> String {
+        Base::Base16.encode(&self.digest)
+    }
+
+    /// The hexadecimal multihash without the seal mark, the inverse of
+    /// [`from_hash_str`](#method.from_hash_str).
+    pub fn multihash_hex(&self) -> String {
+        let mut bytes = self.tag.code().to_bytes();
+        bytes.extend(uvar::encode_u64(self.digest.len() as u64));
+        bytes.extend_from_slice(&self.digest);
+
+        Base::Base16.encode(&bytes)
     }
 
     /// Creates a `Seal` from a string. The string must have either the Objecthash prefix
@@ -69,6 +267,8 @@ impl<T: Multihash> Seal<T> {
     ///
     /// You can use [`from_bytes`] if you have a list of bytes.
     ///
+    /// The seal can use any algorithm known by [`Stamp`], not only `T`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -83,7 +283,54 @@ impl<T: Multihash> Seal<T> {
     /// assert!(seal.is_ok());
     /// assert_eq!(seal.unwrap(), seal_classic.unwrap());
     /// ```
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &str) -> Result<Seal<T>, SealError> {
+        Seal::parse(input, None)
+    }
+
+    /// Like [`from_str`](#method.from_str), also accepting the algorithms of the registry.
+    pub fn from_str_in(input: &str, registry: &Registry) -> Result<Seal<T>, SealError> {
+        Seal::parse(input, Some(registry))
+    }
+
+    /// Like [`from_str`](#method.from_str), also accepting the seals of the original Objecthash:
+    /// `**REDACTED**` followed by a bare sha2-256 digest, without the multihash code and length.
+    ///
+    /// ```
+    /// use blot::multihash::{Sha2256, Stamp};
+    /// use blot::seal::Seal;
+    ///
+    /// let seal: Seal<Sha2256> = Seal::from_objecthash_str("**REDACTED**a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap();
+    ///
+    /// assert_eq!(seal.tag(), &Stamp::Sha2256);
+    /// assert_eq!(seal, Seal::from_str("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap());
+    /// ```
+    pub fn from_objecthash_str(input: &str) -> Result<Seal<T>, SealError> {
+        match Seal::from_bare_str(input) {
+            Some(seal) => Ok(seal),
+            None => Seal::from_str(input),
+        }
+    }
+
+    /// Reads `**REDACTED**` followed by 32 bytes in hexadecimal as a sha2-256 seal. A multihash
+    /// seal is never 32 bytes long unless its digest is truncated.
+    pub(crate) fn from_bare_str(input: &str) -> Option<Seal<T>> {
+        if !input.starts_with("**REDACTED**") {
+            return None;
+        }
+
+        input
+            .get(12..)
+            .and_then(|bare| Vec::from_hex(bare).ok())
+            .filter(|digest| digest.len() == usize::from(Stamp::Sha2256.length()))
+            .map(|digest| Seal {
+                tag: Stamp::Sha2256,
+                digest,
+                algorithm: PhantomData,
+            })
+    }
+
+    fn parse(input: &str, registry: Option<&Registry>) -> Result<Seal<T>, SealError> {
         let bare = if input.starts_with("**REDACTED**") {
             input
                 .get(12..)
@@ -98,9 +345,34 @@ impl<T: Multihash> Seal<T> {
 
         let bytes = Vec::from_hex(bare)?;
 
+        Seal::from_multihash(&bytes, registry)
+    }
+
+    /// Creates a `Seal` from a hexadecimal multihash without the seal mark, as printed by the
+    /// `blot` command line tool.
+    ///
+    /// ```
+    /// use blot::multihash::{Sha2256, Stamp};
+    /// use blot::seal::Seal;
+    ///
+    /// let seal: Seal<Sha2256> = Seal::from_hash_str("1620a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap();
+    ///
+    /// assert_eq!(seal.tag(), &Stamp::Sha3256);
+    /// ```
+    pub fn from_hash_str(input: &str) -> Result<Seal<T>, SealError> {
+        let bytes = Vec::from_hex(input)?;
+
         Seal::from_bytes_without_mark(&bytes)
     }
 
+    /// Like [`from_hash_str`](#method.from_hash_str), also accepting the algorithms of the
+    /// registry.
+    pub fn from_hash_str_in(input: &str, registry: &Registry) -> Result<Seal<T>, SealError> {
+        let bytes = Vec::from_hex(input)?;
+
+        Seal::from_multihash(&bytes, Some(registry))
+    }
+
     /// Creates a `Seal` from a list of bytes. The first byte must be the
     /// [`SEAL_MARK`].
     ///
@@ -124,34 +396,51 @@ impl<T: Multihash> Seal<T> {
     /// # Errors
     ///
     /// This operation fails with [`SealError::NotRedacted`] if the first byte is not `0x77`, the
-    /// seal mark.
+    /// seal mark, and with [`SealError::UnknownStamp`] if the multihash code is not a known
+    /// [`Stamp`].
     pub fn from_bytes(bytes: &[u8]) -> Result<Seal<T>, SealError> {
-        if bytes[0] != SEAL_MARK {
+        if bytes.first() != Some(&SEAL_MARK) {
             return Err(SealError::NotRedacted);
         }
 
         Seal::from_bytes_without_mark(&bytes[1..])
     }
 
-    fn from_bytes_without_mark(bytes: &[u8]) -> Result<Seal<T>, SealError> {
-        let (code, rest) = Uvar::take(&bytes)?;
-        let tag = T::default();
+    pub(crate) fn from_bytes_without_mark(bytes: &[u8]) -> Result<Seal<T>, SealError> {
+        Seal::from_multihash(bytes, None)
+    }
 
-        if tag.code() != code {
-            return Err(SealError::InvalidStamp {
-                actual: code,
-                expected: tag.code(),
-            });
-        }
+    fn from_multihash(bytes: &[u8], registry: Option<&Registry>) -> Result<Seal<T>, SealError> {
+        let (code, rest) = uvar::decode(bytes)?;
+        let parts = uvar::decode(rest);
+        let tag = match Stamp::from_code(code)
+            .or_else(|| match parts {
+                Ok((length, _)) if length <= u64::from(u8::MAX) => {
+                    Stamp::from_parts(code, length as u8)
+                }
+                _ => None,
+            })
+            .or_else(|| {
+                registry
+                    .and_then(|registry| registry.by_code(code))
+                    .map(Algorithm::stamp)
+            }) {
+            Some(tag) => tag,
+            None => return Err(SealError::UnknownStamp(Uvar::from(code))),
+        };
+        let (length, digest) = match parts {
+            Ok((length, digest)) if !digest.is_empty() => (length, digest),
+            Ok(_) | Err(UvarError::Underflow) => return Err(SealError::DigestTooShort),
+            Err(err) => return Err(err.into()),
+        };
 
-        if rest.len() < 2 {
-            return Err(SealError::DigestTooShort);
+        if length > u64::from(u8::MAX) || digest.len() > usize::from(u8::MAX) {
+            return Err(SealError::DigestTooLong);
         }
 
-        let length = *&rest[0];
-        let digest = &rest[1..];
+        let length = length as u8;
 
-        if length != tag.length() {
+        if length > tag.length() {
             return Err(SealError::UnexpectedLength {
                 expected: tag.length(),
                 actual: length,
@@ -160,20 +449,461 @@ impl<T: Multihash> Seal<T> {
 
         if digest.len() as u8 != length {
             return Err(SealError::UnexpectedLength {
-                expected: tag.length(),
+                expected: length,
                 actual: digest.len() as u8,
             });
         }
 
         Ok(Seal {
-            tag: tag,
+            tag,
             digest: digest.into(),
+            algorithm: PhantomData,
         })
     }
 }
 
+/// Returns the stamp of the algorithm, with the length of the digest for the identity.
+fn stamp<T: Multihash>(tag: &T, digest: &[u8]) -> Stamp {
+    match Stamp::of(tag) {
+        Stamp::Identity { .. } => Stamp::Identity {
+            length: digest.len() as u8,
+        },
+        stamp => stamp,
+    }
+}
+
+/// Formats the seal as `0x77` followed by the multihash in hexadecimal. The alternate flag
+/// (`{:#}`) uses the classic Objecthash `**REDACTED**` prefix instead.
+///
+/// ```
+/// use blot::multihash::Sha2256;
+/// use blot::seal::Seal;
+///
+/// let seal: Seal<Sha2256> = Seal::from_str("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038").unwrap();
+///
+/// assert_eq!(format!("{}", seal), "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038");
+/// assert_eq!(format!("{:#}", seal), "**REDACTED**1220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038");
+/// ```
+impl<T: Multihash> fmt::Display for Seal<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if formatter.alternate() {
+            write!(formatter, "**REDACTED**")?;
+        } else {
+            write!(formatter, "{:02x}", SEAL_MARK)?;
+        }
+
+        write!(formatter, "{}", &self.multihash_hex())
+    }
+}
+
+impl<T: Multihash> FromStr for Seal<T> {
+    type Err = SealError;
+
+    fn from_str(input: &str) -> Result<Seal<T>, SealError> {
+        Seal::from_str(input)
+    }
+}
+
+#[cfg(feature = "blot_json")]
+impl<T: Multihash> Serialize for Seal<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "blot_json")]
+impl<'de, T: Multihash> Deserialize<'de> for Seal<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let input = String::deserialize(deserializer)?;
+
+        Seal::from_str(&input).map_err(|err| de::Error::custom(format!("invalid seal: {}", err)))
+    }
+}
+
+/// Seals an already computed hash.
+///
+/// ```
+/// use blot::core::Blot;
+/// use blot::multihash::Sha2256;
+/// use blot::seal::Seal;
+///
+/// let seal: Seal<Sha2256> = "foo".digest(Sha2256).into();
+///
+/// assert_eq!(seal.digest_hex(), "a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038");
+/// ```
+impl<T: Multihash> From<Hash<T>> for Seal<T> {
+    fn from(hash: Hash<T>) -> Seal<T> {
+        let (tag, digest) = hash.into_parts();
+
+        Seal {
+            tag: stamp(&tag, digest.as_slice()),
+            digest: digest.as_slice().to_vec(),
+            algorithm: PhantomData,
+        }
+    }
+}
+
 impl<T: Multihash> Blot for Seal<T> {
     fn blot<D: Multihash>(&self, _: &D) -> Harvest {
-        self.digest.clone().into_boxed_slice().into()
+        self.digest.as_slice().into()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RedactionError {
+    /// The seal at the path doesn't match the digest of the original node.
+    SealMismatch { path: JsonPath },
+    /// The documents differ at the path and the redacted node is not a seal.
+    Mismatch { path: JsonPath },
+}
+
+impl fmt::Display for RedactionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RedactionError::SealMismatch { path } => {
+                write!(formatter, "seal at '{}' doesn't match the original", path)
+            }
+            RedactionError::Mismatch { path } => {
+                write!(
+                    formatter,
+                    "unsealed node at '{}' differs from the original",
+                    path
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for RedactionError {}
+
+/// Checks that `redacted` only differs from `original` in sealed nodes and that every seal is
+/// the digest of the node it replaces.
+///
+/// Sequences are compared position by position, as produced by
+/// [`Value::redact_paths`](../value/enum.Value.html#method.redact_paths).
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate blot;
+/// use blot::multihash::Sha2256;
+/// use blot::seal::verify_redacted;
+/// use blot::value::Value;
+///
+/// # fn main() {
+/// let original: Value<Sha2256> = list!["foo", "bar"];
+/// let redacted = original.redact_paths(&["[1]".parse().unwrap()]);
+///
+/// assert!(verify_redacted(&original, &redacted).is_ok());
+/// # }
+/// ```
+pub fn verify_redacted<T: Multihash>(
+    original: &Value<T>,
+    redacted: &Value<T>,
+) -> Result<(), RedactionError> {
+    verify_redacted_with(original, redacted, &T::default())
+}
+
+/// Like [`verify_redacted`], checking the seals with the given digester, the one the nodes were
+/// sealed with by
+/// [`Value::redact_paths_with`](../value/enum.Value.html#method.redact_paths_with).
+pub fn verify_redacted_with<T: Multihash>(
+    original: &Value<T>,
+    redacted: &Value<T>,
+    digester: &T,
+) -> Result<(), RedactionError> {
+    verify_node(original, redacted, &JsonPath::root(), digester)
+}
+
+fn verify_node<T: Multihash>(
+    original: &Value<T>,
+    redacted: &Value<T>,
+    path: &JsonPath,
+    tag: &T,
+) -> Result<(), RedactionError> {
+    match (original, redacted) {
+        (_, Value::Redacted(seal)) => {
+            if seal.is_native()
+                && !seal.is_truncated()
+                && seal.digest() == original.blot(tag).as_slice()
+            {
+                Ok(())
+            } else {
+                Err(RedactionError::SealMismatch { path: path.clone() })
+            }
+        }
+        (Value::Dict(a), Value::Dict(b)) => {
+            if a.len() != b.len() {
+                return Err(RedactionError::Mismatch { path: path.clone() });
+            }
+
+            for (key, value) in a {
+                let child = path.join(Segment::Key(key.clone()));
+                match b.get(key) {
+                    Some(other) => verify_node(value, other, &child, tag)?,
+                    None => return Err(RedactionError::Mismatch { path: child }),
+                }
+            }
+
+            Ok(())
+        }
+        (Value::List(a), Value::List(b)) | (Value::Set(a), Value::Set(b)) => {
+            if a.len() != b.len() {
+                return Err(RedactionError::Mismatch { path: path.clone() });
+            }
+
+            for (index, (value, other)) in a.iter().zip(b).enumerate() {
+                verify_node(value, other, &path.join(Segment::Index(index)), tag)?;
+            }
+
+            Ok(())
+        }
+        _ => {
+            if original.blot(tag) == redacted.blot(tag) {
+                Ok(())
+            } else {
+                Err(RedactionError::Mismatch { path: path.clone() })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dict;
+    use list;
+    use multihash::Sha2256;
+
+    fn document() -> Value<Sha2256> {
+        dict!{"a" => list![1, "two"], "b" => Value::Bool(false)}
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let seal = Seal::from_value(&document(), &Sha2256);
+
+        assert_eq!(seal.to_string().parse::<Seal<Sha2256>>().unwrap(), seal);
+        assert_eq!(
+            format!("{:#}", seal).parse::<Seal<Sha2256>>().unwrap(),
+            seal
+        );
+    }
+
+    #[test]
+    fn objecthash_seals() {
+        let seal = Seal::from_value(&document(), &Sha2256);
+        let bare = format!("**REDACTED**{}", seal.digest_hex());
+
+        assert_eq!(Seal::<Sha2256>::from_objecthash_str(&bare).unwrap(), seal);
+        assert!(Seal::<Sha2256>::from_str(&bare).is_err());
+        assert_eq!(
+            Seal::<Sha2256>::from_objecthash_str(&format!("{:#}", seal)).unwrap(),
+            seal
+        );
+        assert!(Seal::<Sha2256>::from_objecthash_str("**REDACTED**a6a6").is_err());
+    }
+
+    #[cfg(feature = "blot_json")]
+    #[test]
+    fn serde_roundtrip() {
+        use serde_json;
+
+        let seal = Seal::from_value(&document(), &Sha2256);
+        let json = serde_json::to_string(&seal).unwrap();
+
+        assert_eq!(json, format!("\"{}\"", seal));
+        assert_eq!(serde_json::from_str::<Seal<Sha2256>>(&json).unwrap(), seal);
+        assert!(serde_json::from_str::<Seal<Sha2256>>(r#""foo""#).is_err());
+    }
+
+    #[test]
+    fn from_hash() {
+        let value = document();
+        let seal: Seal<Sha2256> = value.digest(Sha2256).into();
+
+        assert_eq!(seal, Seal::from_value(&value, &Sha2256));
+        assert_eq!(seal.blot(&Sha2256), value.blot(&Sha2256));
+    }
+
+    #[test]
+    fn foreign_seal() {
+        let input = "771620".to_string() + &"ab".repeat(32);
+        let seal: Seal<Sha2256> = Seal::from_str(&input).unwrap();
+        let value: Value<Sha2256> = Value::Redacted(seal.clone());
+
+        assert_eq!(seal.tag(), &Stamp::Sha3256);
+        assert_eq!(seal.to_string(), input);
+        assert_eq!(value.blot(&Sha2256).as_slice(), &[0xab; 32][..]);
+    }
+
+    #[test]
+    fn multibyte_stamp() {
+        let input = "7793201c".to_string() + &"ab".repeat(28);
+        let seal: Seal<Sha2256> = Seal::from_str(&input).unwrap();
+
+        assert_eq!(seal.tag(), &Stamp::Sha2224);
+        assert_eq!(seal.to_string(), input);
+    }
+
+    #[test]
+    fn extendable_output_stamp() {
+        let input = "771914".to_string() + &"ab".repeat(20);
+        let seal: Seal<Sha2256> = Seal::from_str(&input).unwrap();
+
+        assert_eq!(seal.tag(), &Stamp::Shake256 { length: 20 });
+        assert_eq!(seal.to_string(), input);
+    }
+
+    #[test]
+    fn truncated_digest() {
+        let input = "771210".to_string() + &"ab".repeat(16);
+        let seal: Seal<Sha2256> = Seal::from_str(&input).unwrap();
+
+        assert!(seal.is_truncated());
+        assert_eq!(seal.to_string(), input);
+        assert!(seal.matches(&[0xab; 32]));
+        assert!(!seal.matches(&[0xab; 8]));
+
+        let short: Seal<Sha2256> = Seal::from_str("771201ab").unwrap();
+
+        assert!(short.is_truncated());
+        assert!(!short.matches(&[0xab; 32]));
+    }
+
+    #[test]
+    fn truncated_hash() {
+        let hash = "foo".digest(Sha2256).truncate(16).unwrap();
+        let seal: Seal<Sha2256> = hash.into();
+
+        assert_eq!(seal.tag(), &Stamp::Sha2256);
+        assert_eq!(seal.digest().len(), 16);
+        assert!("foo".digest(Sha2256).truncate(33).is_err());
+    }
+
+    #[test]
+    fn length_over_algorithm() {
+        let input = "771221".to_string() + &"ab".repeat(33);
+
+        match Seal::<Sha2256>::from_str(&input) {
+            Err(SealError::UnexpectedLength {
+                expected: 32,
+                actual: 33,
+            }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_stamp() {
+        let input = "779920".to_string() + &"ab".repeat(32);
+
+        match Seal::<Sha2256>::from_str(&input) {
+            Err(SealError::UnknownStamp(_)) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_bytes() {
+        let overlong = [&[SEAL_MARK, 0x12, 0x00][..], &[0xab; 256][..]].concat();
+
+        match Seal::<Sha2256>::from_bytes(&[]) {
+            Err(SealError::NotRedacted) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        match Seal::<Sha2256>::from_bytes(&overlong) {
+            Err(SealError::DigestTooLong) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_redacted_ok() {
+        let original = document();
+        let redacted = original.redact_paths(&["a[1]".parse().unwrap(), "b".parse().unwrap()]);
+
+        assert_eq!(verify_redacted(&original, &redacted), Ok(()));
+        assert_eq!(verify_redacted(&original, &original), Ok(()));
+    }
+
+    #[test]
+    fn verify_redacted_bad_seal() {
+        let original = document();
+        let other: Value<Sha2256> = Value::Bool(true);
+        let seal = Seal::from_value(&other, &Sha2256);
+        let mut redacted = original.clone();
+        if let Value::Dict(ref mut map) = redacted {
+            map.insert("b".into(), Value::Redacted(seal));
+        }
+
+        assert_eq!(
+            verify_redacted(&original, &redacted),
+            Err(RedactionError::SealMismatch {
+                path: "b".parse().unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn verify_redacted_truncated_seal() {
+        let original = document();
+        let mut redacted = original.clone();
+        if let Value::Dict(ref mut map) = redacted {
+            let hash = map["b"].digest(Sha2256).truncate(16).unwrap();
+            map.insert("b".into(), Value::Redacted(hash.into()));
+        }
+
+        assert_ne!(original.digest(Sha2256), redacted.digest(Sha2256));
+        assert_eq!(
+            verify_redacted(&original, &redacted),
+            Err(RedactionError::SealMismatch {
+                path: "b".parse().unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn verify_redacted_with_digester() {
+        use core::FloatPolicy;
+        use multihash::FloatMultihash;
+
+        let digester = FloatMultihash::new(Sha2256, FloatPolicy::Ieee754);
+        let original: Value<FloatMultihash<Sha2256>> = dict!{"a" => 1.5, "b" => "two"};
+        let redacted = original.redact_paths_with(&["a".parse().unwrap()], &digester);
+
+        assert_eq!(
+            original.digest(digester.clone()),
+            redacted.digest(digester.clone())
+        );
+        assert!(verify_redacted_with(&original, &redacted, &digester).is_ok());
+        assert_eq!(
+            verify_redacted(&original, &redacted),
+            Err(RedactionError::SealMismatch {
+                path: "a".parse().unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn verify_redacted_changed_leaf() {
+        let original = document();
+        let mut redacted = original.redact_paths(&["b".parse().unwrap()]);
+        if let Value::Dict(ref mut map) = redacted {
+            map.insert("a".into(), Value::List(vec![1.into(), "three".into()]));
+        }
+
+        assert_eq!(
+            verify_redacted(&original, &redacted),
+            Err(RedactionError::Mismatch {
+                path: "a[1]".parse().unwrap()
+            })
+        );
     }
 }
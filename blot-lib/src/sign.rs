@@ -0,0 +1,184 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Ed25519 signatures over digests.
+//!
+//! A digest proves the integrity of a value, a signature over it proves who published it. The
+//! signed message is the binary multihash of the digest, see [`Hash::to_bytes`], so the
+//! signature covers the algorithm as well.
+//!
+//! Keys, public keys and signatures are exchanged as hexadecimal: 32, 32 and 64 bytes
+//! respectively.
+//!
+//! ```
+//! use blot::core::Blot;
+//! use blot::multihash::Sha2256;
+//! use blot::sign;
+//!
+//! let key = sign::signing_key_from_hex(&"07".repeat(32)).unwrap();
+//! let hash = "foo".digest(Sha2256);
+//! let signature = sign::sign(&hash, &key);
+//!
+//! assert!(sign::verify(&hash, &signature, &key.verifying_key()).is_ok());
+//! assert!(sign::verify(&"bar".digest(Sha2256), &signature, &key.verifying_key()).is_err());
+//! ```
+
+pub use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+use ed25519_dalek::{Signer, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH};
+use encoding::{Base, EncodingError};
+use multihash::{wipe, Hash, Multihash};
+use std::error;
+use std::fmt;
+
+/// Signs the multihash of the digest.
+pub fn sign<T: Multihash>(hash: &Hash<T>, key: &SigningKey) -> Signature {
+    key.sign(&hash.to_bytes())
+}
+
+/// Checks that the signature over the multihash of the digest was made with the key.
+pub fn verify<T: Multihash>(
+    hash: &Hash<T>,
+    signature: &Signature,
+    key: &VerifyingKey,
+) -> Result<(), SignError> {
+    key.verify_strict(&hash.to_bytes(), signature)
+        .map_err(|_| SignError::Mismatch)
+}
+
+pub fn signing_key_from_hex(input: &str) -> Result<SigningKey, SignError> {
+    let mut bytes = [0; SECRET_KEY_LENGTH];
+    decode(input, &mut bytes)?;
+    let key = SigningKey::from_bytes(&bytes);
+    wipe(&mut bytes);
+
+    Ok(key)
+}
+
+pub fn verifying_key_from_hex(input: &str) -> Result<VerifyingKey, SignError> {
+    let mut bytes = [0; PUBLIC_KEY_LENGTH];
+    decode(input, &mut bytes)?;
+
+    VerifyingKey::from_bytes(&bytes).map_err(|_| SignError::InvalidKey)
+}
+
+pub fn signature_from_hex(input: &str) -> Result<Signature, SignError> {
+    let mut bytes = [0; SIGNATURE_LENGTH];
+    decode(input, &mut bytes)?;
+
+    Ok(Signature::from_bytes(&bytes))
+}
+
+/// Decodes the hexadecimal input, ignoring surrounding whitespace, into the buffer. Secret keys
+/// are decoded too, so the intermediate bytes are wiped.
+fn decode(input: &str, buffer: &mut [u8]) -> Result<(), SignError> {
+    let mut bytes = Base::Base16.decode(&input.trim().to_lowercase())?;
+    let result = if bytes.len() == buffer.len() {
+        buffer.copy_from_slice(&bytes);
+
+        Ok(())
+    } else {
+        Err(SignError::InvalidLength {
+            expected: buffer.len(),
+            found: bytes.len(),
+        })
+    };
+    wipe(&mut bytes);
+
+    result
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SignError {
+    /// The key or signature is not hexadecimal.
+    Encoding(EncodingError),
+    /// The key or signature doesn't have the expected number of bytes.
+    InvalidLength { expected: usize, found: usize },
+    /// The public key is not a point of the curve.
+    InvalidKey,
+    /// The signature was not made over the digest with the key.
+    Mismatch,
+}
+
+impl fmt::Display for SignError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignError::Encoding(err) => write!(formatter, "{}", err),
+            SignError::InvalidLength { expected, found } => {
+                write!(formatter, "expected {} bytes, found {}", expected, found)
+            }
+            SignError::InvalidKey => write!(formatter, "invalid public key"),
+            SignError::Mismatch => write!(formatter, "signature mismatch"),
+        }
+    }
+}
+
+impl error::Error for SignError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SignError::Encoding(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<EncodingError> for SignError {
+    fn from(err: EncodingError) -> SignError {
+        SignError::Encoding(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::{Sha2256, Sha3256};
+
+    // RFC8032, section 7.1, test 1.
+    const SECRET: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const PUBLIC: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+    #[test]
+    fn keys() {
+        let key = signing_key_from_hex(SECRET).unwrap();
+
+        assert_eq!(
+            key.verifying_key(),
+            verifying_key_from_hex(&PUBLIC.to_uppercase()).unwrap()
+        );
+        assert_eq!(
+            signing_key_from_hex("00").map(|_| ()),
+            Err(SignError::InvalidLength {
+                expected: 32,
+                found: 1
+            })
+        );
+        assert!(signing_key_from_hex(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn signatures_cover_the_algorithm() {
+        let key = signing_key_from_hex(SECRET).unwrap();
+        let public = verifying_key_from_hex(PUBLIC).unwrap();
+        let signature = sign(&"foo".digest(Sha2256), &key);
+        let encoded = Base::Base16.encode(&signature.to_bytes());
+
+        assert_eq!(signature_from_hex(&encoded), Ok(signature));
+        assert_eq!(verify(&"foo".digest(Sha2256), &signature, &public), Ok(()));
+        assert_eq!(
+            verify(&"foo".digest(Sha3256), &signature, &public),
+            Err(SignError::Mismatch)
+        );
+        assert_eq!(
+            verify(
+                &"foo".digest(Sha2256).truncate(16).unwrap(),
+                &signature,
+                &public
+            ),
+            Err(SignError::Mismatch)
+        );
+    }
+}
@@ -0,0 +1,390 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Streaming hashing.
+//!
+//! [`RawStream`] hashes any reader as a [`Tag::Raw`] value in chunks.
+//!
+//! [`hash_lines`] hashes newline-delimited JSON (NDJSON). Each line is parsed and hashed on its
+//! own so the input never has to be held in memory as a whole. Blank lines are skipped.
+//!
+//! [`Progress`] wraps either reader to report how many bytes have been consumed so far.
+//!
+//! ```
+//! use blot::multihash::Sha2256;
+//! use blot::stream::hash_lines;
+//!
+//! let input = "\"foo\"\n[\"foo\", \"bar\"]\n";
+//! let digests: Vec<String> = hash_lines(input.as_bytes(), Sha2256)
+//!     .map(|hash| hash.unwrap().to_string())
+//!     .collect();
+//!
+//! assert_eq!(
+//!     digests,
+//!     vec![
+//!         "1220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038",
+//!         "122032ae896c413cfdc79eec68be9139c86ded8b279238467c216cf2bec4d5f1e4a2",
+//!     ]
+//! );
+//! ```
+
+use core::Blot;
+use multihash::{Harvest, Hash, Multihash};
+#[cfg(feature = "blot_json")]
+use serde_json;
+use std::cell::RefCell;
+#[cfg(feature = "blot_json")]
+use std::error;
+#[cfg(feature = "blot_json")]
+use std::fmt;
+use std::io::{self, BufRead, Read};
+#[cfg(feature = "blot_json")]
+use std::marker::PhantomData;
+use tag::Tag;
+#[cfg(feature = "blot_json")]
+use value::de::{DeserializeError, DeserializerOptions};
+#[cfg(feature = "blot_json")]
+use value::Value;
+
+/// Raw bytes read from an [`io::Read`] source, hashed in chunks with [`Tag::Raw`].
+///
+/// The digest is the same as hashing the equivalent `[u8]` but the payload is never held in
+/// memory as a whole. The reader is consumed by the first digest.
+///
+/// ```
+/// use blot::core::Blot;
+/// use blot::multihash::Sha2256;
+/// use blot::stream::RawStream;
+///
+/// let bytes = vec![0, 0, 0];
+/// let stream = RawStream::new(&bytes[..]);
+///
+/// assert_eq!(
+///     stream.try_digest(Sha2256).unwrap().to_string(),
+///     bytes[..].digest(Sha2256).to_string()
+/// );
+/// ```
+pub struct RawStream<R: Read> {
+    reader: RefCell<R>,
+}
+
+impl<R: Read> RawStream<R> {
+    pub fn new(reader: R) -> RawStream<R> {
+        RawStream {
+            reader: RefCell::new(reader),
+        }
+    }
+
+    /// Fallible alternative to [`Blot::digest`] surfacing any read error.
+    pub fn try_digest<D: Multihash>(&self, digester: D) -> io::Result<Hash<D>> {
+        let digest = digester.digest_stream(Tag::Raw, &mut *self.reader.borrow_mut())?;
+
+        Ok(Hash::new(digester, digest))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+/// # Panics
+///
+/// Panics if the reader fails. Use [`RawStream::try_digest`] to handle read errors.
+impl<R: Read> Blot for RawStream<R> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        digester
+            .digest_stream(Tag::Raw, &mut *self.reader.borrow_mut())
+            .expect("Readable raw stream")
+    }
+}
+
+/// A reader calling `report` with the total number of bytes consumed after every read.
+///
+/// Wraps the input of [`RawStream`] or [`hash_lines`] to give feedback on large inputs. Buffered
+/// readers count bytes as they are consumed, not as they are buffered.
+///
+/// ```
+/// use blot::multihash::Sha2256;
+/// use blot::stream::{Progress, RawStream};
+///
+/// let bytes = vec![0; 20000];
+/// let mut total = 0;
+///
+/// RawStream::new(Progress::new(&bytes[..], |read| total = read))
+///     .try_digest(Sha2256)
+///     .unwrap();
+///
+/// assert_eq!(total, 20000);
+/// ```
+pub struct Progress<R, F> {
+    reader: R,
+    bytes: u64,
+    report: F,
+}
+
+impl<R, F: FnMut(u64)> Progress<R, F> {
+    pub fn new(reader: R, report: F) -> Progress<R, F> {
+        Progress {
+            reader,
+            bytes: 0,
+            report,
+        }
+    }
+
+    /// The number of bytes consumed so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn advance(&mut self, amount: usize) {
+        if amount > 0 {
+            self.bytes += amount as u64;
+            (self.report)(self.bytes);
+        }
+    }
+}
+
+impl<R: Read, F: FnMut(u64)> Read for Progress<R, F> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let amount = self.reader.read(buffer)?;
+        self.advance(amount);
+
+        Ok(amount)
+    }
+}
+
+impl<R: BufRead, F: FnMut(u64)> BufRead for Progress<R, F> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount);
+        self.advance(amount);
+    }
+}
+
+#[cfg(feature = "blot_json")]
+#[derive(Debug)]
+pub enum StreamError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The record at the given line is not valid JSON.
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+    /// The record at the given line is valid JSON but the options reject it, e.g. it has a seal
+    /// computed with a forbidden algorithm.
+    Document {
+        line: usize,
+        error: DeserializeError,
+    },
+}
+
+#[cfg(feature = "blot_json")]
+impl fmt::Display for StreamError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(formatter, "{}", err),
+            StreamError::Json { line, error } => write!(formatter, "line {}: {}", line, error),
+            StreamError::Document { line, error } => write!(formatter, "line {}: {}", line, error),
+        }
+    }
+}
+
+#[cfg(feature = "blot_json")]
+impl error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            StreamError::Io(err) => Some(err),
+            StreamError::Json { error, .. } => Some(error),
+            StreamError::Document { error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "blot_json")]
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> StreamError {
+        StreamError::Io(err)
+    }
+}
+
+#[cfg(feature = "blot_json")]
+/// Iterator over the values of a newline-delimited JSON reader. See [`values`].
+pub struct Values<R, T> {
+    reader: R,
+    options: DeserializerOptions,
+    buffer: String,
+    line: usize,
+    tag: PhantomData<T>,
+}
+
+#[cfg(feature = "blot_json")]
+impl<R: BufRead, T: Multihash> Iterator for Values<R, T> {
+    type Item = Result<Value<T>, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            self.line += 1;
+
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(err) => return Some(Err(err.into())),
+            }
+
+            if self.buffer.trim().is_empty() {
+                continue;
+            }
+
+            let line = self.line;
+
+            return Some(
+                self.options
+                    .from_str(&self.buffer)
+                    .map_err(|error| match error {
+                        DeserializeError::Json(error) => StreamError::Json { line, error },
+                        error => StreamError::Document { line, error },
+                    }),
+            );
+        }
+    }
+}
+
+#[cfg(feature = "blot_json")]
+/// Parses every line of the reader as a [`Value`], one record at a time.
+pub fn values<R: BufRead, T: Multihash>(reader: R) -> Values<R, T> {
+    values_with(reader, DeserializerOptions::new())
+}
+
+#[cfg(feature = "blot_json")]
+/// Like [`values`], parsing every line with the given options, e.g. an algorithm policy.
+pub fn values_with<R: BufRead, T: Multihash>(
+    reader: R,
+    options: DeserializerOptions,
+) -> Values<R, T> {
+    Values {
+        reader,
+        options,
+        buffer: String::new(),
+        line: 0,
+        tag: PhantomData,
+    }
+}
+
+#[cfg(feature = "blot_json")]
+/// Hashes every line of the reader as an independent JSON document.
+pub fn hash_lines<R: BufRead, D: Multihash + Clone>(
+    reader: R,
+    digester: D,
+) -> impl Iterator<Item = Result<Hash<D>, StreamError>> {
+    hash_lines_with(reader, DeserializerOptions::new(), digester)
+}
+
+#[cfg(feature = "blot_json")]
+/// Like [`hash_lines`], parsing every line with the given options.
+pub fn hash_lines_with<R: BufRead, D: Multihash + Clone>(
+    reader: R,
+    options: DeserializerOptions,
+    digester: D,
+) -> impl Iterator<Item = Result<Hash<D>, StreamError>> {
+    values_with::<R, D>(reader, options)
+        .map(move |value| value.map(|value| value.digest(digester.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+
+    #[test]
+    fn raw_stream_matches_bytes() {
+        let bytes: Vec<u8> = (0..20000).map(|i| (i % 251) as u8).collect();
+        let expected = bytes[..].digest(Sha2256).to_string();
+        let actual = RawStream::new(&bytes[..]).digest(Sha2256).to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn empty_raw_stream() {
+        let expected = "1220454349e422f05297191ead13e21d3db520e5abef52055e4964b82fb213f593a1";
+        let actual = RawStream::new(io::empty()).digest(Sha2256).to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "blot_json")]
+    #[test]
+    fn skips_blank_lines() {
+        let input = "\n\"foo\"\n\n   \n\"foo\"\n";
+        let actual: Vec<String> = hash_lines(input.as_bytes(), Sha2256)
+            .map(|hash| hash.unwrap().to_string())
+            .collect();
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0], actual[1]);
+    }
+
+    #[cfg(feature = "blot_json")]
+    #[test]
+    fn applies_options_to_records() {
+        use multihash::{AlgorithmPolicy, Stamp};
+
+        let input = "\"foo\"\n[\"**REDACTED**1114796384e694ba83d7e3250c171ad59d7109728d2c\"]\n";
+        let options = DeserializerOptions::new().policy(AlgorithmPolicy::new().deny(Stamp::Sha1));
+        let results: Vec<_> = hash_lines_with(input.as_bytes(), options, Sha2256).collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(StreamError::Document {
+                line,
+                error: DeserializeError::ForbiddenSeal(Stamp::Sha1),
+            }) => assert_eq!(*line, 2),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(hash_lines(input.as_bytes(), Sha2256).all(|hash| hash.is_ok()));
+    }
+
+    #[cfg(feature = "blot_json")]
+    #[test]
+    fn reports_line_of_invalid_record() {
+        let input = "\"foo\"\n\n{\"foo\": }\n\"bar\"\n";
+        let results: Vec<_> = hash_lines(input.as_bytes(), Sha2256).collect();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(StreamError::Json { line, .. }) => assert_eq!(*line, 3),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(results[2].is_ok());
+    }
+
+    #[cfg(feature = "blot_json")]
+    #[test]
+    fn progress_counts_consumed_bytes() {
+        let input = "\"foo\"\n\n[\"foo\", \"bar\"]\n";
+        let mut reports = Vec::new();
+        let count = hash_lines(
+            Progress::new(input.as_bytes(), |bytes| reports.push(bytes)),
+            Sha2256,
+        )
+        .count();
+
+        assert_eq!(count, 2);
+        assert_eq!(reports.last(), Some(&(input.len() as u64)));
+        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
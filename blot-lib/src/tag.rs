@@ -6,25 +6,184 @@
 
 //! Blot tags.
 //!
-//! Tags are the same found in Objecthash except for [`Tag::Timestamp`].
+//! Tags are the same found in Objecthash except for [`Tag::Timestamp`] and [`Tag::Decimal`].
+//!
+//! Applications can hash their own kinds of values under a [`Tag::Custom`] byte, e.g. a `Uri`
+//! that must never collide with the string it is written as:
+//!
+//! ```
+//! use blot::multihash::{Multihash, Sha2256};
+//! use blot::tag::{Tag, TagError};
+//!
+//! let uri = Tag::custom(0x55).unwrap();
+//!
+//! assert_eq!(uri.code(), 0x55);
+//! assert_ne!(
+//!     Sha2256.digest_primitive(uri.into(), b"https://example.org"),
+//!     Sha2256.digest_primitive(Tag::Unicode, b"https://example.org")
+//! );
+//! assert_eq!(Tag::custom(0x75), Err(TagError::Reserved(0x75)));
+//! ```
+//!
+//! See [`Leaf`] to plug a custom encoder into a [`Value`].
+//!
+//! [`Leaf`]: ../value/trait.Leaf.html
+//! [`Value`]: ../value/enum.Value.html
 
-#[derive(Debug, Clone, Copy)]
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tag {
-    Bool = 0x62,
-    Dict = 0x64,
-    Float = 0x66,
-    Integer = 0x69,
-    List = 0x6C,
-    Null = 0x6E,
-    Raw = 0x72,
-    Set = 0x73,
-    Timestamp = 0x74,
-    Unicode = 0x75,
+    Bool,
+    Dict,
+    /// An arbitrary-precision decimal number, hashed as its canonical decimal string.
+    Decimal,
+    Float,
+    Integer,
+    List,
+    Null,
+    Raw,
+    Set,
+    Timestamp,
+    Unicode,
+    /// An application-defined tag, see [`Tag::custom`].
+    Custom(CustomTag),
+}
+
+/// The byte of an application-defined tag, never the byte of a built-in tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomTag(u8);
+
+impl CustomTag {
+    pub fn code(&self) -> u8 {
+        self.0
+    }
+}
+
+impl From<CustomTag> for Tag {
+    fn from(tag: CustomTag) -> Tag {
+        Tag::Custom(tag)
+    }
 }
 
 impl Tag {
+    /// An application-defined tag with the given byte.
+    ///
+    /// Fails if a built-in tag already has the byte.
+    pub fn custom(code: u8) -> Result<CustomTag, TagError> {
+        match Tag::try_from(code) {
+            Ok(_) => Err(TagError::Reserved(code)),
+            Err(_) => Ok(CustomTag(code)),
+        }
+    }
+
+    /// The tag with the byte, custom unless a built-in tag has it.
+    pub(crate) fn from_code(code: u8) -> Tag {
+        Tag::try_from(code).unwrap_or(Tag::Custom(CustomTag(code)))
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Tag::Bool => 0x62,
+            Tag::Dict => 0x64,
+            Tag::Decimal => 0x65,
+            Tag::Float => 0x66,
+            Tag::Integer => 0x69,
+            Tag::List => 0x6C,
+            Tag::Null => 0x6E,
+            Tag::Raw => 0x72,
+            Tag::Set => 0x73,
+            Tag::Timestamp => 0x74,
+            Tag::Unicode => 0x75,
+            Tag::Custom(tag) => tag.code(),
+        }
+    }
+
     pub fn to_bytes(&self) -> [u8; 1] {
-        [*self as u8]
+        [self.code()]
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Tag::Custom(_))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tag::Bool => "bool",
+            Tag::Dict => "dict",
+            Tag::Decimal => "decimal",
+            Tag::Float => "float",
+            Tag::Integer => "integer",
+            Tag::List => "list",
+            Tag::Null => "null",
+            Tag::Raw => "raw",
+            Tag::Set => "set",
+            Tag::Timestamp => "timestamp",
+            Tag::Unicode => "unicode",
+            Tag::Custom(_) => "custom",
+        }
+    }
+}
+
+/// Every built-in tag, in byte order.
+const TAGS: [Tag; 11] = [
+    Tag::Bool,
+    Tag::Dict,
+    Tag::Decimal,
+    Tag::Float,
+    Tag::Integer,
+    Tag::List,
+    Tag::Null,
+    Tag::Raw,
+    Tag::Set,
+    Tag::Timestamp,
+    Tag::Unicode,
+];
+
+#[derive(Debug, PartialEq)]
+pub enum TagError {
+    /// No tag has the byte.
+    UnknownCode(u8),
+    /// No tag has the name.
+    UnknownName(String),
+    /// A custom tag cannot take the byte of a built-in tag.
+    Reserved(u8),
+}
+
+impl fmt::Display for TagError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TagError::UnknownCode(code) => write!(formatter, "unknown tag {:#x}", code),
+            TagError::UnknownName(name) => write!(formatter, "unknown tag '{}'", name),
+            TagError::Reserved(code) => write!(formatter, "tag {:#x} is reserved", code),
+        }
+    }
+}
+
+impl error::Error for TagError {}
+
+/// Only built-in tags; custom tags are built with [`Tag::custom`].
+impl TryFrom<u8> for Tag {
+    type Error = TagError;
+
+    fn try_from(code: u8) -> Result<Tag, TagError> {
+        TAGS.iter()
+            .find(|tag| tag.code() == code)
+            .cloned()
+            .ok_or(TagError::UnknownCode(code))
+    }
+}
+
+impl TryFrom<&str> for Tag {
+    type Error = TagError;
+
+    fn try_from(name: &str) -> Result<Tag, TagError> {
+        TAGS.iter()
+            .find(|tag| tag.name() == name)
+            .cloned()
+            .ok_or_else(|| TagError::UnknownName(name.to_string()))
     }
 }
 
@@ -36,4 +195,33 @@ mod tests {
     fn unicode_byte() {
         assert_eq!(Tag::Unicode.to_bytes(), [0x75; 1])
     }
+
+    #[test]
+    fn fallible_conversions() {
+        for tag in TAGS.iter() {
+            assert_eq!(Tag::try_from(tag.to_bytes()[0]), Ok(*tag));
+            assert_eq!(Tag::try_from(tag.name()), Ok(*tag));
+        }
+
+        assert_eq!(Tag::try_from(0x00), Err(TagError::UnknownCode(0x00)));
+        assert_eq!(
+            Tag::try_from("string"),
+            Err(TagError::UnknownName("string".into()))
+        );
+    }
+
+    #[test]
+    fn custom_tags() {
+        for tag in TAGS.iter() {
+            assert_eq!(Tag::custom(tag.code()), Err(TagError::Reserved(tag.code())));
+        }
+
+        let tag = Tag::from(Tag::custom(0x00).unwrap());
+
+        assert_eq!(tag.code(), 0x00);
+        assert_eq!(tag.to_bytes(), [0x00]);
+        assert_eq!(tag.name(), "custom");
+        assert!(tag.is_custom());
+        assert!(!Tag::Raw.is_custom());
+    }
 }
@@ -7,34 +7,118 @@
 //! Uvar is an implementation of unsigned variable integers.
 //!
 //! https://github.com/multiformats/unsigned-varint
+//!
+//! A uvar is an unsigned LEB128 integer: 7 bits per byte, least significant group first, with
+//! the most significant bit of every byte but the last set. The spec limits uvars to 9 bytes, 63
+//! bits, and requires the minimal encoding.
+//!
+//! ```
+//! use blot::uvar;
+//!
+//! assert_eq!(uvar::encode_u64(0xb240), vec![0xc0, 0xe4, 0x02]);
+//! assert_eq!(uvar::decode(&[0xc0, 0xe4, 0x02, 0x40]).unwrap(), (0xb240, &[0x40][..]));
+//! ```
 
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::error;
 use std::fmt;
 
 const MAXBYTES: usize = 9;
 
+/// Encodes the number as a uvar.
+///
+/// Numbers above 2^63 - 1 take 10 bytes, which [`decode`] rejects as the spec does.
+pub fn encode_u64(mut n: u64) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(MAXBYTES);
+
+    while n > 0x7f {
+        buffer.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+
+    buffer.push(n as u8);
+
+    buffer
+}
+
+/// Decodes the uvar at the start of the buffer and returns it with the rest of bytes.
+pub fn decode(buffer: &[u8]) -> Result<(u64, &[u8]), UvarError> {
+    let mut n = 0;
+
+    for (i, b) in buffer.iter().enumerate() {
+        if i >= MAXBYTES {
+            return Err(UvarError::Overflow);
+        }
+
+        n |= u64::from(b & 0x7f) << (7 * i);
+
+        if b & 0x80 == 0 {
+            if *b == 0 && i > 0 {
+                return Err(UvarError::NotMinimal);
+            }
+
+            return Ok((n, &buffer[i + 1..]));
+        }
+    }
+
+    Err(UvarError::Underflow)
+}
+
 // TODO: Internal representation is a vector for the time being. In the future it might change to
 // either u64 or an array.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Uvar(Vec<u8>);
 
 impl Uvar {
-    /// Constructs a new uvar from a byte list. Use {Uvar::from_bytes} if you need a safe
+    /// Constructs a new uvar from its encoded bytes. Use {Uvar::from_bytes} if you need a safe
     /// constructor.
     pub fn new(bytes: Vec<u8>) -> Uvar {
         Uvar(bytes)
     }
 
-    /// Consumes the list of bytes.
+    /// Consumes the uvar returning its encoded bytes.
     ///
     /// ```
     /// use blot::uvar::Uvar;
     ///
     /// assert_eq!(Uvar::from_bytes(&[0x12]).unwrap().to_bytes(), vec![0x12]);
+    /// assert_eq!(Uvar::from(0xb240).to_bytes(), vec![0xc0, 0xe4, 0x02]);
     /// ```
     pub fn to_bytes(self) -> Vec<u8> {
         self.0
     }
 
+    /// The encoded bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The value of the uvar, failing if its bytes are not a valid uvar. Only a uvar built with
+    /// {Uvar::new} can fail.
+    ///
+    /// ```
+    /// use blot::uvar::Uvar;
+    ///
+    /// assert_eq!(Uvar::from(0xb240).to_u64().unwrap(), 0xb240);
+    /// assert!(Uvar::new(vec![0x80]).to_u64().is_err());
+    /// ```
+    pub fn to_u64(&self) -> Result<u64, UvarError> {
+        match decode(&self.0)? {
+            (n, []) => Ok(n),
+            _ => Err(UvarError::TrailingBytes),
+        }
+    }
+
+    /// The value of the uvar, ignoring any bits beyond the 64th of a uvar built with {Uvar::new}.
+    fn value(&self) -> u64 {
+        self.0
+            .iter()
+            .take(10)
+            .enumerate()
+            .fold(0, |n, (i, b)| n | u64::from(b & 0x7f) << (7 * i))
+    }
+
     /// Transforms a byte list into a uvar.
     pub fn from_bytes(buffer: &[u8]) -> Result<Uvar, UvarError> {
         if buffer.len() > MAXBYTES {
@@ -51,44 +135,35 @@ impl Uvar {
     /// ```
     /// use blot::uvar::Uvar;
     ///
-    /// let buffer = vec![0x12, 0x07, 0x06];
+    /// let buffer = vec![0x93, 0x20, 0x1c];
     /// let (uvar, bytes) = Uvar::take(&buffer).unwrap();
     ///
-    /// assert_eq!(uvar, Uvar::from_bytes(&[0x12]).unwrap());
+    /// assert_eq!(uvar, Uvar::from(0x1013));
+    /// assert_eq!(bytes, &[0x1c]);
     /// ```
     pub fn take(buffer: &[u8]) -> Result<(Uvar, &[u8]), UvarError> {
-        for (i, b) in buffer.into_iter().enumerate() {
-            if b & 0x80 == 0 {
-                let code = Uvar((&buffer[..i + 1]).into());
-                let rest = &buffer[i + 1..];
-
-                return Ok((code, rest));
-            }
-
-            if i >= MAXBYTES {
-                return Err(UvarError::Overflow);
-            }
-        }
+        let (_, rest) = decode(buffer)?;
+        let code = Uvar(buffer[..buffer.len() - rest.len()].into());
 
-        Err(UvarError::Underflow)
+        Ok((code, rest))
     }
 }
 
 impl fmt::LowerHex for Uvar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::LowerHex::fmt(&u64::from(self.clone()), f)
+        fmt::LowerHex::fmt(&self.value(), f)
     }
 }
 
 impl fmt::UpperHex for Uvar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::UpperHex::fmt(&u64::from(self.clone()), f)
+        fmt::UpperHex::fmt(&self.value(), f)
     }
 }
 
 impl fmt::Binary for Uvar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Binary::fmt(&u64::from(self.clone()), f)
+        fmt::Binary::fmt(&self.value(), f)
     }
 }
 
@@ -98,75 +173,94 @@ impl fmt::Display for Uvar {
     }
 }
 
-impl From<Uvar> for u64 {
-    fn from(uvar: Uvar) -> u64 {
-        let mut n = 0;
+/// Orders uvars by value. Uvars built with {Uvar::new} with the same value but different bytes
+/// are ordered by their bytes.
+impl Ord for Uvar {
+    fn cmp(&self, other: &Uvar) -> Ordering {
+        self.value()
+            .cmp(&other.value())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
 
-        for (i, b) in uvar.to_bytes().iter().enumerate() {
-            n = n << (i * 8) | u64::from(b & 0xFF);
-        }
+impl PartialOrd for Uvar {
+    fn partial_cmp(&self, other: &Uvar) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-        n
+/// Decodes the uvar, ignoring any bits beyond the 64th of a uvar built with {Uvar::new}. Use
+/// {Uvar::to_u64} for a checked conversion.
+impl From<Uvar> for u64 {
+    fn from(uvar: Uvar) -> u64 {
+        uvar.value()
     }
 }
 
-/// This conversion consumes full bytes, not 7bit bytes as you would expect from variable integers.
+/// Reads a buffer holding exactly one uvar.
 ///
-/// WARNING: This method forces to Big Endian. It hasn't been tested properly with different architectures.
-impl From<u64> for Uvar {
-    fn from(n: u64) -> Uvar {
-        let mut buffer = Vec::with_capacity(MAXBYTES);
-        let mut value = n.to_be();
-
-        while value > 0 {
-            let k = value & 0xFF;
-            if k != 0 {
-                buffer.push(k as u8);
-            }
-
-            value = value >> 8;
+/// ```
+/// use blot::uvar::Uvar;
+/// use std::convert::TryFrom;
+///
+/// assert_eq!(Uvar::try_from(&[0x93, 0x20][..]).unwrap(), Uvar::from(0x1013));
+/// assert!(Uvar::try_from(&[0x12, 0x20][..]).is_err());
+/// ```
+impl<'a> TryFrom<&'a [u8]> for Uvar {
+    type Error = UvarError;
+
+    fn try_from(buffer: &'a [u8]) -> Result<Uvar, UvarError> {
+        match Uvar::take(buffer)? {
+            (uvar, []) => Ok(uvar),
+            _ => Err(UvarError::TrailingBytes),
         }
-
-        Uvar(buffer)
     }
 }
 
-// macro_rules! impl_for_array (($len:expr) => {
-//     impl From<Uvar> for [u8; $len] {
-//         fn from(n: Uvar) -> [u8; $len] {
-//             let mut buffer = [0; $len];
-//             let mut value = n.unbox();
-//             let mut i = 0;
-
-//             while value > 0x7F {
-//                 buffer[i] = (value as u8) | 0x80;
-//                 value >>= 7;
-//                 i += 1;
-//             }
+/// Fails for numbers above 2^63 - 1, the largest a 9 byte uvar holds.
+impl TryFrom<u128> for Uvar {
+    type Error = UvarError;
 
-//             buffer[i] = value as u8;
+    fn try_from(n: u128) -> Result<Uvar, UvarError> {
+        if n >= 1 << 63 {
+            return Err(UvarError::Overflow);
+        }
 
-//             buffer
-//         }
-//     }
-// });
+        Ok(Uvar::from(n as u64))
+    }
+}
 
-// impl_for_array!(9);
-// impl_for_array!(8);
-// impl_for_array!(7);
-// impl_for_array!(6);
-// impl_for_array!(5);
-// impl_for_array!(4);
-// impl_for_array!(3);
-// impl_for_array!(2);
-// impl_for_array!(1);
+impl From<u64> for Uvar {
+    fn from(n: u64) -> Uvar {
+        Uvar(encode_u64(n))
+    }
+}
 
 #[derive(Debug)]
 pub enum UvarError {
+    /// The uvar is longer than 9 bytes.
     Overflow,
+    /// The input ends before the last byte of the uvar.
     Underflow,
+    /// The uvar has trailing zero groups, e.g. `[0x81, 0x00]` instead of `[0x01]`.
+    NotMinimal,
+    /// The input has bytes after the uvar.
+    TrailingBytes,
+}
+
+impl fmt::Display for UvarError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UvarError::Overflow => write!(formatter, "uvar longer than {} bytes", MAXBYTES),
+            UvarError::Underflow => write!(formatter, "truncated uvar"),
+            UvarError::NotMinimal => write!(formatter, "uvar not minimally encoded"),
+            UvarError::TrailingBytes => write!(formatter, "unexpected bytes after uvar"),
+        }
+    }
 }
 
+impl error::Error for UvarError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,8 +274,8 @@ mod tests {
 
     #[test]
     fn from_bytes_multi() {
-        let actual = Uvar::from_bytes(&[0xb2, 0x40]).unwrap();
-        let expected = Uvar(vec![0xb2, 0x40]);
+        let actual = Uvar::from_bytes(&[0xc0, 0xe4, 0x02]).unwrap();
+        let expected = Uvar(vec![0xc0, 0xe4, 0x02]);
         assert_eq!(actual, expected);
     }
 
@@ -201,8 +295,12 @@ mod tests {
 
     #[test]
     fn to_u64() {
-        for (buffer, expected) in &[(vec![0x12], 0x12), (vec![0xb2, 0x40], 0xb240)] {
-            let actual: u64 = Uvar::from_bytes(&buffer).unwrap().into();
+        for (buffer, expected) in &[
+            (vec![0x12], 0x12),
+            (vec![0x93, 0x20], 0x1013),
+            (vec![0xc0, 0xe4, 0x02], 0xb240),
+        ] {
+            let actual: u64 = Uvar::from_bytes(buffer).unwrap().into();
 
             assert_eq!(actual, *expected);
         }
@@ -210,13 +308,115 @@ mod tests {
 
     #[test]
     fn from_u64() {
-        for (buffer, n) in &[(vec![0x12], 0x12), (vec![0xb2, 0x40], 0xb240)] {
+        for (buffer, n) in &[
+            (vec![0x00], 0x00),
+            (vec![0x12], 0x12),
+            (vec![0x93, 0x20], 0x1013),
+            (vec![0xc0, 0xe4, 0x02], 0xb240),
+        ] {
             let num: u64 = *n;
-            let expected = Uvar::from_bytes(&buffer).unwrap();
+            let expected = Uvar::from_bytes(buffer).unwrap();
             let actual: Uvar = num.into();
 
             assert_eq!(actual, expected);
         }
     }
 
+    #[test]
+    fn spec_vectors() {
+        for (n, buffer) in &[
+            (1, vec![0x01]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x01]),
+            (255, vec![0xff, 0x01]),
+            (300, vec![0xac, 0x02]),
+            (16384, vec![0x80, 0x80, 0x01]),
+            (
+                (1 << 63) - 1,
+                vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+            ),
+        ] {
+            assert_eq!(&encode_u64(*n), buffer);
+            assert_eq!(decode(buffer).unwrap(), (*n, &[][..]));
+        }
+    }
+
+    #[test]
+    fn round_trip_boundaries() {
+        for n in 0..=u64::from(u16::MAX) {
+            assert_eq!(decode(&encode_u64(n)).unwrap().0, n);
+        }
+
+        for bits in 0..63 {
+            for n in &[(1 << bits) - 1, 1 << bits, (1 << bits) + 1] {
+                let buffer = encode_u64(*n);
+
+                assert_eq!(
+                    buffer.len(),
+                    (64 - (*n | 1).leading_zeros() as usize).div_ceil(7)
+                );
+                assert_eq!(decode(&buffer).unwrap(), (*n, &[][..]));
+            }
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(decode(&[]), Err(UvarError::Underflow)));
+        assert!(matches!(decode(&[0x80, 0x80]), Err(UvarError::Underflow)));
+        assert!(matches!(decode(&[0x81, 0x00]), Err(UvarError::NotMinimal)));
+        assert!(matches!(decode(&[0xff; 10]), Err(UvarError::Overflow)));
+        assert!(matches!(
+            decode(&encode_u64(u64::MAX)),
+            Err(UvarError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn ordering() {
+        let mut codes: Vec<Uvar> = [0xb240, 0x12, 0x1013, 0x80, 0x7f]
+            .iter()
+            .map(|n| Uvar::from(*n))
+            .collect();
+        codes.sort();
+
+        let sorted: Vec<u64> = codes.into_iter().map(u64::from).collect();
+
+        assert_eq!(sorted, vec![0x12, 0x7f, 0x80, 0x1013, 0xb240]);
+        assert!(Uvar::from(0x80) > Uvar::from(0x7f));
+    }
+
+    #[test]
+    fn checked_conversions() {
+        assert_eq!(Uvar::try_from(0xb240u128).unwrap(), Uvar::from(0xb240));
+        assert!(matches!(
+            Uvar::try_from(1u128 << 63),
+            Err(UvarError::Overflow)
+        ));
+        assert!(matches!(
+            Uvar::try_from(&[0x12, 0x00][..]),
+            Err(UvarError::TrailingBytes)
+        ));
+        assert!(matches!(Uvar::try_from(&[][..]), Err(UvarError::Underflow)));
+        assert!(matches!(
+            Uvar::new(vec![0x81, 0x00]).to_u64(),
+            Err(UvarError::NotMinimal)
+        ));
+        assert!(matches!(
+            Uvar::new(vec![0x12, 0x12]).to_u64(),
+            Err(UvarError::TrailingBytes)
+        ));
+        assert_eq!(Uvar::from(0x1013).as_bytes(), &[0x93, 0x20]);
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(n in 0u64..(1 << 63), rest: Vec<u8>) {
+            let mut buffer = encode_u64(n);
+            buffer.extend_from_slice(&rest);
+
+            prop_assert_eq!(decode(&buffer).unwrap(), (n, &rest[..]));
+            prop_assert_eq!(u64::from(Uvar::take(&buffer).unwrap().0), n);
+        }
+    }
 }
@@ -0,0 +1,350 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Borrowed values.
+//!
+//! A [`BorrowedValue`] is a [`Value`] whose strings and keys borrow from the input when they
+//! have no escape sequences, and whose dictionaries are plain vectors of entries. Deserializing a
+//! large document into it takes far fewer allocations. It is typed with the default
+//! [`DeserializerOptions`] and has the same digest as the equivalent [`Value`].
+//!
+//! ```
+//! use blot::core::Blot;
+//! use blot::multihash::Sha2256;
+//! use blot::value::borrowed::BorrowedValue;
+//! use blot::value::de::DeserializerOptions;
+//! use blot::value::Value;
+//!
+//! let input = r#"{"name": "foo", "tags": ["a", "b"]}"#;
+//! let borrowed: BorrowedValue<Sha2256> = BorrowedValue::from_str(input).unwrap();
+//! let value: Value<Sha2256> = DeserializerOptions::new().from_str(input).unwrap();
+//!
+//! assert_eq!(borrowed.digest(Sha2256), value.digest(Sha2256));
+//! ```
+
+use core::{digest_entries, Blot};
+use datetime::blot_timestamp;
+use multihash::{Harvest, Multihash};
+use seal::Seal;
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json;
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+use tag::Tag;
+
+use super::de::{DeserializerOptions, StringKind};
+use super::traverse::DigestError;
+use super::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedValue<'a, T: Multihash> {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    UInteger(u64),
+    Float(f64),
+    String(Cow<'a, str>),
+    Timestamp(Cow<'a, str>),
+    Redacted(Seal<T>),
+    Raw(Vec<u8>),
+    List(Vec<BorrowedValue<'a, T>>),
+    Set(Vec<BorrowedValue<'a, T>>),
+    /// Entries sorted by key, without duplicates.
+    Dict(Vec<(Cow<'a, str>, BorrowedValue<'a, T>)>),
+}
+
+impl<'a, T: Multihash> BorrowedValue<'a, T> {
+    /// Parses a JSON string, borrowing from it.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'a str) -> Result<BorrowedValue<'a, T>, serde_json::Error> {
+        serde_json::from_str(input)
+    }
+
+    /// Copies the borrowed strings into an owned [`Value`].
+    pub fn into_owned(self) -> Value<T> {
+        match self {
+            BorrowedValue::Null => Value::Null,
+            BorrowedValue::Bool(raw) => Value::Bool(raw),
+            BorrowedValue::Integer(raw) => Value::Integer(raw),
+            BorrowedValue::UInteger(raw) => Value::UInteger(raw),
+            BorrowedValue::Float(raw) => Value::Float(raw),
+            BorrowedValue::String(raw) => Value::String(raw.into_owned()),
+            BorrowedValue::Timestamp(raw) => Value::Timestamp(raw.into_owned()),
+            BorrowedValue::Redacted(raw) => Value::Redacted(raw),
+            BorrowedValue::Raw(raw) => Value::Raw(raw),
+            BorrowedValue::List(raw) => {
+                Value::List(raw.into_iter().map(BorrowedValue::into_owned).collect())
+            }
+            BorrowedValue::Set(raw) => {
+                Value::Set(raw.into_iter().map(BorrowedValue::into_owned).collect())
+            }
+            BorrowedValue::Dict(raw) => Value::Dict(
+                raw.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a, T: Multihash> Blot for BorrowedValue<'a, T> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        match self {
+            BorrowedValue::Null => None::<u8>.blot(digester),
+            BorrowedValue::Bool(raw) => raw.blot(digester),
+            BorrowedValue::Integer(raw) => raw.blot(digester),
+            BorrowedValue::UInteger(raw) => raw.blot(digester),
+            BorrowedValue::Float(raw) => raw.blot(digester),
+            BorrowedValue::String(raw) => raw.blot(digester),
+            BorrowedValue::Timestamp(raw) => blot_timestamp(digester, raw),
+            BorrowedValue::Redacted(raw) => raw.blot(digester),
+            BorrowedValue::Raw(raw) => raw.as_slice().blot(digester),
+            BorrowedValue::List(raw) => {
+                digester.digest_collection(Tag::List, raw.iter().map(|item| item.blot(digester)))
+            }
+            BorrowedValue::Set(raw) => {
+                let mut list: Vec<Harvest> = raw.iter().map(|item| item.blot(digester)).collect();
+
+                list.sort_unstable();
+                list.dedup();
+
+                digester.digest_collection(Tag::Set, list)
+            }
+            BorrowedValue::Dict(raw) => {
+                let entries = raw
+                    .iter()
+                    .map(|(key, value)| (key.blot(digester), value.blot(digester)))
+                    .collect();
+
+                digest_entries(digester, entries)
+            }
+        }
+    }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, DigestError> {
+        match self {
+            BorrowedValue::Float(raw) => raw.try_blot(digester),
+            BorrowedValue::List(raw) => {
+                let list = raw
+                    .iter()
+                    .map(|item| item.try_blot(digester))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(digester.digest_collection(Tag::List, list))
+            }
+            BorrowedValue::Set(raw) => {
+                let mut list = raw
+                    .iter()
+                    .map(|item| item.try_blot(digester))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                list.sort_unstable();
+                list.dedup();
+
+                Ok(digester.digest_collection(Tag::Set, list))
+            }
+            BorrowedValue::Dict(raw) => {
+                let entries = raw
+                    .iter()
+                    .map(|(key, value)| Ok((key.blot(digester), value.try_blot(digester)?)))
+                    .collect::<Result<Vec<_>, DigestError>>()?;
+
+                Ok(digest_entries(digester, entries))
+            }
+            value => Ok(value.blot(digester)),
+        }
+    }
+}
+
+impl<'de, T: Multihash> Deserialize<'de> for BorrowedValue<'de, T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(BorrowedVisitor {
+            marker: PhantomData,
+        })
+    }
+}
+
+struct BorrowedVisitor<T: Multihash> {
+    marker: PhantomData<*const T>,
+}
+
+impl<T: Multihash> BorrowedVisitor<T> {
+    fn string<'de, E: de::Error>(&self, value: Cow<'de, str>) -> Result<BorrowedValue<'de, T>, E> {
+        match DeserializerOptions::default().classify::<T>(&value) {
+            Ok(StringKind::Redacted(seal)) => Ok(BorrowedValue::Redacted(seal)),
+            Ok(StringKind::Raw(raw)) => Ok(BorrowedValue::Raw(raw)),
+            Ok(StringKind::Timestamp) => Ok(BorrowedValue::Timestamp(value)),
+            Ok(StringKind::String) => Ok(BorrowedValue::String(value)),
+            Err(err) => Err(E::custom(err)),
+        }
+    }
+}
+
+impl<'de, T: Multihash> Visitor<'de> for BorrowedVisitor<T> {
+    type Value = BorrowedValue<'de, T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Expecting a valid JSON value.")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        if value <= (i64::MAX as u64) {
+            Ok(BorrowedValue::Integer(value as i64))
+        } else {
+            Ok(BorrowedValue::UInteger(value))
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Float(value))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Self::Value, E> {
+        self.string(Cow::Borrowed(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        self.string(Cow::Owned(value.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+        self.string(Cow::Owned(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Null)
+    }
+
+    fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let mut vec = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+
+        while let Some(elem) = visitor.next_element()? {
+            vec.push(elem);
+        }
+
+        Ok(BorrowedValue::List(vec))
+    }
+
+    fn visit_map<V>(self, mut access: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(access.size_hint().unwrap_or(0));
+
+        while let Some(key) = access.next_key_seed(KeySeed)? {
+            entries.push((key, access.next_value()?));
+        }
+
+        // The last duplicate wins, like in a `Value::Dict`.
+        entries.reverse();
+        entries.sort_by(|a: &(Cow<str>, _), b| a.0.cmp(&b.0));
+        entries.dedup_by(|a, b| a.0 == b.0);
+
+        Ok(BorrowedValue::Dict(entries))
+    }
+}
+
+/// Deserializes a key borrowing from the input when possible.
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Cow<'de, str>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string key")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(value.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+
+    #[test]
+    fn same_digest() {
+        let input = r#"{
+            "a": [1, 1.5, null, true, "2018-01-01T00:00:00Z", "cafe"],
+            "b": "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038",
+            "cd": {"x": "y"},
+            "a": "last"
+        }"#;
+        let borrowed: BorrowedValue<Sha2256> = BorrowedValue::from_str(input).unwrap();
+        let value: Value<Sha2256> = serde_json::from_str(input).unwrap();
+
+        assert_eq!(borrowed.digest(Sha2256), value.digest(Sha2256));
+        assert_eq!(borrowed.into_owned(), value);
+    }
+
+    #[test]
+    fn borrows_unescaped_strings() {
+        let borrowed: BorrowedValue<Sha2256> =
+            BorrowedValue::from_str(r#"{"key": ["foo", "b\u0061r"]}"#).unwrap();
+
+        match borrowed {
+            BorrowedValue::Dict(ref entries) => match entries[0] {
+                (Cow::Borrowed("key"), BorrowedValue::List(ref list)) => {
+                    assert!(matches!(
+                        list[0],
+                        BorrowedValue::String(Cow::Borrowed("foo"))
+                    ));
+                    assert_eq!(list[1], BorrowedValue::String("bar".into()));
+                    assert!(matches!(list[1], BorrowedValue::String(Cow::Owned(_))));
+                }
+                ref other => panic!("unexpected entry {:?}", other),
+            },
+            _ => panic!("expected a dict"),
+        }
+    }
+}
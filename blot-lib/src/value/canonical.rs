@@ -0,0 +1,191 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Canonical JSON.
+//!
+//! Renders a [`Value`] as a deterministic JSON document, so it can be stored alongside its
+//! digest and compared byte for byte. Equal values always render the same:
+//!
+//! * No insignificant whitespace.
+//! * Dictionary keys sorted by code point.
+//! * Set members sorted by their rendering, without duplicates.
+//! * Floats in their shortest round-trip form, always with a fraction or an exponent so they
+//!   read back as floats: `1.0`, `-0.0`, `1e-10`. NaN and infinities have no JSON form and
+//!   render as `null`.
+//! * Strings escaped as `serde_json` does: quotes, backslashes and control characters only.
+//! * Timestamps in their canonical form, seals with the `0x77` mark and raw values as lowercase
+//!   hexadecimal, like the [serializer](../ser/index.html).
+//!
+//! The document reads back with the same digest, except for sets, which read back as lists
+//! unless typed with a schema, and for the values rendered as strings or `null` above.
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate blot;
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//!
+//! # fn main() {
+//! let value: Value<Sha2256> = dict! {
+//!     "b" => list![1, 1.0],
+//!     "a" => Value::Timestamp("2018-10-13T16:50:00+01:00".into())
+//! };
+//!
+//! assert_eq!(
+//!     value.to_canonical_json(),
+//!     r#"{"a":"2018-10-13T15:50:00Z","b":[1,1.0]}"#
+//! );
+//! # }
+//! ```
+
+use datetime::canonical_timestamp;
+use multihash::Multihash;
+use serde_json;
+
+use super::Value;
+
+impl<T: Multihash> Value<T> {
+    /// Renders the value as canonical JSON.
+    pub fn to_canonical_json(&self) -> String {
+        let mut output = String::new();
+        write_canonical(self, &mut output);
+
+        output
+    }
+}
+
+fn write_canonical<T: Multihash>(value: &Value<T>, output: &mut String) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(raw) => output.push_str(if *raw { "true" } else { "false" }),
+        Value::Integer(raw) => output.push_str(&raw.to_string()),
+        Value::UInteger(raw) => output.push_str(&raw.to_string()),
+        #[cfg(feature = "num-bigint")]
+        Value::BigInt(raw) => write_string(&raw.to_string(), output),
+        #[cfg(feature = "rust_decimal")]
+        Value::Decimal(raw) => write_string(&raw.to_string(), output),
+        // The debug form is the shortest that round-trips and keeps the fraction of integral
+        // floats.
+        Value::Float(raw) if raw.is_finite() => output.push_str(&format!("{:?}", raw)),
+        Value::Float(_) => output.push_str("null"),
+        Value::String(raw) => write_string(raw, output),
+        Value::Timestamp(raw) => match canonical_timestamp(raw) {
+            Some(canonical) => write_string(&canonical, output),
+            None => write_string(raw, output),
+        },
+        Value::Redacted(seal) => write_string(&seal.to_string(), output),
+        Value::Raw(raw) | Value::Custom(_, raw) => {
+            let hex: String = raw.iter().map(|byte| format!("{:02x}", byte)).collect();
+            write_string(&hex, output)
+        }
+        Value::List(list) => write_list(list.iter().map(Value::to_canonical_json), output),
+        Value::Set(list) => {
+            let mut members: Vec<String> = list.iter().map(Value::to_canonical_json).collect();
+            members.sort_unstable();
+            members.dedup();
+
+            write_list(members.into_iter(), output)
+        }
+        Value::Dict(dict) => write_entries(
+            dict.iter().map(|(key, item)| (key.clone(), item)).collect(),
+            output,
+        ),
+        // Keys that are not strings are written as their canonical JSON.
+        Value::Map(map) => write_entries(
+            map.iter()
+                .map(|(key, item)| match key {
+                    Value::String(raw) => (raw.clone(), item),
+                    other => (other.to_canonical_json(), item),
+                })
+                .collect(),
+            output,
+        ),
+    }
+}
+
+fn write_string(value: &str, output: &mut String) {
+    output.push_str(&serde_json::Value::from(value).to_string());
+}
+
+fn write_list<I: Iterator<Item = String>>(items: I, output: &mut String) {
+    output.push('[');
+
+    for (index, item) in items.enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+
+        output.push_str(&item);
+    }
+
+    output.push(']');
+}
+
+fn write_entries<T: Multihash>(mut entries: Vec<(String, &Value<T>)>, output: &mut String) {
+    // The last duplicate wins, like in a `Value::Dict`.
+    entries.reverse();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries.dedup_by(|a, b| a.0 == b.0);
+    output.push('{');
+
+    for (index, (key, item)) in entries.into_iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+
+        write_string(&key, output);
+        output.push(':');
+        write_canonical(item, output);
+    }
+
+    output.push('}');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::Sha2256;
+    use std::f64;
+    use value::de::DeserializerOptions;
+
+    #[test]
+    fn deterministic() {
+        let a: Value<Sha2256> = DeserializerOptions::new()
+            .from_str(r#"{ "b": [1, 1.5, 1e-10], "a": {"y": "é\n", "x": null} }"#)
+            .unwrap();
+        let b: Value<Sha2256> = DeserializerOptions::new()
+            .from_str(r#"{"a":{"x":null,"y":"é\n"},"b":[1,1.50,0.0000000001]}"#)
+            .unwrap();
+        let expected = r#"{"a":{"x":null,"y":"é\n"},"b":[1,1.5,1e-10]}"#;
+
+        assert_eq!(a.to_canonical_json(), expected);
+        assert_eq!(b.to_canonical_json(), expected);
+    }
+
+    #[test]
+    fn same_digest() {
+        let input = r#"{"a": [1, -0.0, 1.7976931348623157e308, "cafe", true], "b": "2018-10-13T16:50:00+01:00", "c": "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"}"#;
+        let options = DeserializerOptions::new();
+        let value: Value<Sha2256> = options.from_str(input).unwrap();
+        let canonical: Value<Sha2256> = options.from_str(&value.to_canonical_json()).unwrap();
+
+        assert_eq!(canonical.digest(Sha2256), value.digest(Sha2256));
+        assert_eq!(canonical.to_canonical_json(), value.to_canonical_json());
+    }
+
+    #[test]
+    fn sets_and_floats() {
+        let value: Value<Sha2256> = Value::Set(vec![
+            Value::String("b".into()),
+            Value::Float(f64::NAN),
+            Value::String("a".into()),
+            Value::String("b".into()),
+        ]);
+
+        assert_eq!(value.to_canonical_json(), r#"["a","b",null]"#);
+    }
+}
@@ -4,20 +4,716 @@
 // This file may not be copied, modified, or distributed except
 // according to those terms.
 
+//! Deserializes a [`Value`] from any self-describing serde format.
+//!
+//! The plain [`Deserialize`] implementation is lenient: strings that look like a seal but can't
+//! be read as one are kept as raw values or strings. Use [`DeserializerOptions`] to change that.
+
+use core::{digest_entries, Blot};
+use datetime::{blot_timestamp, parse_timestamp, TimestampError};
 use hex::FromHex;
-use multihash::Multihash;
+use json::Numbers;
+use multihash::{AlgorithmPolicy, Harvest, Hash, Multihash, Registry, Stamp};
 use regex::Regex;
 use seal::Seal;
-use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
-use std::collections::HashMap;
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json;
+use std::cell::RefCell;
+use std::error;
 use std::fmt;
+use std::io;
+use tag::Tag;
 
-use super::Value;
+use super::schema::{Kind, Pointer, Schema};
+use super::{DictMap, Value};
 
 use std::marker::PhantomData;
-struct ValueVisitor<T: Multihash>(PhantomData<*const T>);
 
-impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
+#[derive(Debug)]
+pub enum DeserializeError {
+    /// The input is not valid JSON or doesn't describe a value.
+    Json(serde_json::Error),
+    /// A string looks redacted but is not a valid seal for the document algorithm.
+    SealMismatch { expected: Stamp, found: String },
+    /// The node at the pointer can't be typed as declared by the schema.
+    SchemaMismatch { pointer: Pointer, expected: Kind },
+    /// A seal was computed with an algorithm not permitted by the policy.
+    ForbiddenSeal(Stamp),
+    /// A string looks like a timestamp but is not a valid date and time.
+    InvalidTimestamp {
+        found: String,
+        error: TimestampError,
+    },
+    /// A dictionary has the key more than once and duplicate keys are an error.
+    DuplicateKey(String),
+    /// The input is not valid in its serialization format, with the message of its deserializer.
+    Format(String),
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeserializeError::Json(err) => write!(formatter, "{}", err),
+            DeserializeError::SealMismatch { expected, found } => write!(
+                formatter,
+                "expected a {} seal, found '{}'",
+                expected.name(),
+                found
+            ),
+            DeserializeError::SchemaMismatch { pointer, expected } => write!(
+                formatter,
+                "node at '{}' is not a valid {}",
+                pointer, expected
+            ),
+            DeserializeError::ForbiddenSeal(stamp) => {
+                write!(formatter, "{} seals are forbidden", stamp.name())
+            }
+            DeserializeError::InvalidTimestamp { found, error } => {
+                write!(formatter, "invalid timestamp '{}': {}", found, error)
+            }
+            DeserializeError::DuplicateKey(key) => write!(formatter, "duplicate key '{}'", key),
+            DeserializeError::Format(message) => write!(formatter, "{}", message),
+        }
+    }
+}
+
+impl error::Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DeserializeError::Json(err) => Some(err),
+            DeserializeError::InvalidTimestamp { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for DeserializeError {
+    fn from(err: serde_json::Error) -> DeserializeError {
+        DeserializeError::Json(err)
+    }
+}
+
+/// What to do with a key found more than once in the same dictionary.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DuplicateKeys {
+    /// The last value wins, like `serde_json` does.
+    #[default]
+    Last,
+    /// Fails with [`DeserializeError::DuplicateKey`].
+    Error,
+}
+
+/// A serialization format readable by [`from_reader`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+}
+
+/// Reads a value in the given format with the default options.
+///
+/// ```
+/// use blot::multihash::Sha2256;
+/// use blot::value::de::Format;
+/// use blot::value::Value;
+///
+/// let value: Value<Sha2256> = blot::from_reader(Format::Json, &b"[1, 1.5]"[..]).unwrap();
+///
+/// assert_eq!(value, Value::List(vec![Value::Integer(1), Value::Float(1.5)]));
+/// ```
+pub fn from_reader<T: Multihash, R: io::Read>(
+    format: Format,
+    reader: R,
+) -> Result<Value<T>, DeserializeError> {
+    DeserializerOptions::default().from_reader(format, reader)
+}
+
+/// Options for deserializing a [`Value`].
+///
+/// ```
+/// use blot::multihash::{Sha2256, Sha3256};
+/// use blot::value::de::{DeserializeError, DeserializerOptions};
+/// use blot::value::Value;
+///
+/// let input = r#""771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038""#;
+/// let options = DeserializerOptions::new().strict(true);
+///
+/// assert!(options.from_str::<Sha2256>(input).is_ok());
+/// match options.from_str::<Sha3256>(input) {
+///     Err(DeserializeError::SealMismatch { .. }) => {}
+///     other => panic!("unexpected result {:?}", other),
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeserializerOptions {
+    strict: bool,
+    detect_raw: bool,
+    detect_timestamps: bool,
+    detect_seals: bool,
+    objecthash: bool,
+    numbers: Numbers,
+    duplicate_keys: DuplicateKeys,
+    schema: Schema,
+    registry: Option<Registry>,
+    policy: AlgorithmPolicy,
+}
+
+impl Default for DeserializerOptions {
+    fn default() -> DeserializerOptions {
+        DeserializerOptions {
+            strict: false,
+            detect_raw: true,
+            detect_timestamps: true,
+            detect_seals: true,
+            objecthash: false,
+            numbers: Numbers::Auto,
+            duplicate_keys: DuplicateKeys::Last,
+            schema: Schema::new(),
+            registry: None,
+            policy: AlgorithmPolicy::new(),
+        }
+    }
+}
+
+impl DeserializerOptions {
+    /// Creates the default options: lenient, with every string heuristic enabled.
+    pub fn new() -> DeserializerOptions {
+        DeserializerOptions::default()
+    }
+
+    /// In strict mode every string that looks redacted must be a valid seal computed with the
+    /// document algorithm. Otherwise deserialization fails with
+    /// [`DeserializeError::SealMismatch`].
+    ///
+    /// A string looks redacted when it starts with `**REDACTED**`, or when it is hexadecimal and
+    /// starts with the `77` seal mark.
+    ///
+    /// Strict mode also rejects strings shaped like a timestamp that are not a valid date and
+    /// time, e.g. `2018-13-45T00:00:00Z`, with [`DeserializeError::InvalidTimestamp`]. Lenient
+    /// mode keeps them as strings.
+    pub fn strict(mut self, strict: bool) -> DeserializerOptions {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether hexadecimal strings become [`Value::Raw`].
+    pub fn detect_raw(mut self, detect: bool) -> DeserializerOptions {
+        self.detect_raw = detect;
+        self
+    }
+
+    /// Whether RFC3339 strings become [`Value::Timestamp`].
+    pub fn detect_timestamps(mut self, detect: bool) -> DeserializerOptions {
+        self.detect_timestamps = detect;
+        self
+    }
+
+    /// Whether seals become [`Value::Redacted`]. When disabled, strict mode has no effect.
+    pub fn detect_seals(mut self, detect: bool) -> DeserializerOptions {
+        self.detect_seals = detect;
+        self
+    }
+
+    /// Reads documents like the original Objecthash: numbers are floats, strings are never raw
+    /// bytes nor timestamps, and seals can be `**REDACTED**` followed by a bare sha2-256 digest, see
+    /// [`Seal::from_objecthash_str`].
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::Value;
+    ///
+    /// let options = DeserializerOptions::new().objecthash(true);
+    /// let value: Value<Sha2256> = options
+    ///     .from_str(r#"["**REDACTED**a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038", "cafe"]"#)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(value.digest(Sha2256), vec!["foo", "cafe"].digest(Sha2256));
+    /// ```
+    pub fn objecthash(mut self, enabled: bool) -> DeserializerOptions {
+        self.objecthash = enabled;
+        self.numbers = if enabled {
+            Numbers::Float
+        } else {
+            Numbers::Auto
+        };
+        self.detect_raw = !enabled;
+        self.detect_timestamps = !enabled;
+        self
+    }
+
+    /// How numbers not covered by the schema are typed. In [`Numbers::Float`] mode integers become
+    /// [`Value::Float`].
+    ///
+    /// ```
+    /// use blot::json::Numbers;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::Value;
+    ///
+    /// let options = DeserializerOptions::new().numbers(Numbers::Float);
+    /// let value: Value<Sha2256> = options.from_str("[1, 1.5]").unwrap();
+    ///
+    /// assert_eq!(value, Value::List(vec![Value::Float(1.0), Value::Float(1.5)]));
+    /// ```
+    pub fn numbers(mut self, numbers: Numbers) -> DeserializerOptions {
+        self.numbers = numbers;
+        self
+    }
+
+    /// What to do with duplicate dictionary keys. By default the last value wins, so
+    /// `{"a": 1, "a": 2}` is the same document as `{"a": 2}`.
+    ///
+    /// ```
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::{DeserializeError, DeserializerOptions, DuplicateKeys};
+    /// use blot::value::Value;
+    ///
+    /// let options = DeserializerOptions::new().duplicate_keys(DuplicateKeys::Error);
+    ///
+    /// match options.from_str::<Sha2256>(r#"{"a": 1, "a": 2}"#) {
+    ///     Err(DeserializeError::DuplicateKey(key)) => assert_eq!(key, "a"),
+    ///     other => panic!("unexpected result {:?}", other),
+    /// }
+    /// ```
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> DeserializerOptions {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Types the nodes matched by the schema as declared, regardless of the other heuristics.
+    /// A node that doesn't fit its declared kind fails with [`DeserializeError::SchemaMismatch`].
+    ///
+    /// ```
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::schema::{Kind, Schema};
+    /// use blot::value::Value;
+    ///
+    /// let schema = Schema::new().with("/tags".parse().unwrap(), Kind::Set);
+    /// let options = DeserializerOptions::new().schema(schema);
+    /// let value: Value<Sha2256> = options.from_str(r#"{"tags": ["a", "b"]}"#).unwrap();
+    ///
+    /// assert_eq!(format!("{:?}", value), r#"Dict({"tags": Set([String("a"), String("b")])})"#);
+    /// ```
+    pub fn schema(mut self, schema: Schema) -> DeserializerOptions {
+        self.schema = schema;
+        self
+    }
+
+    /// Accepts seals computed with the algorithms of the registry, on top of the ones known to
+    /// [`Stamp`].
+    pub fn registry(mut self, registry: Registry) -> DeserializerOptions {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Rejects seals computed with algorithms not permitted by the policy with
+    /// [`DeserializeError::ForbiddenSeal`], in strict and lenient mode alike.
+    ///
+    /// ```
+    /// use blot::multihash::{AlgorithmPolicy, Sha2256, Stamp};
+    /// use blot::value::de::{DeserializeError, DeserializerOptions};
+    /// use blot::value::Value;
+    ///
+    /// let options = DeserializerOptions::new().policy(AlgorithmPolicy::new().deny(Stamp::Sha1));
+    /// let result: Result<Value<Sha2256>, _> =
+    ///     options.from_str(r#""7711140beec7b5ea3f0fdbc95d0dd47f3c5bc275da8a33""#);
+    ///
+    /// match result {
+    ///     Err(DeserializeError::ForbiddenSeal(Stamp::Sha1)) => {}
+    ///     other => panic!("unexpected result {:?}", other),
+    /// }
+    /// ```
+    pub fn policy(mut self, policy: AlgorithmPolicy) -> DeserializerOptions {
+        self.policy = policy;
+        self
+    }
+
+    /// Deserializes a value from a JSON string.
+    #[allow(clippy::should_implement_trait, clippy::wrong_self_convention)]
+    pub fn from_str<T: Multihash>(&self, input: &str) -> Result<Value<T>, DeserializeError> {
+        self.from_json(serde_json::Deserializer::from_str(input))
+    }
+
+    /// Deserializes a whole JSON document, keeping the position of syntax errors.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_json<'de, T: Multihash, R: serde_json::de::Read<'de>>(
+        &self,
+        mut deserializer: serde_json::Deserializer<R>,
+    ) -> Result<Value<T>, DeserializeError> {
+        let failure = RefCell::new(None);
+        let seed = ValueSeed::new(
+            Context {
+                options: self,
+                failure: &failure,
+            },
+            Vec::new(),
+        );
+        let result = seed
+            .deserialize(&mut deserializer)
+            .and_then(|value| deserializer.end().map(|_| value));
+
+        match (result, failure.into_inner()) {
+            (Err(_), Some(err)) => Err(err),
+            (result, _) => Ok(result?),
+        }
+    }
+
+    /// Types an already parsed JSON document with the options, without serializing it back to
+    /// text.
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate blot;
+    /// extern crate serde_json;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::schema::{Kind, Schema};
+    /// use blot::value::Value;
+    ///
+    /// # fn main() {
+    /// let document = serde_json::json!({"tags": ["a", "b"], "at": "2018-10-13T15:50:00Z"});
+    /// let mut schema = Schema::new();
+    /// schema.insert("/tags".parse().unwrap(), Kind::Set);
+    /// let options = DeserializerOptions::new().schema(schema);
+    /// let value: Value<Sha2256> = options.from_json_value(document).unwrap();
+    ///
+    /// assert_eq!(
+    ///     value,
+    ///     dict! {
+    ///         "tags" => set!{"a", "b"},
+    ///         "at" => Value::Timestamp("2018-10-13T15:50:00Z".into())
+    ///     }
+    /// );
+    /// # }
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_json_value<T: Multihash>(
+        &self,
+        document: serde_json::Value,
+    ) -> Result<Value<T>, DeserializeError> {
+        let failure = RefCell::new(None);
+        let seed = ValueSeed::new(
+            Context {
+                options: self,
+                failure: &failure,
+            },
+            Vec::new(),
+        );
+
+        match (seed.deserialize(document), failure.into_inner()) {
+            (Err(_), Some(err)) => Err(err),
+            (result, _) => Ok(result?),
+        }
+    }
+
+    /// Reads a value in the given format.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_reader<T: Multihash, R: io::Read>(
+        &self,
+        format: Format,
+        reader: R,
+    ) -> Result<Value<T>, DeserializeError> {
+        match format {
+            Format::Json => self.from_json(serde_json::Deserializer::from_reader(reader)),
+        }
+    }
+
+    /// Reads a value from any self-describing serde deserializer, so formats other than JSON
+    /// share the heuristics and the schema of the options.
+    ///
+    /// ```
+    /// extern crate serde;
+    /// extern crate blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::Value;
+    /// use serde::de::value::{Error, StrDeserializer};
+    /// use serde::de::IntoDeserializer;
+    ///
+    /// let deserializer: StrDeserializer<Error> = "2018-10-13T15:50:00Z".into_deserializer();
+    /// let value: Value<Sha2256> = DeserializerOptions::new()
+    ///     .from_deserializer(deserializer)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(value, Value::Timestamp("2018-10-13T15:50:00Z".into()));
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_deserializer<'de, T: Multihash, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<Value<T>, DeserializeError> {
+        let failure = RefCell::new(None);
+        let seed = ValueSeed::new(
+            Context {
+                options: self,
+                failure: &failure,
+            },
+            Vec::new(),
+        );
+
+        match (seed.deserialize(deserializer), failure.into_inner()) {
+            (Err(_), Some(err)) => Err(err),
+            (Err(err), None) => Err(DeserializeError::Format(err.to_string())),
+            (Ok(value), _) => Ok(value),
+        }
+    }
+
+    /// Digests a JSON string while parsing it, without building the [`Value`]. `T` is the
+    /// algorithm of the document, the one its seals are expected to use.
+    ///
+    /// Falls back to deserializing the value when the options have a schema.
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::Value;
+    ///
+    /// let input = r#"{"foo": ["bar", 1, 1.5, null]}"#;
+    /// let options = DeserializerOptions::new();
+    /// let value: Value<Sha2256> = options.from_str(input).unwrap();
+    ///
+    /// assert_eq!(
+    ///     options.digest_str::<Sha2256, _>(input, Sha2256).unwrap(),
+    ///     value.digest(Sha2256)
+    /// );
+    /// ```
+    pub fn digest_str<T: Multihash, D: Multihash>(
+        &self,
+        input: &str,
+        digester: D,
+    ) -> Result<Hash<D>, DeserializeError> {
+        if !self.schema.is_empty() {
+            return self
+                .from_str::<T>(input)
+                .map(|value| value.digest(digester));
+        }
+
+        let failure = RefCell::new(None);
+        let seed = DigestSeed::<T, D> {
+            context: Context {
+                options: self,
+                failure: &failure,
+            },
+            digester: &digester,
+            marker: PhantomData,
+        };
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+
+        let result = seed
+            .deserialize(&mut deserializer)
+            .and_then(|digest| deserializer.end().map(|_| digest));
+
+        match (result, failure.into_inner()) {
+            (Err(_), Some(err)) => Err(err),
+            (result, _) => Ok(Hash::new(digester, result?)),
+        }
+    }
+}
+
+/// How a string is typed by the heuristics of the options.
+pub(crate) enum StringKind<T: Multihash> {
+    Redacted(Seal<T>),
+    Raw(Vec<u8>),
+    Timestamp,
+    String,
+}
+
+impl DeserializerOptions {
+    /// Types a string not covered by the schema.
+    pub(crate) fn classify<T: Multihash>(
+        &self,
+        value: &str,
+    ) -> Result<StringKind<T>, DeserializeError> {
+        if self.detect_seals {
+            let bare = if self.objecthash {
+                Seal::<T>::from_bare_str(value)
+            } else {
+                None
+            };
+            let seal = match (bare, &self.registry) {
+                (Some(seal), _) => Ok(seal),
+                (None, Some(registry)) => Seal::<T>::from_str_in(value, registry),
+                (None, None) => Seal::<T>::from_str(value),
+            };
+
+            match seal {
+                Ok(seal) if !self.policy.permits(seal.tag()) => {
+                    return Err(DeserializeError::ForbiddenSeal(*seal.tag()));
+                }
+                Ok(seal) if !self.strict || seal.is_native() => {
+                    return Ok(StringKind::Redacted(seal))
+                }
+                _ if self.strict && looks_redacted(value) => {
+                    return Err(DeserializeError::SealMismatch {
+                        expected: Stamp::of(&T::default()),
+                        found: value.to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if self.detect_raw {
+            if let Ok(raw) = Vec::from_hex(value) {
+                return Ok(StringKind::Raw(raw));
+            }
+        }
+
+        if !self.detect_timestamps {
+            return Ok(StringKind::String);
+        }
+
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$"
+            )
+            .expect("Regex to compile");
+        }
+
+        if RE.is_match(value) {
+            match parse_timestamp(value) {
+                Ok(_) => return Ok(StringKind::Timestamp),
+                Err(error) if self.strict => {
+                    return Err(DeserializeError::InvalidTimestamp {
+                        found: value.to_string(),
+                        error,
+                    });
+                }
+                Err(_) => {}
+            }
+        }
+
+        Ok(StringKind::String)
+    }
+
+    /// Types a string with the same heuristics as deserialization, for values built from
+    /// sources other than serde such as language bindings.
+    ///
+    /// ```
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::Value;
+    ///
+    /// let options = DeserializerOptions::new();
+    /// let value: Value<Sha2256> = options.string_value("2018-10-13T15:50:00Z").unwrap();
+    ///
+    /// assert_eq!(value, Value::Timestamp("2018-10-13T15:50:00Z".into()));
+    /// ```
+    pub fn string_value<T: Multihash>(&self, value: &str) -> Result<Value<T>, DeserializeError> {
+        let value = match self.classify::<T>(value)? {
+            StringKind::Redacted(seal) => Value::Redacted(seal),
+            StringKind::Raw(raw) => Value::Raw(raw),
+            StringKind::Timestamp => Value::Timestamp(value.to_string()),
+            StringKind::String => Value::String(value.to_string()),
+        };
+
+        Ok(value)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    options: &'a DeserializerOptions,
+    /// Typed error raised by the visitor. Serde only carries error messages.
+    failure: &'a RefCell<Option<DeserializeError>>,
+}
+
+impl<'a> Context<'a> {
+    fn fail<E: de::Error>(&self, err: DeserializeError) -> E {
+        let error = E::custom(&err);
+        *self.failure.borrow_mut() = Some(err);
+
+        error
+    }
+
+    fn duplicate_key<E: de::Error>(&self, key: &str) -> Result<(), E> {
+        match self.options.duplicate_keys {
+            DuplicateKeys::Last => Ok(()),
+            DuplicateKeys::Error => Err(self.fail(DeserializeError::DuplicateKey(key.to_string()))),
+        }
+    }
+}
+
+struct ValueSeed<'a, T: Multihash> {
+    context: Context<'a>,
+    /// Reference tokens of the node. Only tracked when the options have a schema.
+    pointer: Vec<String>,
+    marker: PhantomData<*const T>,
+}
+
+impl<'a, T: Multihash> ValueSeed<'a, T> {
+    fn new(context: Context<'a>, pointer: Vec<String>) -> ValueSeed<'a, T> {
+        ValueSeed {
+            context,
+            pointer,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, 'de, T: Multihash> DeserializeSeed<'de> for ValueSeed<'a, T> {
+    type Value = Value<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let schema = &self.context.options.schema;
+        let kind = if schema.is_empty() {
+            None
+        } else {
+            schema.kind_at(&self.pointer)
+        };
+
+        deserializer.deserialize_any(ValueVisitor::<T> {
+            context: self.context,
+            pointer: self.pointer,
+            kind,
+            marker: PhantomData,
+        })
+    }
+}
+
+struct ValueVisitor<'a, T: Multihash> {
+    context: Context<'a>,
+    pointer: Vec<String>,
+    /// Kind declared by the schema for the current node.
+    kind: Option<Kind>,
+    marker: PhantomData<*const T>,
+}
+
+impl<'a, T: Multihash> ValueVisitor<'a, T> {
+    fn seed(&self) -> ValueSeed<'a, T> {
+        ValueSeed::new(self.context, self.pointer.clone())
+    }
+
+    fn child<F: FnOnce() -> String>(&self, token: F) -> ValueSeed<'a, T> {
+        let mut pointer = Vec::new();
+
+        if !self.context.options.schema.is_empty() {
+            pointer.extend_from_slice(&self.pointer);
+            pointer.push(token());
+        }
+
+        ValueSeed::new(self.context, pointer)
+    }
+
+    fn mismatch<E: de::Error>(&self, expected: Kind) -> E {
+        self.context.fail(DeserializeError::SchemaMismatch {
+            pointer: self.pointer.clone().into(),
+            expected,
+        })
+    }
+}
+
+fn looks_redacted(value: &str) -> bool {
+    value.starts_with("**REDACTED**") || (value.starts_with("77") && Vec::from_hex(value).is_ok())
+}
+
+impl<'a, 'de, T: Multihash> Visitor<'de> for ValueVisitor<'a, T> {
     type Value = Value<T>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -29,7 +725,10 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         E: de::Error,
     {
-        Ok(Value::Bool(value))
+        match self.kind {
+            None => Ok(Value::Bool(value)),
+            Some(kind) => Err(self.mismatch(kind)),
+        }
     }
 
     #[inline]
@@ -37,7 +736,14 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         E: de::Error,
     {
-        Ok(Value::Integer(value.into()))
+        match self.kind {
+            None if self.context.options.numbers == Numbers::Float => {
+                Ok(Value::Float(value as f64))
+            }
+            None | Some(Kind::Integer) => Ok(Value::Integer(value)),
+            Some(Kind::Float) => Ok(Value::Float(value as f64)),
+            Some(kind) => Err(self.mismatch(kind)),
+        }
     }
 
     #[inline]
@@ -45,12 +751,19 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         E: de::Error,
     {
-        use std::i64;
+        match self.kind {
+            None if self.context.options.numbers == Numbers::Float => {
+                return Ok(Value::Float(value as f64))
+            }
+            None | Some(Kind::Integer) => {}
+            Some(Kind::Float) => return Ok(Value::Float(value as f64)),
+            Some(kind) => return Err(self.mismatch(kind)),
+        }
 
         if value <= (i64::MAX as u64) {
             Ok(Value::Integer(value as i64))
         } else {
-            Err(E::custom(format!("i64 out of range: {}", value)))
+            Ok(Value::UInteger(value))
         }
     }
 
@@ -59,7 +772,15 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         E: de::Error,
     {
-        Ok(Value::Float(value.into()))
+        match self.kind {
+            None | Some(Kind::Float) => Ok(Value::Float(value)),
+            Some(Kind::Integer)
+                if value.fract() == 0.0 && value >= i64::MIN as f64 && value < i64::MAX as f64 =>
+            {
+                Ok(Value::Integer(value as i64))
+            }
+            Some(kind) => Err(self.mismatch(kind)),
+        }
     }
 
     #[inline]
@@ -75,26 +796,24 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         E: de::Error,
     {
-        // TODO: A mismatch between seal and value hashing functions will result in a Raw hash, not
-        // in a failure.
-        if let Ok(seal) = Seal::from_str(&value) {
-            return Ok(Value::Redacted(seal));
-        }
-
-        if let Ok(raw) = Vec::from_hex(&value) {
-            return Ok(Value::Raw(raw));
-        }
-
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z")
-                .expect("Regex to compile");
+        match self.kind {
+            None => {}
+            Some(Kind::Timestamp) => return Ok(Value::Timestamp(value)),
+            Some(Kind::Raw) => {
+                return Vec::from_hex(&value)
+                    .map(Value::Raw)
+                    .map_err(|_| self.mismatch(Kind::Raw))
+            }
+            Some(kind) => return Err(self.mismatch(kind)),
         }
 
-        if RE.is_match(&value) {
-            return Ok(Value::Timestamp(value));
+        match self.context.options.classify::<T>(&value) {
+            Ok(StringKind::Redacted(seal)) => Ok(Value::Redacted(seal)),
+            Ok(StringKind::Raw(raw)) => Ok(Value::Raw(raw)),
+            Ok(StringKind::Timestamp) => Ok(Value::Timestamp(value)),
+            Ok(StringKind::String) => Ok(Value::String(value)),
+            Err(err) => Err(self.context.fail(err)),
         }
-
-        Ok(Value::String(value))
     }
 
     #[inline]
@@ -107,7 +826,7 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         D: Deserializer<'de>,
     {
-        Deserialize::deserialize(deserializer)
+        self.seed().deserialize(deserializer)
     }
 
     #[inline]
@@ -120,22 +839,40 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     where
         V: SeqAccess<'de>,
     {
+        match self.kind {
+            None | Some(Kind::Set) => {}
+            Some(kind) => return Err(self.mismatch(kind)),
+        }
+
         let mut vec = Vec::new();
 
-        while let Some(elem) = visitor.next_element()? {
+        while let Some(elem) = visitor.next_element_seed(self.child(|| vec.len().to_string()))? {
             vec.push(elem);
         }
 
-        Ok(Value::List(vec))
+        match self.kind {
+            Some(Kind::Set) => Ok(Value::Set(vec)),
+            _ => Ok(Value::List(vec)),
+        }
     }
 
     fn visit_map<V>(self, mut access: V) -> Result<Self::Value, V::Error>
     where
         V: MapAccess<'de>,
     {
-        let mut dict = HashMap::new();
+        if let Some(kind) = self.kind {
+            return Err(self.mismatch(kind));
+        }
+
+        let mut dict = DictMap::new();
+
+        while let Some(key) = access.next_key::<String>()? {
+            let value = access.next_value_seed(self.child(|| key.clone()))?;
+
+            if dict.contains_key(&key) {
+                self.context.duplicate_key(&key)?;
+            }
 
-        while let Some((key, value)) = access.next_entry()? {
             dict.insert(key, value);
         }
 
@@ -143,18 +880,164 @@ impl<'de, T: Multihash> Visitor<'de> for ValueVisitor<T> {
     }
 }
 
+/// Digests a node as it is parsed, typing its strings like [`ValueVisitor`] without a schema.
+struct DigestSeed<'a, T: Multihash, D: Multihash + 'a> {
+    context: Context<'a>,
+    digester: &'a D,
+    marker: PhantomData<*const T>,
+}
+
+impl<'a, T: Multihash, D: Multihash> Clone for DigestSeed<'a, T, D> {
+    fn clone(&self) -> Self {
+        DigestSeed {
+            context: self.context,
+            digester: self.digester,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, 'de, T: Multihash, D: Multihash> DeserializeSeed<'de> for DigestSeed<'a, T, D> {
+    type Value = Harvest;
+
+    fn deserialize<De>(self, deserializer: De) -> Result<Self::Value, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'a, 'de, T: Multihash, D: Multihash> Visitor<'de> for DigestSeed<'a, T, D> {
+    type Value = Harvest;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Expecting a valid JSON value.")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(value.blot(self.digester))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        match self.context.options.numbers {
+            Numbers::Float => Ok((value as f64).blot(self.digester)),
+            Numbers::Auto => Ok(value.blot(self.digester)),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        match self.context.options.numbers {
+            Numbers::Float => Ok((value as f64).blot(self.digester)),
+            Numbers::Auto => Ok(value.blot(self.digester)),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(value.blot(self.digester))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        let digester = self.digester;
+
+        match self.context.options.classify::<T>(value) {
+            Ok(StringKind::Redacted(seal)) => Ok(seal.blot(digester)),
+            Ok(StringKind::Raw(raw)) => Ok(raw.as_slice().blot(digester)),
+            Ok(StringKind::Timestamp) => Ok(blot_timestamp(digester, value)),
+            Ok(StringKind::String) => Ok(value.blot(digester)),
+            Err(err) => Err(self.context.fail(err)),
+        }
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(None::<u8>.blot(self.digester))
+    }
+
+    fn visit_some<De>(self, deserializer: De) -> Result<Self::Value, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(None::<u8>.blot(self.digester))
+    }
+
+    fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let mut digests = Vec::new();
+
+        while let Some(digest) = visitor.next_element_seed(self.clone())? {
+            digests.push(digest);
+        }
+
+        Ok(self.digester.digest_collection(Tag::List, digests))
+    }
+
+    fn visit_map<V>(self, mut access: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+
+        while let Some(key) = access.next_key::<String>()? {
+            entries.push((key, access.next_value_seed(self.clone())?));
+        }
+
+        // The last duplicate wins, like in a `Value::Dict`.
+        entries.reverse();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            self.context.duplicate_key(&pair[0].0)?;
+        }
+
+        entries.dedup_by(|a, b| a.0 == b.0);
+
+        let entries = entries
+            .into_iter()
+            .map(|(key, digest)| (key.blot(self.digester), digest))
+            .collect();
+
+        Ok(digest_entries(self.digester, entries))
+    }
+}
+
 impl<'de, T: Multihash> Deserialize<'de> for Value<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(ValueVisitor(PhantomData))
+        let options = DeserializerOptions::default();
+        let failure = RefCell::new(None);
+
+        ValueSeed::new(
+            Context {
+                options: &options,
+                failure: &failure,
+            },
+            Vec::new(),
+        )
+        .deserialize(deserializer)
+    }
+}
+
+/// Types the document with the default options, like deserializing its text.
+impl<T: Multihash> From<serde_json::Value> for Value<T> {
+    fn from(document: serde_json::Value) -> Value<T> {
+        DeserializerOptions::default()
+            .from_json_value(document)
+            .expect("The default options to accept any JSON document")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dict;
     use multihash::{Sha2256, Sha3256};
     use serde_json;
 
@@ -198,7 +1081,7 @@ mod tests {
     #[test]
     fn redacted_value_wrong_algorithm() {
         let input = r#""771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038""#;
-        let expected = r#"Ok(Raw([119, 18, 32, 166, 166, 229, 231, 131, 195, 99, 205, 149, 105, 62, 193, 137, 194, 104, 35, 21, 217, 86, 134, 147, 151, 115, 134, 121, 181, 99, 5, 242, 9, 80, 56]))"#;
+        let expected = r#"Ok(Redacted(Seal { tag: Sha2256, digest: [166, 166, 229, 231, 131, 195, 99, 205, 149, 105, 62, 193, 137, 194, 104, 35, 21, 217, 86, 134, 147, 151, 115, 134, 121, 181, 99, 5, 242, 9, 80, 56] }))"#;
         let res = serde_json::from_str::<Value<Sha3256>>(input);
 
         assert_eq!(format!("{:?}", res), expected);
@@ -243,4 +1126,422 @@ mod tests {
 
         assert_eq!(format!("{:?}", res), expected);
     }
+
+    #[test]
+    fn invalid_timestamp() {
+        let input = r#"["2018-13-45T00:00:00Z", "at 2018-10-13T15:50:00Z"]"#;
+        let value: Value<Sha2256> = DeserializerOptions::new().from_str(input).unwrap();
+
+        assert_eq!(
+            value,
+            Value::List(vec![
+                Value::String("2018-13-45T00:00:00Z".into()),
+                Value::String("at 2018-10-13T15:50:00Z".into()),
+            ])
+        );
+
+        match DeserializerOptions::new()
+            .strict(true)
+            .from_str::<Sha2256>(input)
+        {
+            Err(DeserializeError::InvalidTimestamp { found, error }) => {
+                assert_eq!(found, "2018-13-45T00:00:00Z");
+                assert_eq!(error, TimestampError::OutOfRange);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn timestamp_offset() {
+        let input = r#"["2018-10-13T16:50:00.000+01:00"]"#;
+        let options = DeserializerOptions::new();
+        let value: Value<Sha2256> = options.from_str(input).unwrap();
+        let expected: Value<Sha2256> =
+            Value::List(vec![Value::Timestamp("2018-10-13T15:50:00Z".into())]);
+
+        assert_eq!(
+            value,
+            Value::List(vec![Value::Timestamp(
+                "2018-10-13T16:50:00.000+01:00".into()
+            )])
+        );
+        assert_eq!(value.digest(Sha2256), expected.digest(Sha2256));
+        assert_eq!(
+            options.digest_str::<Sha2256, _>(input, Sha2256).unwrap(),
+            expected.digest(Sha2256)
+        );
+    }
+
+    #[test]
+    fn strict_truncated_seal() {
+        let input =
+            r#"{"a": ["771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f20950"]}"#;
+        let options = DeserializerOptions::new().strict(true);
+
+        match options.from_str::<Sha2256>(input) {
+            Err(DeserializeError::SealMismatch { expected, found }) => {
+                assert_eq!(expected, Stamp::Sha2256);
+                assert!(found.starts_with("771220"));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let lenient = serde_json::from_str::<Value<Sha2256>>(input).unwrap();
+        assert!(format!("{:?}", lenient).contains("Raw"));
+    }
+
+    #[test]
+    fn strict_foreign_seal() {
+        let input = r#""771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038""#;
+        let options = DeserializerOptions::new().strict(true);
+
+        match options.from_str::<Sha3256>(input) {
+            Err(DeserializeError::SealMismatch { expected, .. }) => {
+                assert_eq!(expected, Stamp::Sha3256)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn strict_unrelated_strings() {
+        let input = r#"["ff00", "77 apples", "2018-10-13T15:50:00Z"]"#;
+        let options = DeserializerOptions::new().strict(true);
+        let expected =
+            r#"Ok(List([Raw([255, 0]), String("77 apples"), Timestamp("2018-10-13T15:50:00Z")]))"#;
+
+        assert_eq!(
+            format!("{:?}", options.from_str::<Sha2256>(input)),
+            expected
+        );
+    }
+
+    #[test]
+    fn disabled_heuristics() {
+        let input = r#"["771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038", "ff00", "2018-10-13T15:50:00Z"]"#;
+        let options = DeserializerOptions::new()
+            .detect_seals(false)
+            .detect_timestamps(false);
+        let expected = r#"Ok(List([Raw([119, 18, 32, 166, 166, 229, 231, 131, 195, 99, 205, 149, 105, 62, 193, 137, 194, 104, 35, 21, 217, 86, 134, 147, 151, 115, 134, 121, 181, 99, 5, 242, 9, 80, 56]), Raw([255, 0]), String("2018-10-13T15:50:00Z")]))"#;
+
+        assert_eq!(
+            format!("{:?}", options.from_str::<Sha2256>(input)),
+            expected
+        );
+
+        let options = options.detect_raw(false);
+        let expected = r#"Ok(List([String("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"), String("ff00"), String("2018-10-13T15:50:00Z")]))"#;
+
+        assert_eq!(
+            format!("{:?}", options.from_str::<Sha2256>(input)),
+            expected
+        );
+    }
+
+    #[test]
+    fn objecthash_mode() {
+        let input = r#"["**REDACTED**a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038", "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038", "ff00", "2018-10-13T15:50:00Z"]"#;
+        let options = DeserializerOptions::new().objecthash(true);
+        let value: Value<Sha2256> = options.from_str(input).unwrap();
+        let seal = Seal::from_str(
+            "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038",
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            Value::List(vec![
+                Value::Redacted(seal.clone()),
+                Value::Redacted(seal),
+                Value::String("ff00".into()),
+                Value::String("2018-10-13T15:50:00Z".into()),
+            ])
+        );
+        assert!(DeserializerOptions::new()
+            .strict(true)
+            .from_str::<Sha2256>(input)
+            .is_err());
+    }
+
+    #[test]
+    fn schema_kinds() {
+        let input = r#"{"users": [{"roles": ["a", "b"], "id": 3.0}], "at": "2018", "key": "ff00", "tags": []}"#;
+        let schema = Schema::new()
+            .with("/users/*/roles".parse().unwrap(), Kind::Set)
+            .with("/users/*/id".parse().unwrap(), Kind::Integer)
+            .with("/at".parse().unwrap(), Kind::Timestamp)
+            .with("/key".parse().unwrap(), Kind::Raw);
+        let options = DeserializerOptions::new().schema(schema);
+        let dict = match options.from_str::<Sha2256>(input).unwrap() {
+            Value::Dict(dict) => dict,
+            other => panic!("unexpected value {:?}", other),
+        };
+        let user = match &dict["users"] {
+            Value::List(list) => list[0].clone(),
+            other => panic!("unexpected value {:?}", other),
+        };
+        let expected: Value<Sha2256> = serde_json::from_str(r#"{"id": 3}"#).unwrap();
+
+        assert_eq!(dict["at"], Value::Timestamp("2018".into()));
+        assert_eq!(dict["key"], Value::Raw(vec![255, 0]));
+        assert_eq!(dict["tags"], Value::List(vec![]));
+        match (user, expected) {
+            (Value::Dict(user), Value::Dict(expected)) => {
+                assert_eq!(user["id"], expected["id"]);
+                assert_eq!(user["roles"], Value::Set(vec!["a".into(), "b".into()]));
+            }
+            other => panic!("unexpected values {:?}", other),
+        }
+    }
+
+    #[test]
+    fn schema_mismatch() {
+        let schema = Schema::new().with("/a/1".parse().unwrap(), Kind::Integer);
+        let options = DeserializerOptions::new().schema(schema);
+
+        match options.from_str::<Sha2256>(r#"{"a": [1, 2.5]}"#) {
+            Err(DeserializeError::SchemaMismatch { pointer, expected }) => {
+                assert_eq!(pointer.to_string(), "/a/1");
+                assert_eq!(expected, Kind::Integer);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_json() {
+        let options = DeserializerOptions::new().strict(true);
+
+        match options.from_str::<Sha2256>("[1, 2") {
+            Err(DeserializeError::Json(_)) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn registered_seals() {
+        use multihash::Harvest;
+        use tag::Tag;
+        use uvar::Uvar;
+
+        /// SHA2-256 under a private multihash code.
+        #[derive(Debug, Default, PartialEq)]
+        struct Custom;
+
+        impl Multihash for Custom {
+            type Digester = ();
+
+            fn length(&self) -> u8 {
+                32
+            }
+
+            fn code(&self) -> Uvar {
+                Uvar::from(0x8001)
+            }
+
+            fn name(&self) -> &str {
+                "custom"
+            }
+
+            fn digest_primitive(&self, tag: Tag, bytes: &[u8]) -> Harvest {
+                Sha2256.digest_primitive(tag, bytes)
+            }
+
+            fn digest_collection<I>(&self, tag: Tag, list: I) -> Harvest
+            where
+                I: IntoIterator,
+                I::Item: AsRef<[u8]>,
+            {
+                Sha2256.digest_collection(tag, list)
+            }
+        }
+
+        let input = r#""7781800220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038""#;
+        let mut registry = Registry::new();
+        registry.register(Custom).unwrap();
+
+        let unknown: Value<Sha2256> = DeserializerOptions::new().from_str(input).unwrap();
+        let known: Value<Sha2256> = DeserializerOptions::new()
+            .registry(registry)
+            .from_str(input)
+            .unwrap();
+
+        assert!(matches!(unknown, Value::Raw(_)));
+        match known {
+            Value::Redacted(seal) => assert_eq!(
+                seal.tag(),
+                &Stamp::Other {
+                    code: 0x8001,
+                    length: 32
+                }
+            ),
+            other => panic!("unexpected value {:?}", other),
+        }
+    }
+
+    #[test]
+    fn digest_while_parsing() {
+        let input = r#"{
+            "a": [1, -1.5, null, true, "2018-01-01T00:00:00Z", "cafe", {}],
+            "b": "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038",
+            "c": {"x": ["y", "z"]},
+            "a": "duplicate"
+        }"#;
+        let options = DeserializerOptions::new();
+        let value: Value<Sha2256> = options.from_str(input).unwrap();
+
+        assert_eq!(
+            options.digest_str::<Sha2256, _>(input, Sha2256).unwrap(),
+            value.digest(Sha2256)
+        );
+    }
+
+    #[test]
+    fn float_numbers() {
+        let input = r#"{"a": [1, -1, 18446744073709551615, 1.5], "b": 2}"#;
+        let schema = Schema::new().with("/b".parse().unwrap(), Kind::Integer);
+        let options = DeserializerOptions::new().numbers(Numbers::Float);
+        let value: Value<Sha2256> = options.from_str(input).unwrap();
+
+        assert_eq!(
+            value,
+            dict! {
+                "a" => vec![
+                    Value::Float(1.0),
+                    Value::Float(-1.0),
+                    Value::Float(18446744073709551615.0),
+                    Value::Float(1.5),
+                ],
+                "b" => Value::Float(2.0),
+            }
+        );
+        assert_eq!(
+            options.digest_str::<Sha2256, _>(input, Sha2256).unwrap(),
+            value.digest(Sha2256)
+        );
+        assert_eq!(
+            options.schema(schema).from_str::<Sha2256>(input).unwrap()["b"],
+            Value::Integer(2)
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let input = r#"{"a": 1, "b": {"c": 1, "c": 2}, "a": 3}"#;
+        let lenient = DeserializerOptions::new();
+        let strict = DeserializerOptions::new().duplicate_keys(DuplicateKeys::Error);
+        let value: Value<Sha2256> = lenient.from_str(input).unwrap();
+
+        assert_eq!(value, dict! {"a" => 3, "b" => dict! {"c" => 2}});
+        assert_eq!(
+            lenient.digest_str::<Sha2256, _>(input, Sha2256).unwrap(),
+            value.digest(Sha2256)
+        );
+
+        match strict.from_str::<Sha2256>(input) {
+            Err(DeserializeError::DuplicateKey(key)) => assert_eq!(key, "c"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        match strict.digest_str::<Sha2256, Sha2256>(input, Sha2256) {
+            Err(DeserializeError::DuplicateKey(key)) => assert_eq!(key, "c"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(strict.from_str::<Sha2256>(r#"{"a": {"a": 1}}"#).is_ok());
+    }
+
+    #[test]
+    fn json_value() {
+        let input = r#"{"a": [1, 1.5, "cafe"], "b": "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"}"#;
+        let document: serde_json::Value = serde_json::from_str(input).unwrap();
+        let options = DeserializerOptions::new().detect_raw(false).strict(true);
+
+        assert_eq!(
+            Value::<Sha2256>::from(document.clone()),
+            DeserializerOptions::new()
+                .from_str::<Sha2256>(input)
+                .unwrap()
+        );
+        assert_eq!(
+            options
+                .from_json_value::<Sha2256>(document.clone())
+                .unwrap(),
+            options.from_str::<Sha2256>(input).unwrap()
+        );
+        match options.from_json_value::<Sha3256>(document) {
+            Err(DeserializeError::SealMismatch { .. }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn any_format() {
+        use serde::de::value::Error;
+        use serde::de::IntoDeserializer;
+        use std::collections::BTreeMap;
+
+        let options = DeserializerOptions::new();
+        let mut map = BTreeMap::new();
+        map.insert("a", "2018-10-13T15:50:00Z");
+        let value: Value<Sha2256> = options
+            .from_deserializer(IntoDeserializer::<Error>::into_deserializer(map))
+            .unwrap();
+
+        assert_eq!(
+            value,
+            dict! {"a" => Value::Timestamp("2018-10-13T15:50:00Z".into())}
+        );
+        assert_eq!(
+            options
+                .from_reader::<Sha2256, _>(Format::Json, &br#"{"a": "2018-10-13T15:50:00Z"}"#[..])
+                .unwrap(),
+            value
+        );
+
+        match options.from_reader::<Sha2256, _>(Format::Json, &b"[1,\n2"[..]) {
+            Err(DeserializeError::Json(err)) => assert_eq!(err.line(), 2),
+            other => panic!("unexpected result {:?}", other),
+        }
+        match options.from_deserializer::<Sha2256, _>(IntoDeserializer::<Error>::into_deserializer(
+            vec![(1u8, 2u8)].into_iter().collect::<BTreeMap<_, _>>(),
+        )) {
+            Err(DeserializeError::Format(_)) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unsigned_above_i64() {
+        let input = "[18446744073709551615, 9223372036854775807]";
+        let options = DeserializerOptions::new();
+        let value: Value<Sha2256> = options.from_str(input).unwrap();
+
+        assert_eq!(
+            value,
+            Value::List(vec![Value::UInteger(u64::MAX), Value::Integer(i64::MAX)])
+        );
+        assert_eq!(
+            value.digest(Sha2256),
+            vec![u64::MAX, i64::MAX as u64].digest(Sha2256)
+        );
+        assert_eq!(
+            options.digest_str::<Sha2256, _>(input, Sha2256).unwrap(),
+            value.digest(Sha2256)
+        );
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            input.replace(" ", "")
+        );
+    }
+
+    #[test]
+    fn digest_while_parsing_strict() {
+        let input = r#"["771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"]"#;
+        let options = DeserializerOptions::new().strict(true);
+
+        match options.digest_str::<Sha3256, _>(input, Sha3256) {
+            Err(DeserializeError::SealMismatch { .. }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
 }
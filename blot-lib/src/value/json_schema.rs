@@ -0,0 +1,223 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Derives a [`Schema`] from a [JSON Schema](https://json-schema.org/).
+//!
+//! Only the keywords that disambiguate hashing are taken into account:
+//!
+//! * `format: date-time` types strings as timestamps.
+//! * `uniqueItems: true` types arrays as sets.
+//! * `type: integer` and `type: number` type numbers as integers and floats respectively.
+//!
+//! Subschemas are followed through `properties`, `items` (both the single schema and the tuple
+//! forms) and local `$ref`s to `#/definitions/…` or `#/$defs/…`. Every other keyword is ignored.
+//!
+//! ```
+//! extern crate blot;
+//! extern crate serde_json;
+//!
+//! use blot::value::json_schema;
+//! use blot::value::schema::Kind;
+//!
+//! # fn main() {
+//! let schema = json_schema::from_str(r#"{
+//!     "type": "object",
+//!     "properties": {
+//!         "tags": {"type": "array", "uniqueItems": true},
+//!         "scores": {"type": "array", "items": {"type": "number"}}
+//!     }
+//! }"#).unwrap();
+//!
+//! assert_eq!(schema.kind_at(&["tags"]), Some(Kind::Set));
+//! assert_eq!(schema.kind_at(&["scores", "3"]), Some(Kind::Float));
+//! # }
+//! ```
+
+use serde_json::{self, Map, Value as Json};
+
+use super::schema::{Kind, Pointer, Schema};
+
+/// Parses a JSON Schema document and derives its [`Schema`].
+#[allow(clippy::should_implement_trait)]
+pub fn from_str(input: &str) -> Result<Schema, serde_json::Error> {
+    let json: Json = serde_json::from_str(input)?;
+
+    Ok(from_json(&json))
+}
+
+/// Derives the [`Schema`] of an already parsed JSON Schema document.
+pub fn from_json(json: &Json) -> Schema {
+    let mut schema = Schema::new();
+    let mut refs = Vec::new();
+
+    walk(json, json, &mut Vec::new(), &mut refs, &mut schema);
+
+    schema
+}
+
+fn walk<'a>(
+    root: &'a Json,
+    node: &'a Json,
+    pointer: &mut Vec<String>,
+    refs: &mut Vec<&'a str>,
+    schema: &mut Schema,
+) {
+    let node = match node {
+        Json::Object(node) => node,
+        _ => return,
+    };
+
+    if let Some(reference) = node.get("$ref").and_then(Json::as_str) {
+        // Recursive definitions are only expanded once per branch.
+        if !refs.contains(&reference) {
+            if let Some(target) = resolve(root, reference) {
+                refs.push(reference);
+                walk(root, target, pointer, refs, schema);
+                refs.pop();
+            }
+        }
+    }
+
+    if let Some(kind) = kind(node) {
+        schema.insert(Pointer::from(pointer.clone()), kind);
+    }
+
+    if let Some(Json::Object(properties)) = node.get("properties") {
+        for (key, child) in properties {
+            pointer.push(key.clone());
+            walk(root, child, pointer, refs, schema);
+            pointer.pop();
+        }
+    }
+
+    match node.get("items") {
+        Some(Json::Array(items)) => {
+            for (index, child) in items.iter().enumerate() {
+                pointer.push(index.to_string());
+                walk(root, child, pointer, refs, schema);
+                pointer.pop();
+            }
+        }
+        Some(child) => {
+            pointer.push("*".into());
+            walk(root, child, pointer, refs, schema);
+            pointer.pop();
+        }
+        None => {}
+    }
+}
+
+fn kind(node: &Map<String, Json>) -> Option<Kind> {
+    if node.get("format").and_then(Json::as_str) == Some("date-time") {
+        return Some(Kind::Timestamp);
+    }
+
+    match json_type(node) {
+        Some("array") | None if node.get("uniqueItems") == Some(&Json::Bool(true)) => {
+            Some(Kind::Set)
+        }
+        Some("integer") => Some(Kind::Integer),
+        Some("number") => Some(Kind::Float),
+        _ => None,
+    }
+}
+
+/// The declared type, ignoring `null` in type unions such as `["integer", "null"]`.
+fn json_type(node: &Map<String, Json>) -> Option<&str> {
+    match node.get("type") {
+        Some(Json::String(name)) => Some(name),
+        Some(Json::Array(names)) => {
+            let names: Vec<&str> = names
+                .iter()
+                .filter_map(Json::as_str)
+                .filter(|name| *name != "null")
+                .collect();
+
+            if names.len() == 1 {
+                Some(names[0])
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn resolve<'a>(root: &'a Json, reference: &str) -> Option<&'a Json> {
+    reference
+        .strip_prefix('#')
+        .and_then(|pointer| root.pointer(pointer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+    use value::de::DeserializerOptions;
+    use value::Value;
+
+    #[test]
+    fn nested_schema() {
+        let schema = from_str(
+            r##"{
+                "definitions": {
+                    "user": {
+                        "properties": {
+                            "id": {"type": ["integer", "null"]},
+                            "seen": {"type": "string", "format": "date-time"},
+                            "roles": {"$ref": "#/definitions/roles"}
+                        }
+                    },
+                    "roles": {"type": "array", "uniqueItems": true}
+                },
+                "properties": {
+                    "users": {"items": {"$ref": "#/definitions/user"}},
+                    "pair": {"items": [{"type": "number"}, {"type": "string"}]}
+                }
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(schema.kind_at(&["users", "0", "id"]), Some(Kind::Integer));
+        assert_eq!(
+            schema.kind_at(&["users", "4", "seen"]),
+            Some(Kind::Timestamp)
+        );
+        assert_eq!(schema.kind_at(&["users", "1", "roles"]), Some(Kind::Set));
+        assert_eq!(schema.kind_at(&["pair", "0"]), Some(Kind::Float));
+        assert_eq!(schema.kind_at(&["pair", "1"]), None);
+    }
+
+    #[test]
+    fn recursive_schema() {
+        let schema = from_str(
+            r##"{
+                "$ref": "#/$defs/node",
+                "$defs": {
+                    "node": {
+                        "properties": {
+                            "value": {"type": "number"},
+                            "children": {"items": {"$ref": "#/$defs/node"}}
+                        }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(schema.kind_at(&["value"]), Some(Kind::Float));
+    }
+
+    #[test]
+    fn typed_document() {
+        let schema = from_str(r#"{"properties": {"n": {"type": "number"}}}"#).unwrap();
+        let options = DeserializerOptions::new().schema(schema);
+        let typed: Value<Sha2256> = options.from_str(r#"{"n": 1}"#).unwrap();
+        let float: Value<Sha2256> = serde_json::from_str(r#"{"n": 1.0}"#).unwrap();
+
+        assert_eq!(typed, float);
+    }
+}
@@ -6,18 +6,56 @@
 
 //! Represents a multi-type value able to express any Objecthash combination.
 
+use std::cmp::Ordering;
+use std::error;
 use std::fmt::{self, Display};
+use std::hash;
+use std::ops::{Index, IndexMut};
 
 use core::Blot;
-use multihash::{Harvest, Multihash};
+use datetime::{blot_timestamp, parse_timestamp, TimestampError};
+use multihash::{Harvest, Hash, Multihash};
 use seal::Seal;
-use std::collections::HashMap;
-use tag::Tag;
+use tag::{CustomTag, Tag};
 
+use self::path::{JsonPath, Segment};
+
+#[cfg(feature = "blot_json")]
+pub mod borrowed;
+#[cfg(feature = "blot_json")]
+pub mod canonical;
 #[cfg(feature = "blot_json")]
 pub mod de;
+#[cfg(feature = "blot_json")]
+pub mod json_schema;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod path;
+pub mod schema;
+#[cfg(feature = "blot_json")]
+pub mod ser;
+#[cfg(any(test, feature = "testing"))]
+pub mod strategy;
+pub mod traverse;
+pub mod tree;
+
+/// The entries of a [`Value::Dict`].
+///
+/// A `HashMap`, or an `IndexMap` keeping the keys in insertion order when compiled with the
+/// `preserve_order` feature, so parsed documents serialize back with their keys as given. The
+/// digest doesn't depend on the order either way.
+#[cfg(not(feature = "preserve_order"))]
+pub type DictMap<T> = ::std::collections::HashMap<String, Value<T>>;
+#[cfg(feature = "preserve_order")]
+pub type DictMap<T> = ::indexmap::IndexMap<String, Value<T>>;
 
-#[derive(Clone, Debug, PartialEq)]
+/// Values are totally ordered, so they can be sorted and used as keys of maps and sets.
+///
+/// Variants are ordered as declared, then by their content. Floats are ordered with
+/// [`f64::total_cmp`]: `-0.0` is less than `0.0` and `NaN` equals itself. Dictionaries are
+/// compared as their entries sorted by key. Equality follows the same order, so it is structural
+/// and does not match the digest: `1` and `1.0` hash the same but are different values.
+#[derive(Clone, Debug)]
 pub enum Value<T: Multihash> {
     /// Represents a null value (similar to JSON's null).
     Null,
@@ -25,8 +63,18 @@ pub enum Value<T: Multihash> {
     Bool(bool),
     /// Represents a signed 64-bit integer.
     Integer(i64),
+    /// Represents an unsigned 64-bit integer above `i64::MAX`. Smaller values are `Integer`s.
+    ///
+    /// Hashed like any other integer, so `UInteger(5)` and `Integer(5)` have the same digest.
+    UInteger(u64),
+    /// Represents an arbitrary-precision integer.
+    #[cfg(feature = "num-bigint")]
+    BigInt(::num_bigint::BigInt),
     /// Represents a 64-bit floating point.
     Float(f64),
+    /// Represents an arbitrary-precision decimal number.
+    #[cfg(feature = "rust_decimal")]
+    Decimal(::rust_decimal::Decimal),
     /// Represents a string.
     String(String),
     /// Represents a RFC3339 timestamp.
@@ -35,15 +83,54 @@ pub enum Value<T: Multihash> {
     Redacted(Seal<T>),
     /// Represents a raw list of bytes.
     Raw(Vec<u8>),
+    /// Represents a value of an application-defined kind, already encoded, hashed with its own
+    /// tag. See [`Leaf`].
+    ///
+    /// It serializes as the hex of its bytes, so the tag is lost when it is written as JSON.
+    Custom(CustomTag, Vec<u8>),
     /// Represents a list of values.
     List(Vec<Value<T>>),
     /// Represents a set of values.
     Set(Vec<Value<T>>),
     /// Represents an attribute-value dictionary.
-    Dict(HashMap<String, Value<T>>),
+    Dict(DictMap<T>),
+    /// Represents a dictionary with keys of any type, e.g. the integer keys of CBOR and
+    /// MessagePack maps.
+    ///
+    /// Hashed like a dictionary, so a map with string keys has the same digest as the `Dict` with
+    /// the same entries. When a key is repeated the last entry wins.
+    Map(Vec<(Value<T>, Value<T>)>),
 }
 
 impl<T: Multihash> Value<T> {
+    /// Builds a [`Value::Timestamp`] from an RFC3339 string, keeping it as given.
+    ///
+    /// Fails if the string is not a valid date and time.
+    ///
+    /// ```
+    /// use blot::datetime::TimestampError;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::Value;
+    ///
+    /// let value: Value<Sha2256> = Value::timestamp("2018-10-13T15:50:00Z").unwrap();
+    ///
+    /// assert_eq!(value, Value::Timestamp("2018-10-13T15:50:00Z".into()));
+    /// assert_eq!(
+    ///     Value::<Sha2256>::timestamp("2018-13-45T00:00:00Z"),
+    ///     Err(TimestampError::OutOfRange)
+    /// );
+    /// ```
+    pub fn timestamp(input: &str) -> Result<Value<T>, TimestampError> {
+        parse_timestamp(input)?;
+
+        Ok(Value::Timestamp(input.to_string()))
+    }
+
+    /// Builds a [`Value::Custom`] from a leaf of an application-defined kind.
+    pub fn custom<L: Leaf + ?Sized>(leaf: &L) -> Value<T> {
+        Value::Custom(leaf.tag(), leaf.encode())
+    }
+
     pub fn sequences_as_sets(self) -> Self {
         match self {
             Value::List(list) => Value::Set(list),
@@ -55,6 +142,423 @@ impl<T: Multihash> Value<T> {
             value => value,
         }
     }
+
+    /// Replaces the nodes found at the given paths with seals of their digest.
+    ///
+    /// The redacted value has the same digest as the original when hashed with `T`. Paths that
+    /// don't resolve to a node are ignored.
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate blot;
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::Value;
+    ///
+    /// # fn main() {
+    /// let value: Value<Sha2256> = list!["foo", "bar"];
+    /// let redacted = value.redact_paths(&["[0]".parse().unwrap()]);
+    ///
+    /// match redacted {
+    ///     Value::List(ref list) => assert!(match list[0] { Value::Redacted(_) => true, _ => false }),
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(redacted.digest(Sha2256), value.digest(Sha2256));
+    /// # }
+    /// ```
+    pub fn redact_paths(&self, paths: &[JsonPath]) -> Value<T>
+    where
+        T: Clone,
+    {
+        self.redact_paths_with(paths, &T::default())
+    }
+
+    /// Like [`Value::redact_paths`], sealing the nodes with the given digester, e.g. a
+    /// [`KeyedMultihash`] with its key or a [`FloatMultihash`] with its policy.
+    ///
+    /// [`KeyedMultihash`]: ../multihash/struct.KeyedMultihash.html
+    /// [`FloatMultihash`]: ../multihash/struct.FloatMultihash.html
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate blot;
+    /// use blot::core::{Blot, FloatPolicy};
+    /// use blot::multihash::{FloatMultihash, Sha2256};
+    /// use blot::value::Value;
+    ///
+    /// # fn main() {
+    /// let digester = FloatMultihash::new(Sha2256, FloatPolicy::Ieee754);
+    /// let value: Value<FloatMultihash<Sha2256>> = list![1.5, "bar"];
+    /// let redacted = value.redact_paths_with(&["[0]".parse().unwrap()], &digester);
+    ///
+    /// assert_eq!(redacted.digest(digester.clone()), value.digest(digester));
+    /// # }
+    /// ```
+    pub fn redact_paths_with(&self, paths: &[JsonPath], digester: &T) -> Value<T>
+    where
+        T: Clone,
+    {
+        let mut value = self.clone();
+
+        for path in paths {
+            value.redact_segments(path.segments(), digester);
+        }
+
+        value
+    }
+
+    fn redact_segments(&mut self, segments: &[Segment], digester: &T) {
+        match segments.split_first() {
+            None => {
+                *self = Value::Redacted(Seal::from_value(self, digester));
+            }
+            Some((Segment::Key(key), rest)) => {
+                if let Value::Dict(dict) = self {
+                    if let Some(value) = dict.get_mut(key) {
+                        value.redact_segments(rest, digester);
+                    }
+                }
+            }
+            Some((Segment::Index(index), rest)) => match self {
+                Value::List(list) | Value::Set(list) => {
+                    if let Some(value) = list.get_mut(*index) {
+                        value.redact_segments(rest, digester);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    /// Digests the node at the given path. Returns `None` if the path doesn't resolve.
+    ///
+    /// ```
+    /// extern crate blot;
+    /// extern crate serde_json;
+    ///
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::Value;
+    ///
+    /// # fn main() {
+    /// let value: Value<Sha2256> =
+    ///     serde_json::from_str(r#"{"items": [{"payload": "foo"}]}"#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     value.digest_at(&"items[0].payload".parse().unwrap(), Sha2256),
+    ///     Some("foo".digest(Sha2256))
+    /// );
+    /// assert_eq!(value.digest_at(&"items[1]".parse().unwrap(), Sha2256), None);
+    /// # }
+    /// ```
+    pub fn digest_at<D: Multihash>(&self, path: &JsonPath, digester: D) -> Option<Hash<D>> {
+        self.node_at(path.segments()).map(|node| node.digest(digester))
+    }
+
+    /// Returns the node at a dotted path like `items[0].payload`, or at a JSON pointer like
+    /// `/items/0/payload` if the path starts with `/`. Returns `None` if the path is invalid or
+    /// doesn't resolve.
+    ///
+    /// ```
+    /// extern crate blot;
+    /// extern crate serde_json;
+    ///
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::Value;
+    ///
+    /// # fn main() {
+    /// let value: Value<Sha2256> =
+    ///     serde_json::from_str(r#"{"items": [{"payload": "foo"}]}"#).unwrap();
+    ///
+    /// assert_eq!(value.get("items[0].payload"), Some(&"foo".into()));
+    /// assert_eq!(value.get("/items/0/payload"), Some(&"foo".into()));
+    /// assert_eq!(value["items"][0]["payload"], "foo".into());
+    /// assert_eq!(value.get("items[1]"), None);
+    /// # }
+    /// ```
+    pub fn get(&self, path: &str) -> Option<&Value<T>> {
+        if path.starts_with('/') {
+            return self.pointer(path);
+        }
+
+        path.parse::<JsonPath>()
+            .ok()
+            .and_then(|path| self.node_at(path.segments()))
+    }
+
+    /// Like [`get`](#method.get), returning a mutable reference.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut Value<T>> {
+        if path.starts_with('/') {
+            return self.pointer_mut(path);
+        }
+
+        match path.parse::<JsonPath>() {
+            Ok(path) => self.node_at_mut(path.segments()),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the node at a JSON pointer (RFC 6901). The empty pointer addresses the root.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value<T>> {
+        pointer_tokens(pointer)?
+            .iter()
+            .try_fold(self, |node, token| match node {
+                Value::Dict(dict) => dict.get(token),
+                Value::List(list) | Value::Set(list) => token
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| list.get(index)),
+                _ => None,
+            })
+    }
+
+    /// Like [`pointer`](#method.pointer), returning a mutable reference.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value<T>> {
+        pointer_tokens(pointer)?
+            .iter()
+            .try_fold(self, |node, token| match node {
+                Value::Dict(dict) => dict.get_mut(token),
+                Value::List(list) | Value::Set(list) => token
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(move |index| list.get_mut(index)),
+                _ => None,
+            })
+    }
+
+    fn node_at_mut(&mut self, segments: &[Segment]) -> Option<&mut Value<T>> {
+        match segments.split_first() {
+            None => Some(self),
+            Some((Segment::Key(key), rest)) => match self {
+                Value::Dict(dict) => dict.get_mut(key).and_then(|child| child.node_at_mut(rest)),
+                _ => None,
+            },
+            Some((Segment::Index(index), rest)) => match self {
+                Value::List(list) | Value::Set(list) => list
+                    .get_mut(*index)
+                    .and_then(|child| child.node_at_mut(rest)),
+                _ => None,
+            },
+        }
+    }
+
+    pub(crate) fn node_at(&self, segments: &[Segment]) -> Option<&Value<T>> {
+        match segments.split_first() {
+            None => Some(self),
+            Some((Segment::Key(key), rest)) => match self {
+                Value::Dict(dict) => dict.get(key).and_then(|child| child.node_at(rest)),
+                _ => None,
+            },
+            Some((Segment::Index(index), rest)) => match self {
+                Value::List(list) | Value::Set(list) => {
+                    list.get(*index).and_then(|child| child.node_at(rest))
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Splits a JSON pointer into unescaped reference tokens. Returns `None` if the pointer is not
+/// empty and doesn't start with `/`.
+fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    if !pointer.starts_with('/') {
+        return None;
+    }
+
+    Some(
+        pointer[1..]
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Indexes a dictionary by key. Panics if the value is not a dictionary or the key is missing.
+impl<T: Multihash> Index<&str> for Value<T> {
+    type Output = Value<T>;
+
+    fn index(&self, key: &str) -> &Value<T> {
+        match self {
+            Value::Dict(dict) => dict
+                .get(key)
+                .unwrap_or_else(|| panic!("no key {:?} in dictionary", key)),
+            _ => panic!("cannot index a non-dictionary by key {:?}", key),
+        }
+    }
+}
+
+impl<T: Multihash> IndexMut<&str> for Value<T> {
+    fn index_mut(&mut self, key: &str) -> &mut Value<T> {
+        match self {
+            Value::Dict(dict) => dict
+                .get_mut(key)
+                .unwrap_or_else(|| panic!("no key {:?} in dictionary", key)),
+            _ => panic!("cannot index a non-dictionary by key {:?}", key),
+        }
+    }
+}
+
+/// Indexes a list or set by position. Panics if the value is not a sequence or the index is out
+/// of bounds.
+impl<T: Multihash> Index<usize> for Value<T> {
+    type Output = Value<T>;
+
+    fn index(&self, index: usize) -> &Value<T> {
+        match self {
+            Value::List(list) | Value::Set(list) => &list[index],
+            _ => panic!("cannot index a non-sequence by position {}", index),
+        }
+    }
+}
+
+impl<T: Multihash> IndexMut<usize> for Value<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Value<T> {
+        match self {
+            Value::List(list) | Value::Set(list) => &mut list[index],
+            _ => panic!("cannot index a non-sequence by position {}", index),
+        }
+    }
+}
+
+impl<T: Multihash> Value<T> {
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Integer(_) => 2,
+            Value::UInteger(_) => 3,
+            #[cfg(feature = "num-bigint")]
+            Value::BigInt(_) => 4,
+            Value::Float(_) => 5,
+            #[cfg(feature = "rust_decimal")]
+            Value::Decimal(_) => 6,
+            Value::String(_) => 7,
+            Value::Timestamp(_) => 8,
+            Value::Redacted(_) => 9,
+            Value::Raw(_) => 10,
+            Value::Custom(..) => 11,
+            Value::List(_) => 12,
+            Value::Set(_) => 13,
+            Value::Dict(_) => 14,
+            Value::Map(_) => 15,
+        }
+    }
+}
+
+fn sorted_entries<T: Multihash>(dict: &DictMap<T>) -> Vec<(&String, &Value<T>)> {
+    let mut entries: Vec<_> = dict.iter().collect();
+    entries.sort_unstable_by_key(|&(key, _)| key);
+    entries
+}
+
+impl<T: Multihash> PartialEq for Value<T> {
+    fn eq(&self, other: &Value<T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Multihash> Eq for Value<T> {}
+
+impl<T: Multihash> PartialOrd for Value<T> {
+    fn partial_cmp(&self, other: &Value<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Multihash> Ord for Value<T> {
+    fn cmp(&self, other: &Value<T>) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::UInteger(a), Value::UInteger(b)) => a.cmp(b),
+            #[cfg(feature = "num-bigint")]
+            (Value::BigInt(a), Value::BigInt(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            #[cfg(feature = "rust_decimal")]
+            (Value::Decimal(a), Value::Decimal(b)) => a.cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+            (Value::Redacted(a), Value::Redacted(b)) => a.cmp(b),
+            (Value::Raw(a), Value::Raw(b)) => a.cmp(b),
+            (Value::Custom(a, x), Value::Custom(b, y)) => (a, x).cmp(&(b, y)),
+            (Value::List(a), Value::List(b)) => a.cmp(b),
+            (Value::Set(a), Value::Set(b)) => a.cmp(b),
+            (Value::Dict(a), Value::Dict(b)) => sorted_entries(a).cmp(&sorted_entries(b)),
+            (Value::Map(a), Value::Map(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl<T: Multihash> hash::Hash for Value<T> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+
+        match self {
+            Value::Null => {}
+            Value::Bool(raw) => raw.hash(state),
+            Value::Integer(raw) => raw.hash(state),
+            Value::UInteger(raw) => raw.hash(state),
+            #[cfg(feature = "num-bigint")]
+            Value::BigInt(raw) => raw.hash(state),
+            Value::Float(raw) => raw.to_bits().hash(state),
+            #[cfg(feature = "rust_decimal")]
+            Value::Decimal(raw) => raw.hash(state),
+            Value::String(raw) | Value::Timestamp(raw) => raw.hash(state),
+            Value::Redacted(raw) => raw.hash(state),
+            Value::Raw(raw) => raw.hash(state),
+            Value::Custom(tag, raw) => {
+                tag.hash(state);
+                raw.hash(state)
+            }
+            Value::List(list) | Value::Set(list) => list.hash(state),
+            Value::Dict(dict) => sorted_entries(dict).hash(state),
+            Value::Map(map) => map.hash(state),
+        }
+    }
+}
+
+/// An encoder for values of an application-defined kind, e.g. a `Uri` or a money amount, so they
+/// can be part of a [`Value`] without a variant of their own.
+///
+/// The leaf is hashed as its custom tag followed by its encoding, exactly like a built-in
+/// primitive. The tag comes from [`Tag::custom`], so a leaf can never pass for a built-in kind.
+/// Two leaves with the same tag must only have the same encoding when they are equal.
+///
+/// ```
+/// use blot::core::Blot;
+/// use blot::multihash::Sha2256;
+/// use blot::tag::{CustomTag, Tag};
+/// use blot::value::{Leaf, Value};
+///
+/// struct Uri(String);
+///
+/// impl Leaf for Uri {
+///     fn tag(&self) -> CustomTag {
+///         Tag::custom(0x55).unwrap()
+///     }
+///
+///     fn encode(&self) -> Vec<u8> {
+///         self.0.as_bytes().to_vec()
+///     }
+/// }
+///
+/// let uri: Value<Sha2256> = Value::custom(&Uri("https://example.org".into()));
+/// let string: Value<Sha2256> = Value::String("https://example.org".into());
+///
+/// assert_ne!(uri.digest(Sha2256), string.digest(Sha2256));
+/// ```
+pub trait Leaf {
+    /// The tag of the kind.
+    fn tag(&self) -> CustomTag;
+    /// The bytes hashed after the tag.
+    fn encode(&self) -> Vec<u8>;
 }
 
 #[derive(Debug)]
@@ -68,52 +572,77 @@ impl Display for ValueError {
     }
 }
 
-impl<T: Multihash> Blot for Value<T> {
-    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+impl error::Error for ValueError {}
+
+#[cfg(feature = "blot_json")]
+impl<T: Multihash> Value<T> {
+    /// Parses a JSON string with the given deserializer options.
+    ///
+    /// ```
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::de::DeserializerOptions;
+    /// use blot::value::Value;
+    ///
+    /// let options = DeserializerOptions::new().detect_raw(false);
+    /// let value: Value<Sha2256> = Value::from_str_with(r#""cafe""#, &options).unwrap();
+    ///
+    /// assert_eq!(value, Value::String("cafe".into()));
+    /// ```
+    pub fn from_str_with(
+        input: &str,
+        options: &de::DeserializerOptions,
+    ) -> Result<Value<T>, de::DeserializeError> {
+        options.from_str(input)
+    }
+}
+
+impl<T: Multihash> Value<T> {
+    /// Digests the value without tracing it as a node, see [`traverse`].
+    pub(crate) fn blot_untraced<D: Multihash>(&self, digester: &D) -> Harvest {
         match self {
             Value::Null => None::<u8>.blot(digester),
             Value::Bool(raw) => raw.blot(digester),
             Value::Integer(raw) => raw.blot(digester),
+            Value::UInteger(raw) => raw.blot(digester),
+            #[cfg(feature = "num-bigint")]
+            Value::BigInt(raw) => raw.blot(digester),
             Value::Float(raw) => raw.blot(digester),
+            #[cfg(feature = "rust_decimal")]
+            Value::Decimal(raw) => raw.blot(digester),
             Value::String(raw) => raw.blot(digester),
-            Value::Timestamp(raw) => digester
-                .clone()
-                .digest_primitive(Tag::Timestamp, raw.as_bytes()),
+            Value::Timestamp(raw) => blot_timestamp(digester, raw),
             Value::Redacted(raw) => raw.blot(digester),
             Value::Raw(raw) => raw.as_slice().blot(digester),
-            Value::List(raw) => raw.blot(digester),
-            Value::Set(raw) => {
-                println!("in set");
-                let mut list: Vec<Vec<u8>> = raw
-                    .iter()
-                    .map(|item| {
-                        item.blot(digester)
-                            .as_slice()
-                            .iter()
-                            .map(|x| *x)
-                            .collect::<Vec<u8>>()
-                    }).collect();
-
-                list.sort_unstable();
-                list.dedup();
-
-                digester.clone().digest_collection(Tag::Set, list)
+            Value::Custom(tag, raw) => digester.digest_primitive(Tag::Custom(*tag), raw),
+            Value::List(_) | Value::Set(_) | Value::Dict(_) | Value::Map(_) => {
+                traverse::blot(self, digester)
+            }
+        }
+    }
+}
+
+impl<T: Multihash> Blot for Value<T> {
+    fn blot<D: Multihash>(&self, digester: &D) -> Harvest {
+        match self {
+            Value::List(_) | Value::Set(_) | Value::Dict(_) | Value::Map(_) => {
+                traverse::blot(self, digester)
             }
-            Value::Dict(raw) => raw.blot(digester),
+            // A traced scalar is a node of its own, like the items of collections.
+            #[cfg(feature = "tracing")]
+            _ if traverse::traced() => traverse::blot(self, digester),
+            scalar => scalar.blot_untraced(digester),
         }
     }
+
+    fn try_blot<D: Multihash>(&self, digester: &D) -> Result<Harvest, traverse::DigestError> {
+        traverse::try_blot(self, digester)
+    }
 }
 
 #[macro_export]
 macro_rules! set {
     ( $( $x:expr ),* ) => {
-        {
-            let mut temp_vec = Vec::new();
-            $(
-                temp_vec.push($x.into());
-            )*
-            Value::Set(temp_vec)
-        }
+        Value::Set(vec![$( $x.into() ),*])
     };
 }
 
@@ -127,16 +656,35 @@ macro_rules! raw {
 #[macro_export]
 macro_rules! list {
     ( $( $x:expr ),* ) => {
-        {
-            let mut temp_vec = Vec::new();
-            $(
-                temp_vec.push($x.into());
-            )*
-            Value::List(temp_vec)
-        }
+        Value::List(vec![$( $x.into() ),*])
     };
 }
 
+/// Builds a [`Value::Dict`](value/enum.Value.html#variant.Dict) from `key => value` pairs,
+/// converting keys to `String` and values with `Into`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate blot;
+/// use blot::multihash::Sha2256;
+/// use blot::value::Value;
+///
+/// fn main() {
+///     let value: Value<Sha2256> = dict!{"foo" => 1, "bar" => list!["baz"]};
+///
+///     assert_eq!(value["foo"], Value::Integer(1));
+/// }
+/// ```
+#[macro_export]
+macro_rules! dict {
+    ( $( $key:expr => $value:expr ),* $(,)* ) => {{
+        #[allow(unused_mut)]
+        let mut dict = $crate::value::DictMap::new();
+        $( dict.insert(::std::string::String::from($key), $value.into()); )*
+        $crate::value::Value::Dict(dict)
+    }};
+}
+
 #[macro_export]
 macro_rules! seal {
     ($input:expr) => {{
@@ -144,13 +692,109 @@ macro_rules! seal {
     }};
 }
 
-impl<'a, T: Multihash> From<&'a str> for Value<T> {
+/// Builds a [`Value`] from a JSON-like literal, like `serde_json::json!`.
+///
+/// Besides `null`, booleans, lists and dictionaries, it accepts `set [..]` for sets,
+/// `timestamp(..)` for timestamps and `seal(..)` for redacted values. Any other element is
+/// converted with `Value::from`. Panics if a seal is invalid.
+///
+/// ```
+/// #[macro_use]
+/// extern crate blot;
+/// use blot::core::Blot;
+/// use blot::multihash::Sha2256;
+/// use blot::value::Value;
+///
+/// fn main() {
+///     let value: Value<Sha2256> = value!({
+///         "id": 1,
+///         "tags": set ["a", "b"],
+///         "created": timestamp("2018-10-13T12:00:00Z"),
+///         "secret": seal("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"),
+///         "parent": null,
+///         "items": [1.5, true, {"nested": []}]
+///     });
+///
+///     assert_eq!(value["tags"], value!(set ["a", "b"]));
+///     assert_eq!(value["secret"].digest(Sha2256), "foo".digest(Sha2256));
+/// }
+/// ```
+#[macro_export]
+macro_rules! value {
+    // Accumulates the elements of a sequence, split on top-level commas.
+    (@seq [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@seq [$($elems:expr,)*] $($rest:tt)+) => {
+        value!(@elem [$($elems,)*] () $($rest)+)
+    };
+    (@elem [$($elems:expr,)*] ($($current:tt)+) , $($rest:tt)*) => {
+        value!(@seq [$($elems,)* value!($($current)+),] $($rest)*)
+    };
+    (@elem [$($elems:expr,)*] ($($current:tt)*) $next:tt $($rest:tt)*) => {
+        value!(@elem [$($elems,)*] ($($current)* $next) $($rest)*)
+    };
+    (@elem [$($elems:expr,)*] ($($current:tt)+)) => {
+        value!(@seq [$($elems,)* value!($($current)+),])
+    };
+
+    // Inserts the entries of a dictionary, split on top-level commas.
+    (@dict $dict:ident) => {};
+    (@dict $dict:ident $key:tt : $($rest:tt)+) => {
+        value!(@entry $dict $key () $($rest)+)
+    };
+    (@entry $dict:ident $key:tt ($($current:tt)+) , $($rest:tt)*) => {
+        $dict.insert(::std::string::String::from($key), value!($($current)+));
+        value!(@dict $dict $($rest)*);
+    };
+    (@entry $dict:ident $key:tt ($($current:tt)*) $next:tt $($rest:tt)*) => {
+        value!(@entry $dict $key ($($current)* $next) $($rest)*)
+    };
+    (@entry $dict:ident $key:tt ($($current:tt)+)) => {
+        $dict.insert(::std::string::String::from($key), value!($($current)+));
+    };
+
+    (null) => {
+        $crate::value::Value::Null
+    };
+    (true) => {
+        $crate::value::Value::Bool(true)
+    };
+    (false) => {
+        $crate::value::Value::Bool(false)
+    };
+    ([ $($tt:tt)* ]) => {
+        $crate::value::Value::List(value!(@seq [] $($tt)*))
+    };
+    (set [ $($tt:tt)* ]) => {
+        $crate::value::Value::Set(value!(@seq [] $($tt)*))
+    };
+    (timestamp($input:expr)) => {
+        $crate::value::Value::Timestamp(::std::string::String::from($input))
+    };
+    (seal($input:expr)) => {
+        $crate::value::Value::Redacted(
+            $crate::seal::Seal::from_str($input).expect("Seal literals to be valid"),
+        )
+    };
+    ({ $($tt:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut dict = $crate::value::DictMap::new();
+        value!(@dict dict $($tt)*);
+        $crate::value::Value::Dict(dict)
+    }};
+    ($other:expr) => {
+        $crate::value::Value::from($other)
+    };
+}
+
+impl<T: Multihash> From<&str> for Value<T> {
     fn from(raw: &str) -> Value<T> {
         Value::String(raw.into())
     }
 }
 
-impl<'a, T: Multihash> From<String> for Value<T> {
+impl<T: Multihash> From<String> for Value<T> {
     fn from(raw: String) -> Value<T> {
         Value::String(raw)
     }
@@ -168,6 +812,20 @@ impl<T: Multihash> From<f64> for Value<T> {
     }
 }
 
+#[cfg(feature = "num-bigint")]
+impl<T: Multihash> From<::num_bigint::BigInt> for Value<T> {
+    fn from(raw: ::num_bigint::BigInt) -> Value<T> {
+        Value::BigInt(raw)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl<T: Multihash> From<::rust_decimal::Decimal> for Value<T> {
+    fn from(raw: ::rust_decimal::Decimal) -> Value<T> {
+        Value::Decimal(raw)
+    }
+}
+
 impl<T: Multihash> From<Vec<Value<T>>> for Value<T> {
     fn from(raw: Vec<Value<T>>) -> Value<T> {
         Value::List(raw)
@@ -224,22 +882,10 @@ mod tests {
 
     #[test]
     fn floats() {
-        let mut map: HashMap<String, Value<Sha2256>> = HashMap::new();
-        map.insert(
-            "bar".into(),
-            list![
-                "baz",
-                Value::Null,
-                1.0,
-                1.5,
-                0.0001,
-                1000.0,
-                2.0,
-                -23.1234,
-                2.0
-            ],
-        );
-        let value = list!["foo", Value::Dict(map)];
+        let value: Value<Sha2256> = value!([
+            "foo",
+            {"bar": ["baz", null, 1.0, 1.5, 0.0001, 1000.0, 2.0, -23.1234, 2.0]}
+        ]);
         let expected = "1220783a423b094307bcb28d005bc2f026ff44204442ef3513585e7e73b66e3c2213";
         let actual = format!("{}", &value.digest(Sha2256));
 
@@ -248,10 +894,8 @@ mod tests {
 
     #[test]
     fn int_floats() {
-        let mut map: HashMap<String, Value<Sha2256>> = HashMap::new();
-        map.insert(
-            "bar".into(),
-            vec![
+        let map = dict!{
+            "bar" => vec![
                 "baz".into(),
                 Value::Null,
                 1.into(),
@@ -261,9 +905,9 @@ mod tests {
                 2.into(),
                 (-23.1234).into(),
                 2.into(),
-            ].into(),
-        );
-        let value = Value::List(vec!["foo".into(), Value::Dict(map)]);
+            ]
+        };
+        let value: Value<Sha2256> = Value::List(vec!["foo".into(), map]);
         let expected = "1220726e7ae9e3fadf8a2228bf33e505a63df8db1638fa4f21429673d387dbd1c52a";
         let actual = format!("{}", &value.digest(Sha2256));
 
@@ -272,15 +916,10 @@ mod tests {
 
     #[test]
     fn set() {
-        let mut map: HashMap<String, Value<Sha2256>> = HashMap::new();
-        let mut map2: HashMap<String, Value<Sha2256>> = HashMap::new();
-        map2.insert(
-            "thing2".into(),
-            Value::Set(vec![1.into(), 2.into(), "s".into()]),
-        );
-        map.insert("thing1".into(), Value::Dict(map2));
-        map.insert("thing3".into(), 1234.567.into());
-        let value = Value::Dict(map);
+        let value: Value<Sha2256> = value!({
+            "thing1": {"thing2": set [1, 2, "s"]},
+            "thing3": 1234.567
+        });
 
         let expected = "1220618cf0582d2e716a70e99c2f3079d74892fec335e3982eb926835967cb0c246c";
         let actual = format!("{}", &value.digest(Sha2256));
@@ -360,4 +999,190 @@ mod tests {
         assert_eq!(actual.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn redact_paths() {
+        let value: Value<Sha2256> = value!({"a": {"b": [1, 2, set ["x", "y"]], "c": true}});
+
+        let paths: Vec<JsonPath> = ["a.b[2][1]", "a.c", "a.missing", "a.b[9]"]
+            .iter()
+            .map(|path| path.parse().unwrap())
+            .collect();
+        let redacted = value.redact_paths(&paths);
+
+        assert_ne!(redacted, value);
+        assert_eq!(redacted.digest(Sha2256), value.digest(Sha2256));
+    }
+
+    #[test]
+    fn redact_root() {
+        let value: Value<Sha2256> = list!["foo", "bar"];
+        let redacted = value.redact_paths(&[JsonPath::root()]);
+
+        match redacted {
+            Value::Redacted(ref seal) => assert_eq!(seal.digest(), value.blot(&Sha2256).as_slice()),
+            _ => panic!("expected a redacted value"),
+        }
+        assert_eq!(redacted.digest(Sha2256), value.digest(Sha2256));
+    }
+
+    #[test]
+    fn paths_and_pointers() {
+        let mut value: Value<Sha2256> =
+            list!["foo", dict!{"a/b" => list![1, 2], "c~d" => Value::Null}];
+
+        assert_eq!(value.get(""), Some(&value));
+        assert_eq!(value.get("[1][\"a/b\"][1]"), Some(&2.into()));
+        assert_eq!(value.get("/1/a~1b/1"), Some(&2.into()));
+        assert_eq!(value.get("/1/c~0d"), Some(&Value::Null));
+        assert_eq!(value.get("/0/x"), None);
+        assert_eq!(value.get("[1"), None);
+        assert_eq!(value.pointer("1"), None);
+
+        *value.get_mut("/1/a~1b/0").unwrap() = "bar".into();
+        value[1]["c~d"] = Value::Bool(true);
+
+        assert_eq!(value[1]["a/b"][0], "bar".into());
+        assert_eq!(value.get("[1][\"c~d\"]"), Some(&Value::Bool(true)));
+    }
+
+    #[test]
+    #[should_panic(expected = "no key")]
+    fn index_missing_key() {
+        let value: Value<Sha2256> = Value::Dict(DictMap::new());
+        let _ = &value["foo"];
+    }
+
+    #[test]
+    fn value_macro() {
+        let value: Value<Sha2256> = value!([
+            null,
+            false,
+            -1,
+            timestamp("2018-10-13T12:00:00Z"),
+            seal("771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"),
+            set [],
+            {},
+            {"a": [], "b": {"c": "d"},},
+        ]);
+
+        assert_eq!(
+            value,
+            Value::List(vec![
+                Value::Null,
+                Value::Bool(false),
+                Value::Integer(-1),
+                Value::Timestamp("2018-10-13T12:00:00Z".into()),
+                Value::Redacted(
+                    Seal::from_str(
+                        "771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"
+                    )
+                    .unwrap()
+                ),
+                Value::Set(vec![]),
+                dict!{},
+                dict!{"a" => list![], "b" => dict!{"c" => "d"}},
+            ])
+        );
+    }
+
+    #[test]
+    fn total_order() {
+        let mut values: Vec<Value<Sha2256>> = vec![
+            Value::Float(f64::NAN),
+            Value::Float(0.0),
+            list!["b"],
+            Value::Float(-0.0),
+            Value::Null,
+            "a".into(),
+            Value::Integer(1),
+            list!["a", "c"],
+        ];
+        values.sort();
+
+        assert_eq!(
+            values,
+            vec![
+                Value::Null,
+                Value::Integer(1),
+                Value::Float(-0.0),
+                Value::Float(0.0),
+                Value::Float(f64::NAN),
+                "a".into(),
+                list!["a", "c"],
+                list!["b"],
+            ]
+        );
+        assert_ne!(Value::<Sha2256>::Float(0.0), Value::Float(-0.0));
+    }
+
+    #[test]
+    fn dict_keys() {
+        use std::collections::HashSet;
+
+        let mut a: DictMap<Sha2256> = DictMap::new();
+        let mut b: DictMap<Sha2256> = DictMap::new();
+        for i in 0..32 {
+            a.insert(i.to_string(), i.into());
+            b.insert((31 - i).to_string(), (31 - i).into());
+        }
+
+        let mut set = HashSet::new();
+        set.insert(Value::Dict(a));
+        set.insert(Value::Dict(b));
+        set.insert(Value::Float(f64::NAN));
+        set.insert(Value::Float(f64::NAN));
+
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn map_keys() {
+        let map: Value<Sha2256> = Value::Map(vec![
+            ("b".into(), 2.into()),
+            ("a".into(), 0.into()),
+            ("a".into(), 1.into()),
+        ]);
+        let dict: Value<Sha2256> = dict! {"a" => 1, "b" => 2};
+        let integers: Value<Sha2256> = Value::Map(vec![(1.into(), 1.into()), (2.into(), 2.into())]);
+        let strings: Value<Sha2256> =
+            Value::Map(vec![("1".into(), 1.into()), ("2".into(), 2.into())]);
+
+        assert_eq!(map.digest(Sha2256), dict.digest(Sha2256));
+        assert_eq!(
+            format!("{}", integers.digest(Sha2256)),
+            "1220df09eaeb963b2b10f2382eefaaa896b07e24009c431b194e29640d9ba51b0dee"
+        );
+        assert_ne!(integers.digest(Sha2256), strings.digest(Sha2256));
+    }
+
+    #[test]
+    fn digest_at_redacted() {
+        let value: Value<Sha2256> = list!["foo", list!["bar"]];
+        let redacted = value.redact_paths(&["[1]".parse().unwrap()]);
+        let path: JsonPath = "[1]".parse().unwrap();
+
+        assert_eq!(redacted.digest_at(&path, Sha2256), value.digest_at(&path, Sha2256));
+        assert_eq!(redacted.digest_at(&"[1][0]".parse().unwrap(), Sha2256), None);
+    }
+
+    #[test]
+    fn custom_leaves() {
+        let uri = Tag::custom(0x55).unwrap();
+        let value: Value<Sha2256> = list![Value::Custom(uri, b"urn:x".to_vec())];
+        let redacted = value.redact_paths(&["[0]".parse().unwrap()]);
+
+        assert_eq!(
+            value[0].digest(Sha2256).digest(),
+            &Sha2256.digest_primitive(uri.into(), b"urn:x")
+        );
+        assert_ne!(
+            value[0].digest(Sha2256),
+            Value::<Sha2256>::Raw(b"urn:x".to_vec()).digest(Sha2256)
+        );
+        assert_eq!(redacted.digest(Sha2256), value.digest(Sha2256));
+        assert_eq!(
+            value.digest_tree(Sha2256).children()[0].tag(),
+            Some(Tag::Custom(uri))
+        );
+    }
 }
@@ -0,0 +1,109 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Parallel hashing.
+//!
+//! Computes the element digests of large lists, sets and dictionaries on the rayon thread pool
+//! before folding them. The result is the same digest [`Blot::digest`] returns.
+//!
+//! Requires the `parallel` feature.
+
+use core::{digest_entries, Blot};
+use multihash::{Harvest, Hash, Multihash};
+use rayon::prelude::*;
+use tag::Tag;
+
+use super::Value;
+
+/// Collections with fewer elements are hashed on the current thread.
+pub const PARALLEL_THRESHOLD: usize = 1024;
+
+impl<T: Multihash + Sync> Value<T> {
+    /// Digests the value hashing the elements of large collections in parallel.
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::Value;
+    ///
+    /// let value: Value<Sha2256> = Value::List((0..5000).map(Value::Integer).collect());
+    ///
+    /// assert_eq!(value.digest_parallel(Sha2256), value.digest(Sha2256));
+    /// ```
+    pub fn digest_parallel<D: Multihash + Sync>(&self, digester: D) -> Hash<D> {
+        let digest = self.blot_parallel(&digester);
+        Hash::new(digester, digest)
+    }
+
+    fn blot_parallel<D: Multihash + Sync>(&self, digester: &D) -> Harvest {
+        match self {
+            Value::List(list) => {
+                digester.digest_collection(Tag::List, digest_items(list, digester))
+            }
+            Value::Set(list) => {
+                let mut digests = digest_items(list, digester);
+
+                digests.par_sort_unstable();
+                digests.dedup();
+
+                digester.digest_collection(Tag::Set, digests)
+            }
+            Value::Dict(dict) => {
+                let entry = |(key, value): (&String, &Value<T>)| {
+                    (key.blot(digester), value.blot_parallel(digester))
+                };
+                let entries = if dict.len() < PARALLEL_THRESHOLD {
+                    dict.iter().map(entry).collect()
+                } else {
+                    dict.par_iter().map(entry).collect()
+                };
+
+                digest_entries(digester, entries)
+            }
+            value => value.blot(digester),
+        }
+    }
+}
+
+fn digest_items<T, D>(list: &[Value<T>], digester: &D) -> Vec<Harvest>
+where
+    T: Multihash + Sync,
+    D: Multihash + Sync,
+{
+    if list.len() < PARALLEL_THRESHOLD {
+        list.iter()
+            .map(|item| item.blot_parallel(digester))
+            .collect()
+    } else {
+        list.par_iter()
+            .map(|item| item.blot_parallel(digester))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash::Sha2256;
+    use value::DictMap;
+
+    #[test]
+    fn matches_sequential() {
+        let items: Vec<Value<Sha2256>> = (0..3000)
+            .map(|n| Value::String(format!("{}", n % 2000)))
+            .collect();
+        let dict: DictMap<Sha2256> = (0..3000)
+            .map(|n| (n.to_string(), Value::List(items[..n % 10].to_vec())))
+            .collect();
+        let value = Value::List(vec![
+            Value::Set(items.clone()),
+            Value::Dict(dict),
+            Value::List(items),
+        ]);
+
+        assert_eq!(value.digest_parallel(Sha2256), value.digest(Sha2256));
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Paths to nodes of a [`Value`](../enum.Value.html).
+//!
+//! A path is a sequence of dictionary keys and sequence indexes written in dotted notation, e.g.
+//! `a.b[2]`. Keys containing dots or brackets can be quoted: `a["b.c"]`. The empty path
+//! addresses the root.
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// A dictionary key.
+    Key(String),
+    /// A list or set position.
+    Index(usize),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct JsonPath(Vec<Segment>);
+
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    /// A key is empty, e.g. `a..b`.
+    EmptyKey { position: usize },
+    /// An index is not a valid unsigned integer.
+    InvalidIndex { position: usize },
+    /// A bracket or quote is not closed.
+    Unterminated { position: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::EmptyKey { position } => write!(formatter, "empty key at {}", position),
+            PathError::InvalidIndex { position } => {
+                write!(formatter, "invalid index at {}", position)
+            }
+            PathError::Unterminated { position } => {
+                write!(formatter, "unterminated segment at {}", position)
+            }
+        }
+    }
+}
+
+impl error::Error for PathError {}
+
+impl JsonPath {
+    /// The path to the root value.
+    pub fn root() -> JsonPath {
+        JsonPath(Vec::new())
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.0
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns a new path with the given segment appended.
+    pub fn join(&self, segment: Segment) -> JsonPath {
+        let mut segments = self.0.clone();
+        segments.push(segment);
+
+        JsonPath(segments)
+    }
+}
+
+impl From<Vec<Segment>> for JsonPath {
+    fn from(segments: Vec<Segment>) -> JsonPath {
+        JsonPath(segments)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = PathError;
+
+    /// Parses a dotted path.
+    ///
+    /// ```
+    /// use blot::value::path::{JsonPath, Segment};
+    ///
+    /// let path: JsonPath = "a.b[2]".parse().unwrap();
+    ///
+    /// assert_eq!(
+    ///     path.segments(),
+    ///     &[
+    ///         Segment::Key("a".into()),
+    ///         Segment::Key("b".into()),
+    ///         Segment::Index(2)
+    ///     ]
+    /// );
+    /// ```
+    fn from_str(input: &str) -> Result<JsonPath, PathError> {
+        let chars: Vec<char> = input.chars().collect();
+        let mut segments = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '[' => {
+                    let start = i;
+                    i += 1;
+
+                    if chars.get(i) == Some(&'"') {
+                        let (key, next) = take_quoted(&chars, i)?;
+                        if chars.get(next) != Some(&']') {
+                            return Err(PathError::Unterminated { position: start });
+                        }
+                        segments.push(Segment::Key(key));
+                        i = next + 1;
+                    } else {
+                        let end = chars[i..]
+                            .iter()
+                            .position(|c| *c == ']')
+                            .map(|offset| i + offset)
+                            .ok_or(PathError::Unterminated { position: start })?;
+                        let digits: String = chars[i..end].iter().collect();
+                        let index = digits
+                            .parse()
+                            .map_err(|_| PathError::InvalidIndex { position: i })?;
+                        segments.push(Segment::Index(index));
+                        i = end + 1;
+                    }
+                }
+                '.' if i == 0 => {
+                    return Err(PathError::EmptyKey { position: i });
+                }
+                '.' => {
+                    i += 1;
+                    if i == chars.len() {
+                        return Err(PathError::EmptyKey { position: i });
+                    }
+                    if chars[i] == '[' || chars[i] == '.' {
+                        return Err(PathError::EmptyKey { position: i });
+                    }
+                }
+                _ => {
+                    let start = i;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    segments.push(Segment::Key(chars[start..i].iter().collect()));
+                }
+            }
+        }
+
+        Ok(JsonPath(segments))
+    }
+}
+
+fn take_quoted(chars: &[char], start: usize) -> Result<(String, usize), PathError> {
+    let mut key = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                key.push(chars[i + 1]);
+                i += 2;
+            }
+            '"' => return Ok((key, i + 1)),
+            c => {
+                key.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err(PathError::Unterminated { position: start })
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains(&['.', '[', ']', '"'][..])
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Key(key) if is_plain_key(key) => {
+                    if i > 0 {
+                        write!(formatter, ".")?;
+                    }
+                    write!(formatter, "{}", key)?;
+                }
+                Segment::Key(key) => {
+                    write!(formatter, "[\"")?;
+                    for c in key.chars() {
+                        if c == '"' || c == '\\' {
+                            write!(formatter, "\\")?;
+                        }
+                        write!(formatter, "{}", c)?;
+                    }
+                    write!(formatter, "\"]")?;
+                }
+                Segment::Index(index) => write!(formatter, "[{}]", index)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Segment {
+        Segment::Key(s.into())
+    }
+
+    #[test]
+    fn parse_root() {
+        assert_eq!("".parse::<JsonPath>().unwrap(), JsonPath::root());
+    }
+
+    #[test]
+    fn parse_mixed() {
+        let path: JsonPath = r#"a[0]["b.c"][12].d"#.parse().unwrap();
+
+        assert_eq!(
+            path.segments(),
+            &[
+                key("a"),
+                Segment::Index(0),
+                key("b.c"),
+                Segment::Index(12),
+                key("d")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "a..b".parse::<JsonPath>(),
+            Err(PathError::EmptyKey { position: 2 })
+        );
+        assert_eq!(
+            "a[x]".parse::<JsonPath>(),
+            Err(PathError::InvalidIndex { position: 2 })
+        );
+        assert_eq!(
+            "a[1".parse::<JsonPath>(),
+            Err(PathError::Unterminated { position: 1 })
+        );
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for input in &["a.b[2]", r#"a["b.c"].d"#, "[0][1]", ""] {
+            let path: JsonPath = input.parse().unwrap();
+
+            assert_eq!(&path.to_string(), input);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Path-based typing of incoming documents.
+//!
+//! JSON can't tell a list from a set or a timestamp from a string. A [`Schema`] declares the
+//! [`Kind`] of the nodes found at given [JSON pointers](https://tools.ietf.org/html/rfc6901),
+//! e.g. `/tags` or `/users/*/roles`, where `*` matches any key or index.
+//!
+//! Schemas are applied while deserializing, see
+//! [`DeserializerOptions::schema`](../de/struct.DeserializerOptions.html#method.schema).
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The type a node is forced into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// A JSON array hashed as a set.
+    Set,
+    /// A string hashed as a timestamp.
+    Timestamp,
+    /// A hexadecimal string hashed as raw bytes.
+    Raw,
+    /// A number hashed as an integer. Floats are only accepted if they have no fractional part.
+    Integer,
+    /// A number hashed as a float, even if it is written without a fractional part.
+    Float,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Kind::Set => "set",
+            Kind::Timestamp => "timestamp",
+            Kind::Raw => "raw",
+            Kind::Integer => "integer",
+            Kind::Float => "float",
+        };
+
+        write!(formatter, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PointerError {
+    /// A non-empty pointer must start with `/`.
+    MissingSlash,
+    /// A `~` is not followed by `0` or `1`.
+    InvalidEscape { position: usize },
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PointerError::MissingSlash => write!(formatter, "pointer must start with '/'"),
+            PointerError::InvalidEscape { position } => {
+                write!(formatter, "invalid escape at {}", position)
+            }
+        }
+    }
+}
+
+impl error::Error for PointerError {}
+
+/// A JSON pointer pattern. The token `*` matches any single key or index.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pointer(Vec<String>);
+
+impl Pointer {
+    pub fn tokens(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Checks whether the pattern matches the given list of reference tokens.
+    pub fn matches<S: AsRef<str>>(&self, tokens: &[S]) -> bool {
+        self.0.len() == tokens.len()
+            && self
+                .0
+                .iter()
+                .zip(tokens)
+                .all(|(pattern, token)| pattern == "*" || pattern == token.as_ref())
+    }
+}
+
+impl From<Vec<String>> for Pointer {
+    fn from(tokens: Vec<String>) -> Pointer {
+        Pointer(tokens)
+    }
+}
+
+impl FromStr for Pointer {
+    type Err = PointerError;
+
+    /// Parses an RFC 6901 pointer. The empty string addresses the root.
+    ///
+    /// ```
+    /// use blot::value::schema::Pointer;
+    ///
+    /// let pointer: Pointer = "/a~1b/*/c".parse().unwrap();
+    ///
+    /// assert_eq!(pointer.tokens(), &["a/b", "*", "c"]);
+    /// ```
+    fn from_str(input: &str) -> Result<Pointer, PointerError> {
+        if input.is_empty() {
+            return Ok(Pointer::default());
+        }
+
+        if !input.starts_with('/') {
+            return Err(PointerError::MissingSlash);
+        }
+
+        let mut tokens = Vec::new();
+        let mut position = 1;
+
+        for raw in input[1..].split('/') {
+            let mut token = String::new();
+            let mut chars = raw.chars();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => token.push('~'),
+                        Some('1') => token.push('/'),
+                        _ => return Err(PointerError::InvalidEscape { position }),
+                    },
+                    c => token.push(c),
+                }
+            }
+
+            position += raw.len() + 1;
+            tokens.push(token);
+        }
+
+        Ok(Pointer(tokens))
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for token in &self.0 {
+            write!(
+                formatter,
+                "/{}",
+                token.replace('~', "~0").replace('/', "~1")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Declares the kind of the nodes at given pointers. See [the module level documentation](index.html).
+///
+/// ```
+/// use blot::value::schema::{Kind, Schema};
+///
+/// let schema = Schema::new()
+///     .with("/tags".parse().unwrap(), Kind::Set)
+///     .with("/users/*/roles".parse().unwrap(), Kind::Set);
+///
+/// assert_eq!(schema.kind_at(&["users", "0", "roles"]), Some(Kind::Set));
+/// assert_eq!(schema.kind_at(&["users", "0"]), None);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    rules: Vec<(Pointer, Kind)>,
+}
+
+impl Schema {
+    pub fn new() -> Schema {
+        Schema::default()
+    }
+
+    /// Adds a rule. Later rules take precedence over earlier ones.
+    pub fn with(mut self, pointer: Pointer, kind: Kind) -> Schema {
+        self.insert(pointer, kind);
+        self
+    }
+
+    pub fn insert(&mut self, pointer: Pointer, kind: Kind) {
+        self.rules.push((pointer, kind));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the kind declared for the node at the given reference tokens.
+    pub fn kind_at<S: AsRef<str>>(&self, tokens: &[S]) -> Option<Kind> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pointer, _)| pointer.matches(tokens))
+            .map(|(_, kind)| *kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_root() {
+        assert_eq!("".parse::<Pointer>().unwrap(), Pointer::default());
+        assert_eq!("/".parse::<Pointer>().unwrap().tokens(), &[""]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("a/b".parse::<Pointer>(), Err(PointerError::MissingSlash));
+        assert_eq!(
+            "/a/b~2".parse::<Pointer>(),
+            Err(PointerError::InvalidEscape { position: 3 })
+        );
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for input in &["/a~1b/*/c~0", "/tags", ""] {
+            let pointer: Pointer = input.parse().unwrap();
+
+            assert_eq!(&pointer.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn later_rules_win() {
+        let schema = Schema::new()
+            .with("/*".parse().unwrap(), Kind::Set)
+            .with("/when".parse().unwrap(), Kind::Timestamp);
+
+        assert_eq!(schema.kind_at(&["when"]), Some(Kind::Timestamp));
+        assert_eq!(schema.kind_at(&["tags"]), Some(Kind::Set));
+        assert_eq!(schema.kind_at::<&str>(&[]), None);
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Serializes a [`Value`] back into the JSON shape understood by the deserializer.
+//!
+//! Redacted values are written with the `0x77` seal mark and raw values as hexadecimal strings,
+//! so a serialized value deserializes back to itself. The exception are big integers and
+//! decimals, written as strings to keep their precision.
+//!
+//! A value converts to a `serde_json::Value` in the same shape:
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate blot;
+//! extern crate serde_json;
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//! use std::convert::TryFrom;
+//!
+//! # fn main() {
+//! let value: Value<Sha2256> = list![1, Value::Raw(vec![0xca, 0xfe])];
+//!
+//! assert_eq!(
+//!     serde_json::Value::try_from(value).unwrap(),
+//!     serde_json::json!([1, "cafe"])
+//! );
+//! # }
+//! ```
+
+use multihash::Multihash;
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{self, Map, Number};
+use std::convert::TryFrom;
+
+use super::Value;
+
+impl<T: Multihash> Serialize for Value<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(raw) => serializer.serialize_bool(*raw),
+            Value::Integer(raw) => serializer.serialize_i64(*raw),
+            Value::UInteger(raw) => serializer.serialize_u64(*raw),
+            #[cfg(feature = "num-bigint")]
+            Value::BigInt(raw) => serializer.collect_str(raw),
+            #[cfg(feature = "rust_decimal")]
+            Value::Decimal(raw) => serializer.collect_str(raw),
+            Value::Float(raw) => serializer.serialize_f64(*raw),
+            Value::String(raw) | Value::Timestamp(raw) => serializer.serialize_str(raw),
+            Value::Redacted(seal) => seal.serialize(serializer),
+            Value::Raw(raw) | Value::Custom(_, raw) => {
+                let hex: String = raw.iter().map(|byte| format!("{:02x}", byte)).collect();
+                serializer.serialize_str(&hex)
+            }
+            Value::List(list) | Value::Set(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for item in list {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Value::Dict(dict) => {
+                let mut map = serializer.serialize_map(Some(dict.len()))?;
+                for (key, value) in dict {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Value::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Fails for floats that are not finite and for map keys that are not strings, which have no
+/// JSON form.
+impl<T: Multihash> TryFrom<Value<T>> for serde_json::Value {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Value<T>) -> Result<serde_json::Value, serde_json::Error> {
+        let json = match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(raw) => raw.into(),
+            Value::Integer(raw) => raw.into(),
+            Value::UInteger(raw) => raw.into(),
+            #[cfg(feature = "num-bigint")]
+            Value::BigInt(raw) => raw.to_string().into(),
+            #[cfg(feature = "rust_decimal")]
+            Value::Decimal(raw) => raw.to_string().into(),
+            Value::Float(raw) => match Number::from_f64(raw) {
+                Some(number) => serde_json::Value::Number(number),
+                None => return Err(ser::Error::custom(format!("{} has no JSON form", raw))),
+            },
+            Value::String(raw) | Value::Timestamp(raw) => raw.into(),
+            Value::Redacted(seal) => seal.to_string().into(),
+            Value::Raw(raw) | Value::Custom(_, raw) => raw
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+                .into(),
+            Value::List(list) | Value::Set(list) => serde_json::Value::Array(
+                list.into_iter()
+                    .map(serde_json::Value::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Dict(dict) => serde_json::Value::Object(
+                dict.into_iter()
+                    .map(|(key, item)| Ok((key, serde_json::Value::try_from(item)?)))
+                    .collect::<Result<Map<_, _>, serde_json::Error>>()?,
+            ),
+            Value::Map(entries) => serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, item)| match key {
+                        Value::String(key) | Value::Timestamp(key) => {
+                            Ok((key, serde_json::Value::try_from(item)?))
+                        }
+                        _ => Err(ser::Error::custom("key must be a string")),
+                    })
+                    .collect::<Result<Map<_, _>, serde_json::Error>>()?,
+            ),
+        };
+
+        Ok(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::Sha2256;
+    use serde_json;
+
+    #[test]
+    fn roundtrip() {
+        let input = r#"{"a":[1,2.5,"foo",null,true],"b":"771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038","c":"2018-10-13T15:50:00Z","d":"ff00"}"#;
+        let value: Value<Sha2256> = serde_json::from_str(input).unwrap();
+        let output = serde_json::to_string(&value).unwrap();
+        let actual: Value<Sha2256> = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(actual, value);
+        assert_eq!(actual.digest(Sha2256), value.digest(Sha2256));
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn roundtrip_key_order() {
+        let input = r#"{"c":1,"a":{"z":null,"b":true},"b":"foo"}"#;
+        let value: Value<Sha2256> = serde_json::from_str(input).unwrap();
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), input);
+    }
+
+    #[test]
+    fn try_into_json() {
+        let input = r#"{"a":[1,2.5,"foo",null,true],"b":"771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038","c":"2018-10-13T15:50:00Z","d":"ff00"}"#;
+        let value: Value<Sha2256> = serde_json::from_str(input).unwrap();
+
+        assert_eq!(
+            serde_json::Value::try_from(value).unwrap(),
+            serde_json::from_str::<serde_json::Value>(input).unwrap()
+        );
+        assert!(serde_json::Value::try_from(Value::Float::<Sha2256>(f64::NAN)).is_err());
+        assert!(serde_json::Value::try_from(Value::Map::<Sha2256>(vec![(
+            Value::Integer(1),
+            Value::Null
+        )]))
+        .is_err());
+    }
+
+    #[test]
+    fn redacted_list() {
+        let value: Value<Sha2256> = serde_json::from_str(r#"["foo", "bar"]"#).unwrap();
+        let redacted = value.redact_paths(&["[0]".parse().unwrap()]);
+
+        assert_eq!(
+            serde_json::to_string(&redacted).unwrap(),
+            r#"["771220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038","bar"]"#
+        );
+    }
+}
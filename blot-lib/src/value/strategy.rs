@@ -0,0 +1,162 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Property testing strategies.
+//!
+//! Generators of arbitrary [`Value`]s for [proptest](https://docs.rs/proptest), enabled with the
+//! `testing` feature. `Value<T>` implements [`Arbitrary`], so `any::<Value<Sha2256>>()` works
+//! out of the box:
+//!
+//! ```
+//! extern crate blot;
+//! extern crate proptest;
+//!
+//! use blot::core::Blot;
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//!
+//! # fn main() {
+//! let mut runner = TestRunner::default();
+//! let lists = prop::collection::vec(any::<Value<Sha2256>>(), 0..4);
+//!
+//! runner
+//!     .run(&lists, |list| {
+//!         let mut reversed = list.clone();
+//!         reversed.reverse();
+//!
+//!         prop_assert_eq!(
+//!             Value::Set(list).digest(Sha2256),
+//!             Value::Set(reversed).digest(Sha2256)
+//!         );
+//!
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! Floats are always finite and the variants behind other features, like `BigInt`, are never
+//! generated.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::fmt::Debug;
+
+use multihash::Multihash;
+use seal::Seal;
+
+use super::{DictMap, Value};
+
+/// Any value but lists, sets, dictionaries and maps.
+pub fn leaf<T: Multihash + Clone + Debug + 'static>() -> BoxedStrategy<Value<T>> {
+    use proptest::num::f64::{NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+
+    let scalar = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::Integer),
+        (i64::MAX as u64 + 1..=u64::MAX).prop_map(Value::UInteger),
+        (POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO).prop_map(Value::Float),
+        any::<String>().prop_map(Value::String),
+        timestamp().prop_map(Value::Timestamp),
+        vec(any::<u8>(), 0..32).prop_map(Value::Raw),
+    ];
+
+    prop_oneof![
+        8 => scalar.clone(),
+        1 => scalar.prop_map(|value| Value::Redacted(Seal::from_value(&value, &T::default()))),
+    ]
+    .boxed()
+}
+
+/// Any value, nested up to `depth` collections deep, each of them with up to `width` items.
+pub fn value<T: Multihash + Clone + Debug + 'static>(
+    depth: u32,
+    width: usize,
+) -> BoxedStrategy<Value<T>> {
+    leaf()
+        .prop_recursive(depth, 64, width as u32, move |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..width).prop_map(Value::List),
+                vec(inner.clone(), 0..width).prop_map(Value::Set),
+                vec((any::<String>(), inner.clone()), 0..width)
+                    .prop_map(|entries| Value::Dict(entries.into_iter().collect::<DictMap<T>>())),
+                vec((inner.clone(), inner), 0..width).prop_map(Value::Map),
+            ]
+        })
+        .boxed()
+}
+
+/// A valid RFC3339 timestamp in UTC, with or without fractional seconds.
+fn timestamp() -> impl Strategy<Value = String> {
+    (
+        0..10000u32,
+        1..=12u32,
+        1..=28u32,
+        0..24u32,
+        0..60u32,
+        0..60u32,
+        proptest::option::of(0..1000u32),
+    )
+        .prop_map(|(year, month, day, hour, minute, second, millis)| {
+            let fraction = millis.map_or(String::new(), |millis| format!(".{:03}", millis));
+
+            format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+                year, month, day, hour, minute, second, fraction
+            )
+        })
+}
+
+/// Values up to 4 collections deep with up to 8 items each.
+impl<T: Multihash + Clone + Debug + 'static> Arbitrary for Value<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Value<T>>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        value(4, 8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use multihash::Sha2256;
+    use proptest::sample::Index;
+
+    proptest! {
+        #[test]
+        fn set_order_does_not_change_digest(
+            (list, shuffled) in vec(any::<Value<Sha2256>>(), 0..8)
+                .prop_flat_map(|list| (Just(list.clone()), Just(list).prop_shuffle()))
+        ) {
+            prop_assert_eq!(
+                Value::Set(list).digest(Sha2256),
+                Value::Set(shuffled).digest(Sha2256)
+            );
+        }
+
+        #[test]
+        fn redaction_preserves_root_digest(value in any::<Value<Sha2256>>(), index: Index) {
+            let tree = value.digest_tree(Sha2256);
+            let nodes = tree.nodes();
+            let path = nodes[index.index(nodes.len())].path().clone();
+
+            prop_assert_eq!(
+                value.redact_paths(&[path]).digest(Sha2256),
+                value.digest(Sha2256)
+            );
+        }
+
+        #[test]
+        fn timestamps_are_valid(value in timestamp()) {
+            prop_assert!(Value::<Sha2256>::timestamp(&value).is_ok());
+        }
+    }
+}
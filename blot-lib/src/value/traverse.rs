@@ -0,0 +1,1017 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Iterative traversal.
+//!
+//! Hashes a [`Value`] with an explicit work stack instead of recursion, so deeply nested
+//! documents cannot overflow the call stack. [`DigestOptions`] bounds the depth and the number
+//! of nodes of untrusted documents.
+//!
+//! With the `tracing` feature, every hashed node, the root included, has a `node` span at the
+//! trace level, target `blot::traverse`, nested in the span of its parent, with the fields:
+//!
+//! * `tag`: the tag name of the node, or `redacted` for seals.
+//! * `path`: the path of the node, e.g. `a.b[2]`. Entries of maps with keys other than strings
+//!   have the path of their map.
+//! * `bytes`: the input of the node digest without its tag: the child digests of collections,
+//!   the Objecthash encoding of scalars and nothing for seals.
+//! * `elapsed`: the time spent hashing the node and its children.
+//!
+//! `bytes` and `elapsed` are recorded once the node is hashed, right before its span closes.
+
+use core::{digest_entries, Blot, FloatPolicy};
+use multihash::{Harvest, Hash, Multihash, NamespacedMultihash};
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tag::Tag;
+use uvar::Uvar;
+
+#[cfg(feature = "tracing")]
+use super::path::JsonPath;
+use super::path::Segment;
+use super::tree::tag_of;
+use super::Value;
+#[cfg(feature = "tracing")]
+use tracing::field::{self, Empty};
+#[cfg(feature = "tracing")]
+use tracing::{Level, Span};
+
+/// Limits and compatibility switches for [`Value::digest_with`]. The default options have no
+/// limits and hash timestamps in their canonical form.
+///
+/// ```
+/// use blot::multihash::Sha2256;
+/// use blot::value::traverse::{DigestError, DigestOptions};
+/// use blot::value::Value;
+///
+/// let value: Value<Sha2256> = Value::List(vec![Value::List(vec![Value::Null])]);
+///
+/// assert!(value.digest_with(Sha2256, &DigestOptions::new().max_depth(2)).is_ok());
+/// assert_eq!(
+///     value.digest_with(Sha2256, &DigestOptions::new().max_depth(1)),
+///     Err(DigestError::TooDeep(1))
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DigestOptions {
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    raw_timestamps: bool,
+    duplicates: Duplicates,
+    namespace: Option<Vec<u8>>,
+    /// Encodes the floats the policy rejects instead of failing, for the infallible digests.
+    lenient_floats: bool,
+}
+
+/// What to do with set members that have the same digest.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Duplicates {
+    /// Hashes duplicate members once, like Objecthash.
+    #[default]
+    Dedup,
+    /// Fails with [`DigestError::DuplicateSetMember`].
+    Error,
+}
+
+impl DigestOptions {
+    pub fn new() -> DigestOptions {
+        DigestOptions::default()
+    }
+
+    /// Fails with [`DigestError::TooDeep`] if a node is nested in more than `depth` collections.
+    pub fn max_depth(mut self, depth: usize) -> DigestOptions {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Fails with [`DigestError::TooManyNodes`] if the value has more than `nodes` nodes,
+    /// counting the root and every collection.
+    pub fn max_nodes(mut self, nodes: usize) -> DigestOptions {
+        self.max_nodes = Some(nodes);
+        self
+    }
+
+    /// Whether timestamps are hashed in their canonical form (the default) or byte for byte, as
+    /// older versions did. See [`datetime`](../../datetime/index.html).
+    pub fn canonical_timestamps(mut self, canonical: bool) -> DigestOptions {
+        self.raw_timestamps = !canonical;
+        self
+    }
+
+    /// Sets what to do with duplicate set members. They are deduplicated by default.
+    pub fn duplicates(mut self, duplicates: Duplicates) -> DigestOptions {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Mixes the application namespace into every node, so applications hashing the same data
+    /// with different namespaces get unrelated digests. See [`NamespacedMultihash`].
+    ///
+    /// ```
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::traverse::DigestOptions;
+    /// use blot::value::Value;
+    ///
+    /// let value: Value<Sha2256> = Value::String("foo".into());
+    /// let options = DigestOptions::new().namespace("billing");
+    ///
+    /// assert_ne!(value.digest_with(Sha2256, &options).unwrap(), value.digest(Sha2256));
+    /// ```
+    ///
+    /// [`NamespacedMultihash`]: ../../multihash/struct.NamespacedMultihash.html
+    pub fn namespace<N: AsRef<[u8]>>(mut self, namespace: N) -> DigestOptions {
+        self.namespace = Some(namespace.as_ref().to_vec());
+        self
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DigestError {
+    /// The value is nested deeper than the given limit.
+    TooDeep(usize),
+    /// The value has more nodes than the given limit.
+    TooManyNodes(usize),
+    /// The value has a NaN or infinite float and the digester has a
+    /// [`FloatPolicy::Strict`](../../core/enum.FloatPolicy.html#variant.Strict) policy.
+    NonFiniteFloat,
+    /// A set has members with the same digest and duplicates are an error.
+    DuplicateSetMember,
+}
+
+impl fmt::Display for DigestError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DigestError::TooDeep(limit) => {
+                write!(formatter, "value nested deeper than {} levels", limit)
+            }
+            DigestError::TooManyNodes(limit) => {
+                write!(formatter, "value with more than {} nodes", limit)
+            }
+            DigestError::NonFiniteFloat => write!(formatter, "NaN or infinite float"),
+            DigestError::DuplicateSetMember => write!(formatter, "duplicate set member"),
+        }
+    }
+}
+
+impl error::Error for DigestError {}
+
+/// What hashing a value took, from [`Value::digest_with_stats`].
+///
+/// ```
+/// use blot::multihash::Sha2256;
+/// use blot::value::traverse::DigestOptions;
+/// use blot::value::Value;
+///
+/// let value: Value<Sha2256> = Value::List(vec!["foo".into(), Value::List(vec![])]);
+/// let (_, report) = value
+///     .digest_with_stats(Sha2256, &DigestOptions::new())
+///     .unwrap();
+///
+/// assert_eq!(report.nodes().get("list"), Some(&2));
+/// assert_eq!(report.nodes().get("unicode"), Some(&1));
+/// assert_eq!(report.bytes(), 4 + 1 + 64 + 1);
+/// assert_eq!(report.max_depth(), 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DigestReport {
+    nodes: BTreeMap<&'static str, usize>,
+    bytes: u64,
+    max_depth: usize,
+    elapsed: Duration,
+}
+
+impl DigestReport {
+    /// The number of nodes hashed by tag name, `redacted` for seals. Dictionary keys count as
+    /// `unicode` nodes.
+    pub fn nodes(&self) -> &BTreeMap<&'static str, usize> {
+        &self.nodes
+    }
+
+    /// The total number of nodes hashed.
+    pub fn total_nodes(&self) -> usize {
+        self.nodes.values().sum()
+    }
+
+    /// The total number of bytes fed to the hasher, tags included.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The deepest nesting of a node, 0 for the root.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    fn record(&mut self, tag: Option<Tag>, bytes: usize) {
+        *self
+            .nodes
+            .entry(tag.map_or("redacted", |tag| tag.name()))
+            .or_insert(0) += 1;
+        self.bytes += (bytes + tag.map_or(0, |_| 1)) as u64;
+    }
+}
+
+/// A summary with one line per figure.
+impl fmt::Display for DigestReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect();
+
+        writeln!(
+            formatter,
+            "nodes: {} ({})",
+            self.total_nodes(),
+            nodes.join(", ")
+        )?;
+        writeln!(formatter, "bytes: {}", self.bytes)?;
+        writeln!(formatter, "max depth: {}", self.max_depth)?;
+        write!(formatter, "elapsed: {:?}", self.elapsed)
+    }
+}
+
+impl<T: Multihash> Value<T> {
+    /// Digests the value within the limits of the options.
+    pub fn digest_with<D: Multihash>(
+        &self,
+        digester: D,
+        options: &DigestOptions,
+    ) -> Result<Hash<D>, DigestError> {
+        traverse_in(self, digester, options, None)
+    }
+
+    /// Digests the value within the limits of the options, reporting what it took.
+    pub fn digest_with_stats<D: Multihash>(
+        &self,
+        digester: D,
+        options: &DigestOptions,
+    ) -> Result<(Hash<D>, DigestReport), DigestError> {
+        let mut report = DigestReport::default();
+        let start = Instant::now();
+        let hash = traverse_in(self, digester, options, Some(&mut report))?;
+        report.elapsed = start.elapsed();
+
+        Ok((hash, report))
+    }
+}
+
+/// Pending work: a node to hash or a collection to fold once its children are hashed.
+enum Task<'a, T: Multihash + 'a> {
+    Enter(&'a Value<T>, usize, Probe),
+    List(usize, Probe),
+    Set(usize, Probe),
+    Dict(Vec<&'a String>, Probe),
+    Map(usize, Probe),
+}
+
+/// Where a node is, its span and when its hashing started, to trace it with the `tracing`
+/// feature. Empty when the spans are disabled, so untraced documents don't pay for the paths.
+#[cfg(feature = "tracing")]
+struct Probe(Option<Trace>);
+
+#[cfg(feature = "tracing")]
+struct Trace {
+    path: JsonPath,
+    /// The span of the node once started, the span of its parent until then.
+    span: Option<Span>,
+    start: Instant,
+}
+
+/// Nothing to trace without the `tracing` feature.
+#[cfg(not(feature = "tracing"))]
+struct Probe;
+
+#[cfg(feature = "tracing")]
+impl Probe {
+    fn root() -> Probe {
+        if traced() {
+            Probe(Some(Trace {
+                path: JsonPath::root(),
+                span: None,
+                start: Instant::now(),
+            }))
+        } else {
+            Probe(None)
+        }
+    }
+
+    fn child<F: FnOnce() -> Option<Segment>>(&self, segment: F) -> Probe {
+        Probe(self.0.as_ref().map(|trace| Trace {
+            path: match segment() {
+                Some(segment) => trace.path.join(segment),
+                None => trace.path.clone(),
+            },
+            span: trace.span.clone(),
+            start: trace.start,
+        }))
+    }
+
+    /// Opens the span of the node, a child of the span of its parent.
+    fn started(self, tag: Option<Tag>) -> Probe {
+        Probe(self.0.map(|trace| {
+            let tag = tag.map_or("redacted", |tag| tag.name());
+            let span = match trace.span {
+                Some(parent) => tracing::trace_span!(
+                    target: "blot::traverse",
+                    parent: &parent,
+                    "node",
+                    tag = tag,
+                    path = %trace.path,
+                    bytes = Empty,
+                    elapsed = Empty
+                ),
+                None => tracing::trace_span!(
+                    target: "blot::traverse",
+                    "node",
+                    tag = tag,
+                    path = %trace.path,
+                    bytes = Empty,
+                    elapsed = Empty
+                ),
+            };
+
+            Trace {
+                path: trace.path,
+                span: Some(span),
+                start: Instant::now(),
+            }
+        }))
+    }
+
+    /// Counts the input bytes of the node only when they are reported or traced.
+    fn measure<F: FnOnce() -> usize>(&self, reported: bool, bytes: F) -> usize {
+        if reported || self.0.is_some() {
+            bytes()
+        } else {
+            0
+        }
+    }
+
+    /// Records the input bytes and the elapsed time in the span of the node.
+    fn emit(&self, _tag: Option<Tag>, bytes: usize) {
+        if let Some(Trace {
+            span: Some(span),
+            start,
+            ..
+        }) = &self.0
+        {
+            span.record("bytes", bytes);
+            span.record("elapsed", field::debug(start.elapsed()));
+        }
+    }
+}
+
+/// Whether the spans of the nodes are enabled.
+#[cfg(feature = "tracing")]
+pub(crate) fn traced() -> bool {
+    tracing::enabled!(target: "blot::traverse", Level::TRACE)
+}
+
+#[cfg(not(feature = "tracing"))]
+impl Probe {
+    fn root() -> Probe {
+        Probe
+    }
+
+    fn child<F: FnOnce() -> Option<Segment>>(&self, _segment: F) -> Probe {
+        Probe
+    }
+
+    fn started(self, _tag: Option<Tag>) -> Probe {
+        Probe
+    }
+
+    fn measure<F: FnOnce() -> usize>(&self, reported: bool, bytes: F) -> usize {
+        if reported {
+            bytes()
+        } else {
+            0
+        }
+    }
+
+    fn emit(&self, _tag: Option<Tag>, _bytes: usize) {}
+}
+
+/// The input of a scalar digest without its tag, with floats encoded by the given policy.
+fn leaf_bytes<T: Multihash>(value: &Value<T>, policy: FloatPolicy) -> usize {
+    match value {
+        Value::Redacted(_) => 0,
+        leaf => {
+            let mut count = [0; 8];
+            count.copy_from_slice(leaf.blot_untraced(&Counter(policy)).as_slice());
+
+            u64::from_be_bytes(count) as usize
+        }
+    }
+}
+
+/// Counts the input of a digest, tag excluded, instead of hashing it. The digest is the count as
+/// 8 big-endian bytes.
+#[derive(Default, PartialEq)]
+struct Counter(FloatPolicy);
+
+impl Counter {
+    fn harvest(count: usize) -> Harvest {
+        (count as u64).to_be_bytes()[..].into()
+    }
+}
+
+impl Multihash for Counter {
+    type Digester = ();
+
+    fn length(&self) -> u8 {
+        8
+    }
+
+    fn code(&self) -> Uvar {
+        Uvar::from(0x00)
+    }
+
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    fn float_policy(&self) -> FloatPolicy {
+        self.0
+    }
+
+    fn digest_primitive(&self, _tag: Tag, bytes: &[u8]) -> Harvest {
+        Counter::harvest(bytes.len())
+    }
+
+    fn digest_bytes(&self, bytes: &[u8]) -> Harvest {
+        Counter::harvest(bytes.len())
+    }
+
+    fn digest_collection<I>(&self, _tag: Tag, list: I) -> Harvest
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        Counter::harvest(list.into_iter().map(|item| item.as_ref().len()).sum())
+    }
+}
+
+/// The length of the digests, the input of the collection that folds them.
+fn digests_bytes<'a, I: IntoIterator<Item = &'a Harvest>>(digests: I) -> usize {
+    digests
+        .into_iter()
+        .map(|digest| digest.as_slice().len())
+        .sum()
+}
+
+/// Digests the value like [`Blot::blot`], encoding the floats the policy of the digester
+/// rejects.
+pub(crate) fn blot<T, D>(value: &Value<T>, digester: &D) -> Harvest
+where
+    T: Multihash,
+    D: Multihash,
+{
+    let options = DigestOptions {
+        lenient_floats: true,
+        ..DigestOptions::default()
+    };
+
+    // Without limits, duplicate checks nor float checks there is nothing to fail.
+    traverse(value, digester, &options, None).expect("Lenient unlimited traversals to succeed")
+}
+
+/// Digests the value like [`Blot::try_blot`].
+pub(crate) fn try_blot<T, D>(value: &Value<T>, digester: &D) -> Result<Harvest, DigestError>
+where
+    T: Multihash,
+    D: Multihash,
+{
+    traverse(value, digester, &DigestOptions::default(), None)
+}
+
+/// Traverses the value with the digester wrapped in the namespace of the options, if any.
+fn traverse_in<T, D>(
+    value: &Value<T>,
+    digester: D,
+    options: &DigestOptions,
+    report: Option<&mut DigestReport>,
+) -> Result<Hash<D>, DigestError>
+where
+    T: Multihash,
+    D: Multihash,
+{
+    match options.namespace {
+        Some(ref namespace) => {
+            let namespaced = NamespacedMultihash::new(digester, namespace);
+            let digest = traverse(value, &namespaced, options, report)?;
+
+            Ok(Hash::new(namespaced.into_inner(), digest))
+        }
+        None => {
+            let digest = traverse(value, &digester, options, report)?;
+
+            Ok(Hash::new(digester, digest))
+        }
+    }
+}
+
+fn traverse<T, D>(
+    value: &Value<T>,
+    digester: &D,
+    options: &DigestOptions,
+    mut report: Option<&mut DigestReport>,
+) -> Result<Harvest, DigestError>
+where
+    T: Multihash,
+    D: Multihash,
+{
+    let reported = report.is_some();
+    let mut tasks = vec![Task::Enter(value, 0, Probe::root())];
+    let mut digests: Vec<Harvest> = Vec::new();
+    let mut nodes = 0;
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            Task::Enter(node, depth, probe) => {
+                let probe = probe.started(tag_of(node));
+                nodes += 1;
+
+                if let Some(limit) = options.max_nodes {
+                    if nodes > limit {
+                        return Err(DigestError::TooManyNodes(limit));
+                    }
+                }
+
+                if let Some(limit) = options.max_depth {
+                    if depth > limit {
+                        return Err(DigestError::TooDeep(limit));
+                    }
+                }
+
+                if let Some(report) = report.as_mut() {
+                    report.max_depth = report.max_depth.max(depth);
+                }
+
+                match node {
+                    Value::List(list) => {
+                        let children: Vec<_> = list
+                            .iter()
+                            .enumerate()
+                            .rev()
+                            .map(|(index, child)| {
+                                Task::Enter(
+                                    child,
+                                    depth + 1,
+                                    probe.child(|| Some(Segment::Index(index))),
+                                )
+                            })
+                            .collect();
+
+                        tasks.push(Task::List(list.len(), probe));
+                        tasks.extend(children);
+                    }
+                    Value::Set(list) => {
+                        let children: Vec<_> = list
+                            .iter()
+                            .enumerate()
+                            .rev()
+                            .map(|(index, child)| {
+                                Task::Enter(
+                                    child,
+                                    depth + 1,
+                                    probe.child(|| Some(Segment::Index(index))),
+                                )
+                            })
+                            .collect();
+
+                        tasks.push(Task::Set(list.len(), probe));
+                        tasks.extend(children);
+                    }
+                    Value::Dict(dict) => {
+                        let (keys, values): (Vec<_>, Vec<_>) = dict.iter().unzip();
+                        let children: Vec<_> = keys
+                            .iter()
+                            .zip(values)
+                            .rev()
+                            .map(|(key, child)| {
+                                Task::Enter(
+                                    child,
+                                    depth + 1,
+                                    probe.child(|| Some(Segment::Key((*key).clone()))),
+                                )
+                            })
+                            .collect();
+
+                        tasks.push(Task::Dict(keys, probe));
+                        tasks.extend(children);
+                    }
+                    Value::Map(map) => {
+                        let children: Vec<_> = map
+                            .iter()
+                            .rev()
+                            .flat_map(|(key, value)| {
+                                vec![
+                                    Task::Enter(value, depth + 1, probe.child(|| None)),
+                                    Task::Enter(key, depth + 1, probe.child(|| None)),
+                                ]
+                            })
+                            .collect();
+
+                        tasks.push(Task::Map(map.len(), probe));
+                        tasks.extend(children);
+                    }
+                    Value::Float(raw)
+                        if !options.lenient_floats && !digester.float_policy().permits(*raw) =>
+                    {
+                        return Err(DigestError::NonFiniteFloat);
+                    }
+                    Value::Timestamp(raw) if options.raw_timestamps => {
+                        let bytes = probe.measure(reported, || raw.len());
+
+                        observe(&probe, &mut report, Some(Tag::Timestamp), bytes);
+                        digests.push(digester.digest_primitive(Tag::Timestamp, raw.as_bytes()))
+                    }
+                    leaf => {
+                        let digest = leaf.blot_untraced(digester);
+
+                        let bytes =
+                            probe.measure(reported, || leaf_bytes(leaf, digester.float_policy()));
+
+                        observe(&probe, &mut report, tag_of(leaf), bytes);
+                        digests.push(digest);
+                    }
+                }
+            }
+            Task::List(length, probe) => {
+                let start = digests.len() - length;
+                let bytes = probe.measure(reported, || digests_bytes(&digests[start..]));
+                let digest = digester.digest_collection(Tag::List, digests.drain(start..));
+
+                observe(&probe, &mut report, Some(Tag::List), bytes);
+                digests.push(digest);
+            }
+            Task::Set(length, probe) => {
+                let start = digests.len() - length;
+                let mut list = digests.split_off(start);
+
+                list.sort_unstable();
+
+                match options.duplicates {
+                    Duplicates::Dedup => list.dedup(),
+                    Duplicates::Error => {
+                        if list.windows(2).any(|pair| pair[0] == pair[1]) {
+                            return Err(DigestError::DuplicateSetMember);
+                        }
+                    }
+                }
+
+                let bytes = probe.measure(reported, || digests_bytes(&list));
+
+                digests.push(digester.digest_collection(Tag::Set, list));
+                observe(&probe, &mut report, Some(Tag::Set), bytes);
+            }
+            Task::Dict(keys, probe) => {
+                let start = digests.len() - keys.len();
+                let entries: Vec<(Harvest, Harvest)> = keys
+                    .iter()
+                    .map(|key| key.blot(digester))
+                    .zip(digests.split_off(start))
+                    .collect();
+
+                if let Some(report) = report.as_mut() {
+                    for key in &keys {
+                        report.record(Some(Tag::Unicode), key.len());
+                    }
+                }
+                let bytes = probe.measure(reported, || {
+                    digests_bytes(entries.iter().flat_map(|(key, value)| vec![key, value]))
+                });
+
+                digests.push(digest_entries(digester, entries));
+                observe(&probe, &mut report, Some(Tag::Dict), bytes);
+            }
+            Task::Map(length, probe) => {
+                let start = digests.len() - 2 * length;
+                let mut entries: Vec<(Harvest, Harvest)> = Vec::with_capacity(length);
+                let mut pairs = digests.split_off(start).into_iter();
+
+                while let (Some(key), Some(value)) = (pairs.next(), pairs.next()) {
+                    entries.push((key, value));
+                }
+
+                // The last duplicate wins, like in a `Value::Dict`.
+                entries.reverse();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                entries.dedup_by(|a, b| a.0 == b.0);
+                let bytes = probe.measure(reported, || {
+                    digests_bytes(entries.iter().flat_map(|(key, value)| vec![key, value]))
+                });
+
+                digests.push(digest_entries(digester, entries));
+                observe(&probe, &mut report, Some(Tag::Dict), bytes);
+            }
+        }
+    }
+
+    Ok(digests
+        .pop()
+        .expect("The traversal to leave the root digest"))
+}
+
+/// Reports and traces a hashed node.
+fn observe(probe: &Probe, report: &mut Option<&mut DigestReport>, tag: Option<Tag>, bytes: usize) {
+    if let Some(report) = report.as_mut() {
+        report.record(tag, bytes);
+    }
+
+    probe.emit(tag, bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "tracing")]
+    use dict;
+    use multihash::Sha2256;
+    use std::mem;
+
+    fn nested(depth: usize) -> Value<Sha2256> {
+        let mut value = Value::Null;
+
+        for _ in 0..depth {
+            value = Value::List(vec![value]);
+        }
+
+        value
+    }
+
+    #[test]
+    fn deep_value() {
+        let value = nested(100_000);
+
+        assert!(value.digest_with(Sha2256, &DigestOptions::new()).is_ok());
+
+        // Dropping the value recurses.
+        mem::forget(value);
+    }
+
+    #[test]
+    fn too_many_nodes() {
+        let value = nested(10);
+
+        assert!(value
+            .digest_with(Sha2256, &DigestOptions::new().max_nodes(11))
+            .is_ok());
+        assert_eq!(
+            value.digest_with(Sha2256, &DigestOptions::new().max_nodes(10)),
+            Err(DigestError::TooManyNodes(10))
+        );
+    }
+
+    #[test]
+    fn strict_floats() {
+        use core::FloatPolicy;
+        use multihash::FloatMultihash;
+        use std::f64;
+
+        let strict = FloatMultihash::new(Sha2256, FloatPolicy::Strict);
+        let value: Value<Sha2256> = Value::List(vec![Value::Float(1.5), Value::Float(f64::NAN)]);
+        let finite: Value<Sha2256> = Value::List(vec![Value::Float(1.5)]);
+
+        assert_eq!(
+            value.digest_with(strict.clone(), &DigestOptions::new()),
+            Err(DigestError::NonFiniteFloat)
+        );
+        assert!(value.digest_with(Sha2256, &DigestOptions::new()).is_ok());
+        assert_eq!(
+            finite
+                .digest_with(strict.clone(), &DigestOptions::new())
+                .map(|hash| hash.digest().to_hex()),
+            Ok(finite.digest(Sha2256).digest().to_hex())
+        );
+        assert_eq!(
+            value.try_digest(strict.clone()),
+            Err(DigestError::NonFiniteFloat)
+        );
+        assert_eq!(
+            Value::<Sha2256>::Float(f64::INFINITY).try_digest(strict.clone()),
+            Err(DigestError::NonFiniteFloat)
+        );
+        assert_eq!(
+            value.digest(strict).digest(),
+            value.digest(Sha2256).digest()
+        );
+    }
+
+    #[test]
+    fn duplicate_set_members() {
+        let value: Value<Sha2256> = Value::List(vec![
+            Value::Set(vec![Value::Integer(1), Value::Integer(2)]),
+            Value::Set(vec![Value::Integer(1), Value::Integer(1)]),
+        ]);
+        let unique: Value<Sha2256> = Value::Set(vec![Value::Integer(1), Value::Integer(2)]);
+        let strict = DigestOptions::new().duplicates(Duplicates::Error);
+
+        assert_eq!(
+            value.digest_with(Sha2256, &DigestOptions::new()),
+            Ok(value.digest(Sha2256))
+        );
+        assert_eq!(
+            value.digest_with(Sha2256, &strict),
+            Err(DigestError::DuplicateSetMember)
+        );
+        assert_eq!(
+            unique.digest_with(Sha2256, &strict),
+            Ok(unique.digest(Sha2256))
+        );
+    }
+
+    #[test]
+    fn namespace() {
+        let value: Value<Sha2256> = Value::List(vec![Value::String("foo".into())]);
+        let options = DigestOptions::new().namespace("ns");
+        let hash = value.digest_with(Sha2256, &options).unwrap();
+        let (reported, _) = value.digest_with_stats(Sha2256, &options).unwrap();
+
+        assert_eq!(
+            hash.digest(),
+            value
+                .digest(NamespacedMultihash::new(Sha2256, "ns"))
+                .digest()
+        );
+        assert_eq!(reported, hash);
+        assert_eq!(hash.tag(), &Sha2256);
+        assert_ne!(
+            hash,
+            value
+                .digest_with(Sha2256, &DigestOptions::new().namespace("other"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn raw_timestamps() {
+        let value: Value<Sha2256> =
+            Value::List(vec![Value::Timestamp("2018-10-13T16:50:00+01:00".into())]);
+        let canonical: Value<Sha2256> =
+            Value::List(vec![Value::Timestamp("2018-10-13T15:50:00Z".into())]);
+        let raw = DigestOptions::new().canonical_timestamps(false);
+
+        assert_eq!(
+            value.digest_with(Sha2256, &DigestOptions::new()),
+            Ok(canonical.digest(Sha2256))
+        );
+        assert_ne!(
+            value.digest_with(Sha2256, &raw),
+            Ok(canonical.digest(Sha2256))
+        );
+        assert_eq!(
+            canonical.digest_with(Sha2256, &raw),
+            Ok(canonical.digest(Sha2256))
+        );
+    }
+
+    #[test]
+    fn stats_of_large_leaves() {
+        let value: Value<Sha2256> = Value::String("x".repeat(1000));
+        let (_, report) = value
+            .digest_with_stats(Sha2256, &DigestOptions::new())
+            .unwrap();
+
+        // The string and its tag.
+        assert_eq!(report.bytes(), 1001);
+    }
+
+    #[test]
+    fn stats() {
+        let seal = "foo".digest(Sha2256).into();
+        let value: Value<Sha2256> = Value::Dict(
+            vec![
+                (
+                    "a".to_string(),
+                    Value::Set(vec!["foo".into(), "foo".into()]),
+                ),
+                ("b".to_string(), Value::Redacted(seal)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let (hash, report) = value
+            .digest_with_stats(Sha2256, &DigestOptions::new())
+            .unwrap();
+        let nodes: Vec<(&str, usize)> = report.nodes().iter().map(|(k, v)| (*k, *v)).collect();
+
+        assert_eq!(hash, value.digest(Sha2256));
+        assert_eq!(
+            nodes,
+            vec![("dict", 1), ("redacted", 1), ("set", 1), ("unicode", 4)]
+        );
+        assert_eq!(report.total_nodes(), 7);
+        // Four strings of one or three bytes, the set of one member digest and the dict of two
+        // pairs of digests, each with its tag.
+        assert_eq!(report.bytes(), (4 + 4 + 2 + 2) + (32 + 1) + (4 * 32 + 1));
+        assert_eq!(report.max_depth(), 2);
+        assert!(report
+            .to_string()
+            .starts_with("nodes: 7 (dict 1, redacted 1, set 1, unicode 4)\nbytes: 174\n"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn traces_nodes() {
+        use std::collections::HashMap;
+        use std::fmt::Debug;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{subscriber, Event, Metadata, Subscriber};
+
+        /// The parent and the fields of a span.
+        type Fields = (Option<u64>, Vec<String>);
+
+        /// The fields of the spans, one line per span once its elapsed time is recorded.
+        #[derive(Default)]
+        struct Capture {
+            next: AtomicUsize,
+            spans: Mutex<HashMap<u64, Fields>>,
+            lines: Arc<Mutex<Vec<String>>>,
+        }
+
+        struct Visitor<'a>(&'a mut Vec<String>, &'a mut bool);
+
+        impl<'a> Visit for Visitor<'a> {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                match field.name() {
+                    "elapsed" => *self.1 = true,
+                    _ => self
+                        .0
+                        .push(format!("{:?}", value).trim_matches('"').to_string()),
+                }
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes) -> Id {
+                let id = self.next.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+                let parent = span.parent().map(Id::into_u64);
+                let mut fields = Vec::new();
+                span.record(&mut Visitor(&mut fields, &mut false));
+                self.spans.lock().unwrap().insert(id, (parent, fields));
+
+                Id::from_u64(id)
+            }
+
+            fn record(&self, span: &Id, values: &Record) {
+                let mut spans = self.spans.lock().unwrap();
+                let (parent, fields) = spans.get_mut(&span.into_u64()).unwrap();
+                let mut done = false;
+                values.record(&mut Visitor(fields, &mut done));
+
+                if done {
+                    let parent = parent.map_or("-".to_string(), |id| id.to_string());
+                    let line = format!("{} {} {}", span.into_u64(), parent, fields.join(" "));
+                    self.lines.lock().unwrap().push(line);
+                }
+            }
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let capture = Capture::default();
+        let lines = capture.lines.clone();
+        let value: Value<Sha2256> = dict! {"a" => Value::Set(vec!["foo".into(), "foo".into()])};
+
+        subscriber::with_default(capture, || {
+            value.digest_with(Sha2256, &DigestOptions::new()).unwrap();
+            Value::<Sha2256>::Float(1.5).digest(Sha2256);
+        });
+
+        // Span id, parent id, tag, path and bytes.
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![
+                "3 2 unicode a[0] 3",
+                "4 2 unicode a[1] 3",
+                "2 1 set a 32",
+                "1 - dict  64",
+                "5 - float  6",
+            ]
+        );
+    }
+}
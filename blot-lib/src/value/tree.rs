@@ -0,0 +1,253 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Digest trees.
+//!
+//! A [`DigestTree`] mirrors a [`Value`] annotating every node with its path, tag and digest. It
+//! shows where two documents diverge when their root digests differ.
+
+use core::{digest_entries, Blot};
+use multihash::{Harvest, Hash, Multihash};
+use std::fmt;
+use tag::Tag;
+
+use super::path::{JsonPath, Segment};
+use super::Value;
+
+/// The digest of a node and of all its descendants.
+#[derive(Debug, PartialEq)]
+pub struct DigestTree<D: Multihash> {
+    path: JsonPath,
+    tag: Option<Tag>,
+    hash: Hash<D>,
+    children: Vec<DigestTree<D>>,
+}
+
+impl<D: Multihash> DigestTree<D> {
+    pub fn path(&self) -> &JsonPath {
+        &self.path
+    }
+
+    /// The Objecthash tag of the node. Redacted nodes have no tag.
+    pub fn tag(&self) -> Option<Tag> {
+        self.tag
+    }
+
+    pub fn hash(&self) -> &Hash<D> {
+        &self.hash
+    }
+
+    /// The subtrees of a list, set or dictionary. Dictionary entries are sorted by key. Maps
+    /// with non-string keys have no path to their entries, so they are shown as a single node.
+    pub fn children(&self) -> &[DigestTree<D>] {
+        &self.children
+    }
+
+    /// Returns every node of the tree, parents before their children.
+    pub fn nodes(&self) -> Vec<&DigestTree<D>> {
+        let mut nodes = vec![self];
+
+        for child in &self.children {
+            nodes.extend(child.nodes());
+        }
+
+        nodes
+    }
+
+    fn fmt_depth(&self, formatter: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        let tag = self.tag.map(|tag| tag.name()).unwrap_or("redacted");
+        let path = if self.path.is_root() {
+            "(root)".to_string()
+        } else {
+            self.path.to_string()
+        };
+
+        writeln!(
+            formatter,
+            "{:indent$}{} {} {}",
+            "",
+            path,
+            tag,
+            self.hash,
+            indent = depth * 2
+        )?;
+
+        for child in &self.children {
+            child.fmt_depth(formatter, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints one node per line, indented by depth: `path tag multihash`.
+impl<D: Multihash> fmt::Display for DigestTree<D> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_depth(formatter, 0)
+    }
+}
+
+impl<T: Multihash> Value<T> {
+    /// Digests the value keeping the digest of every node.
+    ///
+    /// The root of the tree has the same digest as [`Blot::digest`]. Every node is hashed
+    /// once: collections are digested from the digests of their children.
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate blot;
+    /// use blot::core::Blot;
+    /// use blot::multihash::Sha2256;
+    /// use blot::value::Value;
+    ///
+    /// # fn main() {
+    /// let value: Value<Sha2256> = list!["foo", "bar"];
+    /// let tree = value.digest_tree(Sha2256);
+    ///
+    /// assert_eq!(tree.hash(), &value.digest(Sha2256));
+    /// assert_eq!(tree.children()[1].path().to_string(), "[1]");
+    /// # }
+    /// ```
+    pub fn digest_tree<D: Multihash + Clone>(&self, digester: D) -> DigestTree<D> {
+        build(self, JsonPath::root(), &digester)
+    }
+}
+
+fn build<T: Multihash, D: Multihash + Clone>(
+    value: &Value<T>,
+    path: JsonPath,
+    digester: &D,
+) -> DigestTree<D> {
+    let (tag, children, digest) = match value {
+        Value::List(list) | Value::Set(list) => {
+            let children: Vec<DigestTree<D>> = list
+                .iter()
+                .enumerate()
+                .map(|(index, child)| build(child, path.join(Segment::Index(index)), digester))
+                .collect();
+            let mut digests: Vec<&Harvest> =
+                children.iter().map(|child| child.hash.digest()).collect();
+
+            let tag = match value {
+                Value::Set(_) => {
+                    digests.sort_unstable();
+                    digests.dedup();
+                    Tag::Set
+                }
+                _ => Tag::List,
+            };
+
+            let digest = digester.digest_collection(tag, digests);
+            (Some(tag), children, digest)
+        }
+        Value::Dict(dict) => {
+            let mut keys: Vec<&String> = dict.keys().collect();
+            keys.sort();
+
+            let children: Vec<DigestTree<D>> = keys
+                .iter()
+                .map(|key| {
+                    build(
+                        &dict[*key],
+                        path.join(Segment::Key((*key).clone())),
+                        digester,
+                    )
+                })
+                .collect();
+            let entries = keys
+                .iter()
+                .zip(&children)
+                .map(|(key, child)| (key.blot(digester), child.hash.digest()))
+                .collect();
+
+            let digest = digest_entries(digester, entries);
+            (Some(Tag::Dict), children, digest)
+        }
+        leaf => (tag_of(leaf), Vec::new(), leaf.blot(digester)),
+    };
+
+    DigestTree {
+        path,
+        tag,
+        hash: Hash::new(digester.clone(), digest),
+        children,
+    }
+}
+
+pub(crate) fn tag_of<T: Multihash>(value: &Value<T>) -> Option<Tag> {
+    match value {
+        Value::Null => Some(Tag::Null),
+        Value::Bool(_) => Some(Tag::Bool),
+        Value::Integer(_) | Value::UInteger(_) => Some(Tag::Integer),
+        #[cfg(feature = "num-bigint")]
+        Value::BigInt(_) => Some(Tag::Integer),
+        Value::Float(_) => Some(Tag::Float),
+        #[cfg(feature = "rust_decimal")]
+        Value::Decimal(_) => Some(Tag::Decimal),
+        Value::String(_) => Some(Tag::Unicode),
+        Value::Timestamp(_) => Some(Tag::Timestamp),
+        Value::Raw(_) => Some(Tag::Raw),
+        Value::Custom(tag, _) => Some(Tag::Custom(*tag)),
+        Value::Redacted(_) => None,
+        Value::List(_) => Some(Tag::List),
+        Value::Set(_) => Some(Tag::Set),
+        Value::Dict(_) | Value::Map(_) => Some(Tag::Dict),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dict;
+    use list;
+    use multihash::Sha2256;
+    use set;
+
+    fn document() -> Value<Sha2256> {
+        dict!{
+            "b" => set![1, 1, "x"],
+            "a" => list![Value::Null, 2.5]
+        }
+    }
+
+    #[test]
+    fn root_matches_digest() {
+        let value = document();
+        let tree = value.digest_tree(Sha2256);
+
+        assert_eq!(tree.hash(), &value.digest(Sha2256));
+    }
+
+    #[test]
+    fn every_node_matches_digest() {
+        let value = document().redact_paths(&["a[0]".parse().unwrap()]);
+        let tree = value.digest_tree(Sha2256);
+        let nodes = tree.nodes();
+
+        assert_eq!(nodes.len(), 8);
+        assert_eq!(nodes[2].path().to_string(), "a[0]");
+        assert_eq!(nodes[2].tag(), None);
+        assert_eq!(nodes[4].tag(), Some(Tag::Set));
+
+        let expected: Value<Sha2256> = Value::Float(2.5);
+        assert_eq!(nodes[3].hash(), &expected.digest(Sha2256));
+    }
+
+    #[test]
+    fn display() {
+        let value: Value<Sha2256> = Value::List(vec!["foo".into()]);
+        let tree = value.digest_tree(Sha2256);
+        let output = tree.to_string();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("(root) list 1220"));
+        assert_eq!(
+            lines[1],
+            "  [0] unicode 1220a6a6e5e783c363cd95693ec189c2682315d956869397738679b56305f2095038"
+        );
+    }
+}
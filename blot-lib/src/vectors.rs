@@ -0,0 +1,253 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Test vectors for other implementations.
+//!
+//! Writes JSON documents and their digests in the format of the Objecthash `common_json.test`
+//! golden file: a line with the document followed by a line with its digest in hexadecimal,
+//! grouped under `#` comments. The algorithm is named in the header.
+//!
+//! Documents are read like the command line does, so they cover every tag JSON can express:
+//! integers and floats are distinct, hexadecimal strings are raw bytes, RFC3339 strings are
+//! timestamps and seals are redacted values. Sets and decimals have no JSON form and are left out.
+//!
+//! ```
+//! use blot::multihash::Sha2256;
+//! use blot::vectors;
+//!
+//! let mut output = Vec::new();
+//! vectors::write(&mut output, Sha2256).unwrap();
+//! let output = String::from_utf8(output).unwrap();
+//!
+//! assert!(output.contains("[\"foo\"]\n268bc27d4974d9d576222e4cdbb8f7c6bd6791894098645a19eeca9c102d0964\n"));
+//! ```
+
+use core::Blot;
+use multihash::Multihash;
+use seal::Seal;
+use std::io::{self, Write};
+use value::de::DeserializerOptions;
+use value::Value;
+
+/// Documents sharing a comment.
+struct Section {
+    comment: &'static str,
+    documents: Vec<String>,
+}
+
+impl Section {
+    fn new(comment: &'static str, documents: &[&str]) -> Section {
+        Section {
+            comment,
+            documents: documents
+                .iter()
+                .map(|document| document.to_string())
+                .collect(),
+        }
+    }
+}
+
+fn sections<T: Multihash>(digester: &T) -> Vec<Section> {
+    let seal = Seal::from_value(&Value::<T>::from("foo"), digester);
+
+    vec![
+        Section::new(
+            "Lists with strings",
+            &["[]", r#"["foo"]"#, r#"["foo", "bar"]"#],
+        ),
+        Section::new(
+            "Integers",
+            &[
+                "[0]",
+                "[-1]",
+                "[123456789012345]",
+                "[-9223372036854775808]",
+                "[9223372036854775807]",
+                "[18446744073709551615]",
+            ],
+        ),
+        Section::new(
+            "Floats",
+            &[
+                "[0.0]",
+                "[-0.0]",
+                "[1.5]",
+                "[-10.1234]",
+                "[1e-10]",
+                "[1.7976931348623157e308]",
+            ],
+        ),
+        Section::new("Integers and floats differ", &["[1]", "[1.0]"]),
+        Section::new(
+            "Dictionaries",
+            &[
+                "{}",
+                r#"{"foo": "bar"}"#,
+                r#"{"foo": ["bar", "baz"], "qux": ["norf"]}"#,
+            ],
+        ),
+        Section::new(
+            "Key order does not matter",
+            &[
+                r#"{"k1": "v1", "k2": "v2", "k3": "v3"}"#,
+                r#"{"k2": "v2", "k1": "v1", "k3": "v3"}"#,
+            ],
+        ),
+        Section::new("Null and booleans", &["null", "[null]", "true", "false"]),
+        Section::new(
+            "Unicode, without normalisation",
+            &[r#""ԱԲաբ""#, r#""\u03d3""#, r#""\u03d2\u0301""#],
+        ),
+        Section::new(
+            "Hexadecimal strings are raw bytes",
+            &[r#""""#, r#""cafe""#, r#"["00", "0"]"#],
+        ),
+        Section::new(
+            "Timestamps are hashed in UTC without trailing fractional zeros",
+            &[
+                r#""2018-10-13T15:50:00Z""#,
+                r#""2018-10-13T16:50:00+01:00""#,
+                r#""2018-10-13T15:50:00.120Z""#,
+                r#""2018-10-13T15:50:00.12Z""#,
+            ],
+        ),
+        Section {
+            comment: "Redacted values hash to their seal, the same as the original list",
+            documents: vec![
+                format!(r#"["{}", "bar"]"#, seal),
+                format!(r#"["{:#}", "bar"]"#, seal),
+            ],
+        },
+        Section::new(
+            "Mixture of all types",
+            &[r#"["foo", {"bar": ["baz", null, 1, 1.5, true, "cafe", "2018-10-13T15:50:00Z"]}]"#],
+        ),
+    ]
+}
+
+/// Writes the test vectors digested with the given algorithm.
+pub fn write<W: Write, T: Multihash + Clone>(writer: &mut W, digester: T) -> io::Result<()> {
+    let options = DeserializerOptions::new();
+
+    writeln!(writer, "# -*- coding: utf-8 -*-")?;
+    writeln!(writer, "# Algorithm: {}", digester.name())?;
+
+    for section in sections(&digester) {
+        writeln!(writer)?;
+        writeln!(writer, "# {}", section.comment)?;
+
+        for document in section.documents {
+            let value: Value<T> = options
+                .from_str(&document)
+                .expect("Test vectors to be valid documents");
+
+            writeln!(writer, "{}", document)?;
+            writeln!(writer, "{}", value.digest(digester.clone()).digest())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audit;
+    use multihash::{Sha2256, Sha3256};
+    use std::collections::HashSet;
+    use tag::Tag;
+
+    fn pairs(output: &[u8]) -> Vec<(String, String)> {
+        let output = String::from_utf8(output.to_vec()).unwrap();
+        let lines: Vec<&str> = output
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+
+        lines
+            .chunks(2)
+            .map(|pair| (pair[0].to_string(), pair[1].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn golden_format() {
+        let mut output = Vec::new();
+        write(&mut output, Sha2256).unwrap();
+        let pairs = pairs(&output);
+        let digest = |document: &str| {
+            pairs
+                .iter()
+                .find(|pair| pair.0 == document)
+                .map(|pair| pair.1.as_str())
+        };
+
+        assert!(output.starts_with(b"# -*- coding: utf-8 -*-\n# Algorithm: sha2-256\n"));
+        assert_eq!(
+            digest("[]"),
+            Some("acac86c0e609ca906f632b0e2dacccb2b77d22b0621f20ebece1a4835b93f6f0")
+        );
+        assert_eq!(
+            digest(r#"{"foo": "bar"}"#),
+            Some("7ef5237c3027d6c58100afadf37796b3d351025cf28038280147d42fdc53b960")
+        );
+        assert_eq!(
+            digest(r#""2018-10-13T15:50:00Z""#),
+            digest(r#""2018-10-13T16:50:00+01:00""#)
+        );
+        assert_eq!(
+            digest(r#""\u03d2\u0301""#),
+            Some("42d5b13fb064849a988a86eb7650a22881c0a9ecf77057a1b07ab0dad385889c")
+        );
+        assert_ne!(digest("[1]"), digest("[1.0]"));
+
+        let redacted: Vec<&(String, String)> = pairs
+            .iter()
+            .filter(|pair| pair.0.contains(r#"", "bar"]"#))
+            .collect();
+
+        assert_eq!(redacted.len(), 3);
+        assert!(redacted
+            .iter()
+            .all(|pair| Some(pair.1.as_str()) == digest(r#"["foo", "bar"]"#)));
+    }
+
+    #[test]
+    fn every_json_tag() {
+        let options = DeserializerOptions::new();
+        let tags: HashSet<u8> = sections(&Sha3256)
+            .into_iter()
+            .flat_map(|section| section.documents)
+            .flat_map(|document| {
+                let value: Value<Sha3256> = options.from_str(&document).unwrap();
+                let (_, trace) = audit::trace(&value, Sha3256);
+
+                trace
+                    .records()
+                    .iter()
+                    .map(|record| record.tag().code())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let missing: Vec<Tag> = [
+            Tag::Bool,
+            Tag::Dict,
+            Tag::Float,
+            Tag::Integer,
+            Tag::List,
+            Tag::Null,
+            Tag::Raw,
+            Tag::Timestamp,
+            Tag::Unicode,
+        ]
+        .iter()
+        .cloned()
+        .filter(|tag| !tags.contains(&tag.code()))
+        .collect();
+
+        assert_eq!(missing, vec![]);
+    }
+}
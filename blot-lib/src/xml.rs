@@ -0,0 +1,558 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! XML documents as values.
+//!
+//! XML has no types of its own, so a document is read into a [`Value`] with a fixed mapping and
+//! hashed like any other value:
+//!
+//! * The document is a dictionary with the name of the root element as its only key.
+//! * An element without attributes nor child elements is its text, or null when it has none.
+//! * Any other element is a dictionary of its child elements by name. A name repeated in the
+//!   same element is a list of the children in document order, a single child is its value.
+//! * Attributes are a dictionary of strings under the reserved `@attributes` key.
+//! * The text around child elements is concatenated under the reserved `#text` key, unless it is
+//!   only whitespace, so indentation doesn't change the digest.
+//! * Character references, the predefined entities and CDATA sections are text. Comments,
+//!   processing instructions and the XML declaration are ignored.
+//! * Names keep their namespace prefix as written and `xmlns` declarations are attributes.
+//!
+//! Neither `@` nor `#` can start an XML name, so the reserved keys never clash with an element.
+//! Document type declarations are rejected: entities other than the predefined ones are never
+//! expanded.
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate blot;
+//! use blot::multihash::Sha2256;
+//! use blot::value::Value;
+//! use blot::xml;
+//!
+//! # fn main() {
+//! let input = r#"<feed lang="en">
+//!     <item>foo</item>
+//!     <item>bar</item>
+//!     <empty/>
+//! </feed>"#;
+//! let value: Value<Sha2256> = xml::from_str(input).unwrap();
+//!
+//! assert_eq!(
+//!     value,
+//!     dict! {
+//!         "feed" => dict! {
+//!             "@attributes" => dict! {"lang" => "en"},
+//!             "item" => list!["foo", "bar"],
+//!             "empty" => Value::Null
+//!         }
+//!     }
+//! );
+//! # }
+//! ```
+
+use multihash::Multihash;
+use std::error;
+use std::fmt;
+use value::{DictMap, Value};
+
+/// The reserved key for the attributes of an element.
+pub const ATTRIBUTES_KEY: &str = "@attributes";
+/// The reserved key for the text of an element with attributes or child elements.
+pub const TEXT_KEY: &str = "#text";
+
+#[derive(Debug, PartialEq)]
+pub enum XmlError {
+    /// The document ended before its root element was closed.
+    UnexpectedEnd,
+    /// The document doesn't follow the XML syntax at the byte offset.
+    Syntax {
+        position: usize,
+        expected: &'static str,
+    },
+    /// A closing tag doesn't match the open element.
+    MismatchedTag {
+        position: usize,
+        expected: String,
+        found: String,
+    },
+    /// An entity reference is neither predefined nor a valid character reference.
+    UnknownEntity { position: usize, found: String },
+    /// An element has the same attribute more than once.
+    DuplicateAttribute { position: usize, name: String },
+    /// The document has a document type declaration.
+    Doctype { position: usize },
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XmlError::UnexpectedEnd => write!(formatter, "unexpected end of document"),
+            XmlError::Syntax { position, expected } => {
+                write!(formatter, "expected {} at byte {}", expected, position)
+            }
+            XmlError::MismatchedTag {
+                position,
+                expected,
+                found,
+            } => write!(
+                formatter,
+                "expected '</{}>', found '</{}>' at byte {}",
+                expected, found, position
+            ),
+            XmlError::UnknownEntity { position, found } => {
+                write!(
+                    formatter,
+                    "unknown entity '&{};' at byte {}",
+                    found, position
+                )
+            }
+            XmlError::DuplicateAttribute { position, name } => {
+                write!(
+                    formatter,
+                    "duplicate attribute '{}' at byte {}",
+                    name, position
+                )
+            }
+            XmlError::Doctype { position } => write!(
+                formatter,
+                "document type declarations are not supported, found one at byte {}",
+                position
+            ),
+        }
+    }
+}
+
+impl error::Error for XmlError {}
+
+/// Reads an XML document as a value.
+pub fn from_str<T: Multihash>(input: &str) -> Result<Value<T>, XmlError> {
+    let mut parser = Parser { input, position: 0 };
+
+    parser.skip_misc()?;
+    let (name, value) = parser.element()?;
+    parser.skip_misc()?;
+
+    if parser.position < input.len() {
+        return Err(parser.syntax("the end of the document"));
+    }
+
+    let mut dict = DictMap::new();
+    dict.insert(name, value);
+
+    Ok(Value::Dict(dict))
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.rest().starts_with(prefix)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn syntax(&self, expected: &'static str) -> XmlError {
+        if self.position >= self.input.len() {
+            XmlError::UnexpectedEnd
+        } else {
+            XmlError::Syntax {
+                position: self.position,
+                expected,
+            }
+        }
+    }
+
+    fn expect(&mut self, token: &'static str) -> Result<(), XmlError> {
+        if self.starts_with(token) {
+            self.position += token.len();
+            Ok(())
+        } else {
+            Err(self.syntax(token))
+        }
+    }
+
+    /// Moves past the terminator, returning the text before it.
+    fn until(&mut self, terminator: &'static str) -> Result<&'a str, XmlError> {
+        match self.rest().find(terminator) {
+            Some(index) => {
+                let text = &self.rest()[..index];
+                self.position += index + terminator.len();
+
+                Ok(text)
+            }
+            None => Err(XmlError::UnexpectedEnd),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start_matches(is_whitespace).len();
+    }
+
+    /// Skips whitespace, comments and processing instructions outside the root element.
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+
+            if self.starts_with("<!--") {
+                self.until("-->")?;
+            } else if self.starts_with("<?") {
+                self.until("?>")?;
+            } else if self.starts_with("<!DOCTYPE") {
+                return Err(XmlError::Doctype {
+                    position: self.position,
+                });
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, XmlError> {
+        let rest = self.rest();
+        let length = rest
+            .char_indices()
+            .find(|&(index, c)| !is_name_char(c) || (index == 0 && !is_name_start(c)))
+            .map_or(rest.len(), |(index, _)| index);
+
+        if length == 0 {
+            return Err(self.syntax("a name"));
+        }
+
+        self.position += length;
+
+        Ok(rest[..length].to_string())
+    }
+
+    /// Reads an element starting at its `<`.
+    fn element<T: Multihash>(&mut self) -> Result<(String, Value<T>), XmlError> {
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut attributes = DictMap::new();
+
+        loop {
+            let had_whitespace = self.peek().is_some_and(is_whitespace);
+            self.skip_whitespace();
+
+            if self.starts_with("/>") {
+                self.position += 2;
+                return Ok((name, element_value(attributes, Vec::new(), String::new())));
+            }
+
+            if self.starts_with(">") {
+                self.position += 1;
+                break;
+            }
+
+            if !had_whitespace {
+                return Err(self.syntax("whitespace"));
+            }
+
+            let position = self.position;
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.attribute_value()?;
+
+            if attributes.contains_key(&key) {
+                return Err(XmlError::DuplicateAttribute {
+                    position,
+                    name: key,
+                });
+            }
+
+            attributes.insert(key, Value::String(value));
+        }
+
+        let mut children: Vec<(String, Vec<Value<T>>)> = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            if self.starts_with("</") {
+                let position = self.position;
+                self.position += 2;
+                let found = self.name()?;
+
+                if found != name {
+                    return Err(XmlError::MismatchedTag {
+                        position,
+                        expected: name,
+                        found,
+                    });
+                }
+
+                self.skip_whitespace();
+                self.expect(">")?;
+
+                return Ok((name, element_value(attributes, children, text)));
+            } else if self.starts_with("<!--") {
+                self.until("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.position += 9;
+                push_text(&mut text, self.until("]]>")?);
+            } else if self.starts_with("<?") {
+                self.until("?>")?;
+            } else if self.starts_with("<!") {
+                return Err(self.syntax("an element"));
+            } else if self.starts_with("<") {
+                let (child, value) = self.element()?;
+
+                match children.iter_mut().find(|(name, _)| *name == child) {
+                    Some((_, values)) => values.push(value),
+                    None => children.push((child, vec![value])),
+                }
+            } else if self.starts_with("&") {
+                text.push(self.reference()?);
+            } else {
+                let rest = self.rest();
+                let length = rest.find(['<', '&']).unwrap_or(rest.len());
+
+                if length == 0 {
+                    return Err(XmlError::UnexpectedEnd);
+                }
+
+                push_text(&mut text, &rest[..length]);
+                self.position += length;
+            }
+        }
+    }
+
+    /// Reads a quoted attribute value, with its whitespace normalized to spaces.
+    fn attribute_value(&mut self) -> Result<String, XmlError> {
+        let quote = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => return Err(self.syntax("a quoted value")),
+        };
+        let mut value = String::new();
+        self.position += 1;
+
+        loop {
+            match self.peek() {
+                None => return Err(XmlError::UnexpectedEnd),
+                Some(c) if c == quote => {
+                    self.position += 1;
+                    return Ok(value);
+                }
+                Some('<') => return Err(self.syntax("no '<' in an attribute value")),
+                Some('&') => value.push(self.reference()?),
+                Some(c) => {
+                    // A CRLF pair is a single line break.
+                    if !(c == '\r' && self.rest()[1..].starts_with('\n')) {
+                        value.push(if is_whitespace(c) { ' ' } else { c });
+                    }
+
+                    self.position += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Reads a character or predefined entity reference starting at its `&`.
+    fn reference(&mut self) -> Result<char, XmlError> {
+        let position = self.position;
+        self.position += 1;
+        let found = self.until(";")?;
+        let character = match found {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if found.starts_with("#x") => u32::from_str_radix(&found[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            _ if found.starts_with('#') => found[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+
+        character.ok_or_else(|| XmlError::UnknownEntity {
+            position,
+            found: found.to_string(),
+        })
+    }
+}
+
+/// Appends text with its line breaks normalized to `\n`.
+fn push_text(text: &mut String, raw: &str) {
+    if raw.contains('\r') {
+        text.push_str(&raw.replace("\r\n", "\n").replace('\r', "\n"));
+    } else {
+        text.push_str(raw);
+    }
+}
+
+fn element_value<T: Multihash>(
+    attributes: DictMap<T>,
+    children: Vec<(String, Vec<Value<T>>)>,
+    text: String,
+) -> Value<T> {
+    if attributes.is_empty() && children.is_empty() {
+        return if text.is_empty() {
+            Value::Null
+        } else {
+            Value::String(text)
+        };
+    }
+
+    let mut dict = DictMap::new();
+
+    if !attributes.is_empty() {
+        dict.insert(ATTRIBUTES_KEY.to_string(), Value::Dict(attributes));
+    }
+
+    if !text.trim_matches(is_whitespace).is_empty() {
+        dict.insert(TEXT_KEY.to_string(), Value::String(text));
+    }
+
+    for (name, mut values) in children {
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::List(values)
+        };
+
+        dict.insert(name, value);
+    }
+
+    Value::Dict(dict)
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\n' || c == '\r'
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == ':'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == ':' || c == '-' || c == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Blot;
+    use dict;
+    use list;
+    use multihash::Sha2256;
+
+    #[test]
+    fn mapping() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- A feed -->
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Blot &amp; co</title>
+    <dc:creator>arnau</dc:creator>
+    <item id="1">First</item>
+    <item id="2"><![CDATA[<b>Second</b>]]></item>
+    <p>Hello <b>world</b>!</p>
+  </channel>
+</rss>"#;
+        let value: Value<Sha2256> = from_str(input).unwrap();
+
+        assert_eq!(
+            value,
+            dict! {
+                "rss" => dict! {
+                    "@attributes" => dict! {
+                        "version" => "2.0",
+                        "xmlns:dc" => "http://purl.org/dc/elements/1.1/"
+                    },
+                    "channel" => dict! {
+                        "title" => "Blot & co",
+                        "dc:creator" => "arnau",
+                        "item" => list![
+                            dict! {"@attributes" => dict! {"id" => "1"}, "#text" => "First"},
+                            dict! {"@attributes" => dict! {"id" => "2"}, "#text" => "<b>Second</b>"}
+                        ],
+                        "p" => dict! {"#text" => "Hello !", "b" => "world"}
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn formatting() {
+        let compact = "<a b='1'><c>x&#233;</c><c/><d>line\nbreak</d></a>";
+        let indented =
+            "<a  b=\"1\" >\r\n  <c>x&#xe9;</c>\r\n  <!-- empty -->\r\n  <c></c>\r\n  <d>line\r\nbreak</d>\r\n</a >\r\n";
+        let value: Value<Sha2256> = from_str(compact).unwrap();
+
+        assert_eq!(value["a"]["c"], list!["xé", Value::Null]);
+        assert_eq!(
+            from_str::<Sha2256>(indented).unwrap().digest(Sha2256),
+            value.digest(Sha2256)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let pairs = [
+            ("<a>", XmlError::UnexpectedEnd),
+            (
+                "<a></b>",
+                XmlError::MismatchedTag {
+                    position: 3,
+                    expected: "a".into(),
+                    found: "b".into(),
+                },
+            ),
+            (
+                "<a>&nbsp;</a>",
+                XmlError::UnknownEntity {
+                    position: 3,
+                    found: "nbsp".into(),
+                },
+            ),
+            (
+                "<a b='1' b='2'/>",
+                XmlError::DuplicateAttribute {
+                    position: 9,
+                    name: "b".into(),
+                },
+            ),
+            (
+                "<!DOCTYPE a [<!ENTITY x 'y'>]><a>&x;</a>",
+                XmlError::Doctype { position: 0 },
+            ),
+            (
+                "<a/><b/>",
+                XmlError::Syntax {
+                    position: 4,
+                    expected: "the end of the document",
+                },
+            ),
+            (
+                "<a b='1'c='2'/>",
+                XmlError::Syntax {
+                    position: 8,
+                    expected: "whitespace",
+                },
+            ),
+        ];
+
+        for (input, expected) in pairs.iter() {
+            assert_eq!(
+                &from_str::<Sha2256>(input).unwrap_err(),
+                expected,
+                "{}",
+                input
+            );
+        }
+    }
+}
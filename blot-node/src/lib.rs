@@ -0,0 +1,249 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Node.js bindings for blot.
+//!
+//! Values are native JavaScript values, converted as follows:
+//!
+//! * `null` and `undefined` are null. Object properties set to `undefined` are skipped, like
+//!   `JSON.stringify` does.
+//! * Integral numbers are integers and any other number is a float, so an object has the same
+//!   digest as its `JSON.stringify` form. `BigInt`s are integers.
+//! * Strings follow the heuristics of the JSON deserializer: seals, hexadecimal raw bytes and
+//!   timestamps are detected.
+//! * `Date`s are timestamps and `Buffer`s are raw bytes.
+//! * Arrays are lists, `Set`s are sets and any other object is a dictionary of its own enumerable
+//!   properties.
+
+#[macro_use]
+extern crate napi_derive;
+extern crate blot;
+extern crate napi;
+
+use blot::core::Blot;
+use blot::datetime::format_timestamp;
+use blot::multihash::{self, Multihash};
+use blot::value::de::DeserializerOptions;
+use blot::value::path::JsonPath;
+use blot::value::{DictMap, Value};
+use napi::{
+    Env, Error, JsBigInt, JsBuffer, JsDate, JsFunction, JsObject, JsUnknown, Result, Status,
+    ValueType,
+};
+
+/// Values nested deeper than this are rejected, which also guards against cycles.
+const MAX_DEPTH: usize = 128;
+
+/// Calls the function with the digester of the given algorithm name, `sha2-256` by default.
+macro_rules! dispatch {
+    ($name:expr, $function:ident($($arg:expr),*)) => {
+        match $name.as_deref().unwrap_or("sha2-256") {
+            "sha1" => $function($($arg),*, multihash::Sha1),
+            "sha2-224" => $function($($arg),*, multihash::Sha2224),
+            "sha2-256" => $function($($arg),*, multihash::Sha2256),
+            "sha2-384" => $function($($arg),*, multihash::Sha2384),
+            "dbl-sha2-256" => $function($($arg),*, multihash::DblSha2256),
+            "sha2-512" => $function($($arg),*, multihash::Sha2512),
+            "sha3-224" => $function($($arg),*, multihash::Sha3224),
+            "sha3-256" => $function($($arg),*, multihash::Sha3256),
+            "sha3-384" => $function($($arg),*, multihash::Sha3384),
+            "sha3-512" => $function($($arg),*, multihash::Sha3512),
+            "keccak-256" => $function($($arg),*, multihash::Keccak256),
+            "keccak-512" => $function($($arg),*, multihash::Keccak512),
+            "blake2b-512" => $function($($arg),*, multihash::Blake2b512),
+            "blake2s-256" => $function($($arg),*, multihash::Blake2s256),
+            "blake3" => $function($($arg),*, multihash::Blake3),
+            name => Err(invalid(format!("unsupported algorithm '{}'", name))),
+        }
+    };
+}
+
+/// Returns the hexadecimal multihash of the value.
+#[napi]
+pub fn digest(env: Env, value: JsUnknown, algorithm: Option<String>) -> Result<String> {
+    dispatch!(algorithm, digest_with(&env, value))
+}
+
+/// Whether the value digests to the expected hexadecimal multihash.
+#[napi]
+pub fn verify(
+    env: Env,
+    value: JsUnknown,
+    expected: String,
+    algorithm: Option<String>,
+) -> Result<bool> {
+    let actual = dispatch!(algorithm, digest_with(&env, value))?;
+
+    Ok(actual.eq_ignore_ascii_case(&expected))
+}
+
+/// Replaces the nodes at the given paths, e.g. `a.b[0]`, with their seals. The result has the
+/// same digest as the original value and is returned in its JSON form.
+#[napi]
+pub fn redact(
+    env: Env,
+    value: JsUnknown,
+    paths: Vec<String>,
+    algorithm: Option<String>,
+) -> Result<JsUnknown> {
+    dispatch!(algorithm, redact_with(&env, value, &paths))
+}
+
+fn digest_with<T: Multihash>(env: &Env, value: JsUnknown, digester: T) -> Result<String> {
+    let value: Value<T> = Converter::new(env)?.convert(value, 0)?;
+
+    Ok(value.digest(digester).to_string())
+}
+
+fn redact_with<T: Multihash + Clone>(
+    env: &Env,
+    value: JsUnknown,
+    paths: &[String],
+    digester: T,
+) -> Result<JsUnknown> {
+    let value: Value<T> = Converter::new(env)?.convert(value, 0)?;
+    let paths = paths
+        .iter()
+        .map(|path| {
+            path.parse::<JsonPath>()
+                .map_err(|err| invalid(format!("invalid path '{}': {}", path, err)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    env.to_js_value(&value.redact_paths_with(&paths, &digester))
+}
+
+fn invalid(reason: String) -> Error {
+    Error::new(Status::InvalidArg, reason)
+}
+
+/// Converts JavaScript values into blot values.
+struct Converter {
+    options: DeserializerOptions,
+    /// `Array.from`, to list the elements of sets.
+    array_from: JsFunction,
+    /// `Object.keys`, to list the properties of objects like `JSON.stringify` does.
+    object_keys: JsFunction,
+    set: JsFunction,
+}
+
+impl Converter {
+    fn new(env: &Env) -> Result<Converter> {
+        let global = env.get_global()?;
+
+        Ok(Converter {
+            options: DeserializerOptions::new(),
+            array_from: global
+                .get_named_property::<JsFunction>("Array")?
+                .coerce_to_object()?
+                .get_named_property("from")?,
+            object_keys: global
+                .get_named_property::<JsFunction>("Object")?
+                .coerce_to_object()?
+                .get_named_property("keys")?,
+            set: global.get_named_property("Set")?,
+        })
+    }
+
+    fn convert<T: Multihash>(&self, value: JsUnknown, depth: usize) -> Result<Value<T>> {
+        if depth > MAX_DEPTH {
+            return Err(invalid(format!(
+                "value nested deeper than {} levels",
+                MAX_DEPTH
+            )));
+        }
+
+        match value.get_type()? {
+            ValueType::Null | ValueType::Undefined => Ok(Value::Null),
+            ValueType::Boolean => Ok(Value::Bool(value.coerce_to_bool()?.get_value()?)),
+            ValueType::Number => {
+                let number = value.coerce_to_number()?.get_double()?;
+
+                if number.fract() == 0.0 && number.abs() <= 9_007_199_254_740_991.0 {
+                    Ok(Value::Integer(number as i64))
+                } else {
+                    Ok(Value::Float(number))
+                }
+            }
+            ValueType::BigInt => {
+                let bigint = unsafe { value.cast::<JsBigInt>() };
+
+                match bigint.get_i64()? {
+                    (integer, true) => Ok(Value::Integer(integer)),
+                    _ => match bigint.get_u64()? {
+                        (integer, true) => Ok(Value::UInteger(integer)),
+                        _ => Err(invalid("BigInt out of the 64-bit range".to_string())),
+                    },
+                }
+            }
+            ValueType::String => {
+                let string = value.coerce_to_string()?.into_utf8()?.into_owned()?;
+
+                self.options
+                    .string_value(&string)
+                    .map_err(|err| invalid(err.to_string()))
+            }
+            ValueType::Object if value.is_date()? => {
+                let millis = unsafe { value.cast::<JsDate>() }.value_of()?;
+
+                if !millis.is_finite() {
+                    return Err(invalid("invalid Date".to_string()));
+                }
+
+                let seconds = (millis / 1000.0).floor();
+                let nanos = ((millis - seconds * 1000.0) * 1_000_000.0).round() as u32;
+
+                Ok(Value::Timestamp(format_timestamp(seconds as i64, nanos)))
+            }
+            ValueType::Object if value.is_buffer()? => {
+                let buffer = unsafe { value.cast::<JsBuffer>() }.into_value()?;
+
+                Ok(Value::Raw(buffer.to_vec()))
+            }
+            ValueType::Object if value.is_array()? => {
+                let list = self.elements(value.coerce_to_object()?, depth)?;
+
+                Ok(Value::List(list))
+            }
+            ValueType::Object if value.instanceof(&self.set)? => {
+                let array = self.array_from.call(None, &[value])?;
+                let list = self.elements(array.coerce_to_object()?, depth)?;
+
+                Ok(Value::Set(list))
+            }
+            ValueType::Object => {
+                let object = value.coerce_to_object()?;
+                let keys = self
+                    .object_keys
+                    .call(None, &[&object])?
+                    .coerce_to_object()?;
+                let mut dict = DictMap::new();
+
+                for index in 0..keys.get_array_length()? {
+                    let key = keys
+                        .get_element::<JsUnknown>(index)?
+                        .coerce_to_string()?
+                        .into_utf8()?
+                        .into_owned()?;
+                    let child: JsUnknown = object.get_named_property(&key)?;
+
+                    if child.get_type()? != ValueType::Undefined {
+                        dict.insert(key, self.convert(child, depth + 1)?);
+                    }
+                }
+
+                Ok(Value::Dict(dict))
+            }
+            other => Err(invalid(format!("unsupported JavaScript type {}", other))),
+        }
+    }
+
+    fn elements<T: Multihash>(&self, array: JsObject, depth: usize) -> Result<Vec<Value<T>>> {
+        (0..array.get_array_length()?)
+            .map(|index| self.convert(array.get_element::<JsUnknown>(index)?, depth + 1))
+            .collect()
+    }
+}
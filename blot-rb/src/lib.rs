@@ -0,0 +1,286 @@
+// Copyright 2018 Arnau Siches
+//
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except according to
+// those terms.
+
+//! Ruby bindings for blot.
+//!
+//! The extension defines the `Blot` module. Values are native Ruby objects, converted as follows:
+//!
+//! * `nil` is null, `true` and `false` are booleans.
+//! * `Integer`s are integers and `Float`s are floats.
+//! * Strings in the `ASCII-8BIT` (binary) encoding are raw bytes. Any other string, and any
+//!   symbol, follows the heuristics of the JSON deserializer: seals, hexadecimal raw bytes and
+//!   timestamps are detected.
+//! * `Time`s are timestamps.
+//! * `Array`s are lists, `Set`s are sets and `Hash`es with string or symbol keys are
+//!   dictionaries.
+
+extern crate blot;
+extern crate magnus;
+extern crate serde_json;
+
+use blot::core::Blot;
+use blot::datetime::format_timestamp;
+use blot::multihash::{self, Multihash};
+use blot::value::de::DeserializerOptions;
+use blot::value::path::JsonPath;
+use blot::value::{DictMap, Value};
+use magnus::prelude::*;
+use magnus::r_hash::ForEach;
+use magnus::scan_args::scan_args;
+use magnus::{function, Error, Float, Integer, RArray, RClass, RHash, RString, Ruby, Symbol, Time};
+
+/// Values nested deeper than this are rejected, which also guards against cycles.
+const MAX_DEPTH: usize = 128;
+
+/// Calls the function with the digester of the given algorithm name, `sha2-256` by default.
+macro_rules! dispatch {
+    ($ruby:expr, $name:expr, $function:ident($($arg:expr),*)) => {
+        match $name.as_deref().unwrap_or("sha2-256") {
+            "identity" => $function($($arg),*, multihash::Identity),
+            "sha1" => $function($($arg),*, multihash::Sha1),
+            "sha2-224" => $function($($arg),*, multihash::Sha2224),
+            "sha2-256" => $function($($arg),*, multihash::Sha2256),
+            "sha2-384" => $function($($arg),*, multihash::Sha2384),
+            "dbl-sha2-256" => $function($($arg),*, multihash::DblSha2256),
+            "sha2-512" => $function($($arg),*, multihash::Sha2512),
+            "sha3-224" => $function($($arg),*, multihash::Sha3224),
+            "sha3-256" => $function($($arg),*, multihash::Sha3256),
+            "sha3-384" => $function($($arg),*, multihash::Sha3384),
+            "sha3-512" => $function($($arg),*, multihash::Sha3512),
+            "keccak-256" => $function($($arg),*, multihash::Keccak256),
+            "keccak-512" => $function($($arg),*, multihash::Keccak512),
+            "blake2b-512" => $function($($arg),*, multihash::Blake2b512),
+            "blake2s-256" => $function($($arg),*, multihash::Blake2s256),
+            "blake3" => $function($($arg),*, multihash::Blake3),
+            name => Err(invalid($ruby, format!("unsupported algorithm '{}'", name))),
+        }
+    };
+}
+
+#[magnus::init(name = "blot")]
+fn init(ruby: &Ruby) -> Result<(), Error> {
+    ruby.require("set")?;
+
+    let module = ruby.define_module("Blot")?;
+    module.define_module_function("digest", function!(digest, -1))?;
+    module.define_module_function("verify", function!(verify, -1))?;
+    module.define_module_function("redact", function!(redact, -1))?;
+
+    Ok(())
+}
+
+/// `Blot.digest(value, algorithm = "sha2-256")`: the hexadecimal multihash of the value.
+fn digest(ruby: &Ruby, args: &[magnus::Value]) -> Result<String, Error> {
+    let args = scan_args::<(magnus::Value,), (Option<String>,), (), (), (), ()>(args)?;
+    let (value,) = args.required;
+    let (algorithm,) = args.optional;
+
+    dispatch!(ruby, algorithm, digest_with(ruby, value))
+}
+
+/// `Blot.verify(value, expected, algorithm = "sha2-256")`: whether the value digests to the
+/// expected hexadecimal multihash.
+fn verify(ruby: &Ruby, args: &[magnus::Value]) -> Result<bool, Error> {
+    let args = scan_args::<(magnus::Value, String), (Option<String>,), (), (), (), ()>(args)?;
+    let (value, expected) = args.required;
+    let (algorithm,) = args.optional;
+    let actual = dispatch!(ruby, algorithm, digest_with(ruby, value))?;
+
+    Ok(actual.eq_ignore_ascii_case(&expected))
+}
+
+/// `Blot.redact(value, paths, algorithm = "sha2-256")`: replaces the nodes at the given paths,
+/// e.g. `a.b[0]`, with their seals. The result has the same digest as the original value and is
+/// returned in its JSON form.
+fn redact(ruby: &Ruby, args: &[magnus::Value]) -> Result<magnus::Value, Error> {
+    let args = scan_args::<(magnus::Value, Vec<String>), (Option<String>,), (), (), (), ()>(args)?;
+    let (value, paths) = args.required;
+    let (algorithm,) = args.optional;
+
+    dispatch!(ruby, algorithm, redact_with(ruby, value, &paths))
+}
+
+fn digest_with<T: Multihash>(
+    ruby: &Ruby,
+    value: magnus::Value,
+    digester: T,
+) -> Result<String, Error> {
+    let value: Value<T> = Converter::new(ruby)?.convert(value, 0)?;
+
+    Ok(value.digest(digester).to_string())
+}
+
+fn redact_with<T: Multihash + Clone>(
+    ruby: &Ruby,
+    value: magnus::Value,
+    paths: &[String],
+    _: T,
+) -> Result<magnus::Value, Error> {
+    let value: Value<T> = Converter::new(ruby)?.convert(value, 0)?;
+    let paths = paths
+        .iter()
+        .map(|path| {
+            path.parse::<JsonPath>()
+                .map_err(|err| invalid(ruby, format!("invalid path '{}': {}", path, err)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let json = serde_json::to_value(value.redact_paths(&paths))
+        .map_err(|err| invalid(ruby, err.to_string()))?;
+
+    from_json(ruby, json)
+}
+
+fn invalid(ruby: &Ruby, reason: String) -> Error {
+    Error::new(ruby.exception_arg_error(), reason)
+}
+
+/// Converts a JSON value into the equivalent Ruby object.
+fn from_json(ruby: &Ruby, json: serde_json::Value) -> Result<magnus::Value, Error> {
+    let value = match json {
+        serde_json::Value::Null => ruby.qnil().as_value(),
+        serde_json::Value::Bool(boolean) => ruby.into_value(boolean),
+        serde_json::Value::Number(number) => {
+            if let Some(integer) = number.as_i64() {
+                ruby.integer_from_i64(integer).as_value()
+            } else if let Some(integer) = number.as_u64() {
+                ruby.integer_from_u64(integer).as_value()
+            } else {
+                ruby.float_from_f64(number.as_f64().unwrap_or(f64::NAN))
+                    .as_value()
+            }
+        }
+        serde_json::Value::String(string) => ruby.str_new(&string).as_value(),
+        serde_json::Value::Array(list) => {
+            let array = ruby.ary_new_capa(list.len());
+
+            for item in list {
+                array.push(from_json(ruby, item)?)?;
+            }
+
+            array.as_value()
+        }
+        serde_json::Value::Object(object) => {
+            let hash = ruby.hash_new();
+
+            for (key, item) in object {
+                hash.aset(key, from_json(ruby, item)?)?;
+            }
+
+            hash.as_value()
+        }
+    };
+
+    Ok(value)
+}
+
+/// Converts Ruby objects into blot values.
+struct Converter<'a> {
+    ruby: &'a Ruby,
+    options: DeserializerOptions,
+    set: RClass,
+}
+
+impl<'a> Converter<'a> {
+    fn new(ruby: &'a Ruby) -> Result<Converter<'a>, Error> {
+        Ok(Converter {
+            ruby,
+            options: DeserializerOptions::new(),
+            set: ruby.class_object().const_get("Set")?,
+        })
+    }
+
+    fn convert<T: Multihash>(&self, value: magnus::Value, depth: usize) -> Result<Value<T>, Error> {
+        let ruby = self.ruby;
+
+        if depth > MAX_DEPTH {
+            return Err(invalid(
+                ruby,
+                format!("value nested deeper than {} levels", MAX_DEPTH),
+            ));
+        }
+
+        if value.is_nil() {
+            Ok(Value::Null)
+        } else if value.is_kind_of(ruby.class_true_class()) {
+            Ok(Value::Bool(true))
+        } else if value.is_kind_of(ruby.class_false_class()) {
+            Ok(Value::Bool(false))
+        } else if let Some(integer) = Integer::from_value(value) {
+            match integer.to_i64() {
+                Ok(integer) => Ok(Value::Integer(integer)),
+                Err(_) => integer.to_u64().map(Value::UInteger).map_err(|_| {
+                    Error::new(
+                        ruby.exception_range_error(),
+                        "Integer out of the 64-bit range",
+                    )
+                }),
+            }
+        } else if let Some(float) = Float::from_value(value) {
+            Ok(Value::Float(float.to_f64()))
+        } else if let Some(string) = RString::from_value(value) {
+            if string.enc_get() == ruby.ascii8bit_encindex() {
+                // Safe because the bytes are copied before any other Ruby call.
+                Ok(Value::Raw(unsafe { string.as_slice() }.to_vec()))
+            } else {
+                self.string(&string.to_string()?)
+            }
+        } else if let Some(symbol) = Symbol::from_value(value) {
+            self.string(&symbol.name()?)
+        } else if let Some(time) = Time::from_value(value) {
+            let timespec = time.timespec()?;
+
+            Ok(Value::Timestamp(format_timestamp(
+                timespec.tv_sec,
+                timespec.tv_nsec as u32,
+            )))
+        } else if let Some(array) = RArray::from_value(value) {
+            Ok(Value::List(self.elements(array, depth)?))
+        } else if value.is_kind_of(self.set) {
+            let array: RArray = value.funcall("to_a", ())?;
+
+            Ok(Value::Set(self.elements(array, depth)?))
+        } else if let Some(hash) = RHash::from_value(value) {
+            let mut dict = DictMap::new();
+
+            hash.foreach(|key: magnus::Value, child: magnus::Value| {
+                let key = if let Some(symbol) = Symbol::from_value(key) {
+                    symbol.name()?.into_owned()
+                } else if let Some(string) = RString::from_value(key) {
+                    string.to_string()?
+                } else {
+                    return Err(Error::new(
+                        ruby.exception_type_error(),
+                        format!("Hash keys must be strings or symbols, not {}", key.class()),
+                    ));
+                };
+
+                dict.insert(key, self.convert(child, depth + 1)?);
+
+                Ok(ForEach::Continue)
+            })?;
+
+            Ok(Value::Dict(dict))
+        } else {
+            Err(Error::new(
+                ruby.exception_type_error(),
+                format!("unsupported Ruby type {}", value.class()),
+            ))
+        }
+    }
+
+    fn string<T: Multihash>(&self, string: &str) -> Result<Value<T>, Error> {
+        self.options
+            .string_value(string)
+            .map_err(|err| invalid(self.ruby, err.to_string()))
+    }
+
+    fn elements<T: Multihash>(&self, array: RArray, depth: usize) -> Result<Vec<Value<T>>, Error> {
+        array
+            .into_iter()
+            .map(|item| self.convert(item, depth + 1))
+            .collect()
+    }
+}
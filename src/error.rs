@@ -0,0 +1,168 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Errors reported by the command line.
+//!
+//! Every error maps to a distinct exit code, following `sysexits.h`. A failed verification or
+//! comparison is not an error: it exits with 1.
+
+use blot::seal::SealError;
+use blot::stream::StreamError;
+use blot::value::de::DeserializeError;
+use blot::value::traverse::DigestError;
+use std::fmt;
+use std::io;
+
+/// The invalid arguments exit code (`EX_USAGE`).
+pub const USAGE: i32 = 64;
+/// The invalid input exit code (`EX_DATAERR`).
+pub const DATA: i32 = 65;
+/// The unsupported algorithm exit code (`EX_UNAVAILABLE`).
+pub const UNAVAILABLE: i32 = 69;
+/// The input/output exit code (`EX_IOERR`).
+pub const IO: i32 = 74;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An argument value is not valid.
+    Argument { name: &'static str, message: String },
+    /// The input is not a valid document.
+    Parse {
+        error: DeserializeError,
+        context: Option<Context>,
+    },
+    /// The document cannot be digested with the given options.
+    Digest(DigestError),
+    /// A record of newline-delimited JSON is not valid.
+    Record {
+        line: usize,
+        error: DeserializeError,
+    },
+    /// The multihash algorithm is not supported by this build.
+    UnsupportedAlgorithm(String),
+    /// Reading the input failed.
+    Io {
+        path: Option<String>,
+        error: io::Error,
+    },
+}
+
+/// The line of the input where a parse error happened.
+#[derive(Debug)]
+pub struct Context {
+    line: usize,
+    column: usize,
+    text: String,
+}
+
+impl Error {
+    pub fn argument<E: fmt::Display>(name: &'static str, error: E) -> Error {
+        Error::Argument {
+            name,
+            message: error.to_string(),
+        }
+    }
+
+    /// Wraps a deserialization error, keeping the offending line of the input.
+    pub fn parse(input: &str, error: DeserializeError) -> Error {
+        let context = match error {
+            DeserializeError::Json(ref json) if json.line() > 0 => {
+                input.lines().nth(json.line() - 1).map(|text| Context {
+                    line: json.line(),
+                    column: json.column(),
+                    text: text.to_string(),
+                })
+            }
+            _ => None,
+        };
+
+        Error::Parse { error, context }
+    }
+
+    pub fn io(path: Option<&str>, error: io::Error) -> Error {
+        Error::Io {
+            path: path.map(str::to_string),
+            error,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Argument { .. } => USAGE,
+            Error::Parse { .. } | Error::Digest(_) | Error::Record { .. } => DATA,
+            Error::UnsupportedAlgorithm(_) => UNAVAILABLE,
+            Error::Io { .. } => IO,
+        }
+    }
+}
+
+impl From<StreamError> for Error {
+    fn from(error: StreamError) -> Error {
+        match error {
+            StreamError::Io(error) => Error::io(None, error),
+            StreamError::Json { line, error } => Error::Record {
+                line,
+                error: DeserializeError::Json(error),
+            },
+            StreamError::Document { line, error } => Error::Record { line, error },
+        }
+    }
+}
+
+impl From<DigestError> for Error {
+    fn from(error: DigestError) -> Error {
+        Error::Digest(error)
+    }
+}
+
+impl From<SealError> for Error {
+    fn from(error: SealError) -> Error {
+        match error {
+            SealError::UnknownStamp(code) => Error::UnsupportedAlgorithm(format!("{:#x}", code)),
+            error => Error::argument("expected", error),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Argument { name, message } => write!(formatter, "invalid {}: {}", name, message),
+            Error::Parse { error, context } => {
+                write!(formatter, "invalid input: {}", error)?;
+
+                if let Some(context) = context {
+                    let gutter = context.line.to_string();
+
+                    write!(
+                        formatter,
+                        "\n{} | {}\n{:gutter$} | {:column$}^",
+                        gutter,
+                        context.text,
+                        "",
+                        "",
+                        gutter = gutter.len(),
+                        column = context.column.saturating_sub(1)
+                    )?;
+                }
+
+                Ok(())
+            }
+            Error::Digest(error) => write!(formatter, "invalid input: {}", error),
+            Error::Record { line, error } => {
+                write!(formatter, "invalid record at line {}: {}", line, error)
+            }
+            Error::UnsupportedAlgorithm(name) => {
+                write!(formatter, "unsupported algorithm: {}", name)
+            }
+            Error::Io {
+                path: Some(path),
+                error,
+            } => write!(formatter, "{}: {}", path, error),
+            Error::Io { path: None, error } => write!(formatter, "{}", error),
+        }
+    }
+}
@@ -8,18 +8,123 @@
 extern crate clap;
 extern crate ansi_term;
 extern crate blot;
+extern crate glob;
+#[macro_use]
 extern crate serde_json;
+#[cfg(feature = "server")]
+extern crate tiny_http;
+
+mod error;
+mod output;
+mod progress;
 
-use ansi_term::Colour::{Black, Fixed};
+use ansi_term::Colour::{Green, Red, Yellow};
+use blot::audit;
+use blot::chain::EntryLog;
 use blot::core::Blot;
-use blot::multihash::{self, Hash, Multihash};
-use blot::value::Value;
-use std::io::{self, Read};
+use blot::datetime::format_timestamp;
+use blot::diff::{self, PathDiff};
+use blot::jcs;
+use blot::json::Numbers;
+use blot::multihash::{
+    self, Algorithm, AlgorithmPolicy, Hash, Keyable, KeyedMultihash, Multihash, Registry,
+};
+use blot::register;
+use blot::seal::{self, Seal};
+use blot::stream::{self, Progress, RawStream};
+use blot::value::de::{DeserializeError, DeserializerOptions, DuplicateKeys};
+use blot::value::json_schema;
+use blot::value::path::JsonPath;
+use blot::value::schema::{Kind, Schema};
+use blot::value::traverse::{DigestOptions, Duplicates};
+use blot::value::{DictMap, Value};
+use blot::vectors;
+use error::Error;
+use glob::Pattern;
+use output::{Palette, Printer};
+use progress::Meter;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+/// Calls the given command with the digester selected by the `--algorithm` argument.
+macro_rules! dispatch {
+    ($matches:expr, $command:ident($($arg:expr),*)) => {
+        dispatch!(@name $matches.value_of("algorithm").unwrap(), $command($($arg),*))
+    };
+    (@name $name:expr, $command:ident($($arg:expr),*)) => {
+        match $name {
+            "sha1" => $command($($arg),*, multihash::Sha1),
+            "sha2-224" => $command($($arg),*, multihash::Sha2224),
+            "sha2-256" => $command($($arg),*, multihash::Sha2256),
+            "sha2-384" => $command($($arg),*, multihash::Sha2384),
+            "dbl-sha2-256" => $command($($arg),*, multihash::DblSha2256),
+            "sha2-512" => $command($($arg),*, multihash::Sha2512),
+            "sha3-224" => $command($($arg),*, multihash::Sha3224),
+            "sha3-256" => $command($($arg),*, multihash::Sha3256),
+            "sha3-384" => $command($($arg),*, multihash::Sha3384),
+            "sha3-512" => $command($($arg),*, multihash::Sha3512),
+            "keccak-256" => $command($($arg),*, multihash::Keccak256),
+            "keccak-512" => $command($($arg),*, multihash::Keccak512),
+            "blake2b-512" => $command($($arg),*, multihash::Blake2b512),
+            "blake2s-256" => $command($($arg),*, multihash::Blake2s256),
+            "blake3" => $command($($arg),*, multihash::Blake3),
+            name => Err(Error::UnsupportedAlgorithm(name.to_string())),
+        }
+    };
+    (@keyed $key:expr, $matches:expr, $command:ident($($arg:expr),*)) => {
+        match ($matches.value_of("algorithm").unwrap(), $key) {
+            (name, None) => dispatch!(@name name, $command($($arg),*)),
+            ("sha2-256", Some(key)) => $command($($arg),*, keyed(multihash::Sha2256, key)?),
+            ("sha2-512", Some(key)) => $command($($arg),*, keyed(multihash::Sha2512, key)?),
+            ("blake2b-512", Some(key)) => $command($($arg),*, keyed(multihash::Blake2b512, key)?),
+            ("blake2s-256", Some(key)) => $command($($arg),*, keyed(multihash::Blake2s256, key)?),
+            (name, Some(_)) => Err(Error::argument(
+                "key-file",
+                format!("{} has no keyed mode", name),
+            )),
+        }
+    };
+}
 
-use clap::{App, AppSettings, Arg};
+// Declared after `dispatch!` so they can use it.
+mod rpc;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(feature = "ed25519")]
+mod sign;
+#[cfg(feature = "xml")]
+mod xml;
+
+#[cfg(feature = "ed25519")]
+use sign::{sign_command, verify_signature_command};
+#[cfg(feature = "xml")]
+use xml::xml_command;
 
 fn main() {
-    let matches = App::new(crate_name!())
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let registry = Registry::builtin();
+    let algorithms: Vec<&str> = registry
+        .algorithms()
+        .iter()
+        .filter(|algorithm| hashes_with(algorithm))
+        .map(Algorithm::name)
+        .collect();
+    let app = App::new(crate_name!())
         .version(crate_version!())
         .author(crate_authors!())
         .setting(AppSettings::ColoredHelp)
@@ -38,9 +143,12 @@ algorithm adapted to work with [Multihash] hints.
                     r#"
 JSON data to hash. For example, "foo", {"foo": "bar"}, [1, "foo"].
 
-Use a dash ('-') or no argument to read from standard input.
+Use a dash ('-') or no argument to read from standard input. Given more than
+one input, every input is read as a file path and its digest is printed next to
+its name.
                 "#,
                 )
+                .multiple(true)
                 .index(1),
         ).arg(
             Arg::with_name("algorithm")
@@ -48,114 +156,1523 @@ Use a dash ('-') or no argument to read from standard input.
                 .short("a")
                 .long("algorithm")
                 .takes_value(true)
+                .global(true)
                 .default_value("sha2-256")
-                .possible_values(&[
-                    "sha1",
-                    "sha2-256",
-                    "sha2-512",
-                    "sha3-224",
-                    "sha3-256",
-                    "sha3-384",
-                    "sha3-512",
-                    "blake2b-512",
-                    "blake2s-256",
-                ]),
+                .possible_values(&algorithms),
         ).arg(Arg::with_name("sequence")
               .help("Sequence mode. JSON")
               .long_help("JSON only has arrays but Blot has lists and sets where the former is hashed as is and the latter disregards the order of the items and ensures there are no duplicates.")
               .long("sequence")
               .takes_value(true)
+              .global(true)
               .default_value("list")
               .possible_values(&["list", "set"])
+        ).arg(
+            Arg::with_name("set-path")
+                .help("JSON pointer to a sequence to hash as a set, e.g. /users/*/roles")
+                .long_help("Hashes the arrays found at the given JSON pointer as sets. The '*' token matches any key or index. Can be given multiple times.")
+                .long("set-path")
+                .takes_value(true)
+                .value_name("POINTER")
+                .multiple(true)
+                .number_of_values(1)
+                .global(true)
+                .conflicts_with("lines"),
+        ).arg(
+            Arg::with_name("schema")
+                .help("JSON Schema used to type the input")
+                .long_help("Types the input nodes following the given JSON Schema: strings with 'format: date-time' are timestamps, arrays with 'uniqueItems: true' are sets and numbers follow their 'integer' or 'number' type.")
+                .long("schema")
+                .takes_value(true)
+                .value_name("PATH")
+                .global(true)
+                .conflicts_with("lines"),
         ).arg(
             Arg::with_name("verbose")
                 .help("Verbose mode")
                 .long("verbose"),
-        ).get_matches();
+        ).arg(
+            Arg::with_name("format")
+                .help("Output format")
+                .long_help("Output format. 'json' prints one JSON object per digest with the algorithm, code, length, digest and multihash, taking precedence over --verbose.")
+                .long("format")
+                .takes_value(true)
+                .global(true)
+                .default_value("text")
+                .possible_values(&["text", "json"]),
+        ).arg(
+            Arg::with_name("digest-format")
+                .help("Digest encoding")
+                .long_help("Digest encoding. 'multihash-hex' prints the hexadecimal multihash, 'bare-hex' only the hexadecimal digest, 'base64' and 'base58btc' the binary multihash in the given base and 'multibase' the base58btc multihash prefixed with its multibase code.")
+                .long("digest-format")
+                .takes_value(true)
+                .global(true)
+                .default_value("multihash-hex")
+                .possible_values(&["multihash-hex", "bare-hex", "base64", "base58btc", "multibase"]),
+        ).arg(
+            Arg::with_name("cid")
+                .help("Prints a CIDv1 instead of the multihash")
+                .long_help("Prints a base32 CIDv1 wrapping the multihash, for content of the given codec: raw (default), json, dag-json or dag-cbor. Overrides --digest-format.")
+                .long("cid")
+                .takes_value(true)
+                .value_name("CODEC")
+                .min_values(0)
+                .require_equals(true)
+                .global(true)
+                .possible_values(&["raw", "json", "dag-json", "dag-cbor"]),
+        ).arg(
+            Arg::with_name("color")
+                .help("When to use colors")
+                .long_help("When to use colors. 'auto' colors the output only when it is a terminal and the NO_COLOR environment variable is not set.")
+                .long("color")
+                .takes_value(true)
+                .value_name("WHEN")
+                .global(true)
+                .default_value("auto")
+                .possible_values(&["auto", "always", "never"]),
+        ).arg(
+            Arg::with_name("no-color")
+                .help("Disables colors, same as --color=never")
+                .long("no-color")
+                .global(true),
+        ).arg(
+            Arg::with_name("lines")
+                .help("Newline-delimited JSON mode")
+                .long_help("Treats the input as newline-delimited JSON (NDJSON) and prints one digest per record. Records are hashed as they are read.")
+                .long("lines")
+                .conflicts_with("raw"),
+        ).arg(
+            Arg::with_name("audit")
+                .help("Prints the inputs fed to the hasher")
+                .long_help("Prints every input fed to the hasher, tag byte included, to the standard error: one line per call with the tag, the input and the digest in hexadecimal. Helps finding where another Objecthash implementation disagrees.")
+                .long("audit")
+                .conflicts_with_all(&["raw", "lines", "check", "rpc"]),
+        ).arg(
+            Arg::with_name("stats")
+                .help("Prints hashing statistics")
+                .long_help("Prints a summary of the hashing to the standard error: the nodes hashed by tag, the bytes fed to the hasher, the deepest nesting and the elapsed time.")
+                .long("stats")
+                .conflicts_with_all(&["raw", "lines", "audit", "check", "rpc", "canon"]),
+        ).arg(
+            Arg::with_name("progress")
+                .help("Reports progress on large inputs")
+                .long_help("Draws a status line on the standard error with the bytes read and, with --lines, the records hashed so far. Only available with --raw or --lines.")
+                .long("progress")
+                .conflicts_with_all(&["audit", "stats", "check", "rpc"]),
+        ).arg(
+            Arg::with_name("raw")
+                .help("Raw mode")
+                .long_help("Hashes the input bytes as a raw value instead of parsing them as JSON. The input is read in chunks so it can be arbitrarily large.")
+                .long("raw"),
+        ).arg(
+            Arg::with_name("check")
+                .help("Checks the digests listed in the given file")
+                .long_help("Reads lines of the form '<multihash>  <file>', as printed by sha256sum and friends, digests every file with the algorithm of its multihash and reports OK or FAILED for each one. Files are hashed as JSON unless --raw is given. Exits with 1 if any file fails.")
+                .long("check")
+                .short("c")
+                .takes_value(true)
+                .value_name("SUMS")
+                .conflicts_with_all(&["lines", "input", "file"]),
+        ).arg(
+            Arg::with_name("file")
+                .help("Reads the input from the given file")
+                .long_help("Reads the input from the given file. Can be given multiple times to hash several files, printing each digest next to its file name. Can't be combined with positional inputs.")
+                .long("file")
+                .short("f")
+                .takes_value(true)
+                .value_name("PATH")
+                .multiple(true)
+                .number_of_values(1)
+                .global(true),
+        ).arg(
+            Arg::with_name("jobs")
+                .help("Number of files hashed in parallel")
+                .long("jobs")
+                .short("j")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("1")
+                .validator(|value| match value.parse::<usize>() {
+                    Ok(jobs) if jobs > 0 => Ok(()),
+                    _ => Err("must be a positive integer".into()),
+                }),
+        ).arg(
+            Arg::with_name("forbid")
+                .help("Rejects the given algorithm")
+                .long_help("Rejects the given algorithm, e.g. sha1: it can't be selected with --algorithm, seals computed with it fail to parse and expected digests using it fail verification. Can be given multiple times.")
+                .long("forbid")
+                .takes_value(true)
+                .value_name("ALGORITHM")
+                .multiple(true)
+                .number_of_values(1)
+                .global(true)
+                .possible_values(&algorithms),
+        ).arg(
+            Arg::with_name("numbers")
+                .help("How JSON numbers are hashed")
+                .long_help("How JSON numbers are hashed. 'auto' hashes integers as integers and the rest as floats, 'float' hashes every number as a float like the Objecthash common JSON. Defaults to 'auto', or 'float' with --objecthash. Types declared by --schema take precedence.")
+                .long("numbers")
+                .takes_value(true)
+                .value_name("MODE")
+                .global(true)
+                .possible_values(&["auto", "float"])
+                .conflicts_with("lines"),
+        ).arg(
+            Arg::with_name("duplicates")
+                .help("What to do with duplicate set members")
+                .long_help("What to do with set members that have the same digest. 'dedup' hashes them once, like Objecthash, and 'error' rejects the input. Defaults to 'dedup'.")
+                .long("duplicates")
+                .takes_value(true)
+                .value_name("POLICY")
+                .possible_values(&["dedup", "error"])
+                .conflicts_with_all(&["lines", "raw", "audit", "check", "rpc"]),
+        ).arg(
+            Arg::with_name("namespace")
+                .help("Separates the digests of an application")
+                .long_help("Mixes the given application namespace into the digest of every node, so applications hashing the same data with different namespaces get unrelated digests. The same namespace is needed to reproduce a digest.")
+                .long("namespace")
+                .takes_value(true)
+                .value_name("NAME")
+                .conflicts_with_all(&["lines", "raw", "audit", "check", "rpc", "canon"]),
+        ).arg(
+            Arg::with_name("duplicate-keys")
+                .help("What to do with duplicate dictionary keys")
+                .long_help("What to do with a key found more than once in the same dictionary. 'last' keeps the last value, like most JSON parsers, and 'error' rejects the input. Defaults to 'last'.")
+                .long("duplicate-keys")
+                .takes_value(true)
+                .value_name("POLICY")
+                .global(true)
+                .possible_values(&["last", "error"])
+                .conflicts_with("lines"),
+        ).arg(
+            Arg::with_name("canon")
+                .help("Canonicalization scheme")
+                .long_help("How the document is canonicalized before hashing. 'objecthash' hashes the typed value tree, 'jcs' hashes the RFC 8785 JSON canonical form as a single digest, for systems standardised on JCS. Defaults to 'objecthash'.")
+                .long("canon")
+                .takes_value(true)
+                .value_name("SCHEME")
+                .possible_values(&["objecthash", "jcs"])
+                .conflicts_with_all(&["lines", "raw", "audit", "duplicates", "objecthash", "rpc"]),
+        ).arg(
+            Arg::with_name("objecthash")
+                .help("Pure Objecthash compatibility mode")
+                .long_help("Digests like the original Objecthash: sha2-256 printed as the bare hexadecimal digest, strings never read as raw bytes nor timestamps, and seals accepted as '**REDACTED**' followed by a bare sha2-256 digest.")
+                .long("objecthash")
+                .global(true)
+                .conflicts_with_all(&["key-file", "cid", "lines"]),
+        ).arg(
+            Arg::with_name("key-file")
+                .help("Computes keyed digests with the key in the given file")
+                .long_help("Computes message authentication codes with the bytes of the given file as the secret key: HMAC for sha2-256 and sha2-512, the keyed mode for blake2b-512 and blake2s-256. Without the key, redacted values cannot be brute-forced.")
+                .long("key-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with("check"),
+        ).arg(
+            Arg::with_name("rpc")
+                .help("JSON-RPC mode")
+                .long_help("Reads JSON-RPC 2.0 requests from the standard input, one per line, and answers each on the standard output. Supports the digest, tree and compare methods. Documents are read with the same options as the command line, e.g. --sequence or --schema.")
+                .long("rpc")
+                .conflicts_with_all(&["input", "file", "check", "lines", "raw", "key-file"]),
+        ).subcommand(
+            SubCommand::with_name("redact")
+                .about("Replaces the nodes at the given paths with seals of their digest")
+                .long_about(r#"
+Replaces the nodes at the given paths with seals of their digest and prints the
+resulting JSON document. The redacted document has the same digest as the
+original.
+"#)
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the node to redact, e.g. a.b[2]")
+                        .long("path")
+                        .short("p")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(true),
+                ).arg(
+                    Arg::with_name("input")
+                        .help("The data as JSON")
+                        .index(1),
+                ),
+        ).subcommand(
+            SubCommand::with_name("tree")
+                .about("Prints the digest of every node of the document")
+                .long_about(r#"
+Prints the digest of every node of the document, one node per line indented by
+depth, alongside its path and tag. Useful to find where two documents diverge.
+"#)
+                .arg(
+                    Arg::with_name("input")
+                        .help("The data as JSON")
+                        .index(1),
+                ),
+        ).subcommand(
+            SubCommand::with_name("canonicalize")
+                .about("Prints the canonical JSON of the document")
+                .long_about(r#"
+Prints the document as canonical JSON: without whitespace, with dictionary keys
+sorted, set members sorted and deduplicated, floats in their shortest form and
+timestamps in UTC. Equal documents print the same, so the output can be stored
+alongside the digest.
+"#)
+                .arg(
+                    Arg::with_name("input")
+                        .help("The data as JSON")
+                        .index(1),
+                ),
+        ).subcommand(
+            SubCommand::with_name("item-hash")
+                .about("Prints the GOV.UK registers item hash of the input")
+                .long_about(r#"
+Prints the item hash of the input as GOV.UK registers define it: the SHA-256
+digest of its canonical JSON, e.g. sha-256:74d528a1…. It is not an Objecthash,
+so --algorithm, --sequence and the other hashing options don't apply.
+"#)
+                .arg(
+                    Arg::with_name("input")
+                        .help("The item as JSON")
+                        .index(1),
+                ),
+        ).subcommand(
+            SubCommand::with_name("log")
+                .about("Appends to and verifies hash-chained logs")
+                .long_about(r#"
+Maintains a hash-chained log: a file of newline-delimited JSON records with the
+timestamp, value and digest of every entry. The digest of each entry covers the
+previous one, so the digest of the last entry, the head, commits to the whole
+log. Use the same --algorithm to append to and verify a log.
+"#)
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("append")
+                        .about("Appends the input to the log and prints the new head")
+                        .arg(
+                            Arg::with_name("log")
+                                .help("Path to the log, created if missing")
+                                .required(true)
+                                .index(1),
+                        ).arg(
+                            Arg::with_name("input")
+                                .help("The data as JSON")
+                                .index(2),
+                        ).arg(
+                            Arg::with_name("timestamp")
+                                .help("RFC3339 timestamp of the entry, now by default")
+                                .long("timestamp")
+                                .takes_value(true),
+                        ),
+                ).subcommand(
+                    SubCommand::with_name("verify")
+                        .about("Verifies every entry of the log and prints its head")
+                        .long_about("Recomputes the digest of every entry of the log. Prints OK and the head if they match the recorded ones, and the head matches --head if given. Exits with 1 otherwise.")
+                        .arg(
+                            Arg::with_name("log")
+                                .help("Path to the log")
+                                .required(true)
+                                .index(1),
+                        ).arg(
+                            Arg::with_name("head")
+                                .help("The expected head multihash in hexadecimal")
+                                .long("head")
+                                .takes_value(true),
+                        ),
+                ),
+        ).subcommand(
+            SubCommand::with_name("compare")
+                .about("Reports the paths where two documents differ")
+                .long_about(r#"
+Compares two documents by digest and prints the minimal set of paths where they
+differ: '+' for added nodes, '-' for removed nodes and '~' for changed nodes.
+Exits with 1 if the documents differ.
+"#)
+                .arg(
+                    Arg::with_name("a")
+                        .help("Path to the first JSON document")
+                        .required(true)
+                        .index(1),
+                ).arg(
+                    Arg::with_name("b")
+                        .help("Path to the second JSON document")
+                        .required(true)
+                        .index(2),
+                ),
+        ).subcommand(
+            SubCommand::with_name("verify")
+                .about("Verifies that the input digest matches the expected multihash")
+                .long_about(r#"
+Digests the input, read from --file or the standard input, with the algorithm
+of the expected multihash and compares both. Prints OK and exits with 0 if they
+match, exits with 1 otherwise.
+"#)
+                .arg(
+                    Arg::with_name("expected")
+                        .help("The expected multihash in hexadecimal, e.g. 1220a6a6…")
+                        .required(true)
+                        .index(1),
+                ).arg(
+                    Arg::with_name("raw")
+                        .help("Hashes the input bytes as a raw value")
+                        .long("raw"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("dir")
+                .about("Digests a directory tree")
+                .long_about(r#"
+Digests a directory as a dictionary of entry names to their contents: files are
+raw values and subdirectories are nested dictionaries. Symbolic links are not
+followed: they are hashed as the string of their target path. Entry names must
+be valid UTF-8.
+
+Entries matching any --ignore glob are skipped. Globs are matched against the
+path relative to the given directory, e.g. 'target' or 'src/**/*.bak', and
+against the bare entry name, e.g. '*.o'.
+"#)
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the directory")
+                        .required(true)
+                        .index(1),
+                ).arg(
+                    Arg::with_name("ignore")
+                        .help("Glob of the entries to skip, e.g. '*.o'")
+                        .long("ignore")
+                        .short("i")
+                        .takes_value(true)
+                        .value_name("GLOB")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .validator(|value| {
+                            Pattern::new(&value).map(|_| ()).map_err(|err| err.to_string())
+                        }),
+                ).arg(
+                    Arg::with_name("verbose")
+                        .help("Verbose mode")
+                        .long("verbose"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("algorithms")
+                .about("Lists the available algorithms with their multihash code and digest length"),
+        ).subcommand(
+            SubCommand::with_name("vectors")
+                .about("Prints test vectors for other implementations")
+                .long_about("Prints JSON documents covering every tag and their digests with --algorithm, in the format of the Objecthash common_json.test golden file: a line with the document followed by a line with its digest in hexadecimal."),
+        ).subcommand(
+            SubCommand::with_name("verify-redacted")
+                .about("Verifies that a redacted document only seals nodes of the original")
+                .arg(
+                    Arg::with_name("original")
+                        .help("Path to the original JSON document")
+                        .required(true)
+                        .index(1),
+                ).arg(
+                    Arg::with_name("redacted")
+                        .help("Path to the redacted JSON document")
+                        .required(true)
+                        .index(2),
+                ),
+        );
+    #[cfg(feature = "server")]
+    let app = app.subcommand(server::subcommand());
+    #[cfg(feature = "ed25519")]
+    let app = app.subcommands(sign::subcommands());
+    #[cfg(feature = "xml")]
+    let app = app.subcommand(xml::subcommand());
+    let matches = app.get_matches();
+
+    if matches.is_present("key-file") && matches.subcommand_name().is_some() {
+        return Err(Error::argument(
+            "key-file",
+            "keyed digests are only supported when hashing",
+        ));
+    }
+
+    let active = matches.subcommand().1.unwrap_or(&matches);
+
+    if let Some(name) = active.value_of("algorithm") {
+        if active
+            .values_of("forbid")
+            .into_iter()
+            .flatten()
+            .any(|forbidden| forbidden == name)
+        {
+            return Err(Error::argument(
+                "algorithm",
+                format!("{} is forbidden", name),
+            ));
+        }
+    }
+
+    if active.is_present("objecthash") && active.value_of("algorithm") != Some("sha2-256") {
+        return Err(Error::argument(
+            "objecthash",
+            "only supported with sha2-256",
+        ));
+    }
+
+    if let ("algorithms", Some(matches)) = matches.subcommand() {
+        algorithms_command(&registry, matches.value_of("format") == Some("json"));
+
+        return Ok(());
+    }
+
+    if let ("vectors", Some(matches)) = matches.subcommand() {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+
+        return dispatch!(matches, vectors_command(&mut handle));
+    }
+
+    #[cfg(feature = "server")]
+    {
+        if let ("serve", Some(matches)) = matches.subcommand() {
+            return server::serve(matches);
+        }
+    }
+
+    #[cfg(feature = "ed25519")]
+    {
+        match matches.subcommand() {
+            ("sign", Some(matches)) => return dispatch!(matches, sign_command(matches)),
+            ("verify-signature", Some(matches)) => {
+                return dispatch!(matches, verify_signature_command(matches))
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "xml")]
+    {
+        if let ("xml", Some(matches)) = matches.subcommand() {
+            return dispatch!(matches, xml_command(matches));
+        }
+    }
+
+    if let ("verify", Some(matches)) = matches.subcommand() {
+        let expected: Seal<multihash::Sha2256> =
+            Seal::from_hash_str_in(matches.value_of("expected").unwrap(), &registry)?;
+        expected.check(&algorithm_policy(matches))?;
+        let seq_mode = matches.value_of("sequence").unwrap();
+        let options = deserializer_options(matches)?;
+
+        return dispatch!(
+            @name registry.name_of(expected.tag()).unwrap_or_default(),
+            verify_command(matches, &expected, seq_mode, &options)
+        );
+    }
+
+    if let ("verify-redacted", Some(matches)) = matches.subcommand() {
+        let original = read_file(matches.value_of("original").unwrap())?;
+        let redacted = read_file(matches.value_of("redacted").unwrap())?;
+        let seq_mode = matches.value_of("sequence").unwrap();
+        let options = deserializer_options(matches)?;
+
+        return dispatch!(
+            matches,
+            verify_redacted_command(&original, &redacted, seq_mode, &options)
+        );
+    }
+
+    if let ("compare", Some(matches)) = matches.subcommand() {
+        let a = read_file(matches.value_of("a").unwrap())?;
+        let b = read_file(matches.value_of("b").unwrap())?;
+        let seq_mode = matches.value_of("sequence").unwrap();
+        let options = deserializer_options(matches)?;
+        let palette = Printer::from_matches(matches).palette();
+
+        return dispatch!(
+            matches,
+            compare_command(&a, &b, seq_mode, &options, palette)
+        );
+    }
+
+    if let ("dir", Some(matches)) = matches.subcommand() {
+        let path = matches.value_of("path").unwrap();
+        let ignore: Vec<Pattern> = matches
+            .values_of("ignore")
+            .into_iter()
+            .flatten()
+            .map(|glob| Pattern::new(glob).map_err(|err| Error::argument("ignore", err)))
+            .collect::<Result<_, _>>()?;
+        let printer = Printer::from_matches(matches);
+
+        return dispatch!(matches, dir_command(Path::new(path), &ignore, &printer));
+    }
+
+    if let ("tree", Some(matches)) = matches.subcommand() {
+        let input = read_input(matches)?;
+        let seq_mode = matches.value_of("sequence").unwrap();
+        let options = deserializer_options(matches)?;
+
+        return dispatch!(matches, tree_command(&input, seq_mode, &options));
+    }
+
+    if let ("canonicalize", Some(matches)) = matches.subcommand() {
+        let input = read_input(matches)?;
+        let seq_mode = matches.value_of("sequence").unwrap();
+        let options = deserializer_options(matches)?;
+
+        return dispatch!(matches, canonicalize_command(&input, seq_mode, &options));
+    }
+
+    if let ("log", Some(matches)) = matches.subcommand() {
+        return match matches.subcommand() {
+            ("append", Some(matches)) => dispatch!(matches, log_append_command(matches)),
+            ("verify", Some(matches)) => dispatch!(matches, log_verify_command(matches)),
+            _ => unreachable!("log requires a subcommand"),
+        };
+    }
+
+    if let ("item-hash", Some(matches)) = matches.subcommand() {
+        let input = read_input(matches)?;
+        let item = serde_json::from_str(&input)
+            .map_err(|err| Error::parse(&input, DeserializeError::Json(err)))?;
+
+        println!("{}", register::item_hash(&item));
+
+        return Ok(());
+    }
+
+    if let ("redact", Some(matches)) = matches.subcommand() {
+        let input = read_input(matches)?;
+        let seq_mode = matches.value_of("sequence").unwrap();
+        let paths: Vec<JsonPath> = matches
+            .values_of("path")
+            .unwrap()
+            .map(|path| path.parse().map_err(|err| Error::argument("path", err)))
+            .collect::<Result<_, _>>()?;
+        let options = deserializer_options(matches)?;
+
+        return dispatch!(matches, redact_command(&input, seq_mode, &options, &paths));
+    }
+
+    if matches.is_present("rpc") {
+        return rpc::serve(&matches);
+    }
 
-    let input = matches
-        .value_of("input")
-        .map(handle_stdin)
-        .unwrap_or_else(|| consume_stdin());
     let seq_mode = matches.value_of("sequence").unwrap();
-    let verbose = matches.is_present("verbose");
-
-    match matches.value_of("algorithm").unwrap() {
-        "sha1" => digest_command(&input, seq_mode, verbose, multihash::Sha1),
-        "sha2-256" => digest_command(&input, seq_mode, verbose, multihash::Sha2256),
-        "sha2-512" => digest_command(&input, seq_mode, verbose, multihash::Sha2512),
-        "sha3-224" => digest_command(&input, seq_mode, verbose, multihash::Sha3224),
-        "sha3-256" => digest_command(&input, seq_mode, verbose, multihash::Sha3256),
-        "sha3-384" => digest_command(&input, seq_mode, verbose, multihash::Sha3384),
-        "sha3-512" => digest_command(&input, seq_mode, verbose, multihash::Sha3512),
-        "blake2b-512" => digest_command(&input, seq_mode, verbose, multihash::Blake2b512),
-        "blake2s-256" => digest_command(&input, seq_mode, verbose, multihash::Blake2s256),
-        _ => unreachable!(),
+    let printer = Printer::from_matches(&matches);
+    let key = match matches.value_of("key-file") {
+        Some(path) => Some(fs::read(path).map_err(|err| Error::io(Some(path), err))?),
+        None => None,
+    };
+
+    if let Some(sums) = matches.value_of("check") {
+        let options = deserializer_options(&matches)?;
+
+        return check_command(
+            &read_file(sums)?,
+            matches.is_present("raw"),
+            seq_mode,
+            &options,
+            &registry,
+            &algorithm_policy(&matches),
+        );
+    }
+
+    if matches.is_present("input") && matches.is_present("file") {
+        return Err(Error::argument(
+            "file",
+            "can't be combined with positional inputs",
+        ));
+    }
+
+    let paths: Vec<&str> = match matches.values_of("input") {
+        Some(inputs) if inputs.len() > 1 => inputs.collect(),
+        _ => matches.values_of("file").into_iter().flatten().collect(),
     };
+
+    if paths.len() > 1 {
+        if matches.is_present("audit") {
+            return Err(Error::argument(
+                "audit",
+                "only supported for a single input",
+            ));
+        }
+
+        if matches.value_of("canon") == Some("jcs") {
+            return Err(Error::argument(
+                "canon",
+                "only supported for a single input",
+            ));
+        }
+
+        if matches.is_present("stats") {
+            return Err(Error::argument(
+                "stats",
+                "only supported for a single input",
+            ));
+        }
+
+        if matches.is_present("progress") {
+            return Err(Error::argument(
+                "progress",
+                "only supported for a single input",
+            ));
+        }
+
+        if matches.is_present("namespace") {
+            return Err(Error::argument(
+                "namespace",
+                "only supported for a single input",
+            ));
+        }
+
+        if matches.is_present("lines") {
+            return Err(Error::argument(
+                "lines",
+                "only supported for a single input",
+            ));
+        }
+
+        let jobs = value_t!(matches, "jobs", usize).unwrap_or_else(|err| err.exit());
+        let raw = matches.is_present("raw");
+        let options = deserializer_options(&matches)?;
+
+        return dispatch!(
+            @keyed key,
+            matches,
+            files_command(
+                &paths,
+                jobs,
+                raw,
+                seq_mode,
+                &options,
+                &Printer::from_matches(&matches)
+            )
+        );
+    }
+
+    let file = match matches.value_of("file") {
+        Some(path) => Some(File::open(path).map_err(|err| Error::io(Some(path), err))?),
+        None => None,
+    };
+
+    if matches.is_present("progress") && !matches.is_present("raw") && !matches.is_present("lines")
+    {
+        return Err(Error::argument("progress", "requires --raw or --lines"));
+    }
+
+    let meter = if matches.is_present("progress") {
+        let total = match (&file, matches.value_of("input")) {
+            (Some(file), _) => file.metadata().ok().map(|metadata| metadata.len()),
+            (None, None) | (None, Some("-")) => None,
+            (None, Some(input)) => Some(input.len() as u64),
+        };
+
+        Meter::new(true, total)
+    } else {
+        Meter::disabled()
+    };
+
+    if matches.is_present("raw") {
+        return match (file, matches.value_of("input")) {
+            (Some(file), _) => {
+                dispatch!(@keyed key, matches, raw_command(file, &printer, &meter))
+            }
+            (None, None) | (None, Some("-")) => {
+                let stdin = io::stdin();
+                let handle = stdin.lock();
+                dispatch!(@keyed key, matches, raw_command(handle, &printer, &meter))
+            }
+            (None, Some(input)) => {
+                dispatch!(@keyed key, matches, raw_command(input.as_bytes(), &printer, &meter))
+            }
+        };
+    }
+
+    if matches.is_present("lines") {
+        let options = deserializer_options(&matches)?;
+
+        return match (file, matches.value_of("input")) {
+            (Some(file), _) => dispatch!(
+                @keyed key,
+                matches,
+                lines_command(BufReader::new(file), seq_mode, &options, &printer, &meter)
+            ),
+            (None, None) | (None, Some("-")) => {
+                let stdin = io::stdin();
+                let handle = stdin.lock();
+                dispatch!(
+                    @keyed key,
+                    matches,
+                    lines_command(handle, seq_mode, &options, &printer, &meter)
+                )
+            }
+            (None, Some(input)) => dispatch!(
+                @keyed key,
+                matches,
+                lines_command(input.as_bytes(), seq_mode, &options, &printer, &meter)
+            ),
+        };
+    }
+
+    let input = read_input(&matches)?;
+
+    if matches.value_of("canon") == Some("jcs") {
+        return dispatch!(@keyed key, matches, jcs_command(&input, &printer));
+    }
+
+    let options = deserializer_options(&matches)?;
+    let mut digest_options =
+        DigestOptions::new().duplicates(match matches.value_of("duplicates") {
+            Some("error") => Duplicates::Error,
+            _ => Duplicates::Dedup,
+        });
+
+    if let Some(namespace) = matches.value_of("namespace") {
+        digest_options = digest_options.namespace(namespace);
+    }
+
+    dispatch!(
+        @keyed key,
+        matches,
+        digest_command(
+            &input,
+            seq_mode,
+            &options,
+            &digest_options,
+            Diagnostics::from_matches(&matches),
+            &printer
+        )
+    )
+}
+
+/// Wraps the digester to compute keyed digests.
+fn keyed<T: Keyable>(tag: T, key: Vec<u8>) -> Result<KeyedMultihash<T>, Error> {
+    KeyedMultihash::new(tag, key).map_err(|err| Error::argument("key-file", err))
+}
+
+/// Builds the deserializer options from the `--schema`, `--set-path`, `--numbers`,
+/// `--duplicate-keys` and `--objecthash` arguments.
+fn deserializer_options(matches: &ArgMatches) -> Result<DeserializerOptions, Error> {
+    let mut schema = match matches.value_of("schema") {
+        Some(path) => json_schema::from_str(&read_file(path)?)
+            .map_err(|err| Error::argument("schema", err))?,
+        None => Schema::new(),
+    };
+
+    for pointer in matches.values_of("set-path").into_iter().flatten() {
+        let pointer = pointer
+            .parse()
+            .map_err(|err| Error::argument("set-path", err))?;
+
+        schema.insert(pointer, Kind::Set);
+    }
+
+    let options = DeserializerOptions::new()
+        .objecthash(matches.is_present("objecthash"))
+        .duplicate_keys(match matches.value_of("duplicate-keys") {
+            Some("error") => DuplicateKeys::Error,
+            _ => DuplicateKeys::Last,
+        })
+        .schema(schema)
+        .policy(algorithm_policy(matches));
+
+    Ok(match matches.value_of("numbers") {
+        Some("float") => options.numbers(Numbers::Float),
+        Some("auto") => options.numbers(Numbers::Auto),
+        _ => options,
+    })
+}
+
+/// Builds the policy denying the `--forbid` algorithms.
+fn algorithm_policy(matches: &ArgMatches) -> AlgorithmPolicy {
+    matches
+        .values_of("forbid")
+        .into_iter()
+        .flatten()
+        .filter_map(multihash::find)
+        .fold(AlgorithmPolicy::new(), AlgorithmPolicy::deny)
+}
+
+/// Returns the algorithm name unless the policy denies it.
+fn permitted_algorithm<'a>(name: &'a str, policy: &AlgorithmPolicy) -> Result<&'a str, Error> {
+    match multihash::find(name) {
+        Some(stamp) if !policy.permits(&stamp) => Err(Error::argument(
+            "algorithm",
+            format!("{} is forbidden", name),
+        )),
+        _ => Ok(name),
+    }
+}
+
+/// Reads the JSON input from `--file`, the `input` argument or the standard input.
+fn read_input(matches: &ArgMatches) -> Result<String, Error> {
+    match matches.value_of("file") {
+        Some(path) => read_file(path),
+        None => matches
+            .value_of("input")
+            .map(handle_stdin)
+            .unwrap_or_else(consume_stdin),
+    }
+}
+
+fn read_file(path: &str) -> Result<String, Error> {
+    let mut buffer = String::new();
+
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut buffer))
+        .map_err(|err| Error::io(Some(path), err))?;
+
+    Ok(buffer)
 }
 
-fn consume_stdin() -> String {
+fn consume_stdin() -> Result<String, Error> {
     let mut buffer = String::new();
     let stdin = io::stdin();
     let mut handle = stdin.lock();
 
-    handle.read_to_string(&mut buffer).unwrap();
+    handle
+        .read_to_string(&mut buffer)
+        .map_err(|err| Error::io(None, err))?;
 
-    buffer
+    Ok(buffer)
 }
 
-fn handle_stdin(input: &str) -> String {
+fn handle_stdin(input: &str) -> Result<String, Error> {
     if input == "-" {
         consume_stdin()
     } else {
-        input.to_string()
+        Ok(input.to_string())
+    }
+}
+
+fn vectors_command<W: Write, D: Multihash + Clone>(
+    output: &mut W,
+    digester: D,
+) -> Result<(), Error> {
+    vectors::write(output, digester).map_err(|err| Error::io(None, err))
+}
+
+/// Whether the command line hashes with the algorithm. The identity is left out: its digests
+/// are capped at 255 bytes, which any larger input would exceed.
+fn hashes_with(algorithm: &Algorithm) -> bool {
+    algorithm.name() != "identity"
+}
+
+/// Prints the registered algorithms, one per line or as a JSON array.
+fn algorithms_command(registry: &Registry, json: bool) {
+    let algorithms: Vec<&Algorithm> = registry
+        .algorithms()
+        .iter()
+        .filter(|algorithm| hashes_with(algorithm))
+        .collect();
+
+    if json {
+        let list: Vec<serde_json::Value> = algorithms
+            .iter()
+            .map(|algorithm| {
+                json!({
+                    "name": algorithm.name(),
+                    "code": format!("{:#x}", algorithm.code()),
+                    "length": algorithm.length(),
+                })
+            })
+            .collect();
+
+        println!("{}", serde_json::Value::from(list));
+    } else {
+        for algorithm in algorithms {
+            println!(
+                "{:<12} {:>8} {:>3}",
+                algorithm.name(),
+                format!("{:#x}", algorithm.code()),
+                algorithm.length()
+            );
+        }
+    }
+}
+
+/// What the digest command prints to the standard error besides the digest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Diagnostics {
+    None,
+    /// Every input fed to the hasher, from `--audit`.
+    Audit,
+    /// The hashing statistics, from `--stats`.
+    Stats,
+}
+
+impl Diagnostics {
+    fn from_matches(matches: &ArgMatches) -> Diagnostics {
+        if matches.is_present("audit") {
+            Diagnostics::Audit
+        } else if matches.is_present("stats") {
+            Diagnostics::Stats
+        } else {
+            Diagnostics::None
+        }
     }
 }
 
-fn digest_command<D: Multihash>(input: &str, seq_mode: &str, verbose: bool, digester: D) {
-    let value = serde_json::from_str::<Value<D>>(&input)
+fn digest_command<D: Multihash>(
+    input: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    digest_options: &DigestOptions,
+    diagnostics: Diagnostics,
+    printer: &Printer,
+    digester: D,
+) -> Result<(), Error> {
+    let hash = match diagnostics {
+        Diagnostics::Audit => {
+            let value = parse_value::<D>(input, seq_mode, options)?;
+            let (hash, trace) = audit::trace(&value, digester);
+            eprint!("{}", trace);
+
+            hash
+        }
+        Diagnostics::Stats => {
+            let (hash, report) = parse_value::<D>(input, seq_mode, options)?
+                .digest_with_stats(digester, digest_options)?;
+            eprintln!("{}", report);
+
+            hash
+        }
+        // Set mode rewrites the value, so only lists can be hashed while parsing.
+        Diagnostics::None if seq_mode == "set" || *digest_options != DigestOptions::new() => {
+            parse_value::<D>(input, seq_mode, options)?.digest_with(digester, digest_options)?
+        }
+        Diagnostics::None => options
+            .digest_str::<D, D>(input, digester)
+            .map_err(|err| Error::parse(input, err))?,
+    };
+
+    printer.hash(&hash);
+
+    Ok(())
+}
+
+fn jcs_command<D: Multihash>(input: &str, printer: &Printer, digester: D) -> Result<(), Error> {
+    let document: serde_json::Value = serde_json::from_str(input)
+        .map_err(|err| Error::parse(input, DeserializeError::Json(err)))?;
+
+    printer.hash(&jcs::digest(&document, digester));
+
+    Ok(())
+}
+
+fn parse_value<D: Multihash>(
+    input: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+) -> Result<Value<D>, Error> {
+    Value::from_str_with(input, options)
         .map(|v| {
             if seq_mode == "set" {
                 v.sequences_as_sets()
             } else {
                 v
             }
-        }).expect("Valid json");
+        })
+        .map_err(|err| Error::parse(input, err))
+}
 
-    let hash = value.digest(digester);
+fn verify_command<T: Multihash, D: Multihash>(
+    matches: &ArgMatches,
+    expected: &Seal<T>,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    digester: D,
+) -> Result<(), Error> {
+    let hash = if matches.is_present("raw") {
+        let path = matches.value_of("file");
+        let result = match path {
+            Some(path) => {
+                File::open(path).and_then(|file| RawStream::new(file).try_digest(digester))
+            }
+            None => {
+                let stdin = io::stdin();
+                let handle = stdin.lock();
+                RawStream::new(handle).try_digest(digester)
+            }
+        };
 
-    if verbose {
-        display_verbose(&hash);
+        result.map_err(|err| Error::io(path, err))?
     } else {
-        display(&hash);
+        parse_value::<D>(&read_input(matches)?, seq_mode, options)?.digest(digester)
+    };
+
+    if expected.matches(hash.digest().as_slice()) {
+        println!("OK");
+    } else {
+        eprintln!(
+            "FAILED: expected {}, found {}",
+            expected.multihash_hex(),
+            hash
+        );
+        process::exit(1);
     }
+
+    Ok(())
 }
 
-fn display<T: Multihash>(hash: &Hash<T>) {
-    let code = format!("{:02x}", &hash.tag().code());
-    let length = format!("{:02x}", &hash.tag().length());
-    let digest = format!("{}", &hash.digest());
+/// Verifies every `<multihash>  <file>` line of a checksum file.
+fn check_command(
+    sums: &str,
+    raw: bool,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    registry: &Registry,
+    policy: &AlgorithmPolicy,
+) -> Result<(), Error> {
+    let mut failures = 0;
+    let mut malformed = 0;
 
-    print!("{}", Black.on(Fixed(198)).paint(code));
-    print!("{}", Black.on(Fixed(39)).paint(length));
-    println!("{}", Fixed(221).on(Black).paint(digest));
+    for line in sums.lines().filter(|line| !line.trim().is_empty()) {
+        let mut parts = line.splitn(2, ' ');
+        let expected = parts
+            .next()
+            .and_then(|hash| Seal::<multihash::Sha2256>::from_hash_str_in(hash, registry).ok());
+        let path = parts
+            .next()
+            .map(|path| path.trim_start_matches(' ').trim_start_matches('*'));
+
+        let (expected, path) = match (expected, path) {
+            (Some(expected), Some(path)) if !path.is_empty() => (expected, path),
+            _ => {
+                malformed += 1;
+                continue;
+            }
+        };
+
+        if let Err(err) = expected.check(policy) {
+            failures += 1;
+            println!("{}: FAILED ({})", path, err);
+            continue;
+        }
+
+        let digest = dispatch!(
+            @name registry.name_of(expected.tag()).unwrap_or_default(),
+            file_digest(path, raw, seq_mode, options)
+        );
+
+        match digest {
+            Ok(ref digest) if expected.matches(digest.as_slice()) => println!("{}: OK", path),
+            Ok(_) => {
+                failures += 1;
+                println!("{}: FAILED", path);
+            }
+            Err(err) => {
+                failures += 1;
+                println!("{}: FAILED ({})", path, err);
+            }
+        }
+    }
+
+    if malformed > 0 {
+        eprintln!("WARNING: {} line(s) are improperly formatted", malformed);
+    }
+
+    if failures > 0 {
+        eprintln!("WARNING: {} computed checksum(s) did NOT match", failures);
+        process::exit(1);
+    }
+
+    Ok(())
 }
 
-fn display_verbose<T: Multihash>(hash: &Hash<T>) {
-    println!(
-        "{} {:#02x} ({})",
-        Black.on(Fixed(198)).paint("Codec: "),
-        &hash.tag().code(),
-        hash.tag().name()
-    );
-    println!(
-        "{} {:#02x}",
-        Black.on(Fixed(39)).paint("Length:"),
-        &hash.tag().length()
-    );
+/// Returns the bare digest of the file at the given path, see `file_hash`.
+fn file_digest<D: Multihash>(
+    path: &str,
+    raw: bool,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    digester: D,
+) -> Result<Vec<u8>, Error> {
+    file_hash(path, raw, seq_mode, options, digester).map(|hash| hash.digest().as_slice().to_vec())
+}
+
+/// Digests the file at the given path as JSON or, in raw mode, as raw bytes.
+fn file_hash<D: Multihash>(
+    path: &str,
+    raw: bool,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    digester: D,
+) -> Result<Hash<D>, Error> {
+    if raw {
+        File::open(path)
+            .and_then(|file| RawStream::new(file).try_digest(digester))
+            .map_err(|err| Error::io(Some(path), err))
+    } else {
+        let value = parse_value::<D>(&read_file(path)?, seq_mode, options)?;
+
+        Ok(value.digest(digester))
+    }
+}
+
+/// Digests every file on `jobs` threads, printing `<multihash>  <path>` in the given order.
+fn files_command<D: Multihash + Clone + Send + Sync>(
+    paths: &[&str],
+    jobs: usize,
+    raw: bool,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    printer: &Printer,
+    digester: D,
+) -> Result<(), Error> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(paths.iter().map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(paths.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+
+                if index >= paths.len() {
+                    break;
+                }
+
+                let result = file_hash(paths[index], raw, seq_mode, options, digester.clone());
+                results.lock().expect("Unpoisoned results")[index] = Some(result);
+            });
+        }
+    });
+
+    let mut failures = 0;
+
+    for (path, result) in paths
+        .iter()
+        .zip(results.into_inner().expect("Unpoisoned results"))
+    {
+        match result.expect("Every file to be processed") {
+            Ok(hash) => printer.file(&hash, path),
+            Err(err) => {
+                failures += 1;
+                eprintln!("{}: {}", path, err);
+            }
+        }
+    }
+
+    if failures > 0 {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn verify_redacted_command<D: Multihash>(
+    original: &str,
+    redacted: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    digester: D,
+) -> Result<(), Error> {
+    let original = parse_value::<D>(original, seq_mode, options)?;
+    let redacted = parse_value::<D>(redacted, seq_mode, options)?;
+
+    match seal::verify_redacted_with(&original, &redacted, &digester) {
+        Ok(()) => println!("OK"),
+        Err(err) => {
+            eprintln!("FAILED: {}", err);
+            process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+fn redact_command<D: Multihash + Clone>(
+    input: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    paths: &[JsonPath],
+    digester: D,
+) -> Result<(), Error> {
+    let value = parse_value::<D>(input, seq_mode, options)?;
+    let redacted = value.redact_paths_with(paths, &digester);
+
     println!(
-        "{} 0x{}",
-        Black.on(Fixed(221)).paint("Digest:"),
-        &hash.digest()
+        "{}",
+        serde_json::to_string(&redacted).expect("Serializable value")
     );
+
+    Ok(())
+}
+
+/// Appends the input to the log, after verifying it, and prints the new head.
+fn log_append_command<D: Multihash>(matches: &ArgMatches, _digester: D) -> Result<(), Error> {
+    let path = matches.value_of("log").unwrap();
+    let seq_mode = matches.value_of("sequence").unwrap();
+    let options = deserializer_options(matches)?;
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(Error::io(Some(path), err)),
+    };
+    let mut log = EntryLog::verify(log_records::<D>(&content, seq_mode, &options)?)
+        .map_err(|err| Error::argument("log", err))?;
+    let input = read_input(matches)?;
+    let json: serde_json::Value = serde_json::from_str(&input)
+        .map_err(|err| Error::parse(&input, DeserializeError::Json(err)))?;
+    let value = parse_value::<D>(&json.to_string(), seq_mode, &options)?;
+    let timestamp = match matches.value_of("timestamp") {
+        Some(timestamp) => timestamp.to_string(),
+        None => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Clock after the Unix epoch");
+
+            format_timestamp(now.as_secs() as i64, now.subsec_nanos())
+        }
+    };
+    let head = log
+        .append(value, &timestamp)
+        .map_err(|err| Error::argument("timestamp", err))?;
+    let record = json!({
+        "timestamp": timestamp,
+        "value": json,
+        "digest": head.to_string(),
+    });
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", record))
+        .map_err(|err| Error::io(Some(path), err))?;
+
+    println!("{}", head);
+
+    Ok(())
+}
+
+/// Verifies every entry of the log and its head against `--head`.
+fn log_verify_command<D: Multihash>(matches: &ArgMatches, _digester: D) -> Result<(), Error> {
+    let path = matches.value_of("log").unwrap();
+    let seq_mode = matches.value_of("sequence").unwrap();
+    let options = deserializer_options(matches)?;
+    let records = log_records::<D>(&read_file(path)?, seq_mode, &options)?;
+    let expected = match matches.value_of("head") {
+        Some(head) => Some(Seal::<D>::from_hash_str(head)?),
+        None => None,
+    };
+
+    let log = match EntryLog::verify(records) {
+        Ok(log) => log,
+        Err(err) => {
+            eprintln!("FAILED: {}", err);
+            process::exit(1);
+        }
+    };
+
+    match (log.head(), expected) {
+        (Some(head), Some(ref expected)) if expected.matches(head.digest().as_slice()) => {
+            println!("OK {}", head)
+        }
+        (head, Some(expected)) => {
+            eprintln!(
+                "FAILED: expected head {}, found {}",
+                expected.multihash_hex(),
+                head.map_or("an empty log".to_string(), |head| head.to_string())
+            );
+            process::exit(1);
+        }
+        (Some(head), None) => println!("OK {}", head),
+        (None, None) => println!("OK"),
+    }
+
+    Ok(())
+}
+
+/// The timestamp, value and recorded digest of a log entry.
+type LogRecord<D> = (String, Value<D>, Seal<D>);
+
+/// Parses the records of a log: `{"timestamp": …, "value": …, "digest": …}` per line.
+fn log_records<D: Multihash>(
+    content: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+) -> Result<Vec<LogRecord<D>>, Error> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let record: serde_json::Value =
+                serde_json::from_str(line).map_err(|error| Error::Record {
+                    line: index + 1,
+                    error: DeserializeError::Json(error),
+                })?;
+            let field = |name: &str| {
+                record.get(name).ok_or_else(|| {
+                    Error::argument("log", format!("line {}: missing {}", index + 1, name))
+                })
+            };
+            let timestamp = field("timestamp")?.as_str().unwrap_or_default().to_string();
+            let value = parse_value::<D>(&field("value")?.to_string(), seq_mode, options)?;
+            let digest = Seal::from_hash_str(field("digest")?.as_str().unwrap_or_default())?;
+
+            Ok((timestamp, value, digest))
+        })
+        .collect()
+}
+
+fn compare_command<D: Multihash + Clone>(
+    a: &str,
+    b: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    palette: Palette,
+    _digester: D,
+) -> Result<(), Error> {
+    let a = parse_value::<D>(a, seq_mode, options)?;
+    let b = parse_value::<D>(b, seq_mode, options)?;
+    let diffs = diff::diff(&a, &b);
+
+    for path_diff in &diffs {
+        let line = path_diff.to_string();
+
+        match path_diff {
+            PathDiff::Added { .. } => println!("{}", palette.paint(Green, &line)),
+            PathDiff::Removed { .. } => println!("{}", palette.paint(Red, &line)),
+            PathDiff::Changed { .. } => println!("{}", palette.paint(Yellow, &line)),
+        }
+    }
+
+    if !diffs.is_empty() {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn tree_command<D: Multihash + Clone>(
+    input: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    digester: D,
+) -> Result<(), Error> {
+    let value = parse_value::<D>(input, seq_mode, options)?;
+
+    print!("{}", value.digest_tree(digester));
+
+    Ok(())
+}
+
+fn canonicalize_command<D: Multihash>(
+    input: &str,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    _digester: D,
+) -> Result<(), Error> {
+    let value = parse_value::<D>(input, seq_mode, options)?;
+
+    println!("{}", value.to_canonical_json());
+
+    Ok(())
+}
+
+fn dir_command<D: Multihash>(
+    path: &Path,
+    ignore: &[Pattern],
+    printer: &Printer,
+    digester: D,
+) -> Result<(), Error> {
+    let value: Value<D> =
+        dir_value(path, Path::new(""), ignore).map_err(|err| Error::io(path.to_str(), err))?;
+    let hash = value.digest(digester);
+
+    printer.hash(&hash);
+
+    Ok(())
+}
+
+/// Reads the directory at `root/relative` as a dictionary of entry names to raw file contents
+/// and nested dictionaries.
+///
+/// Symbolic links are not followed, so a link to an ancestor cannot recurse forever: they are
+/// hashed as the string of their target path.
+fn dir_value<T: Multihash>(
+    root: &Path,
+    relative: &Path,
+    ignore: &[Pattern],
+) -> io::Result<Value<T>> {
+    let mut dict = DictMap::new();
+
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let name = utf8(entry.file_name(), &entry.path())?;
+        let path = relative.join(&name);
+
+        if ignore
+            .iter()
+            .any(|pattern| pattern.matches_path(&path) || pattern.matches(&name))
+        {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        let value = if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            Value::String(utf8(target.into_os_string(), &entry.path())?)
+        } else if file_type.is_dir() {
+            dir_value(root, &path, ignore)?
+        } else {
+            Value::Raw(fs::read(entry.path())?)
+        };
+
+        dict.insert(name, value);
+    }
+
+    Ok(Value::Dict(dict))
+}
+
+/// Fails on names that are not valid UTF-8 rather than replacing the invalid bytes, which would
+/// let two distinct names hash the same.
+fn utf8(name: OsString, path: &Path) -> io::Result<String> {
+    name.into_string().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not valid UTF-8", path.display()),
+        )
+    })
+}
+
+fn raw_command<R: Read, D: Multihash>(
+    reader: R,
+    printer: &Printer,
+    meter: &Meter,
+    digester: D,
+) -> Result<(), Error> {
+    let hash = RawStream::new(Progress::new(reader, |bytes| meter.read(bytes)))
+        .try_digest(digester)
+        .map_err(|err| Error::io(None, err))?;
+
+    meter.finish();
+    printer.hash(&hash);
+
+    Ok(())
+}
+
+fn lines_command<R: BufRead, D: Multihash + Clone>(
+    reader: R,
+    seq_mode: &str,
+    options: &DeserializerOptions,
+    printer: &Printer,
+    meter: &Meter,
+    digester: D,
+) -> Result<(), Error> {
+    let reader = Progress::new(reader, |bytes| meter.read(bytes));
+
+    for value in stream::values_with::<_, D>(reader, options.clone()) {
+        let value = value?;
+        let value = if seq_mode == "set" {
+            value.sequences_as_sets()
+        } else {
+            value
+        };
+
+        let hash = value.digest(digester.clone());
+
+        meter.record();
+        printer.hash(&hash);
+    }
+
+    meter.finish();
+
+    Ok(())
 }
@@ -0,0 +1,337 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Output formatting.
+//!
+//! A [`Printer`] renders digests through a [`Formatter`], one per output style, and a
+//! [`Palette`] that only emits ANSI colors when the color policy allows it.
+
+use ansi_term::Colour::{Black, Fixed};
+use ansi_term::Style;
+use blot::cid::Codec;
+use blot::encoding::Base;
+use blot::multihash::{Hash, Multihash};
+use blot::uvar::{self, Uvar};
+use clap::ArgMatches;
+use serde_json;
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// When to emit colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorPolicy {
+    /// Colors only if the standard output is a terminal and `NO_COLOR` is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorPolicy {
+    pub fn from_matches(matches: &ArgMatches) -> ColorPolicy {
+        if matches.is_present("no-color") {
+            return ColorPolicy::Never;
+        }
+
+        match matches.value_of("color") {
+            Some("always") => ColorPolicy::Always,
+            Some("never") => ColorPolicy::Never,
+            _ => ColorPolicy::Auto,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorPolicy::Always => true,
+            ColorPolicy::Never => false,
+            ColorPolicy::Auto => {
+                let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+                !no_color && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Paints text, or leaves it untouched when colors are disabled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    enabled: bool,
+}
+
+impl Palette {
+    pub fn new(enabled: bool) -> Palette {
+        Palette { enabled }
+    }
+
+    pub fn paint<S: Into<Style>>(&self, style: S, text: &str) -> String {
+        if self.enabled {
+            style.into().paint(text).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// The parts of a hash shown to the user, independent of the digester type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub algorithm: String,
+    pub code: Uvar,
+    pub length: u8,
+    pub digest: String,
+    /// The hash in the requested digest format, unless it is the default hexadecimal multihash.
+    pub encoded: Option<String>,
+}
+
+impl Report {
+    /// The report as a JSON object, see [`Json`].
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut object = json!({
+            "algorithm": self.algorithm,
+            "code": format!("{:#x}", self.code),
+            "length": self.length,
+            "digest": self.digest,
+            "multihash": format!("{}{}{}", self.code_hex(), self.length_hex(), self.digest),
+        });
+
+        if let Some(ref encoded) = self.encoded {
+            object["encoded"] = serde_json::Value::from(encoded.as_str());
+        }
+
+        object
+    }
+
+    /// The code as encoded in the binary multihash, in hexadecimal.
+    fn code_hex(&self) -> String {
+        Base::Base16.encode(&self.code.clone().to_bytes())
+    }
+
+    /// The length as encoded in the binary multihash, in hexadecimal.
+    fn length_hex(&self) -> String {
+        Base::Base16.encode(&uvar::encode_u64(u64::from(self.length)))
+    }
+}
+
+impl<T: Multihash> From<&Hash<T>> for Report {
+    fn from(hash: &Hash<T>) -> Report {
+        Report {
+            algorithm: hash.tag().name().to_string(),
+            code: hash.tag().code(),
+            length: hash.length(),
+            digest: hash.digest().to_hex(),
+            encoded: None,
+        }
+    }
+}
+
+/// How digests are encoded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DigestFormat {
+    /// The hexadecimal multihash: code, length and digest.
+    MultihashHex,
+    /// The hexadecimal digest without the multihash prefix.
+    BareHex,
+    /// The binary multihash in base64.
+    Base64,
+    /// The binary multihash in base58btc.
+    Base58Btc,
+    /// The binary multihash in base58btc prefixed with its multibase code, `z`.
+    Multibase,
+    /// A base32 CIDv1 for content of the given codec.
+    Cid(Codec),
+}
+
+impl DigestFormat {
+    /// Reads `--digest-format`, overridden by `--cid` and `--objecthash`.
+    pub fn from_matches(matches: &ArgMatches) -> DigestFormat {
+        if matches.is_present("objecthash") {
+            return DigestFormat::BareHex;
+        }
+
+        if matches.is_present("cid") {
+            let codec = matches
+                .value_of("cid")
+                .and_then(Codec::from_name)
+                .unwrap_or(Codec::Raw);
+
+            return DigestFormat::Cid(codec);
+        }
+
+        match matches.value_of("digest-format") {
+            Some("bare-hex") => DigestFormat::BareHex,
+            Some("base64") => DigestFormat::Base64,
+            Some("base58btc") => DigestFormat::Base58Btc,
+            Some("multibase") => DigestFormat::Multibase,
+            _ => DigestFormat::MultihashHex,
+        }
+    }
+
+    /// Encodes the hash, or returns `None` for the default hexadecimal multihash.
+    pub fn encode<T: Multihash>(&self, hash: &Hash<T>) -> Option<String> {
+        match self {
+            DigestFormat::MultihashHex => None,
+            DigestFormat::BareHex => Some(hash.digest().to_hex()),
+            DigestFormat::Base64 => Some(Base::Base64.encode(&hash.to_bytes())),
+            DigestFormat::Base58Btc => Some(Base::Base58Btc.encode(&hash.to_bytes())),
+            DigestFormat::Multibase => Some(hash.to_multibase(Base::Base58Btc)),
+            DigestFormat::Cid(codec) => Some(hash.to_cid(*codec).to_string()),
+        }
+    }
+}
+
+/// Renders a report in a given style.
+pub trait Formatter {
+    /// Formats the report, labelled with the file it belongs to if any.
+    fn format(&self, report: &Report, file: Option<&str>, palette: Palette) -> String;
+}
+
+/// `<multihash>`, or `<multihash>  <file>` like `sha256sum`.
+pub struct Plain;
+
+impl Formatter for Plain {
+    fn format(&self, report: &Report, file: Option<&str>, palette: Palette) -> String {
+        let multihash = match report.encoded {
+            Some(ref encoded) => palette.paint(Fixed(221).on(Black), encoded),
+            None => format!(
+                "{}{}{}",
+                palette.paint(Black.on(Fixed(198)), &report.code_hex()),
+                palette.paint(Black.on(Fixed(39)), &report.length_hex()),
+                palette.paint(Fixed(221).on(Black), &report.digest)
+            ),
+        };
+
+        match file {
+            Some(file) => format!("{}  {}", multihash, file),
+            None => multihash,
+        }
+    }
+}
+
+/// One labelled line per component of the multihash.
+pub struct Verbose;
+
+impl Formatter for Verbose {
+    fn format(&self, report: &Report, file: Option<&str>, palette: Palette) -> String {
+        let mut lines = Vec::new();
+
+        if let Some(file) = file {
+            lines.push(format!(
+                "{} {}",
+                palette.paint(Style::new().bold(), "File:  "),
+                file
+            ));
+        }
+
+        lines.push(format!(
+            "{} {:#02x} ({})",
+            palette.paint(Black.on(Fixed(198)), "Codec: "),
+            report.code,
+            report.algorithm
+        ));
+        lines.push(format!(
+            "{} {:#02x}",
+            palette.paint(Black.on(Fixed(39)), "Length:"),
+            report.length
+        ));
+        lines.push(format!(
+            "{} 0x{}",
+            palette.paint(Black.on(Fixed(221)), "Digest:"),
+            report.digest
+        ));
+
+        if let Some(ref encoded) = report.encoded {
+            lines.push(format!(
+                "{} {}",
+                palette.paint(Style::new().bold(), "Output:"),
+                encoded
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// A single-line JSON object. Never colored.
+pub struct Json;
+
+impl Formatter for Json {
+    fn format(&self, report: &Report, file: Option<&str>, _palette: Palette) -> String {
+        let mut object = report.to_json();
+
+        if let Some(file) = file {
+            object["file"] = serde_json::Value::from(file);
+        }
+
+        object.to_string()
+    }
+}
+
+/// Prints digests with the formatter and colors selected on the command line.
+pub struct Printer {
+    formatter: Box<dyn Formatter>,
+    palette: Palette,
+    digest_format: DigestFormat,
+}
+
+impl Printer {
+    pub fn new(formatter: Box<dyn Formatter>, palette: Palette) -> Printer {
+        Printer {
+            formatter,
+            palette,
+            digest_format: DigestFormat::MultihashHex,
+        }
+    }
+
+    pub fn with_digest_format(mut self, digest_format: DigestFormat) -> Printer {
+        self.digest_format = digest_format;
+        self
+    }
+
+    /// Selects the formatter from `--format` and `--verbose`, the palette from `--color` and the
+    /// digest encoding from `--digest-format`.
+    pub fn from_matches(matches: &ArgMatches) -> Printer {
+        let formatter: Box<dyn Formatter> = if matches.value_of("format") == Some("json") {
+            Box::new(Json)
+        } else if matches.is_present("verbose") {
+            Box::new(Verbose)
+        } else {
+            Box::new(Plain)
+        };
+
+        Printer::new(
+            formatter,
+            Palette::new(ColorPolicy::from_matches(matches).enabled()),
+        )
+        .with_digest_format(DigestFormat::from_matches(matches))
+    }
+
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    pub fn hash<T: Multihash>(&self, hash: &Hash<T>) {
+        println!(
+            "{}",
+            self.formatter
+                .format(&self.report(hash), None, self.palette)
+        );
+    }
+
+    pub fn file<T: Multihash>(&self, hash: &Hash<T>, file: &str) {
+        println!(
+            "{}",
+            self.formatter
+                .format(&self.report(hash), Some(file), self.palette)
+        );
+    }
+
+    fn report<T: Multihash>(&self, hash: &Hash<T>) -> Report {
+        Report {
+            encoded: self.digest_format.encode(hash),
+            ..Report::from(hash)
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Progress reporting.
+//!
+//! A [`Meter`] draws a single status line on the standard error with the bytes read and the
+//! records hashed so far, redrawn at most every [`INTERVAL`] so it never slows the hashing down.
+
+use std::cell::Cell;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+const INTERVAL: Duration = Duration::from_millis(100);
+const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+pub struct Meter {
+    enabled: bool,
+    terminal: bool,
+    total: Option<u64>,
+    bytes: Cell<u64>,
+    records: Cell<u64>,
+    drawn: Cell<Option<Instant>>,
+    shown: Cell<(u64, u64)>,
+}
+
+impl Meter {
+    /// A meter drawing only when `enabled`. The `total` size, if known, adds a percentage.
+    pub fn new(enabled: bool, total: Option<u64>) -> Meter {
+        Meter {
+            enabled,
+            terminal: io::stderr().is_terminal(),
+            total,
+            bytes: Cell::new(0),
+            records: Cell::new(0),
+            drawn: Cell::new(None),
+            shown: Cell::new((0, 0)),
+        }
+    }
+
+    /// A meter that never draws.
+    pub fn disabled() -> Meter {
+        Meter::new(false, None)
+    }
+
+    /// Sets the total number of bytes read so far.
+    pub fn read(&self, bytes: u64) {
+        self.bytes.set(bytes);
+        self.tick();
+    }
+
+    /// Counts one more record hashed.
+    pub fn record(&self) {
+        self.records.set(self.records.get() + 1);
+        self.tick();
+    }
+
+    /// Draws the final state and moves to a new line.
+    pub fn finish(&self) {
+        if self.enabled {
+            if self.shown.get() != (self.bytes.get(), self.records.get()) {
+                self.draw();
+            }
+
+            if self.terminal {
+                eprintln!();
+            }
+        }
+    }
+
+    fn tick(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+
+        match self.drawn.get() {
+            Some(drawn) if now.duration_since(drawn) < INTERVAL => {}
+            _ => {
+                self.drawn.set(Some(now));
+                self.draw();
+            }
+        }
+    }
+
+    fn draw(&self) {
+        self.shown.set((self.bytes.get(), self.records.get()));
+
+        let mut line = format!("{} read", human_bytes(self.bytes.get()));
+
+        if let Some(total) = self.total.filter(|total| *total > 0) {
+            line.push_str(&format!(
+                " of {} ({}%)",
+                human_bytes(total),
+                self.bytes.get() * 100 / total
+            ));
+        }
+
+        if self.records.get() > 0 {
+            line.push_str(&format!(", {} records hashed", self.records.get()));
+        }
+
+        // Only a terminal understands rewinding the line; anything else gets one line per draw.
+        let stderr = io::stderr();
+        let mut handle = stderr.lock();
+        let _ = if self.terminal {
+            write!(handle, "\r\x1b[2K{}", line)
+        } else {
+            writeln!(handle, "{}", line)
+        };
+        let _ = handle.flush();
+    }
+}
+
+fn human_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
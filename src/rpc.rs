@@ -0,0 +1,209 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The JSON-RPC 2.0 service behind `blot --rpc`.
+//!
+//! Requests are read from the standard input, one per line, and answered in order on the standard
+//! output, one per line. Notifications, requests without an `id`, get no answer.
+//!
+//! * `digest` takes `{"value": …}` and answers like `--format json`.
+//! * `tree` takes `{"value": …}` and answers every node, parents first, with its `path`, `tag`
+//!   and `multihash`. Redacted nodes have no tag.
+//! * `compare` takes `{"a": …, "b": …}` and answers the differences, each with a `change`
+//!   (`added`, `removed` or `changed`) and a `path`.
+//!
+//! Every method takes an optional `algorithm`, `--algorithm` by default.
+
+use blot::core::Blot;
+use blot::diff::{self, PathDiff};
+use blot::multihash::{self, AlgorithmPolicy, Multihash};
+use blot::value::de::DeserializerOptions;
+use clap::ArgMatches;
+use error::Error;
+use output::Report;
+use serde_json::{self, Value as Json};
+use std::io::{self, BufRead, Write};
+
+use super::{algorithm_policy, deserializer_options, parse_value, permitted_algorithm};
+
+/// The request is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// The request is not a JSON-RPC 2.0 request object.
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters, or the documents they carry, are not valid.
+const INVALID_PARAMS: i64 = -32602;
+
+/// Answers requests until the standard input is closed.
+pub fn serve(matches: &ArgMatches) -> Result<(), Error> {
+    let service = Service {
+        options: deserializer_options(matches)?,
+        policy: algorithm_policy(matches),
+        seq_mode: matches.value_of("sequence").unwrap().to_string(),
+        algorithm: matches.value_of("algorithm").unwrap().to_string(),
+    };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|err| Error::io(None, err))?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = service.respond(&line) {
+            writeln!(output, "{}", response)
+                .and_then(|_| output.flush())
+                .map_err(|err| Error::io(None, err))?;
+        }
+    }
+
+    Ok(())
+}
+
+struct Service {
+    options: DeserializerOptions,
+    policy: AlgorithmPolicy,
+    seq_mode: String,
+    /// The algorithm of requests that don't name one.
+    algorithm: String,
+}
+
+impl Service {
+    /// Answers the request, unless it is a notification.
+    fn respond(&self, line: &str) -> Option<Json> {
+        let request: Json = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => return Some(failure(Json::Null, PARSE_ERROR, err)),
+        };
+        let id = request.get("id").cloned();
+        let method = match request.get("method").and_then(Json::as_str) {
+            Some(method) if request.get("jsonrpc") == Some(&json!("2.0")) => method,
+            _ => {
+                return Some(failure(
+                    id.unwrap_or(Json::Null),
+                    INVALID_REQUEST,
+                    "invalid request",
+                ))
+            }
+        };
+        let params = request.get("params").unwrap_or(&Json::Null);
+        let result = match method {
+            "digest" => self.digest(params),
+            "tree" => self.tree(params),
+            "compare" => self.compare(params),
+            _ => {
+                let message = format!("unknown method '{}'", method);
+
+                return id.map(|id| failure(id, METHOD_NOT_FOUND, message));
+            }
+        };
+
+        id.map(|id| match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => failure(id, INVALID_PARAMS, err),
+        })
+    }
+
+    fn digest(&self, params: &Json) -> Result<Json, Error> {
+        let input = document(params, "value")?;
+
+        dispatch!(@name self.algorithm(params)?, digest_value(self, &input))
+    }
+
+    fn tree(&self, params: &Json) -> Result<Json, Error> {
+        let input = document(params, "value")?;
+
+        dispatch!(@name self.algorithm(params)?, tree_value(self, &input))
+    }
+
+    fn compare(&self, params: &Json) -> Result<Json, Error> {
+        let a = document(params, "a")?;
+        let b = document(params, "b")?;
+
+        dispatch!(@name self.algorithm(params)?, compare_values(self, &a, &b))
+    }
+
+    fn algorithm<'a>(&'a self, params: &'a Json) -> Result<&'a str, Error> {
+        match params.get("algorithm") {
+            None | Some(Json::Null) => Ok(&self.algorithm),
+            Some(name) => permitted_algorithm(
+                name.as_str()
+                    .ok_or_else(|| Error::argument("algorithm", "not a string"))?,
+                &self.policy,
+            ),
+        }
+    }
+}
+
+fn digest_value<D: Multihash>(service: &Service, input: &str, digester: D) -> Result<Json, Error> {
+    let hash = parse_value::<D>(input, &service.seq_mode, &service.options)?.digest(digester);
+
+    Ok(Report::from(&hash).to_json())
+}
+
+fn tree_value<D: Multihash + Clone>(
+    service: &Service,
+    input: &str,
+    digester: D,
+) -> Result<Json, Error> {
+    let tree = parse_value::<D>(input, &service.seq_mode, &service.options)?.digest_tree(digester);
+    let nodes = tree
+        .nodes()
+        .iter()
+        .map(|node| {
+            json!({
+                "path": node.path().to_string(),
+                "tag": node.tag().map(|tag| tag.name()),
+                "multihash": node.hash().to_string(),
+            })
+        })
+        .collect();
+
+    Ok(Json::Array(nodes))
+}
+
+fn compare_values<D: Multihash + Clone>(
+    service: &Service,
+    a: &str,
+    b: &str,
+    _digester: D,
+) -> Result<Json, Error> {
+    let a = parse_value::<D>(a, &service.seq_mode, &service.options)?;
+    let b = parse_value::<D>(b, &service.seq_mode, &service.options)?;
+    let diffs = diff::diff(&a, &b)
+        .iter()
+        .map(|path_diff| {
+            let change = match path_diff {
+                PathDiff::Added { .. } => "added",
+                PathDiff::Removed { .. } => "removed",
+                PathDiff::Changed { .. } => "changed",
+            };
+
+            json!({ "change": change, "path": path_diff.path().to_string() })
+        })
+        .collect();
+
+    Ok(Json::Array(diffs))
+}
+
+/// The document in the given parameter, as JSON text for the deserializer.
+fn document(params: &Json, name: &'static str) -> Result<String, Error> {
+    params
+        .get(name)
+        .map(Json::to_string)
+        .ok_or_else(|| Error::argument(name, "missing document"))
+}
+
+fn failure<E: ToString>(id: Json, code: i64, error: E) -> Json {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": error.to_string() },
+    })
+}
@@ -0,0 +1,267 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The HTTP service behind `blot serve`.
+//!
+//! Every endpoint takes a JSON object with the document in `value` and answers with JSON:
+//!
+//! * `POST /digest`, with an optional `algorithm`, answers like `--format json`.
+//! * `POST /verify`, with the `expected` multihash, answers `{"ok": …, "multihash": …}`.
+//! * `POST /redact`, with a list of `paths` and an optional `algorithm`, answers the redacted
+//!   document.
+//!
+//! Failures answer `{"error": …}` with a 4xx status, or 500 if handling the request panicked.
+//! Documents are read with the options given on the command line, e.g. `--sequence` or
+//! `--schema`.
+
+use blot::core::Blot;
+use blot::multihash::{self, AlgorithmPolicy, Multihash, Registry};
+use blot::seal::Seal;
+use blot::value::de::DeserializerOptions;
+use blot::value::path::JsonPath;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use error::Error;
+use output::{Formatter, Json, Palette, Report};
+use serde_json;
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use super::{algorithm_policy, deserializer_options, parse_value, permitted_algorithm};
+
+/// Request bodies larger than this are rejected.
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("serve")
+        .about("Serves digest, verify and redact over HTTP")
+        .long_about(
+            r#"
+Serves digest, verify and redact as JSON endpoints over HTTP:
+
+    POST /digest  {"value": <document>, "algorithm": "sha2-256"}
+    POST /verify  {"value": <document>, "expected": "1220a6a6…"}
+    POST /redact  {"value": <document>, "paths": ["a.b[2]"]}
+
+The algorithm defaults to --algorithm. Requests are handled one at a time.
+"#,
+        )
+        .arg(
+            Arg::with_name("port")
+                .help("Port to listen on")
+                .long("port")
+                .short("p")
+                .takes_value(true)
+                .default_value("8080")
+                .validator(|value| {
+                    value
+                        .parse::<u16>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                }),
+        )
+        .arg(
+            Arg::with_name("host")
+                .help("Address to listen on")
+                .long("host")
+                .takes_value(true)
+                .default_value("127.0.0.1"),
+        )
+}
+
+/// Listens for requests until the process is stopped.
+pub fn serve(matches: &ArgMatches) -> Result<(), Error> {
+    let address = format!(
+        "{}:{}",
+        matches.value_of("host").unwrap(),
+        matches.value_of("port").unwrap()
+    );
+    let service = Service {
+        registry: Registry::builtin(),
+        options: deserializer_options(matches)?,
+        policy: algorithm_policy(matches),
+        seq_mode: matches.value_of("sequence").unwrap().to_string(),
+        algorithm: matches.value_of("algorithm").unwrap().to_string(),
+    };
+    let server =
+        Server::http(&address).map_err(|err| Error::io(Some(&address), io::Error::other(err)))?;
+
+    eprintln!("listening on http://{}", address);
+
+    for request in server.incoming_requests() {
+        service.respond(request);
+    }
+
+    Ok(())
+}
+
+struct Service {
+    registry: Registry,
+    options: DeserializerOptions,
+    policy: AlgorithmPolicy,
+    seq_mode: String,
+    /// The algorithm of requests that don't name one.
+    algorithm: String,
+}
+
+impl Service {
+    /// Answers the request. A panic answers 500 instead of taking the whole service down.
+    fn respond(&self, mut request: Request) {
+        let (status, body) = panic::catch_unwind(AssertUnwindSafe(|| self.handle(&mut request)))
+            .unwrap_or_else(|_| (500, error_body("internal error")));
+
+        send(request, status, body)
+    }
+
+    fn handle(&self, request: &mut Request) -> (u16, String) {
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let result = match (&method, url.as_str()) {
+            (Method::Post, "/digest") => read_body(request).and_then(|body| self.digest(&body)),
+            (Method::Post, "/verify") => read_body(request).and_then(|body| self.verify(&body)),
+            (Method::Post, "/redact") => read_body(request).and_then(|body| self.redact(&body)),
+            (_, "/digest") | (_, "/verify") | (_, "/redact") => {
+                return (405, error_body("method not allowed"));
+            }
+            _ => return (404, error_body("not found")),
+        };
+
+        match result {
+            Ok(body) => (200, body),
+            Err(err) => (400, error_body(err)),
+        }
+    }
+
+    fn digest(&self, body: &serde_json::Value) -> Result<String, Error> {
+        let input = document(body)?;
+
+        dispatch!(@name self.algorithm(body)?, digest_value(self, &input))
+    }
+
+    fn verify(&self, body: &serde_json::Value) -> Result<String, Error> {
+        let input = document(body)?;
+        let expected = body
+            .get("expected")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| Error::argument("expected", "missing multihash"))?;
+        let expected: Seal<multihash::Sha2256> = Seal::from_hash_str_in(expected, &self.registry)?;
+        expected.check(&self.policy)?;
+
+        dispatch!(
+            @name self.registry.name_of(expected.tag()).unwrap_or_default(),
+            verify_value(self, &input, &expected)
+        )
+    }
+
+    fn redact(&self, body: &serde_json::Value) -> Result<String, Error> {
+        let input = document(body)?;
+        let paths: Vec<JsonPath> = body
+            .get("paths")
+            .and_then(serde_json::Value::as_array)
+            .ok_or_else(|| Error::argument("paths", "missing list of paths"))?
+            .iter()
+            .map(|path| {
+                path.as_str()
+                    .ok_or_else(|| Error::argument("path", "not a string"))?
+                    .parse()
+                    .map_err(|err| Error::argument("path", err))
+            })
+            .collect::<Result<_, _>>()?;
+
+        dispatch!(@name self.algorithm(body)?, redact_value(self, &input, &paths))
+    }
+
+    /// The algorithm named by the request, unless it is forbidden.
+    fn algorithm<'a>(&'a self, body: &'a serde_json::Value) -> Result<&'a str, Error> {
+        let name = match body.get("algorithm") {
+            None | Some(serde_json::Value::Null) => return Ok(&self.algorithm),
+            Some(name) => name
+                .as_str()
+                .ok_or_else(|| Error::argument("algorithm", "not a string"))?,
+        };
+
+        permitted_algorithm(name, &self.policy)
+    }
+}
+
+fn digest_value<D: Multihash>(
+    service: &Service,
+    input: &str,
+    digester: D,
+) -> Result<String, Error> {
+    let hash = parse_value::<D>(input, &service.seq_mode, &service.options)?.digest(digester);
+
+    Ok(Json.format(&Report::from(&hash), None, Palette::new(false)))
+}
+
+fn verify_value<T: Multihash, D: Multihash>(
+    service: &Service,
+    input: &str,
+    expected: &Seal<T>,
+    digester: D,
+) -> Result<String, Error> {
+    let hash = parse_value::<D>(input, &service.seq_mode, &service.options)?.digest(digester);
+
+    Ok(json!({
+        "ok": expected.matches(hash.digest().as_slice()),
+        "multihash": hash.to_string(),
+    })
+    .to_string())
+}
+
+fn redact_value<D: Multihash + Clone>(
+    service: &Service,
+    input: &str,
+    paths: &[JsonPath],
+    digester: D,
+) -> Result<String, Error> {
+    let value = parse_value::<D>(input, &service.seq_mode, &service.options)?;
+    let redacted = value.redact_paths_with(paths, &digester);
+
+    Ok(serde_json::to_string(&redacted).expect("Serializable value"))
+}
+
+/// The document to process, as JSON text for the deserializer.
+fn document(body: &serde_json::Value) -> Result<String, Error> {
+    body.get("value")
+        .map(serde_json::Value::to_string)
+        .ok_or_else(|| Error::argument("value", "missing document"))
+}
+
+fn read_body(request: &mut Request) -> Result<serde_json::Value, Error> {
+    let mut body = String::new();
+
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut body)
+        .map_err(|err| Error::io(None, err))?;
+
+    if body.len() as u64 > MAX_BODY {
+        return Err(Error::argument(
+            "body",
+            format!("larger than {} bytes", MAX_BODY),
+        ));
+    }
+
+    serde_json::from_str(&body).map_err(|err| Error::argument("body", err))
+}
+
+fn error_body<E: ToString>(error: E) -> String {
+    json!({ "error": error.to_string() }).to_string()
+}
+
+fn send(request: Request, status: u16, body: String) {
+    let header =
+        Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("Valid header");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+
+    if let Err(err) = request.respond(response) {
+        eprintln!("error: {}", err);
+    }
+}
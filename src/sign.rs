@@ -0,0 +1,116 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The `blot sign` and `blot verify-signature` subcommands.
+//!
+//! Keys are read from files with the 32-byte secret key in hexadecimal. Public keys and signatures
+//! are passed as hexadecimal arguments.
+
+use blot::core::Blot;
+use blot::encoding::Base;
+use blot::multihash::Multihash;
+use blot::sign;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use error::Error;
+use std::process;
+
+use super::{deserializer_options, parse_value, read_file, read_input};
+
+pub fn subcommands<'a, 'b>() -> Vec<App<'a, 'b>> {
+    vec![
+        SubCommand::with_name("sign")
+            .about("Signs the digest of the input with an ed25519 key")
+            .long_about(
+                r#"
+Signs the multihash of the input digest with an ed25519 key and prints the
+signature in hexadecimal. The key file holds the 32-byte secret key in
+hexadecimal, e.g.:
+
+    head -c 32 /dev/urandom | xxd -p -c 32 > blot.key
+"#,
+            )
+            .arg(Arg::with_name("input").help("The data as JSON").index(1))
+            .arg(
+                Arg::with_name("key")
+                    .help("Path to the secret key")
+                    .long("key")
+                    .takes_value(true)
+                    .value_name("PATH")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("print-public-key")
+                    .help("Prints the public key of the secret key instead of signing")
+                    .long("print-public-key")
+                    .conflicts_with("input"),
+            ),
+        SubCommand::with_name("verify-signature")
+            .about("Verifies the signature of the digest of the input")
+            .arg(Arg::with_name("input").help("The data as JSON").index(1))
+            .arg(
+                Arg::with_name("public-key")
+                    .help("The public key in hexadecimal")
+                    .long("public-key")
+                    .takes_value(true)
+                    .value_name("HEX")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("signature")
+                    .help("The signature in hexadecimal")
+                    .long("signature")
+                    .takes_value(true)
+                    .value_name("HEX")
+                    .required(true),
+            ),
+    ]
+}
+
+pub fn sign_command<D: Multihash>(matches: &ArgMatches, digester: D) -> Result<(), Error> {
+    let path = matches.value_of("key").unwrap();
+    let key =
+        sign::signing_key_from_hex(&read_file(path)?).map_err(|err| Error::argument("key", err))?;
+
+    if matches.is_present("print-public-key") {
+        println!("{}", Base::Base16.encode(key.verifying_key().as_bytes()));
+
+        return Ok(());
+    }
+
+    let seq_mode = matches.value_of("sequence").unwrap();
+    let options = deserializer_options(matches)?;
+    let hash = parse_value::<D>(&read_input(matches)?, seq_mode, &options)?.digest(digester);
+
+    println!(
+        "{}",
+        Base::Base16.encode(&sign::sign(&hash, &key).to_bytes())
+    );
+
+    Ok(())
+}
+
+pub fn verify_signature_command<D: Multihash>(
+    matches: &ArgMatches,
+    digester: D,
+) -> Result<(), Error> {
+    let key = sign::verifying_key_from_hex(matches.value_of("public-key").unwrap())
+        .map_err(|err| Error::argument("public-key", err))?;
+    let signature = sign::signature_from_hex(matches.value_of("signature").unwrap())
+        .map_err(|err| Error::argument("signature", err))?;
+    let seq_mode = matches.value_of("sequence").unwrap();
+    let options = deserializer_options(matches)?;
+    let hash = parse_value::<D>(&read_input(matches)?, seq_mode, &options)?.digest(digester);
+
+    match sign::verify(&hash, &signature, &key) {
+        Ok(()) => println!("OK"),
+        Err(err) => {
+            eprintln!("FAILED: {} for {}", err, hash);
+            process::exit(1);
+        }
+    }
+
+    Ok(())
+}
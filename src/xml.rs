@@ -0,0 +1,43 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The `blot xml` subcommand.
+
+use blot::core::Blot;
+use blot::multihash::Multihash;
+use blot::value::de::DeserializeError;
+use blot::value::Value;
+use blot::xml;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use error::Error;
+use output::Printer;
+
+use super::read_input;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("xml")
+        .about("Digests an XML document")
+        .long_about(
+            r#"
+Digests an XML document read as a value: elements are dictionaries of their
+child elements, repeated elements are lists, attributes are under the
+'@attributes' key and text around child elements under the '#text' key. An
+element with neither attributes nor children is its text. Whitespace between
+elements, comments and processing instructions don't change the digest.
+"#,
+        )
+        .arg(Arg::with_name("input").help("The data as XML").index(1))
+}
+
+pub fn xml_command<D: Multihash>(matches: &ArgMatches, digester: D) -> Result<(), Error> {
+    let input = read_input(matches)?;
+    let value: Value<D> = xml::from_str(&input)
+        .map_err(|err| Error::parse(&input, DeserializeError::Format(err.to_string())))?;
+
+    Printer::from_matches(matches).hash(&value.digest(digester));
+
+    Ok(())
+}
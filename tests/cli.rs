@@ -0,0 +1,54 @@
+// Copyright 2018 Arnau Siches
+
+// Licensed under the MIT license <LICENSE or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the blot binary with the arguments, writing the input to its standard input.
+fn blot(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_blot"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn lines_forbid_sha1_seal() {
+    let input = "[\"**REDACTED**1114796384e694ba83d7e3250c171ad59d7109728d2c\"]\n";
+
+    let allowed = blot(&["--lines"], input);
+    let forbidden = blot(&["--forbid", "sha1", "--lines"], input);
+
+    assert!(allowed.status.success());
+    assert_eq!(forbidden.status.code(), Some(65));
+    assert!(String::from_utf8_lossy(&forbidden.stderr).contains("sha1 seals are forbidden"));
+}
+
+#[test]
+fn files_and_positional_inputs() {
+    for args in &[
+        &["\"foo\"", "--file", "Cargo.toml"][..],
+        &["Cargo.toml", "README.md", "--file", "Cargo.toml"][..],
+        &["Cargo.toml", "--file", "README.md", "--file", "Cargo.toml"][..],
+    ] {
+        let output = blot(args, "");
+
+        assert_eq!(output.status.code(), Some(64));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("invalid file"));
+    }
+}